@@ -0,0 +1,5 @@
+-- Soft delete for wallets: archived wallets keep their history but are no longer synced
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+-- Index for the sync loop's active-wallet listing
+CREATE INDEX IF NOT EXISTS idx_wallets_active ON wallets(created_at DESC) WHERE archived_at IS NULL;
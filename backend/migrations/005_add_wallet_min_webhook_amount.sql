@@ -0,0 +1,2 @@
+-- Minimum received amount that triggers a payment.received webhook (0 = notify on everything)
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS min_webhook_amount DECIMAL(20, 6) NOT NULL DEFAULT 0;
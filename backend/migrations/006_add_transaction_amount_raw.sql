@@ -0,0 +1,12 @@
+-- Exact on-chain amount in base units plus the mint's decimals; `amount` stays as a derived convenience
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS amount_raw BIGINT;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS decimals SMALLINT;
+
+-- Backfill existing rows (all USDC, 6 decimals)
+UPDATE transactions
+SET amount_raw = (amount * 1000000)::BIGINT, decimals = 6
+WHERE amount_raw IS NULL;
+
+ALTER TABLE transactions ALTER COLUMN amount_raw SET NOT NULL;
+ALTER TABLE transactions ALTER COLUMN decimals SET NOT NULL;
+ALTER TABLE transactions ALTER COLUMN decimals SET DEFAULT 6;
@@ -0,0 +1,2 @@
+-- Static headers (name -> value) sent with every webhook delivery, e.g. a bearer token
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS webhook_headers JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- Response captured from the most recent delivery attempt, for debugging merchant endpoints
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS last_response_status INTEGER;
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS last_response_time_ms INTEGER;
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS last_response_body TEXT;
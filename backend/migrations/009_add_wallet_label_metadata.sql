@@ -0,0 +1,3 @@
+-- Merchant bookkeeping: a human-readable label and free-form metadata per wallet
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS label VARCHAR(100);
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}';
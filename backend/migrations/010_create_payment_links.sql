@@ -0,0 +1,20 @@
+-- Shareable payment links: a destination wallet plus optional amount, marked
+-- paid when the sync loop sees a matching receive
+CREATE TABLE IF NOT EXISTS payment_links (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug VARCHAR(32) NOT NULL UNIQUE,
+    wallet_address VARCHAR(44) NOT NULL REFERENCES wallets(address) ON DELETE CASCADE,
+    amount DECIMAL(20, 6),
+    memo VARCHAR(200),
+    reusable BOOLEAN NOT NULL DEFAULT FALSE,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'paid')),
+    expires_at TIMESTAMPTZ,
+    paid_signature VARCHAR(88) REFERENCES transactions(signature) ON DELETE SET NULL,
+    paid_at TIMESTAMPTZ,
+    payment_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Index for matching incoming payments against a wallet's open links
+CREATE INDEX IF NOT EXISTS idx_payment_links_wallet_active
+    ON payment_links(wallet_address, amount) WHERE status = 'active';
@@ -0,0 +1,2 @@
+-- Cutoff below which sync and backfill stop walking a wallet's history
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS ignore_before TIMESTAMPTZ;
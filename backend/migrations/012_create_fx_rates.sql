@@ -0,0 +1,10 @@
+-- Latest USD exchange rates, refreshed by the FX background loop
+CREATE TABLE IF NOT EXISTS fx_rates (
+    currency VARCHAR(3) PRIMARY KEY,
+    rate DECIMAL(20, 10) NOT NULL,
+    as_of TIMESTAMPTZ NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Preferred fiat currency for displaying a wallet's amounts
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS display_currency VARCHAR(3);
@@ -0,0 +1,2 @@
+-- Keyset pagination over all webhook events for the admin export
+CREATE INDEX idx_webhook_events_created ON webhook_events(created_at, id);
@@ -0,0 +1,12 @@
+-- One USDC balance reading per wallet and day, for balance-over-time charts
+CREATE TABLE IF NOT EXISTS balance_snapshots (
+    wallet_address VARCHAR(44) NOT NULL REFERENCES wallets(address) ON DELETE CASCADE,
+    snapshot_date DATE NOT NULL,
+    amount DECIMAL(20, 6) NOT NULL,
+    amount_raw BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, snapshot_date)
+);
+
+-- Retention sweep
+CREATE INDEX IF NOT EXISTS idx_balance_snapshots_date ON balance_snapshots(snapshot_date);
@@ -0,0 +1,3 @@
+-- Exact payload bytes signed at creation; retries deliver these verbatim since
+-- the JSONB round-trip reorders keys and would break the signature
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS payload_body TEXT;
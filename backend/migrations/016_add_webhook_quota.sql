@@ -0,0 +1,3 @@
+-- Per-wallet webhook rate limit; events over quota wait until next_attempt_at
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS max_webhooks_per_minute INTEGER NOT NULL DEFAULT 60;
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ;
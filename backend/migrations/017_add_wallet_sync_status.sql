@@ -0,0 +1,9 @@
+-- Per-wallet sync outcome, kept by the sync loop for the status endpoint
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS last_synced_at TIMESTAMPTZ;
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS sync_error_streak INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS last_sync_error TEXT;
+
+-- Latest delivered webhook per wallet
+CREATE INDEX IF NOT EXISTS idx_webhook_events_wallet_delivered
+    ON webhook_events(wallet_address, delivered_at DESC)
+    WHERE status = 'delivered';
@@ -0,0 +1,3 @@
+-- SPL transfers decoded from a transaction's instructions, kept for inspecting
+-- how the counterparty was attributed
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS transfer_legs JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,14 @@
+-- One row per background sync cycle, for throughput and error trends
+CREATE TABLE IF NOT EXISTS sync_runs (
+    id BIGSERIAL PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL,
+    wallets_synced INTEGER NOT NULL,
+    new_transactions INTEGER NOT NULL,
+    webhooks_triggered INTEGER NOT NULL,
+    error_count INTEGER NOT NULL,
+    -- First errors of the cycle, each truncated
+    errors JSONB NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_runs_started ON sync_runs(started_at DESC);
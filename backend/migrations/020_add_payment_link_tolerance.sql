@@ -0,0 +1,11 @@
+-- Amount tolerance for matching payments to links, resolved from the global
+-- defaults when a link is created. Shortfalls within either tolerance pay the
+-- link; larger ones on single-use links mark it underpaid.
+ALTER TABLE payment_links
+    ADD COLUMN IF NOT EXISTS amount_tolerance DECIMAL(20, 6) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS amount_tolerance_pct DECIMAL(7, 4) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS received_amount DECIMAL(20, 6);
+
+ALTER TABLE payment_links DROP CONSTRAINT IF EXISTS payment_links_status_check;
+ALTER TABLE payment_links ADD CONSTRAINT payment_links_status_check
+    CHECK (status IN ('active', 'paid', 'underpaid'));
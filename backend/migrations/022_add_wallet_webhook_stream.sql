@@ -0,0 +1,3 @@
+-- Publish a wallet's webhook events to this Redis stream instead of POSTing
+-- them to webhook_url
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS webhook_stream VARCHAR(200);
@@ -0,0 +1,13 @@
+-- Composite indexes backing transaction search. (wallet_address, block_time DESC)
+-- already exists as idx_transactions_wallet_time and signature is the primary key.
+
+-- Counterparty filter within a wallet, still ordered by time
+CREATE INDEX IF NOT EXISTS idx_transactions_wallet_counterparty
+    ON transactions(wallet_address, counterparty, block_time DESC);
+
+-- Amount range filter within a wallet
+CREATE INDEX IF NOT EXISTS idx_transactions_wallet_amount
+    ON transactions(wallet_address, amount);
+
+-- Leading column of idx_transactions_wallet_time covers every lookup this served
+DROP INDEX IF EXISTS idx_transactions_wallet;
@@ -0,0 +1,17 @@
+-- Responses remembered per Idempotency-Key so a retried mutating request
+-- replays the first outcome instead of running again
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key VARCHAR(255) NOT NULL,
+    -- Method and path, e.g. "POST /wallets"
+    endpoint VARCHAR(300) NOT NULL,
+    -- SHA-256 of the request body, hex
+    request_hash CHAR(64) NOT NULL,
+    -- NULL while the first request is still running
+    status_code INTEGER,
+    content_type VARCHAR(100),
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (key, endpoint)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created ON idempotency_keys(created_at);
@@ -0,0 +1,6 @@
+-- USDC balance levels whose crossing fires a balance.threshold webhook
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS balance_thresholds DECIMAL(20, 6)[] NOT NULL DEFAULT '{}';
+
+-- Balance seen by the last threshold check, to detect the crossing edge
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS last_balance DECIMAL(20, 6);
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS last_balance_at TIMESTAMPTZ;
@@ -0,0 +1,37 @@
+-- Chain each row belongs to; everything stored so far is Solana
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS chain VARCHAR(20) NOT NULL DEFAULT 'solana';
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS chain VARCHAR(20) NOT NULL DEFAULT 'solana';
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS chain VARCHAR(20) NOT NULL DEFAULT 'solana';
+-- Only carried so their foreign keys can reference the scoped keys below;
+-- left at the default until payment links and snapshots support other chains
+ALTER TABLE payment_links ADD COLUMN IF NOT EXISTS chain VARCHAR(20) NOT NULL DEFAULT 'solana';
+ALTER TABLE balance_snapshots ADD COLUMN IF NOT EXISTS chain VARCHAR(20) NOT NULL DEFAULT 'solana';
+
+-- Addresses and signatures are only unique within a chain. The address or
+-- signature leads the key so lookups by it alone keep using the index.
+ALTER TABLE transactions DROP CONSTRAINT transactions_wallet_address_fkey;
+ALTER TABLE webhook_events DROP CONSTRAINT webhook_events_wallet_address_fkey;
+ALTER TABLE webhook_events DROP CONSTRAINT webhook_events_transaction_signature_fkey;
+ALTER TABLE payment_links DROP CONSTRAINT payment_links_wallet_address_fkey;
+ALTER TABLE payment_links DROP CONSTRAINT payment_links_paid_signature_fkey;
+ALTER TABLE balance_snapshots DROP CONSTRAINT balance_snapshots_wallet_address_fkey;
+
+ALTER TABLE wallets DROP CONSTRAINT wallets_pkey;
+ALTER TABLE wallets ADD PRIMARY KEY (address, chain);
+ALTER TABLE transactions DROP CONSTRAINT transactions_pkey;
+ALTER TABLE transactions ADD PRIMARY KEY (signature, chain);
+
+ALTER TABLE transactions ADD CONSTRAINT transactions_wallet_fkey
+    FOREIGN KEY (wallet_address, chain) REFERENCES wallets (address, chain) ON DELETE CASCADE;
+ALTER TABLE webhook_events ADD CONSTRAINT webhook_events_wallet_fkey
+    FOREIGN KEY (wallet_address, chain) REFERENCES wallets (address, chain) ON DELETE CASCADE;
+ALTER TABLE webhook_events ADD CONSTRAINT webhook_events_transaction_fkey
+    FOREIGN KEY (transaction_signature, chain) REFERENCES transactions (signature, chain)
+    ON DELETE SET NULL (transaction_signature);
+ALTER TABLE payment_links ADD CONSTRAINT payment_links_wallet_fkey
+    FOREIGN KEY (wallet_address, chain) REFERENCES wallets (address, chain) ON DELETE CASCADE;
+ALTER TABLE payment_links ADD CONSTRAINT payment_links_paid_transaction_fkey
+    FOREIGN KEY (paid_signature, chain) REFERENCES transactions (signature, chain)
+    ON DELETE SET NULL (paid_signature);
+ALTER TABLE balance_snapshots ADD CONSTRAINT balance_snapshots_wallet_fkey
+    FOREIGN KEY (wallet_address, chain) REFERENCES wallets (address, chain) ON DELETE CASCADE;
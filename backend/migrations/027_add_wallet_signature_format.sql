@@ -0,0 +1,6 @@
+-- Header the webhook HMAC is sent in; NULL keeps X-Webhook-Signature
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS webhook_signature_header VARCHAR(100);
+
+-- How the HMAC-SHA256 digest is written into that header
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS webhook_signature_encoding VARCHAR(10) NOT NULL DEFAULT 'hex'
+    CHECK (webhook_signature_encoding IN ('hex', 'base64'));
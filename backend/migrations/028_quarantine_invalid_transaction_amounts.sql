@@ -0,0 +1,20 @@
+-- Rows set aside by data-quality checks rather than deleted, with why
+CREATE TABLE IF NOT EXISTS transactions_quarantine (
+    LIKE transactions,
+    reason VARCHAR(50) NOT NULL,
+    quarantined_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A transaction always moves a positive amount; zero or negative rows came
+-- from a parser bug. Events and payment links pointing at them lose the
+-- reference through their foreign keys.
+WITH bad AS (
+    DELETE FROM transactions
+    WHERE amount <= 0 OR amount_raw <= 0
+    RETURNING *
+)
+INSERT INTO transactions_quarantine
+SELECT *, 'non_positive_amount', NOW() FROM bad;
+
+ALTER TABLE transactions ADD CONSTRAINT transactions_amount_positive
+    CHECK (amount > 0 AND amount_raw > 0);
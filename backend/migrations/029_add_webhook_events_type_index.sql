@@ -0,0 +1,3 @@
+-- Listing a wallet's events of one type, newest first
+CREATE INDEX IF NOT EXISTS idx_webhook_events_wallet_type
+    ON webhook_events (wallet_address, event_type, created_at DESC);
@@ -0,0 +1,20 @@
+-- Groups of wallets (e.g. one per seller or store) for aggregate reporting
+CREATE TABLE IF NOT EXISTS wallet_groups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Deleting a group leaves its wallets ungrouped
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS group_id UUID
+    REFERENCES wallet_groups(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_wallets_group ON wallets(group_id) WHERE group_id IS NOT NULL;
+
+-- Group transaction pages walk each member newest first with signature as
+-- the tie-breaker, so the key carries it; its prefix still serves every
+-- lookup the old index did
+DROP INDEX IF EXISTS idx_transactions_wallet_time;
+CREATE INDEX IF NOT EXISTS idx_transactions_wallet_time
+    ON transactions(wallet_address, block_time DESC, signature DESC);
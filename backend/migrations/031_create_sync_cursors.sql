@@ -0,0 +1,14 @@
+-- How far back a wallet's backfill got, so an interrupted import resumes
+-- instead of rescanning from the newest signature
+CREATE TABLE IF NOT EXISTS sync_cursors (
+    wallet_address VARCHAR(44) NOT NULL,
+    chain VARCHAR(20) NOT NULL DEFAULT 'solana',
+    -- Oldest signature whose page was fully processed
+    oldest_signature VARCHAR(88),
+    scanned BIGINT NOT NULL DEFAULT 0,
+    -- Set once the walk reached the start of history or the wallet's cutoff
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, chain),
+    FOREIGN KEY (wallet_address, chain) REFERENCES wallets (address, chain) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Links can be cancelled when checkout is abandoned, and amended while no
+-- payment has matched them. Each amendment bumps the version and appends the
+-- changed fields to the history.
+ALTER TABLE payment_links
+    ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS history JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS cancelled_at TIMESTAMPTZ;
+
+ALTER TABLE payment_links DROP CONSTRAINT IF EXISTS payment_links_status_check;
+ALTER TABLE payment_links ADD CONSTRAINT payment_links_status_check
+    CHECK (status IN ('active', 'paid', 'underpaid', 'cancelled'));
@@ -0,0 +1,15 @@
+-- One row per delivery attempt, so an event's whole timeline survives the
+-- last_* columns being overwritten by the next attempt
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    event_id UUID NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    -- Counts every attempt, including those before a manual reset
+    attempt_number INTEGER NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    succeeded BOOLEAN NOT NULL,
+    -- NULL when no HTTP response came back (network error, stream delivery)
+    response_status INTEGER,
+    error TEXT,
+    UNIQUE (event_id, attempt_number)
+);
@@ -0,0 +1,15 @@
+-- Symbol, name and decimals of mints resolved from the chain, so a mint is
+-- only looked up once. The bundled stablecoin list is never stored here.
+CREATE TABLE IF NOT EXISTS token_metadata (
+    mint VARCHAR(44) NOT NULL,
+    chain VARCHAR(20) NOT NULL DEFAULT 'solana',
+    symbol VARCHAR(32) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    decimals SMALLINT,
+    logo_uri TEXT,
+    -- 'metaplex' when read from the mint's metadata account, 'mint' when the
+    -- mint has none and only its decimals are known
+    source VARCHAR(20) NOT NULL,
+    resolved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (mint, chain)
+);
@@ -0,0 +1,10 @@
+-- Customer references expected in the memo of payments to a shared address
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS payment_references TEXT[] NOT NULL DEFAULT '{}';
+
+-- Memo attached to the transaction, and the wallet reference it matched
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS memo TEXT;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS reference VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_wallet_reference
+    ON transactions (wallet_address, reference)
+    WHERE reference IS NOT NULL;
@@ -0,0 +1,2 @@
+-- Webhook payload fields a wallet's deliveries truncate or leave out
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS payload_redactions TEXT[] NOT NULL DEFAULT '{}';
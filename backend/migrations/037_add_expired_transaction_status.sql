@@ -0,0 +1,11 @@
+-- Pending transactions that never landed are expired rather than failed
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('confirmed', 'pending', 'failed', 'expired'));
+
+CREATE INDEX IF NOT EXISTS idx_transactions_pending
+    ON transactions (wallet_address, created_at)
+    WHERE status = 'pending';
+
+-- Transactions a sync cycle expired
+ALTER TABLE sync_runs ADD COLUMN IF NOT EXISTS expired_transactions INTEGER NOT NULL DEFAULT 0;
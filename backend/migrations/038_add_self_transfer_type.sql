@@ -0,0 +1,8 @@
+-- Moves between a wallet's own token accounts are recorded as self-transfers
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_tx_type_check;
+ALTER TABLE transactions ALTER COLUMN tx_type TYPE VARCHAR(20);
+ALTER TABLE transactions ADD CONSTRAINT transactions_tx_type_check
+    CHECK (tx_type IN ('send', 'receive', 'self_transfer'));
+
+-- Quarantine rows copy the transactions columns
+ALTER TABLE transactions_quarantine ALTER COLUMN tx_type TYPE VARCHAR(20);
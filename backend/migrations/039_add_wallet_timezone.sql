@@ -0,0 +1,2 @@
+-- IANA zone timestamps are additionally shown in when a request names none
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS timezone VARCHAR(64);
@@ -0,0 +1,6 @@
+-- Keys are scoped to the credential they were first used with, so a replay
+-- never hands one caller's response to another. Keys stored before this have
+-- no caller and are never replayed.
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS caller VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE idempotency_keys DROP CONSTRAINT IF EXISTS idempotency_keys_pkey;
+ALTER TABLE idempotency_keys ADD PRIMARY KEY (key, endpoint, caller);
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+
+use crate::error::AppError;
+use crate::services::audit::AuditContext;
+use crate::AppState;
+
+/// Longest `X-Request-Id` kept in the audit log (matches the column)
+const MAX_REQUEST_ID_LEN: usize = 100;
+
+/// Extractor guarding admin endpoints: requires `Authorization: Bearer <key>`
+/// matching `ADMIN_API_KEY`. Admin endpoints are refused outright when no key
+/// is configured.
+pub struct AdminAuth;
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let expected = state
+            .config
+            .admin_api_key
+            .as_deref()
+            .ok_or_else(|| AppError::Unauthorized("Admin API is disabled".into()))?;
+
+        let provided = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Unauthorized("Missing admin bearer token".into()))?;
+
+        if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Err(AppError::Unauthorized("Invalid admin token".into()));
+        }
+
+        Ok(AdminAuth)
+    }
+}
+
+/// The caller as recorded in the audit log. Never rejects: calls without
+/// the admin key are attributed to "anonymous".
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AuditContext {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let is_admin = AdminAuth::from_request_parts(parts, state).await.is_ok();
+
+        let request_id = parts
+            .headers
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.chars().take(MAX_REQUEST_ID_LEN).collect());
+
+        Ok(AuditContext {
+            actor: if is_admin { "admin" } else { "anonymous" }.to_string(),
+            request_id,
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
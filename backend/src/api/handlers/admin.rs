@@ -0,0 +1,508 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+use super::{local_time, parse_timezone};
+use crate::api::auth::AdminAuth;
+use crate::api::extract::Json;
+use crate::db::MigrationStatus;
+use crate::domain::{AuditLogEntry, WebhookDeliveryAttempt, WebhookEvent, WebhookStatus};
+use crate::error::AppError;
+use crate::repository::{
+    AuditLogRepository, TransactionRepository, WalletRepository, WebhookEventRepository,
+};
+use crate::services::audit::AuditContext;
+use crate::AppState;
+
+/// Serialized lines buffered ahead of a slow export client
+const EXPORT_BUFFER_LINES: usize = 256;
+
+// Reset failed webhooks response
+#[derive(Debug, Serialize)]
+pub struct ResetWebhooksResponse {
+    pub address: String,
+    pub reset: u64,
+}
+
+/// Re-queue every failed webhook event of a wallet with a fresh attempt
+/// budget, e.g. after the merchant fixed their endpoint. Safe to repeat:
+/// a second call finds nothing left to reset.
+pub async fn reset_failed_webhooks(
+    _admin: AdminAuth,
+    audit: AuditContext,
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<ResetWebhooksResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    // Check if wallet is registered
+    WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let reset = WebhookEventRepository::reset_failed(&state.db.pool, Some(&address)).await?;
+
+    tracing::info!(wallet = %address, count = reset, "Reset failed webhook events");
+
+    state
+        .audit
+        .record(
+            &audit,
+            "webhooks.reset",
+            "wallet",
+            &address,
+            None,
+            Some(serde_json::json!({ "reset": reset })),
+        )
+        .await;
+
+    Ok(Json(ResetWebhooksResponse { address, reset }))
+}
+
+// Webhook event export query
+#[derive(Debug, Deserialize)]
+pub struct ExportWebhookEventsQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    /// Id of the last event already received, to resume an interrupted export
+    pub cursor: Option<String>,
+    /// IANA zone for the `_local` timestamps; events span wallets, so there's
+    /// no wallet default
+    pub timezone: Option<String>,
+}
+
+/// Stream every webhook event matching the filters, across wallets, as
+/// newline-delimited JSON in `(created_at, id)` order. Rows are fetched and
+/// written one at a time through a bounded channel, so memory stays flat no
+/// matter how many rows match. An interrupted download resumes by passing the
+/// id of the last complete line as `cursor`.
+pub async fn export_webhook_events(
+    _admin: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportWebhookEventsQuery>,
+) -> Result<Response, AppError> {
+    let zone = query.timezone.as_deref().map(parse_timezone).transpose()?;
+    let status = query
+        .status
+        .as_deref()
+        .map(WebhookStatus::from_str)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let id = Uuid::parse_str(cursor)
+                .map_err(|_| AppError::BadRequest(format!("Invalid cursor: {}", cursor)))?;
+            WebhookEventRepository::find_by_id(&state.db.pool, id)
+                .await?
+                .ok_or_else(|| AppError::BadRequest(format!("Unknown cursor: {}", cursor)))?;
+            Some(id)
+        }
+        None => None,
+    };
+
+    let (since, until) = (query.since, query.until);
+    let total = WebhookEventRepository::count_for_export(&state.db.pool, since, until, status, cursor)
+        .await?;
+
+    let (tx, rx) = mpsc::channel::<Result<String, AppError>>(EXPORT_BUFFER_LINES);
+    let pool = state.db.pool.clone();
+
+    tokio::spawn(async move {
+        let mut rows = WebhookEventRepository::export(&pool, since, until, status, cursor);
+        let mut exported: u64 = 0;
+
+        while let Some(row) = rows.next().await {
+            let line = row
+                .map_err(AppError::from)
+                .and_then(|event| {
+                    serde_json::to_string(&WebhookEventView::new(event, zone))
+                        .map_err(AppError::from)
+                })
+                .map(|mut line| {
+                    line.push('\n');
+                    line
+                });
+
+            let failed = line.is_err();
+            if let Err(e) = &line {
+                tracing::error!(error = %e, exported, "Webhook event export aborted");
+            }
+
+            // Client went away
+            if tx.send(line).await.is_err() {
+                tracing::info!(exported, "Webhook event export cancelled by client");
+                return;
+            }
+            if failed {
+                return;
+            }
+            exported += 1;
+        }
+
+        tracing::info!(exported, "Webhook event export finished");
+    });
+
+    // An error item aborts the chunked body, so the client sees a truncated
+    // transfer rather than a clean end and can resume from its last line
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::HeaderName::from_static("x-total-estimate"), total.to_string()),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response())
+}
+
+/// Applied schema version and any pending migrations, so operators can
+/// confirm a deploy migrated without querying the database
+pub async fn get_migration_status(
+    _admin: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<MigrationStatus>, AppError> {
+    let status = state.db.migration_status().await?;
+
+    Ok(Json(status))
+}
+
+// Audit log query
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// Entity type, e.g. "wallet" or "payment_link"
+    pub entity: Option<String>,
+    pub entity_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// Audit log response
+#[derive(Debug, Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogEntry>,
+    pub count: usize,
+}
+
+/// Configuration changes made through the API, newest first
+pub async fn get_audit_log(
+    _admin: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogResponse>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    let entries = AuditLogRepository::list(
+        &state.db.pool,
+        query.entity.as_deref(),
+        query.entity_id.as_deref(),
+        limit,
+    )
+    .await?;
+    let count = entries.len();
+
+    Ok(Json(AuditLogResponse { entries, count }))
+}
+
+// Data quality response
+#[derive(Debug, Serialize)]
+pub struct DataQualityResponse {
+    /// Transactions set aside by data-quality checks, in total and per reason
+    pub quarantined_transactions: i64,
+    pub quarantined_by_reason: BTreeMap<String, i64>,
+    /// Parsed transactions refused for a non-positive amount since startup
+    pub rejected_amounts_since_start: u64,
+}
+
+/// Quarantined rows and amounts refused at ingestion, to spot parser
+/// regressions before they reach summaries and webhooks
+pub async fn get_data_quality(
+    _admin: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DataQualityResponse>, AppError> {
+    let quarantined_by_reason: BTreeMap<String, i64> =
+        TransactionRepository::quarantine_counts(&state.db.pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    Ok(Json(DataQualityResponse {
+        quarantined_transactions: quarantined_by_reason.values().sum(),
+        quarantined_by_reason,
+        rejected_amounts_since_start: state.sync.rejected_amounts(),
+    }))
+}
+
+// A webhook event with its timestamps in a requested zone, if any
+#[derive(Debug, Serialize)]
+pub struct WebhookEventView {
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+    #[serde(flatten)]
+    pub local: Option<EventLocalTimestamps>,
+}
+
+impl WebhookEventView {
+    fn new(event: WebhookEvent, zone: Option<Tz>) -> Self {
+        Self {
+            local: zone.map(|zone| EventLocalTimestamps::new(&event, zone)),
+            event,
+        }
+    }
+}
+
+// A webhook event's timestamps in a time zone, next to the canonical UTC ones
+#[derive(Debug, Serialize)]
+pub struct EventLocalTimestamps {
+    pub created_at_local: String,
+    pub last_attempt_at_local: Option<String>,
+    pub delivered_at_local: Option<String>,
+}
+
+impl EventLocalTimestamps {
+    fn new(event: &WebhookEvent, zone: Tz) -> Self {
+        Self {
+            created_at_local: local_time(event.created_at, zone),
+            last_attempt_at_local: event.last_attempt_at.map(|at| local_time(at, zone)),
+            delivered_at_local: event.delivered_at.map(|at| local_time(at, zone)),
+        }
+    }
+}
+
+// A delivery attempt with its time in a requested zone, if any
+#[derive(Debug, Serialize)]
+pub struct DeliveryAttemptView {
+    #[serde(flatten)]
+    pub attempt: WebhookDeliveryAttempt,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempted_at_local: Option<String>,
+}
+
+// Webhook event timeline query params
+#[derive(Debug, Deserialize)]
+pub struct WebhookEventDetailQuery {
+    /// IANA zone for the `_local` timestamps; defaults to the event's wallet's
+    pub timezone: Option<String>,
+}
+
+// Webhook event with its delivery timeline
+#[derive(Debug, Serialize)]
+pub struct WebhookEventDetailResponse {
+    /// Zone of the `_local` timestamps, when they're included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(flatten)]
+    pub event: WebhookEventView,
+    /// Every delivery attempt, oldest first
+    pub delivery_attempts: Vec<DeliveryAttemptView>,
+}
+
+fn parse_event_id(id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid webhook event id: {}", id)))
+}
+
+/// One webhook event of any wallet with every delivery attempt's status
+/// code, error and duration, for working out why deliveries keep failing
+pub async fn get_webhook_event(
+    _admin: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<WebhookEventDetailQuery>,
+) -> Result<Json<WebhookEventDetailResponse>, AppError> {
+    let id = parse_event_id(&id)?;
+
+    let event = WebhookEventRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Webhook event {} not found", id)))?;
+    let delivery_attempts = WebhookEventRepository::delivery_attempts(&state.db.pool, id).await?;
+
+    // The requested zone, else the default of the event's wallet
+    let timezone = match query.timezone {
+        Some(timezone) => Some(timezone),
+        None => WalletRepository::find_by_address(&state.db.pool, &event.wallet_address)
+            .await?
+            .and_then(|wallet| wallet.timezone),
+    };
+    let zone = timezone.as_deref().map(parse_timezone).transpose()?;
+
+    let delivery_attempts = delivery_attempts
+        .into_iter()
+        .map(|attempt| DeliveryAttemptView {
+            attempted_at_local: zone.map(|zone| local_time(attempt.attempted_at, zone)),
+            attempt,
+        })
+        .collect();
+
+    Ok(Json(WebhookEventDetailResponse {
+        timezone,
+        event: WebhookEventView::new(event, zone),
+        delivery_attempts,
+    }))
+}
+
+// Inspection link response
+#[derive(Debug, Serialize)]
+pub struct InspectionLinkResponse {
+    pub event_id: Uuid,
+    /// Path on this API; opening it needs no admin key
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Issue a time-limited signed link to one webhook event's exact payload,
+/// signature header and delivery details. Support can paste it into a ticket
+/// for the merchant instead of reading payloads out of the database.
+pub async fn create_inspection_link(
+    _admin: AdminAuth,
+    audit: AuditContext,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<InspectionLinkResponse>, AppError> {
+    let id = parse_event_id(&id)?;
+
+    let event = WebhookEventRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Webhook event {} not found", id)))?;
+
+    let ttl = chrono::Duration::seconds(state.config.webhook_inspection_ttl_secs);
+    let expires_at = Utc::now() + ttl;
+    let token = state.webhook.inspection_token(event.id, expires_at);
+
+    state
+        .audit
+        .record(
+            &audit,
+            "webhook_event.inspection_link",
+            "webhook_event",
+            &id.to_string(),
+            None,
+            Some(serde_json::json!({ "expires_at": expires_at })),
+        )
+        .await;
+
+    Ok(Json(InspectionLinkResponse {
+        event_id: event.id,
+        url: format!("/webhook-events/inspect?token={}", token),
+        expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Chain, WalletSettings};
+    use crate::services::testing::{register_wallet, Harness, WALLET};
+    use sqlx::PgPool;
+
+    /// An event of `WALLET`, in Berlin time, with one attempt at a summer noon UTC
+    async fn berlin_event(pool: &PgPool) -> WebhookEvent {
+        register_wallet(
+            pool,
+            WalletSettings {
+                timezone: Some("Europe/Berlin".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        let event = WebhookEventRepository::create(
+            pool,
+            WALLET,
+            Chain::Solana,
+            None,
+            "payment.received",
+            "{}",
+        )
+        .await
+        .unwrap();
+        let at = "2025-07-01T12:00:00Z".parse().unwrap();
+        WebhookEventRepository::record_delivery_attempt(
+            pool,
+            event.id,
+            at,
+            15,
+            false,
+            Some(500),
+            Some("HTTP 500"),
+        )
+        .await
+        .unwrap();
+        event
+    }
+
+    fn timeline(timezone: Option<&str>) -> Query<WebhookEventDetailQuery> {
+        Query(WebhookEventDetailQuery { timezone: timezone.map(str::to_string) })
+    }
+
+    #[sqlx::test]
+    async fn the_timeline_defaults_to_the_wallets_timezone(pool: PgPool) {
+        let state = Harness::new(&pool).state(&pool);
+        let event = berlin_event(&pool).await;
+        let id = || Path(event.id.to_string());
+
+        let Json(detail) = get_webhook_event(AdminAuth, State(state.clone()), id(), timeline(None))
+            .await
+            .unwrap();
+        assert_eq!(detail.timezone.as_deref(), Some("Europe/Berlin"));
+        let attempt = &detail.delivery_attempts[0];
+        assert_eq!(attempt.attempted_at_local.as_deref(), Some("2025-07-01T14:00:00+02:00"));
+        let local = detail.event.local.as_ref().unwrap();
+        let berlin = parse_timezone("Europe/Berlin").unwrap();
+        assert_eq!(local.created_at_local, local_time(event.created_at, berlin));
+
+        let Json(tokyo) =
+            get_webhook_event(AdminAuth, State(state), id(), timeline(Some("Asia/Tokyo")))
+                .await
+                .unwrap();
+        assert_eq!(tokyo.timezone.as_deref(), Some("Asia/Tokyo"));
+        let attempt = &tokyo.delivery_attempts[0];
+        assert_eq!(attempt.attempted_at_local.as_deref(), Some("2025-07-01T21:00:00+09:00"));
+    }
+
+    async fn export_lines(state: Arc<AppState>, timezone: Option<&str>) -> Vec<serde_json::Value> {
+        let query = ExportWebhookEventsQuery {
+            since: None,
+            until: None,
+            status: None,
+            cursor: None,
+            timezone: timezone.map(str::to_string),
+        };
+        let response = export_webhook_events(AdminAuth, State(state), Query(query)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        body.split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn exported_events_show_local_timestamps_in_the_requested_zone(pool: PgPool) {
+        let state = Harness::new(&pool).state(&pool);
+        let event = berlin_event(&pool).await;
+
+        // Exports span wallets, so the wallet's zone doesn't apply
+        let plain = export_lines(state.clone(), None).await;
+        assert!(plain[0].get("created_at_local").is_none());
+
+        let tokyo = export_lines(state, Some("Asia/Tokyo")).await;
+        let zone = parse_timezone("Asia/Tokyo").unwrap();
+        assert_eq!(tokyo[0]["id"], event.id.to_string());
+        assert_eq!(tokyo[0]["created_at_local"], local_time(event.created_at, zone));
+        assert!(tokyo[0]["delivered_at_local"].is_null());
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::{audit_snapshot, parse_timezone, LocalTimestamps, MAX_METADATA_BYTES};
+use crate::api::extract::Json;
+use crate::domain::{GroupBalance, ReceiveVolume, Transaction, WalletGroup};
+use crate::error::AppError;
+use crate::repository::{BalanceSnapshotRepository, TransactionRepository, WalletGroupRepository};
+use crate::services::audit::AuditContext;
+use crate::AppState;
+
+/// Maximum group name length (matches the column)
+const MAX_GROUP_NAME_LEN: usize = 100;
+
+// Create group request
+#[derive(Debug, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    pub metadata: Option<serde_json::Value>,
+}
+
+// Update group request (omitted fields are left unchanged)
+#[derive(Debug, Deserialize)]
+pub struct UpdateGroupRequest {
+    pub name: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+// List groups response
+#[derive(Debug, Serialize)]
+pub struct GroupsResponse {
+    pub groups: Vec<WalletGroup>,
+    pub count: usize,
+}
+
+fn parse_group_id(id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(id).map_err(|_| AppError::BadRequest(format!("Invalid group id: {}", id)))
+}
+
+fn group_not_found(id: Uuid) -> AppError {
+    AppError::NotFound(format!("Wallet group {} not found", id))
+}
+
+fn validate_group_fields(
+    name: Option<&str>,
+    metadata: Option<&serde_json::Value>,
+) -> Result<(), AppError> {
+    if let Some(name) = name {
+        if name.trim().is_empty() || name.chars().count() > MAX_GROUP_NAME_LEN {
+            return Err(AppError::BadRequest(format!(
+                "name must be 1 to {} characters",
+                MAX_GROUP_NAME_LEN
+            )));
+        }
+    }
+
+    if let Some(metadata) = metadata {
+        if !metadata.is_object() {
+            return Err(AppError::BadRequest("metadata must be a JSON object".into()));
+        }
+        if metadata.to_string().len() > MAX_METADATA_BYTES {
+            return Err(AppError::BadRequest(format!(
+                "metadata must be at most {} bytes when serialized",
+                MAX_METADATA_BYTES
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn create_group(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<CreateGroupRequest>,
+) -> Result<Json<WalletGroup>, AppError> {
+    validate_group_fields(Some(&req.name), req.metadata.as_ref())?;
+
+    let group =
+        WalletGroupRepository::create(&state.db.pool, &req.name, req.metadata.as_ref()).await?;
+
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet_group.create",
+            "wallet_group",
+            &group.id.to_string(),
+            None,
+            audit_snapshot(&group),
+        )
+        .await;
+
+    Ok(Json(group))
+}
+
+pub async fn list_groups(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<GroupsResponse>, AppError> {
+    let groups = WalletGroupRepository::list(&state.db.pool).await?;
+    let count = groups.len();
+
+    Ok(Json(GroupsResponse { groups, count }))
+}
+
+pub async fn get_group(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<WalletGroup>, AppError> {
+    let id = parse_group_id(&id)?;
+
+    let group = WalletGroupRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| group_not_found(id))?;
+
+    Ok(Json(group))
+}
+
+pub async fn update_group(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateGroupRequest>,
+) -> Result<Json<WalletGroup>, AppError> {
+    let id = parse_group_id(&id)?;
+    validate_group_fields(req.name.as_deref(), req.metadata.as_ref())?;
+
+    let before = WalletGroupRepository::find_by_id(&state.db.pool, id).await?;
+
+    let group = WalletGroupRepository::update(
+        &state.db.pool,
+        id,
+        req.name.as_deref(),
+        req.metadata.as_ref(),
+    )
+    .await?
+    .ok_or_else(|| group_not_found(id))?;
+
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet_group.update",
+            "wallet_group",
+            &id.to_string(),
+            before.as_ref().and_then(audit_snapshot),
+            audit_snapshot(&group),
+        )
+        .await;
+
+    Ok(Json(group))
+}
+
+/// Delete a group. Its wallets stay registered and simply become ungrouped.
+pub async fn delete_group(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+) -> Result<Json<WalletGroup>, AppError> {
+    let id = parse_group_id(&id)?;
+
+    let group = WalletGroupRepository::delete(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| group_not_found(id))?;
+
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet_group.delete",
+            "wallet_group",
+            &id.to_string(),
+            audit_snapshot(&group),
+            None,
+        )
+        .await;
+
+    Ok(Json(group))
+}
+
+// Group summary response
+#[derive(Debug, Serialize)]
+pub struct GroupSummaryResponse {
+    #[serde(flatten)]
+    pub group: WalletGroup,
+    /// Member wallets, archived ones included
+    pub member_count: i64,
+    /// From cached daily snapshots, not live balances
+    pub balance: GroupBalance,
+    pub volume: ReceiveVolume,
+}
+
+/// Aggregates across a group's wallets, answered from the database alone
+pub async fn get_group_summary(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<GroupSummaryResponse>, AppError> {
+    let id = parse_group_id(&id)?;
+
+    let group = WalletGroupRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| group_not_found(id))?;
+
+    let (member_count, balance, volume) = tokio::try_join!(
+        WalletGroupRepository::member_count(&state.db.pool, id),
+        BalanceSnapshotRepository::latest_total_for_group(&state.db.pool, id),
+        TransactionRepository::receive_volume_for_group(&state.db.pool, id),
+    )?;
+
+    Ok(Json(GroupSummaryResponse {
+        group,
+        member_count,
+        balance,
+        volume,
+    }))
+}
+
+// Group transactions query params
+#[derive(Debug, Deserialize)]
+pub struct GroupTransactionsQuery {
+    pub limit: Option<i64>,
+    /// Signature of the last transaction of the previous page
+    pub cursor: Option<String>,
+    /// IANA zone for the `_local` timestamps; members may each have their
+    /// own, so there's no wallet default
+    pub timezone: Option<String>,
+}
+
+// A group transaction with its timestamps in a requested zone, if any
+#[derive(Debug, Serialize)]
+pub struct GroupTransactionView {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    #[serde(flatten)]
+    pub local: Option<LocalTimestamps>,
+}
+
+// Group transactions response
+#[derive(Debug, Serialize)]
+pub struct GroupTransactionsResponse {
+    /// Zone of the `_local` timestamps, when they're included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub transactions: Vec<GroupTransactionView>,
+    pub count: usize,
+    /// Pass as `cursor` for the next page; null on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Transactions of all the group's wallets merged newest first, paged by
+/// cursor so pages stay stable while new transactions arrive
+pub async fn get_group_transactions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<GroupTransactionsQuery>,
+) -> Result<Json<GroupTransactionsResponse>, AppError> {
+    let id = parse_group_id(&id)?;
+    let zone = query.timezone.as_deref().map(parse_timezone).transpose()?;
+
+    WalletGroupRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| group_not_found(id))?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let after = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let transaction = TransactionRepository::find_by_signature(&state.db.pool, cursor)
+                .await?
+                .ok_or_else(|| AppError::BadRequest(format!("Unknown cursor: {}", cursor)))?;
+            Some((transaction.block_time, cursor))
+        }
+        None => None,
+    };
+
+    // One extra row tells whether another page follows
+    let mut transactions =
+        TransactionRepository::find_by_group(&state.db.pool, id, after, limit + 1).await?;
+    let has_more = transactions.len() as i64 > limit;
+    transactions.truncate(limit as usize);
+
+    let next_cursor = if has_more {
+        transactions.last().map(|t| t.signature.clone())
+    } else {
+        None
+    };
+    let count = transactions.len();
+    let transactions = transactions
+        .into_iter()
+        .map(|transaction| GroupTransactionView {
+            local: zone.map(|zone| LocalTimestamps::new(&transaction, zone)),
+            transaction,
+        })
+        .collect();
+
+    Ok(Json(GroupTransactionsResponse {
+        timezone: query.timezone,
+        transactions,
+        count,
+        next_cursor,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WalletSettings;
+    use crate::services::testing::{register_wallet, signature, transfer, Harness};
+    use chrono::{DateTime, Utc};
+    use sqlx::PgPool;
+
+    fn page(timezone: Option<&str>) -> Query<GroupTransactionsQuery> {
+        Query(GroupTransactionsQuery {
+            limit: None,
+            cursor: None,
+            timezone: timezone.map(str::to_string),
+        })
+    }
+
+    #[sqlx::test]
+    async fn group_transactions_show_local_timestamps_in_the_requested_zone(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let state = harness.state(&pool);
+        let group = WalletGroupRepository::create(&pool, "EU shops", None).await.unwrap();
+        let wallet = register_wallet(
+            &pool,
+            WalletSettings {
+                group_id: Some(group.id.to_string()),
+                timezone: Some("Europe/Berlin".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        let at: DateTime<Utc> = "2025-07-01T12:00:00Z".parse().unwrap();
+        harness.chain.push_transaction(transfer(&signature(1), "receive", 1_000_000, at));
+        harness.sync.sync_wallet(&wallet, 10, false).await.unwrap();
+        let id = || Path(group.id.to_string());
+
+        // Members may each have a zone, so only a requested one applies
+        let Json(plain) = get_group_transactions(State(state.clone()), id(), page(None))
+            .await
+            .unwrap();
+        let plain = serde_json::to_value(&plain).unwrap();
+        assert!(plain.get("timezone").is_none());
+        assert!(plain["transactions"][0].get("block_time_local").is_none());
+
+        let Json(local) =
+            get_group_transactions(State(state.clone()), id(), page(Some("America/New_York")))
+                .await
+                .unwrap();
+        assert_eq!(local.timezone.as_deref(), Some("America/New_York"));
+        let timestamps = local.transactions[0].local.as_ref().unwrap();
+        assert_eq!(timestamps.block_time_local, "2025-07-01T08:00:00-04:00");
+
+        let unknown = get_group_transactions(State(state), id(), page(Some("Mars/Olympus"))).await;
+        assert!(matches!(unknown, Err(AppError::BadRequest(_))));
+    }
+}
@@ -1,14 +1,50 @@
+pub mod admin;
+pub mod groups;
+pub mod payment_links;
+pub mod swap;
+pub mod sync_runs;
+pub mod token_accounts;
+pub mod tokens;
+
+use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use axum::{
     extract::{Path, Query, State},
-    Json,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 
-use crate::domain::{Transaction, TransactionStatus, TransactionType, WebhookEvent};
+use crate::api::extract::Json;
+use crate::db::{Database, PoolStatus};
+use crate::domain::{
+    carry_forward, BalancePoint, Chain, DisplayAmount, SignatureEncoding, Transaction,
+    TransactionStats, Wallet, WalletSettings, WebhookEvent, WebhookStatus,
+    PAYLOAD_REDACTIONS, SUPPORTED_DISPLAY_CURRENCIES, WEBHOOK_EVENT_TYPES,
+};
 use crate::error::AppError;
-use crate::repository::{TransactionRepository, WalletRepository, WebhookEventRepository};
+use crate::repository::{
+    BalanceSnapshotRepository, CreatedWallet, TransactionRepository, WalletGroupRepository,
+    WalletRepository, WebhookEventFilter, WebhookEventRepository,
+};
+use crate::services::audit::AuditContext;
+use crate::services::chain::ChainClient;
+use crate::services::circuit_breaker::{CircuitState, CircuitStatus};
+use crate::services::solana::FeeReadiness;
+use crate::services::sync::{SyncService, SYNC_LIMIT};
+use crate::services::webhook::{
+    validate_custom_headers, validate_signature_header, WebhookHeader, WebhookRequest,
+    DEFAULT_SIGNATURE_HEADER,
+};
 use crate::AppState;
 
 // Health check
@@ -18,40 +54,609 @@ pub async fn health() -> Json<serde_json::Value> {
     }))
 }
 
+// Readiness response
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub failing: Vec<FailingComponent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailingComponent {
+    pub component: &'static str,
+    pub message: String,
+}
+
+/// Readiness probe: 503 until migrations are applied, the database and
+/// Solana RPC answer, and the background sync finished its first cycle.
+/// `/health` stays the liveness probe.
+pub async fn readiness(State(state): State<Arc<AppState>>) -> Response {
+    let failing = readiness_failures(&state.db, state.solana.as_ref(), &state.sync).await;
+
+    if failing.is_empty() {
+        (StatusCode::OK, Json(ReadinessResponse { status: "ready", failing })).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse { status: "not_ready", failing }),
+        )
+            .into_response()
+    }
+}
+
+/// Components keeping this instance out of rotation, checked concurrently
+async fn readiness_failures(
+    db: &Database,
+    chain: &dyn ChainClient,
+    sync: &SyncService,
+) -> Vec<FailingComponent> {
+    let mut failing = Vec::new();
+
+    let (migrations, rpc) = tokio::join!(db.migration_status(), chain.get_latest_blockhash());
+
+    // The migration status query doubles as the database check
+    match migrations {
+        Ok(status) if status.up_to_date => {}
+        Ok(status) => failing.push(FailingComponent {
+            component: "migrations",
+            message: format!(
+                "pending {:?}, failed {:?}",
+                status.pending, status.failed
+            ),
+        }),
+        Err(e) => failing.push(FailingComponent {
+            component: "database",
+            message: e.to_string(),
+        }),
+    }
+
+    if let Err(e) = rpc {
+        failing.push(FailingComponent {
+            component: "solana_rpc",
+            message: e.to_string(),
+        });
+    }
+
+    if !sync.first_cycle_completed() {
+        failing.push(FailingComponent {
+            component: "background_sync",
+            message: "first sync cycle not completed".into(),
+        });
+    }
+
+    failing
+}
+
 // Create wallet request
 #[derive(Debug, Deserialize)]
 pub struct CreateWalletRequest {
     pub address: String,
+    #[serde(default)]
+    pub chain: Chain,
+    /// Run a bounded first sync before responding
+    #[serde(default)]
+    pub sync_now: bool,
+    #[serde(flatten)]
+    pub settings: UpdateWalletRequest,
+}
+
+// Update wallet request (omitted fields are left unchanged)
+#[derive(Debug, Deserialize)]
+pub struct UpdateWalletRequest {
     pub webhook_url: Option<String>,
+    #[serde(alias = "min_notification_amount")]
+    pub min_webhook_amount: Option<Decimal>,
+    pub webhook_headers: Option<HashMap<String, String>>,
+    pub label: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub ignore_before: Option<DateTime<Utc>>,
+    pub display_currency: Option<String>,
+    pub max_webhooks_per_minute: Option<i32>,
+    /// Redis stream to publish events to instead of POSTing; "" clears it
+    pub webhook_stream: Option<String>,
+    /// USDC balance levels that fire balance.threshold; [] clears them
+    pub balance_thresholds: Option<Vec<Decimal>>,
+    /// Header the signature is sent in; "" restores X-Webhook-Signature
+    pub webhook_signature_header: Option<String>,
+    pub webhook_signature_encoding: Option<SignatureEncoding>,
+    /// Wallet group to assign the wallet to; "" removes it from its group
+    pub group_id: Option<String>,
+    /// Customer references expected in payment memos; [] clears them
+    pub payment_references: Option<Vec<String>>,
+    /// Webhook payload fields to truncate or leave out; [] clears them
+    pub payload_redactions: Option<Vec<String>>,
+    /// Default IANA zone for local transaction timestamps; "" clears it
+    pub timezone: Option<String>,
+}
+
+/// Maximum wallet label length (matches the column)
+const MAX_LABEL_LEN: usize = 100;
+
+/// Maximum webhook URL length
+const MAX_WEBHOOK_URL_LEN: usize = 2048;
+
+/// Maximum size of a wallet's metadata, serialized
+const MAX_METADATA_BYTES: usize = 4096;
+
+/// Maximum webhook stream name length (matches the column)
+const MAX_WEBHOOK_STREAM_LEN: usize = 200;
+
+/// Maximum balance thresholds per wallet
+const MAX_BALANCE_THRESHOLDS: usize = 10;
+
+/// Maximum payment references per wallet
+const MAX_PAYMENT_REFERENCES: usize = 1000;
+
+/// Maximum payment reference length (matches the transactions column)
+const MAX_PAYMENT_REFERENCE_LEN: usize = 64;
+
+/// Signatures checked by the inline first sync on registration
+const INITIAL_SYNC_LIMIT: usize = 20;
+
+/// Most signatures the inline sync of a transactions request may check
+const MAX_INLINE_SYNC_LIMIT: usize = 100;
+
+/// How long registration waits for the inline first sync
+const INITIAL_SYNC_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Validate and convert the settable wallet fields shared by create and update.
+/// A webhook stream can only be set when stream delivery is configured.
+fn wallet_settings(
+    req: UpdateWalletRequest,
+    stream_enabled: bool,
+) -> Result<WalletSettings, AppError> {
+    let UpdateWalletRequest {
+        webhook_url,
+        min_webhook_amount,
+        webhook_headers,
+        label,
+        metadata,
+        ignore_before,
+        display_currency,
+        max_webhooks_per_minute,
+        webhook_stream,
+        balance_thresholds,
+        webhook_signature_header,
+        webhook_signature_encoding,
+        group_id,
+        payment_references,
+        payload_redactions,
+        timezone,
+    } = req;
+
+    if webhook_url.as_ref().is_some_and(|u| u.len() > MAX_WEBHOOK_URL_LEN) {
+        return Err(AppError::BadRequest(format!(
+            "webhook_url must be at most {} characters",
+            MAX_WEBHOOK_URL_LEN
+        )));
+    }
+
+    if min_webhook_amount.is_some_and(|amount| amount.is_sign_negative()) {
+        return Err(AppError::BadRequest(
+            "min_webhook_amount must not be negative".into(),
+        ));
+    }
+
+    if let Some(ref headers) = webhook_headers {
+        validate_custom_headers(headers)?;
+    }
+
+    if label.as_ref().is_some_and(|l| l.chars().count() > MAX_LABEL_LEN) {
+        return Err(AppError::BadRequest(format!(
+            "label must be at most {} characters",
+            MAX_LABEL_LEN
+        )));
+    }
+
+    if let Some(ref metadata) = metadata {
+        if !metadata.is_object() {
+            return Err(AppError::BadRequest("metadata must be a JSON object".into()));
+        }
+        if metadata.to_string().len() > MAX_METADATA_BYTES {
+            return Err(AppError::BadRequest(format!(
+                "metadata must be at most {} bytes when serialized",
+                MAX_METADATA_BYTES
+            )));
+        }
+    }
+
+    let display_currency = display_currency.map(|c| c.to_uppercase());
+    if let Some(ref currency) = display_currency {
+        if !SUPPORTED_DISPLAY_CURRENCIES.contains(&currency.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported display_currency {}, expected one of {}",
+                currency,
+                SUPPORTED_DISPLAY_CURRENCIES.join(", ")
+            )));
+        }
+    }
+
+    if max_webhooks_per_minute.is_some_and(|max| max < 1) {
+        return Err(AppError::BadRequest(
+            "max_webhooks_per_minute must be at least 1".into(),
+        ));
+    }
+
+    if let Some(ref stream) = webhook_stream {
+        if stream.chars().count() > MAX_WEBHOOK_STREAM_LEN {
+            return Err(AppError::BadRequest(format!(
+                "webhook_stream must be at most {} characters",
+                MAX_WEBHOOK_STREAM_LEN
+            )));
+        }
+        if !stream.is_empty() && !stream_enabled {
+            return Err(AppError::BadRequest(
+                "webhook_stream needs WEBHOOK_REDIS_URL to be configured".into(),
+            ));
+        }
+    }
+
+    if let Some(name) = webhook_signature_header.as_deref().filter(|n| !n.is_empty()) {
+        validate_signature_header(name)?;
+    }
+
+    let balance_thresholds = match balance_thresholds {
+        Some(mut thresholds) => {
+            if thresholds.len() > MAX_BALANCE_THRESHOLDS {
+                return Err(AppError::BadRequest(format!(
+                    "balance_thresholds may have at most {} entries",
+                    MAX_BALANCE_THRESHOLDS
+                )));
+            }
+            if thresholds.iter().any(|t| t.is_sign_negative() || t.scale() > 6) {
+                return Err(AppError::BadRequest(
+                    "balance_thresholds must be non-negative USDC amounts with at most 6 decimals"
+                        .into(),
+                ));
+            }
+            thresholds.sort();
+            thresholds.dedup();
+            Some(thresholds)
+        }
+        None => None,
+    };
+
+    let payment_references = match payment_references {
+        Some(mut references) => {
+            if references.len() > MAX_PAYMENT_REFERENCES {
+                return Err(AppError::BadRequest(format!(
+                    "payment_references may have at most {} entries",
+                    MAX_PAYMENT_REFERENCES
+                )));
+            }
+            let invalid = |r: &String| {
+                r.is_empty() || r.trim() != r || r.chars().count() > MAX_PAYMENT_REFERENCE_LEN
+            };
+            if references.iter().any(invalid) {
+                return Err(AppError::BadRequest(format!(
+                    "payment_references must be 1 to {} characters without surrounding \
+                     whitespace",
+                    MAX_PAYMENT_REFERENCE_LEN
+                )));
+            }
+            references.sort();
+            references.dedup();
+            Some(references)
+        }
+        None => None,
+    };
+
+    if let Some(zone) = timezone.as_deref().filter(|z| !z.is_empty()) {
+        parse_timezone(zone)?;
+    }
+
+    let payload_redactions = match payload_redactions {
+        Some(mut redactions) => {
+            if let Some(unknown) =
+                redactions.iter().find(|r| !PAYLOAD_REDACTIONS.contains(&r.as_str()))
+            {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown payload redaction: {} (expected one of {})",
+                    unknown,
+                    PAYLOAD_REDACTIONS.join(", ")
+                )));
+            }
+            redactions.sort();
+            redactions.dedup();
+            Some(redactions)
+        }
+        None => None,
+    };
+
+    Ok(WalletSettings {
+        webhook_url,
+        min_webhook_amount,
+        webhook_headers: webhook_headers.map(|h| serde_json::json!(h)),
+        label,
+        metadata,
+        ignore_before,
+        display_currency,
+        max_webhooks_per_minute,
+        webhook_stream,
+        balance_thresholds,
+        webhook_signature_header,
+        webhook_signature_encoding,
+        group_id,
+        payment_references,
+        payload_redactions,
+        timezone,
+    })
+}
+
+/// An IANA zone name, e.g. "Europe/Berlin"
+pub(crate) fn parse_timezone(name: &str) -> Result<Tz, AppError> {
+    name.parse().map_err(|_| {
+        AppError::BadRequest(format!(
+            "Unknown timezone '{}', expected an IANA name such as Europe/Berlin, \
+             America/New_York or Asia/Tokyo",
+            name
+        ))
+    })
+}
+
+/// Check a wallet's group assignment names an existing group
+async fn ensure_group_exists(state: &AppState, settings: &WalletSettings) -> Result<(), AppError> {
+    let Some(id) = settings.group_id.as_deref().filter(|id| !id.is_empty()) else {
+        return Ok(());
+    };
+
+    let id = sqlx::types::Uuid::parse_str(id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid group_id: {}", id)))?;
+    WalletGroupRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Wallet group {} not found", id)))?;
+
+    Ok(())
 }
 
 // Create wallet response
 #[derive(Debug, Serialize)]
 pub struct WalletResponse {
     pub address: String,
+    pub chain: Chain,
     pub webhook_url: Option<String>,
     pub created_at: String,
+    pub archived_at: Option<String>,
+    pub min_webhook_amount: String,
+    pub webhook_headers: serde_json::Value,
+    pub label: Option<String>,
+    pub metadata: serde_json::Value,
+    pub ignore_before: Option<String>,
+    pub display_currency: Option<String>,
+    pub max_webhooks_per_minute: i32,
+    pub webhook_stream: Option<String>,
+    pub balance_thresholds: Vec<String>,
+    pub webhook_signature_header: String,
+    pub webhook_signature_encoding: SignatureEncoding,
+    pub group_id: Option<sqlx::types::Uuid>,
+    pub payment_references: Vec<String>,
+    pub payload_redactions: Vec<String>,
+    pub timezone: Option<String>,
+}
+
+impl From<Wallet> for WalletResponse {
+    fn from(wallet: Wallet) -> Self {
+        Self {
+            address: wallet.address,
+            chain: wallet.chain,
+            webhook_url: wallet.webhook_url,
+            created_at: wallet.created_at.to_rfc3339(),
+            archived_at: wallet.archived_at.map(|t| t.to_rfc3339()),
+            min_webhook_amount: wallet.min_webhook_amount.to_string(),
+            webhook_headers: wallet.webhook_headers,
+            label: wallet.label,
+            metadata: wallet.metadata,
+            ignore_before: wallet.ignore_before.map(|t| t.to_rfc3339()),
+            display_currency: wallet.display_currency,
+            max_webhooks_per_minute: wallet.max_webhooks_per_minute,
+            webhook_stream: wallet.webhook_stream,
+            balance_thresholds: wallet
+                .balance_thresholds
+                .iter()
+                .map(|t| t.normalize().to_string())
+                .collect(),
+            webhook_signature_header: wallet
+                .webhook_signature_header
+                .unwrap_or_else(|| DEFAULT_SIGNATURE_HEADER.to_string()),
+            webhook_signature_encoding: wallet.webhook_signature_encoding,
+            group_id: wallet.group_id,
+            payment_references: wallet.payment_references,
+            payload_redactions: wallet.payload_redactions,
+            timezone: wallet.timezone,
+        }
+    }
+}
+
+/// An entity as recorded in the audit log: its API representation
+pub(crate) fn audit_snapshot<T: Serialize>(value: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+// Registration response; initial_sync is present when sync_now was requested.
+// created is false when the wallet was already registered.
+#[derive(Debug, Serialize)]
+pub struct CreateWalletResponse {
+    #[serde(flatten)]
+    pub wallet: WalletResponse,
+    pub created: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_sync: Option<InitialSync>,
+}
+
+// Outcome of the inline first sync. transactions_found is null when the sync
+// didn't finish; the background loop picks up the rest.
+#[derive(Debug, Serialize)]
+pub struct InitialSync {
+    pub transactions_found: Option<u32>,
+    pub completed: bool,
+}
+
+/// Run the bounded first sync of a registration. A new wallet's history
+/// predates registration, so it is stored without webhooks, the same as
+/// backfill; a wallet already registered (or un-archived) is notified as the
+/// background loop would.
+async fn initial_sync(
+    sync: &SyncService,
+    wallet: &Wallet,
+    created: bool,
+    timeout: std::time::Duration,
+) -> InitialSync {
+    let outcome =
+        tokio::time::timeout(timeout, sync.sync_wallet(wallet, INITIAL_SYNC_LIMIT, !created))
+            .await;
+
+    match outcome {
+        Ok(Ok((new_txs, _))) => InitialSync { transactions_found: Some(new_txs), completed: true },
+        Ok(Err(e)) => {
+            tracing::warn!(wallet = %wallet.address, error = %e, "Initial sync failed");
+            InitialSync { transactions_found: None, completed: false }
+        }
+        Err(_) => {
+            tracing::warn!(wallet = %wallet.address, "Initial sync timed out");
+            InitialSync { transactions_found: None, completed: false }
+        }
+    }
 }
 
 pub async fn create_wallet(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<CreateWalletRequest>,
+) -> Result<(StatusCode, Json<CreateWalletResponse>), AppError> {
+    // Validate address for its chain and store its canonical form
+    let address = crate::services::chain::validate_wallet_address(req.chain, &req.address)?;
+
+    let settings = wallet_settings(req.settings, state.webhook.stream_enabled())?;
+    ensure_group_exists(&state, &settings).await?;
+
+    // A re-registration updates the wallet, so its audit entry diffs against it
+    let before = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .filter(|existing| existing.chain == req.chain)
+        .map(WalletResponse::from);
+
+    let CreatedWallet { wallet, created } =
+        WalletRepository::create(&state.db.pool, req.chain, &address, &settings).await?;
+
+    let initial_sync = if req.sync_now {
+        Some(initial_sync(&state.sync, &wallet, created, INITIAL_SYNC_TIMEOUT).await)
+    } else {
+        None
+    };
+
+    let response = WalletResponse::from(wallet);
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet.create",
+            "wallet",
+            &address,
+            before.as_ref().and_then(audit_snapshot),
+            audit_snapshot(&response),
+        )
+        .await;
+
+    // 201 for a new registration, 200 when it updated an existing wallet
+    let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(CreateWalletResponse { wallet: response, created, initial_sync })))
+}
+
+pub async fn update_wallet(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(address): Path<String>,
+    Json(req): Json<UpdateWalletRequest>,
 ) -> Result<Json<WalletResponse>, AppError> {
     // Validate address
-    crate::services::solana::SolanaClient::validate_address(&req.address)?;
-
-    let wallet = WalletRepository::create(
-        &state.db.pool,
-        &req.address,
-        req.webhook_url.as_deref(),
-    )
-    .await?;
-
-    Ok(Json(WalletResponse {
-        address: wallet.address,
-        webhook_url: wallet.webhook_url,
-        created_at: wallet.created_at.to_rfc3339(),
-    }))
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let settings = wallet_settings(req, state.webhook.stream_enabled())?;
+    ensure_group_exists(&state, &settings).await?;
+
+    let before = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .map(WalletResponse::from);
+
+    let wallet = WalletRepository::update(&state.db.pool, &address, &settings)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let response = WalletResponse::from(wallet);
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet.update",
+            "wallet",
+            &address,
+            before.as_ref().and_then(audit_snapshot),
+            audit_snapshot(&response),
+        )
+        .await;
+
+    Ok(Json(response))
+}
+
+// List wallets query params
+#[derive(Debug, Deserialize)]
+pub struct ListWalletsQuery {
+    pub include_archived: Option<bool>,
+}
+
+// List wallets response
+#[derive(Debug, Serialize)]
+pub struct WalletsResponse {
+    pub wallets: Vec<WalletResponse>,
+    pub count: usize,
+}
+
+pub async fn list_wallets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListWalletsQuery>,
+) -> Result<Json<WalletsResponse>, AppError> {
+    let wallets =
+        WalletRepository::list(&state.db.pool, query.include_archived.unwrap_or(false)).await?;
+
+    let wallets: Vec<WalletResponse> = wallets.into_iter().map(Into::into).collect();
+    let count = wallets.len();
+
+    Ok(Json(WalletsResponse { wallets, count }))
+}
+
+/// Archive (soft-delete) a wallet. History stays queryable; syncing and
+/// webhooks stop until the address is registered again, and payments received
+/// in between are never notified.
+pub async fn delete_wallet(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(address): Path<String>,
+) -> Result<Json<WalletResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let before = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .map(WalletResponse::from);
+
+    let wallet = WalletRepository::archive(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let response = WalletResponse::from(wallet);
+    state
+        .audit
+        .record(
+            &audit,
+            "wallet.archive",
+            "wallet",
+            &address,
+            before.as_ref().and_then(audit_snapshot),
+            audit_snapshot(&response),
+        )
+        .await;
+
+    Ok(Json(response))
 }
 
 // Balance response
@@ -61,40 +666,420 @@ pub struct BalanceResponse {
     pub token: String,
     pub symbol: String,
     pub amount: String,
+    /// Exact amount in base units, for on-chain math without decimal parsing
+    pub amount_raw: u64,
+    pub decimals: u8,
+    /// False when the wallet has no USDC token account yet
+    pub account_exists: bool,
     pub usd_value: String,
+    /// Market USDC/USD price used for usd_value, when strict_price was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usdc_usd_price: Option<String>,
+    /// Value in the currency requested with ?fiat=
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+    pub sol_balance: String,
+    /// When the balances were read from the chain; may trail by the cache TTL
+    pub cached_at: DateTime<Utc>,
+    /// Present when the wallet has a display currency with a known rate
+    #[serde(flatten)]
+    pub display: Option<DisplayAmount>,
+}
+
+// Balance query params
+#[derive(Debug, Default, Deserialize)]
+pub struct BalanceQuery {
+    /// ISO currency to value the balance in, e.g. "eur"
+    pub fiat: Option<String>,
+    /// Value USDC at its market price instead of 1:1 with USD
+    pub strict_price: Option<bool>,
+    /// Read the chain instead of the balance cache
+    pub fresh: Option<bool>,
 }
 
 pub async fn get_balance(
     State(state): State<Arc<AppState>>,
     Path(address): Path<String>,
+    Query(query): Query<BalanceQuery>,
 ) -> Result<Json<BalanceResponse>, AppError> {
     // Validate address
     crate::services::solana::SolanaClient::validate_address(&address)?;
 
-    // Get balance from Solana
-    let balance = state.solana.get_usdc_balance(&address).await?;
+    // Balances work for any address; registered wallets may add a display currency
+    let display_currency = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .and_then(|w| w.display_currency);
+
+    let balance = fetch_balance(&state, &address, display_currency.as_deref(), &query).await?;
+
+    Ok(Json(balance))
+}
+
+/// USDC and SOL balances of an address, with the display amount when a
+/// currency is given and its rate is known. `query` optionally asks for a
+/// fiat valuation and the market USDC price; those fail the request when
+/// unavailable rather than being silently dropped.
+async fn fetch_balance(
+    state: &AppState,
+    address: &str,
+    display_currency: Option<&str>,
+    query: &BalanceQuery,
+) -> Result<BalanceResponse, AppError> {
+    // Check the currency before spending RPC calls on the balance
+    let fiat = match query.fiat.as_deref() {
+        Some(fiat) => {
+            let fiat = fiat.to_ascii_uppercase();
+            if fiat != "USD" && !SUPPORTED_DISPLAY_CURRENCIES.contains(&fiat.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Unsupported fiat currency '{}'. Supported: USD, {}",
+                    fiat,
+                    SUPPORTED_DISPLAY_CURRENCIES.join(", ")
+                )));
+            }
+            Some(fiat)
+        }
+        None => None,
+    };
+
+    // Get USDC and SOL balances, cached briefly for polling clients
+    let cached = state
+        .balances
+        .get(address, query.fresh.unwrap_or(false))
+        .await?;
+    let balance = &cached.usdc;
+
+    // USDC is 1:1 with USD unless the market price was asked for
+    let usdc_usd_price = if query.strict_price.unwrap_or(false) {
+        Some(state.fx.usdc_usd_price().await?)
+    } else {
+        None
+    };
+    let usd_value = match usdc_usd_price {
+        Some(price) => (balance.amount * price).round_dp(balance.decimals as u32),
+        None => balance.amount,
+    };
+
+    let fiat_value = match fiat.as_deref() {
+        Some("USD") => Some(usd_value.to_string()),
+        Some(currency) => {
+            let rate = state.fx.rate(currency).await.ok_or_else(|| {
+                AppError::ExternalApi(format!("No USD to {} exchange rate available", currency))
+            })?;
+            Some(rate.convert(usd_value).to_string())
+        }
+        None => None,
+    };
+
+    let display = state.fx.display(display_currency, usd_value).await;
+    let token = state.tokens.resolve(state.solana.usdc_mint()).await;
+
+    Ok(BalanceResponse {
+        address: address.to_string(),
+        token: token.name,
+        symbol: token.symbol,
+        amount: balance.amount.to_string(),
+        amount_raw: balance.amount_raw,
+        decimals: balance.decimals,
+        account_exists: balance.account_exists,
+        usd_value: usd_value.to_string(),
+        usdc_usd_price: usdc_usd_price.map(|p| p.normalize().to_string()),
+        fiat_value,
+        fiat_currency: fiat,
+        sol_balance: Decimal::from_i128_with_scale(cached.sol_lamports as i128, 9).to_string(), // SOL has 9 decimals
+        cached_at: cached.cached_at,
+        display,
+    })
+}
+
+/// Longest range served by the balance history endpoint
+const MAX_HISTORY_DAYS: i64 = 730;
+
+// Balance history query params
+#[derive(Debug, Deserialize)]
+pub struct BalanceHistoryQuery {
+    pub days: Option<i64>,
+    pub granularity: Option<String>,
+}
+
+// Balance history response
+#[derive(Debug, Serialize)]
+pub struct BalanceHistoryResponse {
+    pub address: String,
+    pub granularity: String,
+    pub points: Vec<BalancePoint>,
+}
+
+/// Daily (or weekly) USDC balance of a wallet over the last `days`, from the
+/// snapshots recorded by the background loop. Days without a snapshot repeat
+/// the previous one; the series starts at the first snapshot in range.
+pub async fn get_balance_history(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(query): Query<BalanceHistoryQuery>,
+) -> Result<Json<BalanceHistoryResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    // Check if wallet is registered
+    WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let days = query.days.unwrap_or(30);
+    if !(1..=MAX_HISTORY_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_HISTORY_DAYS
+        )));
+    }
+
+    let granularity = query.granularity.unwrap_or_else(|| "daily".to_string());
+    let step_days = match granularity.as_str() {
+        "daily" => 1,
+        "weekly" => 7,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown granularity '{}', expected daily or weekly",
+                other
+            )))
+        }
+    };
 
-    Ok(Json(BalanceResponse {
+    let to = Utc::now().date_naive();
+    let from = to - chrono::Duration::days(days - 1);
+    let snapshots = BalanceSnapshotRepository::find_since(&state.db.pool, &address, from).await?;
+
+    Ok(Json(BalanceHistoryResponse {
         address,
-        token: "USD Coin".to_string(),
-        symbol: "USDC".to_string(),
-        amount: balance.amount.to_string(),
-        usd_value: balance.amount.to_string(), // USDC is 1:1 with USD
+        granularity,
+        points: carry_forward(&snapshots, from, to, step_days),
+    }))
+}
+
+// Wallet summary response
+#[derive(Debug, Serialize)]
+pub struct WalletSummaryResponse {
+    pub address: String,
+    pub balance: Option<BalanceResponse>,
+    pub stats: Option<TransactionStats>,
+    /// Why a section is missing; `None` for sections that loaded
+    pub errors: SummaryErrors,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SummaryErrors {
+    pub balance: Option<String>,
+    pub stats: Option<String>,
+}
+
+/// Balance and transaction stats in one call for dashboard overviews. The
+/// sections load concurrently and a failing one is reported in `errors`
+/// instead of failing the whole response.
+pub async fn get_wallet_summary(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<WalletSummaryResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    // Check if wallet is registered
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let plain = BalanceQuery::default();
+    let (balance, stats) = tokio::join!(
+        fetch_balance(&state, &address, wallet.display_currency.as_deref(), &plain),
+        TransactionRepository::stats_for_wallet(&state.db.pool, &address),
+    );
+
+    if let Err(e) = &balance {
+        tracing::warn!(wallet = %address, error = %e, "Summary balance unavailable");
+    }
+    if let Err(e) = &stats {
+        tracing::warn!(wallet = %address, error = %e, "Summary stats unavailable");
+    }
+
+    let errors = SummaryErrors {
+        balance: balance.as_ref().err().map(|e| e.to_string()),
+        stats: stats.as_ref().err().map(|e| e.to_string()),
+    };
+
+    Ok(Json(WalletSummaryResponse {
+        address,
+        balance: balance.ok(),
+        stats: stats.ok(),
+        errors,
     }))
 }
 
+// Wallet status response
+#[derive(Debug, Serialize)]
+pub struct WalletStatusResponse {
+    pub address: String,
+    pub health: WalletHealth,
+    pub registered_at: DateTime<Utc>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_transaction_at: Option<DateTime<Utc>>,
+    pub last_webhook_delivered_at: Option<DateTime<Utc>>,
+    pub sync_error_streak: i32,
+    pub last_sync_error: Option<String>,
+    pub webhook_circuit: CircuitStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletHealth {
+    Ok,
+    /// Syncs are failing or the webhook circuit is open
+    Degraded,
+    /// No successful sync within the staleness threshold
+    Stale,
+}
+
+/// Cheap heartbeat for uptime monitors: whether payment detection is working
+/// for a wallet, answered from the database alone (no RPC calls).
+pub async fn get_wallet_status(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<WalletStatusResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let (last_transaction_at, last_webhook_delivered_at) = tokio::try_join!(
+        TransactionRepository::last_block_time(&state.db.pool, &address),
+        WebhookEventRepository::last_delivered_for_wallet(&state.db.pool, &address),
+    )?;
+
+    let webhook_circuit = state.webhook.circuit_status(&address);
+
+    // A wallet that was never synced counts from its registration
+    let stale_after = chrono::Duration::seconds(state.config.wallet_stale_after_secs);
+    let synced_or_registered = wallet.last_synced_at.unwrap_or(wallet.created_at);
+    let health = if Utc::now() - synced_or_registered > stale_after {
+        WalletHealth::Stale
+    } else if wallet.sync_error_streak >= state.config.wallet_degraded_error_streak
+        || webhook_circuit.state != CircuitState::Closed
+    {
+        WalletHealth::Degraded
+    } else {
+        WalletHealth::Ok
+    };
+
+    Ok(Json(WalletStatusResponse {
+        address,
+        health,
+        registered_at: wallet.created_at,
+        last_synced_at: wallet.last_synced_at,
+        last_transaction_at,
+        last_webhook_delivered_at,
+        sync_error_streak: wallet.sync_error_streak,
+        last_sync_error: wallet.last_sync_error,
+        webhook_circuit,
+    }))
+}
+
+/// Whether the wallet holds enough SOL (MIN_SOL_BALANCE_LAMPORTS) to pay fees
+pub async fn get_fee_readiness(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<FeeReadiness>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let readiness = state
+        .solana
+        .check_fee_readiness(&address, state.config.min_sol_balance_lamports)
+        .await?;
+
+    Ok(Json(readiness))
+}
+
 // Transactions query params
 #[derive(Debug, Deserialize)]
 pub struct TransactionsQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// `false` serves stored transactions without the inline sync
+    pub sync: Option<bool>,
+    /// Recent signatures the inline sync checks; defaults to `SYNC_LIMIT`
+    pub sync_limit: Option<usize>,
+    /// IANA zone for the `_local` timestamps; defaults to the wallet's
+    pub timezone: Option<String>,
 }
 
 // Transactions response
 #[derive(Debug, Serialize)]
 pub struct TransactionsResponse {
-    pub transactions: Vec<Transaction>,
+    /// Zone of the `_local` timestamps, when they're included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub transactions: Vec<TransactionView>,
+    /// Rows in this page
     pub count: usize,
+    #[serde(flatten)]
+    pub page: PageInfo,
+}
+
+// Paging metadata for list responses
+#[derive(Debug, Serialize)]
+pub struct PageInfo {
+    /// Rows matching the query across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl PageInfo {
+    fn new(total: i64, limit: i64, offset: i64, count: usize) -> Self {
+        Self {
+            total,
+            limit,
+            offset,
+            has_more: offset + (count as i64) < total,
+        }
+    }
+}
+
+// A transaction with its token's symbol, its amount in the wallet's
+// display currency and its timestamps in a requested zone, if any
+#[derive(Debug, Serialize)]
+pub struct TransactionView {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub display: Option<DisplayAmount>,
+    #[serde(flatten)]
+    pub local: Option<LocalTimestamps>,
+}
+
+// A transaction's timestamps in a time zone, next to the canonical UTC ones
+#[derive(Debug, Serialize)]
+pub struct LocalTimestamps {
+    pub block_time_local: String,
+    pub created_at_local: String,
+}
+
+impl LocalTimestamps {
+    pub(crate) fn new(transaction: &Transaction, zone: Tz) -> Self {
+        Self {
+            block_time_local: local_time(transaction.block_time, zone),
+            created_at_local: local_time(transaction.created_at, zone),
+        }
+    }
+}
+
+/// An instant as RFC 3339 with `zone`'s offset at that instant
+pub(crate) fn local_time(at: DateTime<Utc>, zone: Tz) -> String {
+    at.with_timezone(&zone).to_rfc3339()
 }
 
 pub async fn get_transactions(
@@ -106,45 +1091,27 @@ pub async fn get_transactions(
     crate::services::solana::SolanaClient::validate_address(&address)?;
 
     // Check if wallet is registered
-    let wallet = WalletRepository::find_by_address(&state.db.pool, &address).await?;
-    if wallet.is_none() {
-        return Err(AppError::NotFound(format!(
-            "Wallet {} not registered. POST /wallets to register it first.",
-            address
-        )));
-    }
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Wallet {} not registered. POST /wallets to register it first.",
+                address
+            ))
+        })?;
 
-    // Sync recent transactions from Solana before returning
-    let sync_limit = 20; // Fetch last 20 signatures to check
-    match state
-        .solana
-        .sync_wallet_transactions(&address, sync_limit)
-        .await
-    {
-        Ok(parsed_txs) => {
-            // Store each transaction (idempotent - ON CONFLICT DO NOTHING)
-            for tx in parsed_txs {
-                let tx_type = if tx.tx_type == "send" {
-                    TransactionType::Send
-                } else {
-                    TransactionType::Receive
-                };
-
-                let _ = TransactionRepository::create(
-                    &state.db.pool,
-                    &tx.signature,
-                    &tx.wallet_address,
-                    tx_type,
-                    tx.amount,
-                    &tx.token_mint,
-                    &tx.counterparty,
-                    TransactionStatus::Confirmed,
-                    tx.block_time,
-                )
-                .await;
-            }
-        }
-        Err(e) => {
+    // The requested zone, else the wallet's default, checked before any RPC work
+    let timezone = query.timezone.or_else(|| wallet.timezone.clone());
+    let zone = timezone.as_deref().map(parse_timezone).transpose()?;
+
+    // Sync recent transactions from Solana before returning. This goes through
+    // the same path as the background loop so a transaction is stored (and its
+    // webhook fired) exactly once, whichever side wins the insert.
+    // Archived wallets only serve their stored history, and `sync=false`
+    // skips the RPC round-trip for clients happy with what's stored.
+    let sync_limit = query.sync_limit.unwrap_or(SYNC_LIMIT).clamp(1, MAX_INLINE_SYNC_LIMIT);
+    if query.sync.unwrap_or(true) && !wallet.is_archived() {
+        if let Err(e) = state.sync.sync_wallet(&wallet, sync_limit, true).await {
             // Log sync error but continue to return cached data
             tracing::warn!("Failed to sync transactions from Solana: {}", e);
         }
@@ -153,29 +1120,115 @@ pub async fn get_transactions(
     let limit = query.limit.unwrap_or(50).min(100);
     let offset = query.offset.unwrap_or(0);
 
-    let transactions =
-        TransactionRepository::find_by_wallet(&state.db.pool, &address, limit, offset).await?;
+    let (transactions, total) = tokio::try_join!(
+        TransactionRepository::find_by_wallet(&state.db.pool, &address, limit, offset),
+        TransactionRepository::count_by_wallet(&state.db.pool, &address),
+    )?;
+
+    // One rate lookup per page; all rows convert at the same rate
+    let rate = match wallet.display_currency.as_deref() {
+        Some(currency) => state.fx.rate(currency).await,
+        None => None,
+    };
+    // Pages almost always hold a single mint, so resolve each one once
+    let mut symbols: HashMap<String, String> = HashMap::new();
+    for transaction in &transactions {
+        if !symbols.contains_key(&transaction.token_mint) {
+            let token = state.tokens.resolve(&transaction.token_mint).await;
+            symbols.insert(transaction.token_mint.clone(), token.symbol);
+        }
+    }
+
+    let transactions: Vec<TransactionView> = transactions
+        .into_iter()
+        .map(|transaction| TransactionView {
+            display: rate.as_ref().map(|r| r.display(transaction.amount)),
+            symbol: symbols[&transaction.token_mint].clone(),
+            local: zone.map(|zone| LocalTimestamps::new(&transaction, zone)),
+            transaction,
+        })
+        .collect();
 
     let count = transactions.len();
 
     Ok(Json(TransactionsResponse {
+        timezone,
         transactions,
         count,
+        page: PageInfo::new(total, limit, offset, count),
     }))
 }
 
+// Transaction lookup query params
+#[derive(Debug, Deserialize)]
+pub struct TransactionLookupQuery {
+    /// Registered wallet to fetch the signature for if it isn't stored yet
+    pub wallet: Option<String>,
+}
+
+pub async fn get_transaction(
+    State(state): State<Arc<AppState>>,
+    Path(signature): Path<String>,
+    Query(query): Query<TransactionLookupQuery>,
+) -> Result<Json<Transaction>, AppError> {
+    // Validate signature
+    crate::services::solana::SolanaClient::validate_signature(&signature)?;
+
+    if let Some(tx) = TransactionRepository::find_by_signature(&state.db.pool, &signature).await? {
+        return Ok(Json(tx));
+    }
+
+    let not_found = || AppError::NotFound(format!("Transaction {} not found", signature));
+
+    // Not stored yet: fetch it on demand for the wallet the caller names
+    let address = query.wallet.ok_or_else(not_found)?;
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .filter(|w| !w.is_archived())
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not registered", address)))?;
+
+    let tx = state
+        .sync
+        .sync_signature(&wallet, &signature)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(tx))
+}
+
 // Webhook events query params
 #[derive(Debug, Deserialize)]
 pub struct WebhookEventsQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    /// Created at or after
+    pub since: Option<DateTime<Utc>>,
+    /// Created before
+    pub until: Option<DateTime<Utc>>,
 }
 
 // Webhook events response
 #[derive(Debug, Serialize)]
 pub struct WebhookEventsResponse {
     pub events: Vec<WebhookEvent>,
+    /// Rows in this page
     pub count: usize,
+    #[serde(flatten)]
+    pub page: PageInfo,
+    pub filters: AppliedWebhookEventFilters,
+}
+
+// The filters a webhook event list was narrowed by; null when not applied
+#[derive(Debug, Serialize)]
+pub struct AppliedWebhookEventFilters {
+    pub status: Option<WebhookStatus>,
+    pub event_type: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
 }
 
 pub async fn get_webhook_events(
@@ -195,11 +1248,146 @@ pub async fn get_webhook_events(
     let limit = query.limit.unwrap_or(50).min(100);
     let offset = query.offset.unwrap_or(0);
 
-    let events =
-        WebhookEventRepository::find_by_wallet(&state.db.pool, &address, limit, offset).await?;
+    let status = query
+        .status
+        .as_deref()
+        .map(WebhookStatus::from_str)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    if let Some(event_type) = query.event_type.as_deref() {
+        if !WEBHOOK_EVENT_TYPES.contains(&event_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown event_type '{}', expected one of {}",
+                event_type,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            )));
+        }
+    }
+
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err(AppError::BadRequest("since must not be after until".into()));
+        }
+    }
+
+    let filter = WebhookEventFilter {
+        status,
+        event_type: query.event_type.as_deref(),
+        since: query.since,
+        until: query.until,
+    };
+    let (events, total) = tokio::try_join!(
+        WebhookEventRepository::find_by_wallet_filtered(
+            &state.db.pool,
+            &address,
+            &filter,
+            limit,
+            offset,
+        ),
+        WebhookEventRepository::count_by_wallet(&state.db.pool, &address, &filter),
+    )?;
     let count = events.len();
 
-    Ok(Json(WebhookEventsResponse { events, count }))
+    Ok(Json(WebhookEventsResponse {
+        events,
+        count,
+        page: PageInfo::new(total, limit, offset, count),
+        filters: AppliedWebhookEventFilters {
+            status,
+            event_type: query.event_type,
+            since: query.since,
+            until: query.until,
+        },
+    }))
+}
+
+pub async fn get_webhook_event(
+    State(state): State<Arc<AppState>>,
+    Path((address, id)): Path<(String, String)>,
+) -> Result<Json<WebhookEvent>, AppError> {
+    // Validate address and event id
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+    let id = sqlx::types::Uuid::from_str(&id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid webhook event id: {}", id)))?;
+
+    // Only return the event under the wallet it belongs to
+    let event = WebhookEventRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .filter(|event| event.wallet_address == address)
+        .ok_or_else(|| AppError::NotFound(format!("Webhook event {} not found", id)))?;
+
+    Ok(Json(event))
+}
+
+// Inspection link query
+#[derive(Debug, Deserialize)]
+pub struct InspectWebhookEventQuery {
+    pub token: String,
+}
+
+// Webhook event inspection response
+#[derive(Debug, Serialize)]
+pub struct WebhookEventInspection {
+    pub event_id: sqlx::types::Uuid,
+    pub wallet_address: String,
+    pub event_type: String,
+    pub created_at: DateTime<Utc>,
+    /// The exact bytes signed and delivered
+    pub body: String,
+    /// Recomputed with the wallet's current signature settings, so it only
+    /// differs from what was sent if those or the secret changed since
+    pub signature_header: WebhookHeader,
+    pub status: WebhookStatus,
+    pub attempts: i32,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_response_status: Option<i32>,
+    pub last_response_time_ms: Option<i32>,
+    pub last_response_body: Option<String>,
+}
+
+/// What one webhook event sent and how its deliveries went, for holders of
+/// an inspection link from the admin API. The token is the only credential;
+/// a malformed, forged or expired one is a plain 404 so it can't be used to
+/// probe which events exist.
+pub async fn inspect_webhook_event(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InspectWebhookEventQuery>,
+) -> Result<Json<WebhookEventInspection>, AppError> {
+    let not_found = || AppError::NotFound("Inspection link not found or expired".into());
+
+    let id = state
+        .webhook
+        .verify_inspection_token(&query.token, Utc::now())
+        .ok_or_else(not_found)?;
+    let event = WebhookEventRepository::find_by_id(&state.db.pool, id)
+        .await?
+        .ok_or_else(not_found)?;
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &event.wallet_address)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let signature_header = state.webhook.event_signature_header(&wallet, &event)?;
+    let body = String::from_utf8_lossy(&event.body_bytes()?).into_owned();
+
+    Ok(Json(WebhookEventInspection {
+        event_id: event.id,
+        wallet_address: event.wallet_address,
+        event_type: event.event_type,
+        created_at: event.created_at,
+        body,
+        signature_header,
+        status: event.status,
+        attempts: event.attempts,
+        last_attempt_at: event.last_attempt_at,
+        delivered_at: event.delivered_at,
+        last_error: event.last_error,
+        last_response_status: event.last_response_status,
+        last_response_time_ms: event.last_response_time_ms,
+        last_response_body: event.last_response_body,
+    }))
 }
 
 // Test webhook response
@@ -241,14 +1429,37 @@ pub async fn test_webhook(
     }
 }
 
+/// Show the exact request (URL, headers with the computed signature, body)
+/// a test webhook would send, without calling the endpoint or recording an
+/// event
+pub async fn preview_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<WebhookRequest>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    // Get wallet
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+
+    let request = state.webhook.preview_test_webhook(&wallet).await?;
+
+    Ok(Json(request))
+}
+
 // Detailed health response
 #[derive(Debug, Serialize)]
 pub struct DetailedHealthResponse {
     pub status: String,
     pub database: HealthStatus,
+    pub database_pool: PoolStatus,
     pub solana_rpc: HealthStatus,
     pub background_sync: BackgroundSyncStatus,
     pub webhooks: WebhookHealthStats,
+    /// Audit log writes that failed since startup
+    pub audit_write_failures: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -261,6 +1472,9 @@ pub struct HealthStatus {
 pub struct BackgroundSyncStatus {
     pub running: bool,
     pub last_sync: Option<String>,
+    /// Whether this instance holds the leader lock and actually runs the sync
+    pub leader: bool,
+    pub lock_namespace: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -268,6 +1482,9 @@ pub struct WebhookHealthStats {
     pub pending: i64,
     pub delivered: i64,
     pub failed: i64,
+    pub deferred: u64,
+    pub skipped_as_dust: u64,
+    pub circuits: Vec<CircuitStatus>,
 }
 
 pub async fn detailed_health(
@@ -316,15 +1533,285 @@ pub async fn detailed_health(
     Ok(Json(DetailedHealthResponse {
         status: overall_status.into(),
         database: db_status,
+        database_pool: state.db.pool_status(),
         solana_rpc: solana_status,
         background_sync: BackgroundSyncStatus {
             running: true, // Background sync is always running if server is up
             last_sync: None, // Could track this in the future
+            leader: state.leader.is_leader(),
+            lock_namespace: state.leader.namespace().to_string(),
         },
         webhooks: WebhookHealthStats {
             pending: webhook_stats.pending,
             delivered: webhook_stats.delivered,
             failed: webhook_stats.failed,
+            deferred: webhook_stats.deferred,
+            skipped_as_dust: webhook_stats.skipped_as_dust,
+            circuits: webhook_stats.circuits,
         },
+        audit_write_failures: state.audit.failures(),
     }))
 }
+
+/// Server-Sent Events stream of a wallet's payment events for checkout pages.
+/// Informational only: the signed webhook remains the source of truth.
+pub async fn stream_wallet_events(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    // Check if wallet exists
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &address).await?;
+    if wallet.is_none() {
+        return Err(AppError::NotFound(format!("Wallet {} not found", address)));
+    }
+
+    let (receiver, guard) = state.events.subscribe(&address)?.into_parts();
+
+    // The connection guard lives in the stream and frees the slot on disconnect
+    let stream = BroadcastStream::new(receiver).filter_map(move |message| {
+        let _connection = &guard;
+        match message {
+            Ok(event) if event.wallet_address == address => Event::default()
+                .event(event.payload.event.clone())
+                .json_data(&event.payload)
+                .ok()
+                .map(Ok),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                tracing::warn!(wallet = %address, skipped, "Event stream consumer lagged");
+                None
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(std::time::Duration::from_secs(15))
+            .text("keep-alive"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::AuditLogRepository;
+    use crate::services::testing::{receive, register_wallet, signature, Harness, WALLET};
+    use sqlx::PgPool;
+    use std::time::Duration;
+
+    async fn event_count(pool: &PgPool) -> usize {
+        WebhookEventRepository::find_by_wallet(pool, WALLET, 100, 0).await.unwrap().len()
+    }
+
+    #[sqlx::test]
+    async fn initial_sync_of_a_new_wallet_sends_no_webhooks(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        harness.chain.push_transaction(receive(&signature(1), 1_000_000));
+
+        let outcome = initial_sync(&harness.sync, &wallet, true, INITIAL_SYNC_TIMEOUT).await;
+        assert_eq!(outcome.transactions_found, Some(1));
+        assert!(outcome.completed);
+        assert_eq!(event_count(&pool).await, 0);
+    }
+
+    #[sqlx::test]
+    async fn initial_sync_of_an_existing_wallet_notifies(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        harness.chain.push_transaction(receive(&signature(1), 1_000_000));
+
+        let outcome = initial_sync(&harness.sync, &wallet, false, INITIAL_SYNC_TIMEOUT).await;
+        assert_eq!(outcome.transactions_found, Some(1));
+        assert!(outcome.completed);
+        assert_eq!(event_count(&pool).await, 1);
+    }
+
+    #[sqlx::test]
+    async fn initial_sync_gives_up_at_its_timeout(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        harness.chain.push_transaction(receive(&signature(1), 1_000_000));
+        harness.chain.set_latency(Duration::from_millis(500));
+
+        let outcome = initial_sync(&harness.sync, &wallet, true, Duration::from_millis(50)).await;
+        assert_eq!(outcome.transactions_found, None);
+        assert!(!outcome.completed);
+        assert!(!TransactionRepository::exists(&pool, &signature(1)).await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn initial_sync_reports_a_failed_sync_as_incomplete(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        harness.chain.set_error(Some("node is behind"));
+
+        let outcome = initial_sync(&harness.sync, &wallet, true, INITIAL_SYNC_TIMEOUT).await;
+        assert_eq!(outcome.transactions_found, None);
+        assert!(!outcome.completed);
+    }
+
+    async fn failing_components(db: &Database, harness: &Harness) -> Vec<&'static str> {
+        readiness_failures(db, harness.chain.as_ref(), &harness.sync)
+            .await
+            .into_iter()
+            .map(|failing| failing.component)
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn readiness_turns_ready_once_rpc_and_the_first_sync_are_up(pool: PgPool) {
+        let db = Database::from_pool(pool.clone());
+        let harness = Harness::new(&pool);
+        harness.chain.set_error(Some("connection refused"));
+
+        assert_eq!(
+            failing_components(&db, &harness).await,
+            ["solana_rpc", "background_sync"]
+        );
+
+        harness.chain.set_error(None);
+        let background = harness.sync.clone().start_background_sync();
+        for _ in 0..100 {
+            if harness.sync.first_cycle_completed() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        harness.sync.shutdown();
+        background.abort();
+
+        assert!(failing_components(&db, &harness).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn readiness_reports_pending_migrations(pool: PgPool) {
+        let db = Database::from_pool(pool.clone());
+        let harness = Harness::new(&pool);
+        // As if this build shipped a migration the database hasn't run
+        let latest = db.migration_status().await.unwrap().latest_version.unwrap();
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+            .bind(latest)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let failing = readiness_failures(&db, harness.chain.as_ref(), &harness.sync).await;
+        assert_eq!(failing[0].component, "migrations");
+        assert_eq!(failing[0].message, format!("pending [{}], failed []", latest));
+    }
+
+    /// A transaction that landed at `block_time` and was stored a second later
+    fn transaction_at(block_time: &str) -> Transaction {
+        let block_time: DateTime<Utc> = block_time.parse().unwrap();
+        Transaction {
+            signature: signature(1),
+            chain: crate::domain::Chain::Solana,
+            wallet_address: WALLET.to_string(),
+            tx_type: crate::domain::TransactionType::Receive,
+            amount: Decimal::ONE,
+            amount_raw: 1_000_000,
+            decimals: 6,
+            token_mint: crate::services::testing::USDC_MINT.to_string(),
+            counterparty: crate::services::testing::SENDER.to_string(),
+            status: crate::domain::TransactionStatus::Confirmed,
+            block_time,
+            created_at: block_time + chrono::Duration::seconds(1),
+            transfer_legs: serde_json::json!([]),
+            memo: None,
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn local_timestamps_skip_the_spring_forward_gap() {
+        // Berlin's clocks jump from 02:00 to 03:00 at 01:00Z on 2025-03-30
+        let berlin = parse_timezone("Europe/Berlin").unwrap();
+
+        let before = LocalTimestamps::new(&transaction_at("2025-03-30T00:59:59Z"), berlin);
+        assert_eq!(before.block_time_local, "2025-03-30T01:59:59+01:00");
+        assert_eq!(before.created_at_local, "2025-03-30T03:00:00+02:00");
+
+        let after = LocalTimestamps::new(&transaction_at("2025-03-30T01:00:00Z"), berlin);
+        assert_eq!(after.block_time_local, "2025-03-30T03:00:00+02:00");
+    }
+
+    #[test]
+    fn local_timestamps_in_the_repeated_fall_back_hour_differ_by_offset() {
+        // 02:30 happens twice in Berlin on 2025-10-26
+        let berlin = parse_timezone("Europe/Berlin").unwrap();
+
+        let first = LocalTimestamps::new(&transaction_at("2025-10-26T00:30:00Z"), berlin);
+        let second = LocalTimestamps::new(&transaction_at("2025-10-26T01:30:00Z"), berlin);
+        assert_eq!(first.block_time_local, "2025-10-26T02:30:00+02:00");
+        assert_eq!(second.block_time_local, "2025-10-26T02:30:00+01:00");
+    }
+
+    #[test]
+    fn unknown_timezones_are_rejected() {
+        for zone in ["Mars/Olympus_Mons", "CEST", "+02:00"] {
+            assert!(matches!(parse_timezone(zone), Err(AppError::BadRequest(_))), "{}", zone);
+        }
+
+        let request = |zone: &str| -> UpdateWalletRequest {
+            serde_json::from_value(serde_json::json!({ "timezone": zone })).unwrap()
+        };
+        let rejected = wallet_settings(request("Mars/Olympus_Mons"), false);
+        assert!(matches!(rejected, Err(AppError::BadRequest(_))));
+        let cleared = wallet_settings(request(""), false).unwrap();
+        assert_eq!(cleared.timezone.as_deref(), Some(""));
+        let berlin = wallet_settings(request("Europe/Berlin"), false).unwrap();
+        assert_eq!(berlin.timezone.as_deref(), Some("Europe/Berlin"));
+    }
+
+    #[sqlx::test]
+    async fn re_registration_audits_the_wallet_it_replaced(pool: PgPool) {
+        let state = Harness::new(&pool).state(&pool);
+        let register = |webhook_url: &str| {
+            let req = serde_json::json!({ "address": WALLET, "webhook_url": webhook_url });
+            Json(serde_json::from_value::<CreateWalletRequest>(req).unwrap())
+        };
+        let admin = || AuditContext { actor: "admin".to_string(), request_id: None };
+
+        let first = "https://merchant.example/old";
+        create_wallet(State(state.clone()), admin(), register(first)).await.unwrap();
+        let second = "https://merchant.example/new";
+        let (status, _) =
+            create_wallet(State(state.clone()), admin(), register(second)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+
+        let entries =
+            AuditLogRepository::list(&pool, Some("wallet"), Some(WALLET), 10).await.unwrap();
+        let (reregistered, registered) = (&entries[0], &entries[1]);
+        assert_eq!(registered.before, None);
+        assert_eq!(
+            reregistered.before,
+            Some(serde_json::json!({ "webhook_url": first }))
+        );
+        assert_eq!(
+            reregistered.after,
+            Some(serde_json::json!({ "webhook_url": second }))
+        );
+    }
+
+    #[sqlx::test]
+    async fn registration_defaults_the_fields_a_request_omits(pool: PgPool) {
+        let state = Harness::new(&pool).state(&pool);
+        let req = serde_json::from_value(serde_json::json!({ "address": WALLET })).unwrap();
+        let admin = AuditContext { actor: "admin".to_string(), request_id: None };
+
+        let (status, Json(created)) = create_wallet(State(state), admin, Json(req)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(created.initial_sync.is_none());
+        let stored = WalletRepository::find_by_address(&pool, WALLET).await.unwrap().unwrap();
+        assert_eq!(stored.chain, Chain::Solana);
+        assert_eq!(stored.min_webhook_amount, Decimal::ZERO);
+        assert_eq!(stored.max_webhooks_per_minute, 60);
+        assert_eq!(stored.webhook_signature_encoding, SignatureEncoding::Hex);
+        assert!(stored.webhook_url.is_none() && stored.timezone.is_none());
+        assert_eq!(serde_json::to_value(&created.wallet).unwrap()["chain"], "solana");
+    }
+}
@@ -0,0 +1,387 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    json_diff, AmountMatchRule, PaymentLink, PaymentLinkCancelledPayload, PaymentLinkStatus,
+};
+use crate::api::extract::Json;
+use crate::error::AppError;
+use crate::repository::{PaymentLinkRepository, WalletRepository};
+use crate::services::audit::AuditContext;
+use crate::AppState;
+
+/// Random bytes per slug; 12 bytes is ~16 base58 characters
+const SLUG_BYTES: usize = 12;
+
+/// Maximum memo length (matches the column)
+const MAX_MEMO_LEN: usize = 200;
+
+/// Short, unguessable base58 slug
+fn generate_slug() -> String {
+    let random = uuid::Uuid::new_v4();
+    solana_sdk::bs58::encode(&random.as_bytes()[..SLUG_BYTES]).into_string()
+}
+
+// Create payment link request
+#[derive(Debug, Deserialize)]
+pub struct CreatePaymentLinkRequest {
+    pub wallet_address: String,
+    pub amount: Option<Decimal>,
+    pub memo: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reusable: bool,
+    /// Overrides PAYMENT_AMOUNT_TOLERANCE (USDC)
+    pub amount_tolerance: Option<Decimal>,
+    /// Overrides PAYMENT_AMOUNT_TOLERANCE_PCT (percent of amount)
+    pub amount_tolerance_pct: Option<Decimal>,
+}
+
+// Amend payment link request (omitted fields are left unchanged, an empty
+// memo clears it)
+#[derive(Debug, Deserialize)]
+pub struct UpdatePaymentLinkRequest {
+    pub amount: Option<Decimal>,
+    pub memo: Option<String>,
+}
+
+// Payment link response, also served publicly to the checkout page
+#[derive(Debug, Serialize)]
+pub struct PaymentLinkResponse {
+    pub slug: String,
+    pub wallet_address: String,
+    pub amount: Option<String>,
+    pub memo: Option<String>,
+    pub reusable: bool,
+    pub status: String,
+    pub expires_at: Option<String>,
+    pub paid_at: Option<String>,
+    pub paid_signature: Option<String>,
+    pub created_at: String,
+    pub amount_tolerance: String,
+    pub amount_tolerance_pct: String,
+    pub received_amount: Option<String>,
+    pub overpaid_by: Option<String>,
+    pub match_rule: Option<AmountMatchRule>,
+    pub version: i32,
+    pub history: serde_json::Value,
+    pub cancelled_at: Option<String>,
+}
+
+impl From<PaymentLink> for PaymentLinkResponse {
+    fn from(link: PaymentLink) -> Self {
+        Self {
+            status: link.display_status(Utc::now()).to_string(),
+            match_rule: link.match_rule(),
+            overpaid_by: link.overpaid_by().map(|a| a.to_string()),
+            slug: link.slug,
+            wallet_address: link.wallet_address,
+            amount: link.amount.map(|a| a.to_string()),
+            memo: link.memo,
+            reusable: link.reusable,
+            expires_at: link.expires_at.map(|t| t.to_rfc3339()),
+            paid_at: link.paid_at.map(|t| t.to_rfc3339()),
+            paid_signature: link.paid_signature,
+            created_at: link.created_at.to_rfc3339(),
+            amount_tolerance: link.amount_tolerance.to_string(),
+            amount_tolerance_pct: link.amount_tolerance_pct.to_string(),
+            received_amount: link.received_amount.map(|a| a.to_string()),
+            version: link.version,
+            history: link.history,
+            cancelled_at: link.cancelled_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+fn link_not_found(slug: &str) -> AppError {
+    AppError::NotFound(format!("Payment link {} not found", slug))
+}
+
+/// Payments are matched by amount, at USDC precision
+fn validate_amount(amount: Option<Decimal>) -> Result<(), AppError> {
+    if let Some(amount) = amount {
+        if amount <= Decimal::ZERO || amount.scale() > 6 {
+            return Err(AppError::BadRequest(
+                "amount must be positive with at most 6 decimal places".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_memo(memo: Option<&str>) -> Result<(), AppError> {
+    if memo.is_some_and(|m| m.chars().count() > MAX_MEMO_LEN) {
+        return Err(AppError::BadRequest(format!(
+            "memo must be at most {} characters",
+            MAX_MEMO_LEN
+        )));
+    }
+
+    Ok(())
+}
+
+/// Payments to a link without an amount are matched by memo alone, so it
+/// needs one
+fn validate_matchable(amount: Option<Decimal>, memo: Option<&str>) -> Result<(), AppError> {
+    if amount.is_none() && memo.is_none_or(|m| m.trim().is_empty()) {
+        return Err(AppError::BadRequest(
+            "a payment link without an amount needs a memo to match payments by".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn create_payment_link(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<CreatePaymentLinkRequest>,
+) -> Result<Json<PaymentLinkResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&req.wallet_address)?;
+
+    // Links can only point at active registered wallets, which we sync
+    WalletRepository::find_by_address(&state.db.pool, &req.wallet_address)
+        .await?
+        .filter(|w| !w.is_archived())
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Wallet {} not registered", req.wallet_address))
+        })?;
+
+    validate_amount(req.amount)?;
+
+    let amount_tolerance = req
+        .amount_tolerance
+        .unwrap_or(state.config.payment_amount_tolerance);
+    if amount_tolerance < Decimal::ZERO || amount_tolerance.scale() > 6 {
+        return Err(AppError::BadRequest(
+            "amount_tolerance must be non-negative with at most 6 decimal places".into(),
+        ));
+    }
+
+    let amount_tolerance_pct = req
+        .amount_tolerance_pct
+        .unwrap_or(state.config.payment_amount_tolerance_pct);
+    if amount_tolerance_pct < Decimal::ZERO
+        || amount_tolerance_pct > Decimal::ONE_HUNDRED
+        || amount_tolerance_pct.scale() > 4
+    {
+        return Err(AppError::BadRequest(
+            "amount_tolerance_pct must be between 0 and 100 with at most 4 decimal places".into(),
+        ));
+    }
+
+    validate_memo(req.memo.as_deref())?;
+    validate_matchable(req.amount, req.memo.as_deref())?;
+
+    if req.expires_at.is_some_and(|t| t <= Utc::now()) {
+        return Err(AppError::BadRequest("expires_at must be in the future".into()));
+    }
+
+    let link = PaymentLinkRepository::create(
+        &state.db.pool,
+        &generate_slug(),
+        &req.wallet_address,
+        req.amount,
+        req.memo.as_deref(),
+        req.reusable,
+        req.expires_at,
+        amount_tolerance,
+        amount_tolerance_pct,
+    )
+    .await?;
+
+    let response = PaymentLinkResponse::from(link);
+    state
+        .audit
+        .record(
+            &audit,
+            "payment_link.create",
+            "payment_link",
+            &response.slug,
+            None,
+            super::audit_snapshot(&response),
+        )
+        .await;
+
+    Ok(Json(response))
+}
+
+pub async fn get_payment_link(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<Json<PaymentLinkResponse>, AppError> {
+    let link = PaymentLinkRepository::find_by_slug(&state.db.pool, &slug)
+        .await?
+        .ok_or_else(|| link_not_found(&slug))?;
+
+    Ok(Json(link.into()))
+}
+
+/// Change an unmatched link's amount or memo, e.g. when the customer edits
+/// their cart. Each change bumps `version` and is appended to `history`.
+pub async fn update_payment_link(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(slug): Path<String>,
+    Json(req): Json<UpdatePaymentLinkRequest>,
+) -> Result<Json<PaymentLinkResponse>, AppError> {
+    validate_amount(req.amount)?;
+    validate_memo(req.memo.as_deref())?;
+
+    let link = PaymentLinkRepository::find_by_slug(&state.db.pool, &slug)
+        .await?
+        .ok_or_else(|| link_not_found(&slug))?;
+
+    if !link.is_unmatched() || link.is_expired(Utc::now()) {
+        return Err(AppError::Conflict(format!(
+            "Payment link {} is {} and can no longer be amended",
+            slug,
+            if link.payment_count > 0 { "matched" } else { link.display_status(Utc::now()) }
+        )));
+    }
+
+    let amount = req.amount.or(link.amount);
+    let memo = match req.memo {
+        Some(memo) if memo.is_empty() => None,
+        Some(memo) => Some(memo),
+        None => link.memo.clone(),
+    };
+    validate_matchable(amount, memo.as_deref())?;
+
+    // Compared as decimals so "12.5" doesn't amend a link of 12.500000
+    if amount == link.amount && memo == link.memo {
+        return Ok(Json(link.into()));
+    }
+
+    let before = serde_json::json!({ "amount": link.amount, "memo": link.memo });
+    let after = serde_json::json!({ "amount": amount, "memo": memo });
+
+    let (changed_before, changed_after) = json_diff(Some(&before), Some(&after));
+    let change = serde_json::json!({
+        "version": link.version + 1,
+        "changed_at": Utc::now(),
+        "before": changed_before,
+        "after": changed_after,
+    });
+
+    // Lost a race with a payment, a cancel or another amendment
+    let amended = PaymentLinkRepository::amend(
+        &state.db.pool,
+        &slug,
+        link.version,
+        amount,
+        memo.as_deref(),
+        &change,
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::Conflict(format!(
+            "Payment link {} changed concurrently, fetch it and retry",
+            slug
+        ))
+    })?;
+
+    let before = PaymentLinkResponse::from(link);
+    let response = PaymentLinkResponse::from(amended);
+    state
+        .audit
+        .record(
+            &audit,
+            "payment_link.update",
+            "payment_link",
+            &slug,
+            super::audit_snapshot(&before),
+            super::audit_snapshot(&response),
+        )
+        .await;
+
+    Ok(Json(response))
+}
+
+/// Cancel a link whose checkout was abandoned so no later payment settles it,
+/// and fire payment_link.cancelled. Cancelling again returns the cancelled
+/// link; a link a payment already settled can't be cancelled.
+pub async fn cancel_payment_link(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(slug): Path<String>,
+) -> Result<Json<PaymentLinkResponse>, AppError> {
+    let before = PaymentLinkRepository::find_by_slug(&state.db.pool, &slug)
+        .await?
+        .ok_or_else(|| link_not_found(&slug))?;
+
+    let link = match PaymentLinkRepository::cancel(&state.db.pool, &slug).await? {
+        Some(link) => link,
+        None => {
+            // Already cancelled, or a payment settled it first
+            let current = PaymentLinkRepository::find_by_slug(&state.db.pool, &slug)
+                .await?
+                .ok_or_else(|| link_not_found(&slug))?;
+            if current.status == PaymentLinkStatus::Cancelled {
+                return Ok(Json(current.into()));
+            }
+            return Err(AppError::Conflict(format!(
+                "Payment link {} is already {}",
+                slug, current.status
+            )));
+        }
+    };
+
+    let payload = PaymentLinkCancelledPayload {
+        slug: link.slug.clone(),
+        wallet_address: link.wallet_address.clone(),
+        amount: link.amount.map(|a| a.to_string()),
+        memo: link.memo.clone(),
+        reusable: link.reusable,
+        payment_count: link.payment_count,
+        version: link.version,
+        cancelled_at: link.cancelled_at.unwrap_or_else(Utc::now),
+    };
+    // The cancellation stands even if the notification can't be recorded
+    let wallet = WalletRepository::find_by_address(&state.db.pool, &link.wallet_address).await?;
+    if let Some(wallet) = wallet {
+        let data = serde_json::to_value(&payload)?;
+        if let Err(e) = state
+            .webhook
+            .notify_wallet_event(&wallet, "payment_link.cancelled", data)
+            .await
+        {
+            tracing::warn!(slug = %slug, error = %e, "Failed to record cancellation webhook");
+        }
+    }
+
+    let before = PaymentLinkResponse::from(before);
+    let response = PaymentLinkResponse::from(link);
+    state
+        .audit
+        .record(
+            &audit,
+            "payment_link.cancel",
+            "payment_link",
+            &slug,
+            super::audit_snapshot(&before),
+            super::audit_snapshot(&response),
+        )
+        .await;
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_need_an_amount_or_a_memo() {
+        assert!(validate_matchable(Some(Decimal::ONE), None).is_ok());
+        assert!(validate_matchable(None, Some("order-42")).is_ok());
+        assert!(validate_matchable(None, None).is_err());
+        assert!(validate_matchable(None, Some("  ")).is_err());
+    }
+}
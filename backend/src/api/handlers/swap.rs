@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use serde::Deserialize;
+
+use crate::api::extract::Json;
+use crate::error::AppError;
+use crate::services::swap::{SwapQuote, SwapTransaction};
+use crate::AppState;
+
+/// Swaps always settle in USDC; accept its symbol or mint as the output
+fn check_output_mint(state: &AppState, output_mint: Option<&str>) -> Result<(), AppError> {
+    match output_mint {
+        None | Some("USDC") => Ok(()),
+        Some(mint) if mint == state.swap.usdc_mint() => Ok(()),
+        Some(mint) => Err(AppError::BadRequest(format!(
+            "Unsupported output mint {}, swaps settle in USDC",
+            mint
+        ))),
+    }
+}
+
+// Swap quote query params
+#[derive(Debug, Deserialize)]
+pub struct SwapQuoteQuery {
+    pub input_mint: String,
+    /// Base units of the input token
+    pub amount: u64,
+    pub output_mint: Option<String>,
+}
+
+/// Quote swapping an allowlisted token into USDC. Quotes expire at
+/// `expires_at`; the frontend should fetch a fresh one after that.
+pub async fn get_swap_quote(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SwapQuoteQuery>,
+) -> Result<Json<SwapQuote>, AppError> {
+    check_output_mint(&state, query.output_mint.as_deref())?;
+
+    let quote = state.swap.quote(&query.input_mint, query.amount).await?;
+
+    Ok(Json(quote))
+}
+
+// Build swap request
+#[derive(Debug, Deserialize)]
+pub struct BuildSwapRequest {
+    pub user_public_key: String,
+    pub input_mint: String,
+    pub amount: u64,
+    pub output_mint: Option<String>,
+}
+
+/// Build an unsigned swap transaction for the payer's wallet, returned base64
+/// encoded for the wallet adapter to sign and send
+pub async fn build_swap(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BuildSwapRequest>,
+) -> Result<Json<SwapTransaction>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&req.user_public_key)?;
+
+    check_output_mint(&state, req.output_mint.as_deref())?;
+
+    let swap = state
+        .swap
+        .build(&req.user_public_key, &req.input_mint, req.amount)
+        .await?;
+
+    Ok(Json(swap))
+}
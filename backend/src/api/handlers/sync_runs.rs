@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::api::extract::Json;
+use crate::domain::SyncRun;
+use crate::error::AppError;
+use crate::repository::SyncRunRepository;
+use crate::AppState;
+
+/// Most runs returned per request
+const MAX_RUNS: i64 = 1000;
+
+// List sync runs query params
+#[derive(Debug, Deserialize)]
+pub struct ListSyncRunsQuery {
+    pub limit: Option<i64>,
+}
+
+// List sync runs response
+#[derive(Debug, Serialize)]
+pub struct ListSyncRunsResponse {
+    pub runs: Vec<SyncRun>,
+    /// Computed over the returned runs
+    pub aggregates: SyncRunAggregates,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncRunAggregates {
+    pub run_count: usize,
+    pub avg_duration_ms: Option<i64>,
+    pub avg_new_transactions: Option<String>,
+    pub total_errors: i64,
+}
+
+impl SyncRunAggregates {
+    fn over(runs: &[SyncRun]) -> Self {
+        let count = runs.len() as i64;
+        let total_duration: i64 = runs.iter().map(SyncRun::duration_ms).sum();
+        let total_new: i64 = runs.iter().map(|r| r.new_transactions as i64).sum();
+
+        Self {
+            run_count: runs.len(),
+            avg_duration_ms: (count > 0).then(|| total_duration / count),
+            avg_new_transactions: (count > 0)
+                .then(|| (Decimal::from(total_new) / Decimal::from(count)).round_dp(2).to_string()),
+            total_errors: runs.iter().map(|r| r.error_count as i64).sum(),
+        }
+    }
+}
+
+/// Recent background sync cycles, newest first, for throughput trends
+pub async fn list_sync_runs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListSyncRunsQuery>,
+) -> Result<Json<ListSyncRunsResponse>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_RUNS);
+    let runs = SyncRunRepository::list_recent(&state.db.pool, limit).await?;
+
+    Ok(Json(ListSyncRunsResponse {
+        aggregates: SyncRunAggregates::over(&runs),
+        runs,
+    }))
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+
+use crate::api::extract::Json;
+use crate::error::AppError;
+use crate::services::token_accounts::{PrepareTransaction, TokenAccountStatus};
+use crate::AppState;
+
+// Token accounts response
+#[derive(Debug, Serialize)]
+pub struct TokenAccountsResponse {
+    pub address: String,
+    pub token_accounts: Vec<TokenAccountStatus>,
+}
+
+/// Whether the wallet has an associated token account for each mint it
+/// receives. Checks are cached briefly.
+pub async fn get_token_accounts(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<TokenAccountsResponse>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+
+    let token_accounts = state.token_accounts.status(&address).await?;
+
+    Ok(Json(TokenAccountsResponse {
+        address,
+        token_accounts,
+    }))
+}
+
+// Prepare wallet request
+#[derive(Debug, Deserialize)]
+pub struct PrepareWalletRequest {
+    /// Signs the transaction and pays the rent
+    pub fee_payer: String,
+}
+
+/// Build an unsigned transaction creating the wallet's USDC token account,
+/// returned base64 encoded for the fee payer to sign and send
+pub async fn prepare_wallet(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Json(req): Json<PrepareWalletRequest>,
+) -> Result<Json<PrepareTransaction>, AppError> {
+    // Validate addresses
+    crate::services::solana::SolanaClient::validate_address(&address)?;
+    crate::services::solana::SolanaClient::validate_address(&req.fee_payer)?;
+
+    let prepared = state
+        .token_accounts
+        .build_prepare(&address, &req.fee_payer)
+        .await?;
+
+    Ok(Json(prepared))
+}
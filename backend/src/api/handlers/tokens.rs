@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use serde::Serialize;
+
+use crate::api::extract::Json;
+use crate::domain::TokenMetadata;
+use crate::error::AppError;
+use crate::AppState;
+
+// List tokens response
+#[derive(Debug, Serialize)]
+pub struct TokensResponse {
+    pub tokens: Vec<TokenMetadata>,
+    pub count: usize,
+}
+
+/// The bundled stablecoins and every mint resolved so far
+pub async fn list_tokens(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TokensResponse>, AppError> {
+    let tokens = state.tokens.list().await?;
+    let count = tokens.len();
+
+    Ok(Json(TokensResponse { tokens, count }))
+}
+
+/// Symbol, name and decimals of a mint. Mints without metadata answer with
+/// their shortened address as the symbol rather than a 404.
+pub async fn get_token(
+    State(state): State<Arc<AppState>>,
+    Path(mint): Path<String>,
+) -> Result<Json<TokenMetadata>, AppError> {
+    // Validate address
+    crate::services::solana::SolanaClient::validate_address(&mint)?;
+
+    Ok(Json(state.tokens.resolve(&mint).await))
+}
@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::domain::IDEMPOTENCY_KEY_TTL_HOURS;
+use crate::error::AppError;
+use crate::repository::IdempotencyKeyRepository;
+use crate::AppState;
+
+/// Request header making a mutating call safe to retry
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses replayed from a stored key
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted key (matches the column)
+const MAX_KEY_LEN: usize = 255;
+
+/// A claim still in flight after this long belongs to a request that died
+const ABANDONED_AFTER_SECS: i64 = 300;
+
+/// Middleware giving every mutating route `Idempotency-Key` support, so
+/// handlers don't need to know about it. The first request with a key runs
+/// and its response is stored; repeating it with the same body and credential
+/// replays that response, while reusing the key with a different body, or
+/// while the first request is still running, is a 409. Server errors and transient 409/429
+/// responses aren't stored, so those can be retried with the same key.
+pub async fn idempotency(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match handle(&state.db.pool, state.config.max_request_body_bytes, request, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn handle(
+    pool: &PgPool,
+    max_body_bytes: usize,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let is_mutating = matches!(*request.method(), Method::POST | Method::PATCH | Method::PUT | Method::DELETE);
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) if is_mutating => value
+            .to_str()
+            .ok()
+            .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Idempotency-Key must be 1 to {} visible ASCII characters",
+                    MAX_KEY_LEN
+                ))
+            })?
+            .to_string(),
+        _ => return Ok(next.run(request).await),
+    };
+
+    let endpoint = format!("{} {}", request.method(), request.uri().path());
+    // Scoped to the credential, so a replay can't hand an admin's response
+    // to a caller without the key; the request then reaches its auth check
+    let caller = hex::encode(Sha256::digest(
+        request.headers().get(AUTHORIZATION).map(|v| v.as_bytes()).unwrap_or_default(),
+    ));
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, max_body_bytes)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body exceeds the size limit".into()))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(parts.uri.query().unwrap_or_default());
+    hasher.update(b"\n");
+    hasher.update(&body);
+    let request_hash = hex::encode(hasher.finalize());
+
+    let now = Utc::now();
+    let claimed = IdempotencyKeyRepository::claim(
+        pool,
+        &key,
+        &endpoint,
+        &caller,
+        &request_hash,
+        now - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS),
+        now - chrono::Duration::seconds(ABANDONED_AFTER_SECS),
+    )
+    .await?;
+
+    if !claimed {
+        // Released or swept between the claim and here; the client can retry
+        let record = IdempotencyKeyRepository::find(pool, &key, &endpoint, &caller)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict("Idempotency-Key was released, retry the request".into())
+            })?;
+
+        if record.request_hash != request_hash {
+            return Err(AppError::Conflict(
+                "Idempotency-Key was already used with a different request".into(),
+            ));
+        }
+
+        return match (record.status_code, record.response_body) {
+            (Some(status), Some(body)) => Ok(replay(status, record.content_type.as_deref(), body)),
+            _ => Err(AppError::Conflict(
+                "A request with this Idempotency-Key is still in progress".into(),
+            )),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let status = response.status();
+
+    if status.is_server_error()
+        || status == StatusCode::CONFLICT
+        || status == StatusCode::TOO_MANY_REQUESTS
+    {
+        release(pool, &key, &endpoint, &caller).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            release(pool, &key, &endpoint, &caller).await;
+            return Err(AppError::Internal(format!("Failed to read response body: {}", e)));
+        }
+    };
+
+    let content_type = parts.headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    if let Err(e) = IdempotencyKeyRepository::complete(
+        pool,
+        &key,
+        &endpoint,
+        &caller,
+        status.as_u16() as i32,
+        content_type,
+        &body,
+    )
+    .await
+    {
+        // The request already ran; answer it, and let a retry run it again
+        // rather than see "in progress" until the claim is abandoned
+        tracing::warn!(endpoint = %endpoint, error = %e, "Failed to store idempotent response");
+        release(pool, &key, &endpoint, &caller).await;
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn replay(status: i32, content_type: Option<&str>, body: Vec<u8>) -> Response {
+    let status = u16::try_from(status)
+        .ok()
+        .and_then(|s| StatusCode::from_u16(s).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut response = (status, body).into_response();
+    let headers = response.headers_mut();
+    if let Some(content_type) = content_type.and_then(|c| HeaderValue::from_str(c).ok()) {
+        headers.insert(CONTENT_TYPE, content_type);
+    }
+    headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+async fn release(pool: &PgPool, key: &str, endpoint: &str, caller: &str) {
+    if let Err(e) = IdempotencyKeyRepository::release(pool, key, endpoint, caller).await {
+        tracing::warn!(endpoint = %endpoint, error = %e, "Failed to release idempotency key");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::{middleware, Json, Router};
+
+    use crate::services::testing::serve;
+
+    /// A local server whose one route takes a while and counts its runs
+    async fn app(pool: PgPool) -> (String, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let router = Router::new()
+            .route(
+                "/wallets",
+                post(move || async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    let run = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    (StatusCode::CREATED, Json(serde_json::json!({ "run": run })))
+                }),
+            )
+            // Stands in for an admin route behind `AdminAuth`
+            .route(
+                "/admin/token",
+                post(|headers: HeaderMap| async move {
+                    match headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+                        Some("Bearer admin-key") => {
+                            (StatusCode::CREATED, r#"{"token":"secret"}"#).into_response()
+                        }
+                        _ => StatusCode::UNAUTHORIZED.into_response(),
+                    }
+                }),
+            )
+            .layer(middleware::from_fn(move |request: Request, next: Next| {
+                let pool = pool.clone();
+                async move {
+                    match handle(&pool, 1024, request, next).await {
+                        Ok(response) => response,
+                        Err(e) => e.into_response(),
+                    }
+                }
+            }));
+
+        (serve(router).await, runs)
+    }
+
+    async fn send(base_url: &str, key: &str, body: &str) -> (StatusCode, Option<String>, String) {
+        send_as(&format!("{}/wallets", base_url), None, key, body).await
+    }
+
+    /// POST `body` to `url` with an Idempotency-Key and, if given, a bearer token
+    async fn send_as(
+        url: &str,
+        token: Option<&str>,
+        key: &str,
+        body: &str,
+    ) -> (StatusCode, Option<String>, String) {
+        let mut request = reqwest::Client::new().post(url);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap();
+
+        let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+        let replayed = response
+            .headers()
+            .get(REPLAYED_HEADER)
+            .map(|v| v.to_str().unwrap().to_string());
+        (status, replayed, response.text().await.unwrap())
+    }
+
+    #[sqlx::test]
+    async fn racing_requests_with_one_key_run_once(pool: PgPool) {
+        let (base_url, runs) = app(pool).await;
+
+        let (first, second) = tokio::join!(
+            send(&base_url, "key-1", r#"{"address":"a"}"#),
+            send(&base_url, "key-1", r#"{"address":"a"}"#),
+        );
+        let mut statuses = [first.0, second.0];
+        statuses.sort();
+        assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT]);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let (status, replayed, body) = send(&base_url, "key-1", r#"{"address":"a"}"#).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(replayed.as_deref(), Some("true"));
+        assert_eq!(body, r#"{"run":1}"#);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[sqlx::test]
+    async fn a_key_reused_for_a_different_request_conflicts(pool: PgPool) {
+        let (base_url, runs) = app(pool).await;
+
+        assert_eq!(send(&base_url, "key-1", r#"{"address":"a"}"#).await.0, StatusCode::CREATED);
+        let (status, _, body) = send(&base_url, "key-1", r#"{"address":"b"}"#).await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.contains("different request"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[sqlx::test]
+    async fn an_oversized_key_is_rejected_before_running(pool: PgPool) {
+        let (base_url, runs) = app(pool).await;
+
+        let key = "k".repeat(MAX_KEY_LEN + 1);
+        assert_eq!(send(&base_url, &key, "{}").await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[sqlx::test]
+    async fn a_replay_without_the_original_credential_is_not_replayed(pool: PgPool) {
+        let (base_url, _) = app(pool).await;
+        let url = format!("{}/admin/token", base_url);
+
+        let (status, _, body) = send_as(&url, Some("admin-key"), "key-1", "{}").await;
+        assert_eq!((status, body.as_str()), (StatusCode::CREATED, r#"{"token":"secret"}"#));
+
+        // Same key and body, but no or another credential: the auth check runs
+        for token in [None, Some("guess")] {
+            let (status, replayed, body) = send_as(&url, token, "key-1", "{}").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(replayed, None);
+            assert!(!body.contains("secret"));
+        }
+
+        let (status, replayed, _) = send_as(&url, Some("admin-key"), "key-1", "{}").await;
+        assert_eq!((status, replayed.as_deref()), (StatusCode::CREATED, Some("true")));
+    }
+}
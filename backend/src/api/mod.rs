@@ -1,22 +1,98 @@
+mod auth;
+mod extract;
 mod handlers;
+mod idempotency;
+mod openapi;
 
 use std::sync::Arc;
 
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, patch, post},
     Router,
 };
 
 use crate::AppState;
 
 pub fn routes(state: Arc<AppState>) -> Router {
+    let body_limit = state.config.max_request_body_bytes;
+
     Router::new()
         .route("/health", get(handlers::health))
         .route("/health/detailed", get(handlers::detailed_health))
-        .route("/wallets", post(handlers::create_wallet))
+        .route("/health/ready", get(handlers::readiness))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/wallets", post(handlers::create_wallet).get(handlers::list_wallets))
+        .route(
+            "/wallets/:address",
+            patch(handlers::update_wallet).delete(handlers::delete_wallet),
+        )
         .route("/wallets/:address/balance", get(handlers::get_balance))
+        .route("/wallets/:address/balance/history", get(handlers::get_balance_history))
+        .route("/wallets/:address/summary", get(handlers::get_wallet_summary))
+        .route("/wallets/:address/status", get(handlers::get_wallet_status))
+        .route("/wallets/:address/fee-readiness", get(handlers::get_fee_readiness))
+        .route(
+            "/wallets/:address/token-accounts",
+            get(handlers::token_accounts::get_token_accounts),
+        )
+        .route("/wallets/:address/prepare", post(handlers::token_accounts::prepare_wallet))
         .route("/wallets/:address/transactions", get(handlers::get_transactions))
         .route("/wallets/:address/webhook-events", get(handlers::get_webhook_events))
+        .route(
+            "/wallets/:address/webhook-events/:id",
+            get(handlers::get_webhook_event),
+        )
+        .route("/webhook-events/inspect", get(handlers::inspect_webhook_event))
         .route("/wallets/:address/webhook/test", post(handlers::test_webhook))
+        .route("/wallets/:address/webhook/preview", post(handlers::preview_webhook))
+        .route("/wallets/:address/events/stream", get(handlers::stream_wallet_events))
+        .route("/transactions/:signature", get(handlers::get_transaction))
+        .route(
+            "/groups",
+            post(handlers::groups::create_group).get(handlers::groups::list_groups),
+        )
+        .route(
+            "/groups/:id",
+            get(handlers::groups::get_group)
+                .patch(handlers::groups::update_group)
+                .delete(handlers::groups::delete_group),
+        )
+        .route("/groups/:id/summary", get(handlers::groups::get_group_summary))
+        .route("/groups/:id/transactions", get(handlers::groups::get_group_transactions))
+        .route("/payment-links", post(handlers::payment_links::create_payment_link))
+        .route(
+            "/payment-links/:slug",
+            get(handlers::payment_links::get_payment_link)
+                .patch(handlers::payment_links::update_payment_link),
+        )
+        .route(
+            "/payment-links/:slug/cancel",
+            post(handlers::payment_links::cancel_payment_link),
+        )
+        .route("/tokens", get(handlers::tokens::list_tokens))
+        .route("/tokens/:mint", get(handlers::tokens::get_token))
+        .route("/swap/quote", get(handlers::swap::get_swap_quote))
+        .route("/swap/build", post(handlers::swap::build_swap))
+        .route("/sync/runs", get(handlers::sync_runs::list_sync_runs))
+        .route(
+            "/admin/wallets/:address/webhooks/reset",
+            post(handlers::admin::reset_failed_webhooks),
+        )
+        .route(
+            "/admin/webhook-events/export",
+            get(handlers::admin::export_webhook_events),
+        )
+        .route("/admin/webhook-events/:id", get(handlers::admin::get_webhook_event))
+        .route(
+            "/admin/webhook-events/:id/inspection-link",
+            post(handlers::admin::create_inspection_link),
+        )
+        .route("/admin/migrations", get(handlers::admin::get_migration_status))
+        .route("/admin/audit-log", get(handlers::admin::get_audit_log))
+        .route("/admin/data-quality", get(handlers::admin::get_data_quality))
+        .layer(middleware::from_fn_with_state(state.clone(), idempotency::idempotency))
+        .layer(DefaultBodyLimit::max(body_limit))
         .with_state(state)
 }
@@ -0,0 +1,964 @@
+//! OpenAPI 3 description of the HTTP API, served at `GET /openapi.json`.
+//!
+//! The document is written by hand next to the router: when a route or a
+//! request/response struct changes, update its entry here in the same change.
+//! Schemas cover the fields integrators depend on; the handler structs remain
+//! the source of truth.
+
+use serde_json::{json, Value};
+
+use crate::api::extract::Json;
+use crate::domain::PAYLOAD_REDACTIONS;
+
+pub async fn openapi_json() -> Json<Value> {
+    Json(document())
+}
+
+fn document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Stablecoin Pay API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "USDC wallet monitoring, transaction history, webhooks and \
+                payment links. Errors are returned as {\"error\": \"...\"}.",
+        },
+        "paths": paths(),
+        "components": {
+            "schemas": schemas(),
+            "responses": {
+                "Error": {
+                    "description": "Request failed",
+                    "content": { "application/json": { "schema": schema("Error") } },
+                },
+            },
+            "securitySchemes": {
+                "adminKey": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "ADMIN_API_KEY",
+                },
+            },
+        },
+    })
+}
+
+fn paths() -> Value {
+    let address = path_param("address", "Wallet address");
+    let event_id = path_param("id", "Webhook event id");
+    let group_id = path_param("id", "Wallet group id");
+    let slug = path_param("slug", "Payment link slug");
+    let limit = query("limit", "integer", "Page size");
+    let offset = query("offset", "integer", "Rows to skip");
+
+    let mut paths = json!({
+        "/health": {
+            "get": op("Liveness check", vec![], None, ok("Service is up", object())),
+        },
+        "/health/detailed": {
+            "get": op(
+                "Database, RPC, sync and webhook health",
+                vec![],
+                None,
+                ok("Component health", object()),
+            ),
+        },
+        "/health/ready": {
+            "get": op(
+                "Readiness check; 503 lists the failing components",
+                vec![],
+                None,
+                ok("Ready", object()),
+            ),
+        },
+        "/openapi.json": {
+            "get": op("This document", vec![], None, ok("OpenAPI document", object())),
+        },
+        "/wallets": {
+            "post": op(
+                "Register a wallet (returns the existing one if already registered)",
+                vec![],
+                Some(schema("CreateWalletRequest")),
+                ok("Registered wallet", schema("CreateWalletResponse")),
+            ),
+            "get": op(
+                "List registered wallets",
+                vec![query("include_archived", "boolean", "Include archived wallets")],
+                None,
+                ok("Wallets", schema("WalletsResponse")),
+            ),
+        },
+        "/wallets/{address}": {
+            "patch": op(
+                "Update a wallet's settings; omitted fields are left unchanged",
+                vec![address.clone()],
+                Some(schema("UpdateWalletRequest")),
+                ok("Updated wallet", schema("Wallet")),
+            ),
+            "delete": op(
+                "Archive a wallet; its history is kept",
+                vec![address.clone()],
+                None,
+                ok("Archived wallet", schema("Wallet")),
+            ),
+        },
+        "/wallets/{address}/balance": {
+            "get": op(
+                "USDC and SOL balance",
+                vec![
+                    address.clone(),
+                    query("fiat", "string", "ISO currency to value the balance in, e.g. eur"),
+                    query("strict_price", "boolean", "Value USDC at its market price"),
+                    query("fresh", "boolean", "Read the chain instead of the balance cache"),
+                ],
+                None,
+                ok("Balance", schema("Balance")),
+            ),
+        },
+        "/wallets/{address}/balance/history": {
+            "get": op(
+                "Daily balance snapshots",
+                vec![
+                    address.clone(),
+                    query("days", "integer", "Days of history"),
+                    query("granularity", "string", "daily or weekly"),
+                ],
+                None,
+                ok("Balance history", object()),
+            ),
+        },
+        "/wallets/{address}/summary": {
+            "get": op(
+                "Balance and transaction stats in one call",
+                vec![address.clone()],
+                None,
+                ok("Summary", object()),
+            ),
+        },
+        "/wallets/{address}/status": {
+            "get": op(
+                "Sync and webhook delivery health",
+                vec![address.clone()],
+                None,
+                ok("Status", object()),
+            ),
+        },
+        "/wallets/{address}/fee-readiness": {
+            "get": op(
+                "Whether the wallet holds enough SOL to pay fees",
+                vec![address.clone()],
+                None,
+                ok("Fee readiness", object()),
+            ),
+        },
+        "/wallets/{address}/token-accounts": {
+            "get": op(
+                "Associated token account status per mint",
+                vec![address.clone()],
+                None,
+                ok("Token accounts", object()),
+            ),
+        },
+        "/wallets/{address}/prepare": {
+            "post": op(
+                "Build an unsigned transaction creating the USDC token account",
+                vec![address.clone()],
+                Some(json!({
+                    "type": "object",
+                    "required": ["fee_payer"],
+                    "properties": { "fee_payer": string() },
+                })),
+                ok("Unsigned transaction, base64 encoded", object()),
+            ),
+        },
+    });
+    merge(&mut paths, &json!({
+        "/wallets/{address}/transactions": {
+            "get": op(
+                "Transactions newest first; syncs recent ones from the chain first",
+                vec![
+                    address.clone(),
+                    limit.clone(),
+                    offset.clone(),
+                    query("sync", "boolean", "false skips the inline sync (default true)"),
+                    query("sync_limit", "integer", "Signatures the inline sync checks (1-100)"),
+                    query("timezone", "string", "IANA zone for _local timestamps"),
+                ],
+                None,
+                ok("Transactions", schema("TransactionsResponse")),
+            ),
+        },
+        "/wallets/{address}/webhook-events": {
+            "get": op(
+                "Webhook events for the wallet",
+                vec![
+                    address.clone(),
+                    limit.clone(),
+                    offset.clone(),
+                    query("status", "string", "pending, delivered or failed"),
+                    query("event_type", "string", "e.g. payment.received"),
+                    query_time("since", "Created at or after"),
+                    query_time("until", "Created before"),
+                ],
+                None,
+                ok("Webhook events", schema("WebhookEventsResponse")),
+            ),
+        },
+        "/wallets/{address}/webhook-events/{id}": {
+            "get": op(
+                "One webhook event",
+                vec![address.clone(), event_id.clone()],
+                None,
+                ok("Webhook event", schema("WebhookEvent")),
+            ),
+        },
+        "/webhook-events/inspect": {
+            "get": op(
+                "Open a signed inspection link issued by an admin",
+                vec![required(query("token", "string", "Token from the inspection link"))],
+                None,
+                ok("Event payload and delivery details", object()),
+            ),
+        },
+        "/wallets/{address}/webhook/test": {
+            "post": op(
+                "Send a test webhook to the wallet's URL",
+                vec![address.clone()],
+                None,
+                ok("Delivery outcome", object()),
+            ),
+        },
+        "/wallets/{address}/webhook/preview": {
+            "post": op(
+                "Render the payload and signature a webhook would carry",
+                vec![address.clone()],
+                None,
+                ok("Preview", object()),
+            ),
+        },
+        "/wallets/{address}/events/stream": {
+            "get": {
+                "summary": "Server-sent events for the wallet",
+                "parameters": [address.clone()],
+                "responses": {
+                    "200": {
+                        "description": "Event stream",
+                        "content": { "text/event-stream": { "schema": string() } },
+                    },
+                    "default": error(),
+                },
+            },
+        },
+    }));
+    merge(&mut paths, &json!({
+        "/transactions/{signature}": {
+            "get": op(
+                "One transaction by signature",
+                vec![
+                    path_param("signature", "Transaction signature"),
+                    query("wallet", "string", "Registered wallet to fetch it for if not stored"),
+                ],
+                None,
+                ok("Transaction", schema("Transaction")),
+            ),
+        },
+        "/groups": {
+            "post": op(
+                "Create a wallet group",
+                vec![],
+                Some(schema("GroupRequest")),
+                ok("Created group", schema("WalletGroup")),
+            ),
+            "get": op("List wallet groups", vec![], None, ok("Groups", object())),
+        },
+        "/groups/{id}": {
+            "get": op(
+                "One wallet group",
+                vec![group_id.clone()],
+                None,
+                ok("Group", schema("WalletGroup")),
+            ),
+            "patch": op(
+                "Rename a group or replace its metadata",
+                vec![group_id.clone()],
+                Some(schema("GroupRequest")),
+                ok("Updated group", schema("WalletGroup")),
+            ),
+            "delete": op(
+                "Delete a group; its wallets become ungrouped",
+                vec![group_id.clone()],
+                None,
+                ok("Deleted group", schema("WalletGroup")),
+            ),
+        },
+        "/groups/{id}/summary": {
+            "get": op(
+                "Member count, balance and receive volume of a group",
+                vec![group_id.clone()],
+                None,
+                ok("Summary", object()),
+            ),
+        },
+        "/groups/{id}/transactions": {
+            "get": op(
+                "Transactions of all the group's wallets, paged by cursor",
+                vec![
+                    group_id.clone(),
+                    limit.clone(),
+                    query("cursor", "string", "next_cursor of the previous page"),
+                    query("timezone", "string", "IANA zone for _local timestamps"),
+                ],
+                None,
+                ok("Transactions", object()),
+            ),
+        },
+    }));
+    merge(&mut paths, &json!({
+        "/payment-links": {
+            "post": op(
+                "Create a payment link",
+                vec![],
+                Some(schema("CreatePaymentLinkRequest")),
+                ok("Payment link", schema("PaymentLink")),
+            ),
+        },
+        "/payment-links/{slug}": {
+            "get": op(
+                "One payment link",
+                vec![slug.clone()],
+                None,
+                ok("Payment link", schema("PaymentLink")),
+            ),
+            "patch": op(
+                "Amend an active link's amount or memo",
+                vec![slug.clone()],
+                Some(schema("UpdatePaymentLinkRequest")),
+                ok("Payment link", schema("PaymentLink")),
+            ),
+        },
+        "/payment-links/{slug}/cancel": {
+            "post": op(
+                "Cancel a link; 409 once it is paid",
+                vec![slug.clone()],
+                None,
+                ok("Payment link", schema("PaymentLink")),
+            ),
+        },
+        "/tokens": {
+            "get": op(
+                "Bundled stablecoins and every mint resolved so far",
+                vec![],
+                None,
+                ok("Tokens", object_with("tokens", array(schema("Token")))),
+            ),
+        },
+        "/tokens/{mint}": {
+            "get": op(
+                "Symbol, name and decimals of a mint; unknown mints get a shortened address",
+                vec![path_param("mint", "Mint address")],
+                None,
+                ok("Token", schema("Token")),
+            ),
+        },
+        "/swap/quote": {
+            "get": op(
+                "Quote swapping an allowlisted token into USDC",
+                vec![
+                    required(query("input_mint", "string", "Token to swap")),
+                    required(query("amount", "integer", "Base units of the input token")),
+                    query("output_mint", "string", "Must be USDC when given"),
+                ],
+                None,
+                ok("Quote", object()),
+            ),
+        },
+        "/swap/build": {
+            "post": op(
+                "Build an unsigned swap transaction",
+                vec![],
+                Some(json!({
+                    "type": "object",
+                    "required": ["user_public_key", "input_mint", "amount"],
+                    "properties": {
+                        "user_public_key": string(),
+                        "input_mint": string(),
+                        "amount": integer(),
+                        "output_mint": string(),
+                    },
+                })),
+                ok("Unsigned transaction, base64 encoded", object()),
+            ),
+        },
+        "/sync/runs": {
+            "get": op(
+                "Recent background sync runs",
+                vec![limit.clone()],
+                None,
+                ok("Sync runs", object()),
+            ),
+        },
+    }));
+    merge(&mut paths, &json!({
+        "/admin/wallets/{address}/webhooks/reset": {
+            "post": admin(op(
+                "Requeue a wallet's failed webhooks",
+                vec![address.clone()],
+                None,
+                ok("Events requeued", object()),
+            )),
+        },
+        "/admin/webhook-events/export": {
+            "get": admin(json!({
+                "summary": "Export webhook events of all wallets",
+                "parameters": [
+                    query_time("since", "Created at or after"),
+                    query_time("until", "Created before"),
+                    query("status", "string", "pending, delivered or failed"),
+                    query("cursor", "string", "Id of the last event already received"),
+                    query("timezone", "string", "IANA zone for _local timestamps"),
+                ],
+                "responses": {
+                    "200": {
+                        "description": "One webhook event per line",
+                        "content": { "application/x-ndjson": { "schema": string() } },
+                    },
+                    "default": error(),
+                },
+            })),
+        },
+        "/admin/webhook-events/{id}": {
+            "get": admin(op(
+                "A webhook event with every delivery attempt",
+                vec![
+                    event_id.clone(),
+                    query("timezone", "string", "IANA zone for _local timestamps"),
+                ],
+                None,
+                ok("Webhook event", schema("WebhookEventDetail")),
+            )),
+        },
+        "/admin/webhook-events/{id}/inspection-link": {
+            "post": admin(op(
+                "Issue a time-limited link to an event's payload",
+                vec![event_id],
+                None,
+                ok("Inspection link", object()),
+            )),
+        },
+        "/admin/migrations": {
+            "get": admin(op(
+                "Applied and pending migrations",
+                vec![],
+                None,
+                ok("Migrations", object()),
+            )),
+        },
+        "/admin/audit-log": {
+            "get": admin(op(
+                "Audit log entries, newest first",
+                vec![
+                    query("entity", "string", "Entity type, e.g. wallet"),
+                    query("entity_id", "string", "Entity id"),
+                    limit,
+                ],
+                None,
+                ok("Audit log", object()),
+            )),
+        },
+        "/admin/data-quality": {
+            "get": admin(op(
+                "Quarantined rows and other data checks",
+                vec![],
+                None,
+                ok("Report", object()),
+            )),
+        },
+    }));
+    paths
+}
+
+fn schemas() -> Value {
+    let decimal = json!({ "type": "string", "format": "decimal" });
+    let time = json!({ "type": "string", "format": "date-time" });
+    let uuid = json!({ "type": "string", "format": "uuid" });
+    let chain = json!({ "type": "string", "enum": ["solana", "base"] });
+    let encoding = json!({ "type": "string", "enum": ["hex", "base64"] });
+    let redaction = json!({ "type": "string", "enum": PAYLOAD_REDACTIONS });
+
+    let wallet_settings = json!({
+        "webhook_url": nullable(string()),
+        "min_webhook_amount": nullable(decimal.clone()),
+        "webhook_headers": {
+            "type": "object",
+            "additionalProperties": string(),
+            "nullable": true,
+        },
+        "label": nullable(string()),
+        "metadata": nullable(object()),
+        "ignore_before": nullable(time.clone()),
+        "display_currency": nullable(string()),
+        "max_webhooks_per_minute": nullable(integer()),
+        "webhook_stream": nullable(string()),
+        "balance_thresholds": nullable(array(decimal.clone())),
+        "webhook_signature_header": nullable(string()),
+        "webhook_signature_encoding": nullable(encoding.clone()),
+        "group_id": nullable(string()),
+        "payment_references": nullable(array(string())),
+        "payload_redactions": nullable(array(redaction.clone())),
+        "timezone": nullable(string()),
+    });
+    let mut create_wallet = wallet_settings.clone();
+    create_wallet["address"] = string();
+    create_wallet["chain"] = chain.clone();
+    create_wallet["sync_now"] = json!({
+        "type": "boolean",
+        "description": "Run a bounded first sync before responding; history \
+            found for a new wallet sends no webhooks",
+    });
+
+    let wallet = json!({
+        "address": string(),
+        "chain": chain.clone(),
+        "webhook_url": nullable(string()),
+        "created_at": time.clone(),
+        "archived_at": nullable(time.clone()),
+        "min_webhook_amount": decimal.clone(),
+        "webhook_headers": object(),
+        "label": nullable(string()),
+        "metadata": object(),
+        "ignore_before": nullable(time.clone()),
+        "display_currency": nullable(string()),
+        "max_webhooks_per_minute": integer(),
+        "webhook_stream": nullable(string()),
+        "balance_thresholds": array(decimal.clone()),
+        "webhook_signature_header": string(),
+        "webhook_signature_encoding": encoding,
+        "group_id": nullable(uuid.clone()),
+        "payment_references": array(string()),
+        "payload_redactions": array(redaction),
+        "timezone": nullable(string()),
+    });
+    let mut create_wallet_response = wallet.clone();
+    create_wallet_response["created"] = json!({ "type": "boolean" });
+    create_wallet_response["initial_sync"] = json!({
+        "type": "object",
+        "description": "Present when sync_now was requested",
+        "properties": {
+            "transactions_found": nullable(integer()),
+            "completed": { "type": "boolean" },
+        },
+    });
+
+    // Flattened into amounts when the wallet has a display currency
+    let display = json!({
+        "display_amount": decimal.clone(),
+        "display_currency": string(),
+        "fx_rate": decimal.clone(),
+        "fx_rate_as_of": time.clone(),
+    });
+
+    let mut transaction = json!({
+        "signature": string(),
+        "chain": chain.clone(),
+        "wallet_address": string(),
+        "tx_type": { "type": "string", "enum": ["send", "receive", "self_transfer"] },
+        "amount": decimal.clone(),
+        "amount_raw": integer(),
+        "decimals": integer(),
+        "token_mint": string(),
+        "counterparty": string(),
+        "status": {
+            "type": "string",
+            "enum": ["confirmed", "pending", "failed", "expired"],
+        },
+        "block_time": time.clone(),
+        "created_at": time.clone(),
+        "transfer_legs": array(json!({
+            "type": "object",
+            "properties": {
+                "source": string(),
+                "destination": string(),
+                "source_owner": nullable(string()),
+                "destination_owner": nullable(string()),
+                "amount_raw": integer(),
+            },
+        })),
+        "memo": nullable(string()),
+        "reference": nullable(string()),
+    });
+    let transaction_schema = schema_object(transaction.clone());
+    transaction["symbol"] = string();
+    merge(&mut transaction, &display);
+    // Only with a timezone, in that zone's offset
+    transaction["block_time_local"] = string();
+    transaction["created_at_local"] = string();
+
+    let page = json!({
+        "count": { "type": "integer", "description": "Rows in this page" },
+        "total": { "type": "integer", "description": "Rows across all pages" },
+        "limit": integer(),
+        "offset": integer(),
+        "has_more": { "type": "boolean" },
+    });
+    let mut transactions_response = json!({
+        "timezone": { "type": "string", "description": "Zone of the _local timestamps" },
+        "transactions": array(schema_object(transaction)),
+    });
+    merge(&mut transactions_response, &page);
+
+    let webhook_status = json!({ "type": "string", "enum": ["pending", "delivered", "failed"] });
+    let webhook_event = json!({
+        "id": uuid.clone(),
+        "wallet_address": string(),
+        "chain": chain,
+        "transaction_signature": nullable(string()),
+        "event_type": string(),
+        "payload": object(),
+        "status": webhook_status.clone(),
+        "attempts": integer(),
+        "last_attempt_at": nullable(time.clone()),
+        "delivered_at": nullable(time.clone()),
+        "last_error": nullable(string()),
+        "created_at": time.clone(),
+        "last_response_status": nullable(integer()),
+        "last_response_time_ms": nullable(integer()),
+        "last_response_body": nullable(string()),
+        "next_attempt_at": nullable(time.clone()),
+    });
+    let mut webhook_events_response = json!({
+        "events": array(schema("WebhookEvent")),
+        "filters": {
+            "type": "object",
+            "properties": {
+                "status": nullable(webhook_status),
+                "event_type": nullable(string()),
+                "since": nullable(time.clone()),
+                "until": nullable(time.clone()),
+            },
+        },
+    });
+    merge(&mut webhook_events_response, &page);
+    let mut webhook_event_detail = webhook_event.clone();
+    // Only with a timezone, in that zone's offset
+    webhook_event_detail["timezone"] = json!({
+        "type": "string",
+        "description": "Zone of the _local timestamps",
+    });
+    webhook_event_detail["created_at_local"] = string();
+    webhook_event_detail["last_attempt_at_local"] = nullable(string());
+    webhook_event_detail["delivered_at_local"] = nullable(string());
+    webhook_event_detail["delivery_attempts"] = array(json!({
+        "type": "object",
+        "properties": {
+            "attempt_number": integer(),
+            "attempted_at": time.clone(),
+            "attempted_at_local": string(),
+            "duration_ms": integer(),
+            "succeeded": { "type": "boolean" },
+            "response_status": nullable(integer()),
+            "error": nullable(string()),
+        },
+    }));
+
+    let mut balance = json!({
+        "address": string(),
+        "token": string(),
+        "symbol": string(),
+        "amount": decimal.clone(),
+        "amount_raw": integer(),
+        "decimals": integer(),
+        "account_exists": {
+            "type": "boolean",
+            "description": "False when the wallet has no USDC token account yet",
+        },
+        "usd_value": decimal.clone(),
+        "usdc_usd_price": decimal.clone(),
+        "fiat_value": decimal.clone(),
+        "fiat_currency": string(),
+        "sol_balance": decimal.clone(),
+        "cached_at": time.clone(),
+    });
+    merge(&mut balance, &display);
+
+    let payment_link = json!({
+        "slug": string(),
+        "wallet_address": string(),
+        "amount": nullable(decimal.clone()),
+        "memo": nullable(string()),
+        "reusable": { "type": "boolean" },
+        "status": {
+            "type": "string",
+            "enum": ["active", "paid", "underpaid", "cancelled", "expired"],
+        },
+        "expires_at": nullable(time.clone()),
+        "paid_at": nullable(time.clone()),
+        "paid_signature": nullable(string()),
+        "created_at": time.clone(),
+        "amount_tolerance": decimal.clone(),
+        "amount_tolerance_pct": decimal.clone(),
+        "received_amount": nullable(decimal.clone()),
+        "overpaid_by": nullable(decimal.clone()),
+        "match_rule": nullable(json!({
+            "type": "string",
+            "enum": [
+                "exact",
+                "overpaid",
+                "absolute_tolerance",
+                "percent_tolerance",
+                "underpaid",
+            ],
+        })),
+        "version": integer(),
+        "history": array(object()),
+        "cancelled_at": nullable(time.clone()),
+    });
+
+    json!({
+        "Error": required_object(json!({ "error": string() }), &["error"]),
+        "CreateWalletRequest": required_object(create_wallet, &["address"]),
+        "UpdateWalletRequest": schema_object(wallet_settings),
+        "Wallet": schema_object(wallet),
+        "CreateWalletResponse": schema_object(create_wallet_response),
+        "WalletsResponse": schema_object(json!({
+            "wallets": array(schema("Wallet")),
+            "count": integer(),
+        })),
+        "Balance": schema_object(balance),
+        "Transaction": transaction_schema,
+        "TransactionsResponse": schema_object(transactions_response),
+        "WebhookEvent": schema_object(webhook_event),
+        "WebhookEventsResponse": schema_object(webhook_events_response),
+        "WebhookEventDetail": schema_object(webhook_event_detail),
+        "WalletGroup": schema_object(json!({
+            "id": uuid,
+            "name": string(),
+            "metadata": object(),
+            "created_at": time.clone(),
+        })),
+        "GroupRequest": schema_object(json!({
+            "name": string(),
+            "metadata": object(),
+        })),
+        "CreatePaymentLinkRequest": required_object(
+            json!({
+                "wallet_address": string(),
+                "amount": nullable(decimal.clone()),
+                "memo": {
+                    "type": "string",
+                    "nullable": true,
+                    "description": "Required without an amount: payments are then matched by memo",
+                },
+                "expires_at": nullable(time),
+                "reusable": { "type": "boolean" },
+                "amount_tolerance": nullable(decimal.clone()),
+                "amount_tolerance_pct": nullable(decimal.clone()),
+            }),
+            &["wallet_address"],
+        ),
+        "UpdatePaymentLinkRequest": schema_object(json!({
+            "amount": nullable(decimal),
+            "memo": { "type": "string", "description": "An empty memo clears it" },
+        })),
+        "PaymentLink": schema_object(payment_link),
+        "Token": schema_object(json!({
+            "mint": string(),
+            "symbol": string(),
+            "name": string(),
+            "decimals": nullable(integer()),
+            "logo_uri": nullable(string()),
+            "source": {
+                "type": "string",
+                "enum": ["static", "metaplex", "mint", "unknown"],
+            },
+        })),
+    })
+}
+
+/// An operation answering 200 with `response`, or an error
+fn op(summary: &str, parameters: Vec<Value>, body: Option<Value>, response: Value) -> Value {
+    let mut op = json!({
+        "summary": summary,
+        "responses": { "200": response, "default": error() },
+    });
+    if !parameters.is_empty() {
+        op["parameters"] = Value::Array(parameters);
+    }
+    if let Some(body) = body {
+        op["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": body } },
+        });
+    }
+    op
+}
+
+/// Mark an operation as requiring the admin key
+fn admin(mut op: Value) -> Value {
+    op["security"] = json!([{ "adminKey": [] }]);
+    op
+}
+
+fn ok(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn error() -> Value {
+    json!({ "$ref": "#/components/responses/Error" })
+}
+
+fn schema(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn path_param(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": string(),
+    })
+}
+
+fn query(name: &str, ty: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "description": description,
+        "schema": { "type": ty },
+    })
+}
+
+fn query_time(name: &str, description: &str) -> Value {
+    let mut param = query(name, "string", description);
+    param["schema"]["format"] = json!("date-time");
+    param
+}
+
+fn required(mut param: Value) -> Value {
+    param["required"] = json!(true);
+    param
+}
+
+/// A list response: `items` plus their `count`
+fn object_with(items: &str, schema: Value) -> Value {
+    schema_object(json!({ items: schema, "count": integer() }))
+}
+
+fn schema_object(properties: Value) -> Value {
+    json!({ "type": "object", "properties": properties })
+}
+
+fn required_object(properties: Value, required: &[&str]) -> Value {
+    let mut schema = schema_object(properties);
+    schema["required"] = json!(required);
+    schema
+}
+
+/// Add `extra`'s entries to the `target` object, e.g. `#[serde(flatten)]`
+/// fields to a struct's properties
+fn merge(target: &mut Value, extra: &Value) {
+    if let (Some(target), Some(extra)) = (target.as_object_mut(), extra.as_object()) {
+        target.extend(extra.clone());
+    }
+}
+
+fn nullable(mut schema: Value) -> Value {
+    schema["nullable"] = json!(true);
+    schema
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn object() -> Value {
+    json!({ "type": "object" })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    /// (path, methods) of every `.route(..)` in the router, with axum's
+    /// `:param` segments written the OpenAPI way as `{param}`
+    fn registered_routes() -> Vec<(String, Vec<&'static str>)> {
+        let source = include_str!("mod.rs");
+        let router = &source[source.find("Router::new()").unwrap()..];
+        let router = &router[..router.find(".layer(").unwrap()];
+
+        router
+            .split(".route(")
+            .skip(1)
+            .map(|route| {
+                let path = route.split('"').nth(1).unwrap();
+                let path = path
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(param) => format!("{{{param}}}"),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let methods = METHODS
+                    .into_iter()
+                    .filter(|method| {
+                        route.match_indices(&format!("{method}(")).any(|(at, _)| {
+                            !route[..at].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                        })
+                    })
+                    .collect();
+                (path, methods)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let routes = registered_routes();
+        // The parser reads multi-line routes and chained methods
+        assert!(routes.contains(&("/groups/{id}".to_string(), vec!["get", "patch", "delete"])));
+
+        let paths = paths();
+        for (path, methods) in routes {
+            assert!(!methods.is_empty(), "no methods found for {path}");
+            for method in methods {
+                assert!(
+                    paths[&path][method].is_object(),
+                    "{} {path} is routed but missing from the OpenAPI paths",
+                    method.to_uppercase()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_documented_operation_is_routed() {
+        let routes = registered_routes();
+
+        for (path, operations) in paths().as_object().unwrap() {
+            for method in operations.as_object().unwrap().keys() {
+                assert!(
+                    routes
+                        .iter()
+                        .any(|(routed, methods)| routed == path && methods.contains(&method.as_str())),
+                    "{} {path} is documented but not routed",
+                    method.to_uppercase()
+                );
+            }
+        }
+    }
+}
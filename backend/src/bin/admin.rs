@@ -0,0 +1,337 @@
+//! Admin CLI for common operations, sharing config and services with the
+//! API server. Human-readable output by default, `--json` for scripting.
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use stablecoin_pay::config::Config;
+use stablecoin_pay::domain::{Chain, Wallet, WalletSettings};
+use stablecoin_pay::error::AppError;
+use stablecoin_pay::repository::{WalletRepository, WebhookEventRepository};
+use stablecoin_pay::services::chain;
+use stablecoin_pay::services::sync::SYNC_LIMIT;
+use stablecoin_pay::AppState;
+
+#[derive(Parser)]
+#[command(name = "admin", about = "Stablecoin Pay admin CLI")]
+struct Cli {
+    /// Print machine-readable JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage registered wallets
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Webhook delivery operations
+    #[command(subcommand)]
+    Webhook(WebhookCommand),
+    /// Run a sync cycle now
+    #[command(subcommand)]
+    Sync(SyncCommand),
+    /// Transaction history operations
+    #[command(subcommand)]
+    Tx(TxCommand),
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Register a wallet (or reactivate an archived one)
+    Add {
+        address: String,
+        #[arg(long, default_value = "solana")]
+        chain: Chain,
+        #[arg(long)]
+        webhook_url: Option<String>,
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// List wallets
+    List {
+        #[arg(long)]
+        include_archived: bool,
+    },
+    /// Archive a wallet; its history is kept
+    Remove { address: String },
+}
+
+#[derive(Subcommand)]
+enum WebhookCommand {
+    /// Send a test webhook to a wallet's endpoint
+    Test { address: String },
+    /// Re-queue failed webhook events and retry them now
+    RetryFailed { address: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum SyncCommand {
+    /// Sync one wallet, or all active wallets when no address is given
+    Run { address: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum TxCommand {
+    /// Walk a wallet's on-chain history and store missing transactions
+    /// without firing webhooks
+    Backfill {
+        address: String,
+        /// Maximum number of signatures to scan
+        #[arg(long, default_value_t = 1000)]
+        max: usize,
+        /// Start over from the newest signature instead of resuming
+        #[arg(long)]
+        restart: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    let config = Config::from_env()?;
+    let state = AppState::build(config).await?;
+    state.db.run_migrations().await?;
+
+    match cli.command {
+        Command::Wallet(cmd) => wallet(&state, cmd, cli.json).await,
+        Command::Webhook(cmd) => webhook(&state, cmd, cli.json).await,
+        Command::Sync(cmd) => sync(&state, cmd, cli.json).await,
+        Command::Tx(cmd) => tx(&state, cmd, cli.json).await,
+    }
+}
+
+async fn wallet(state: &AppState, cmd: WalletCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        WalletCommand::Add {
+            address,
+            chain,
+            webhook_url,
+            label,
+        } => {
+            let address = chain::validate_wallet_address(chain, &address)?;
+            let settings = WalletSettings {
+                webhook_url,
+                label,
+                ..Default::default()
+            };
+            let wallet = WalletRepository::create(&state.db.pool, chain, &address, &settings)
+                .await?
+                .wallet;
+            print_wallets(&[wallet], json)
+        }
+        WalletCommand::List { include_archived } => {
+            let wallets = WalletRepository::list(&state.db.pool, include_archived).await?;
+            print_wallets(&wallets, json)
+        }
+        WalletCommand::Remove { address } => {
+            let wallet = WalletRepository::archive(&state.db.pool, &address)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))?;
+            print_wallets(&[wallet], json)
+        }
+    }
+}
+
+async fn webhook(state: &AppState, cmd: WebhookCommand, json: bool) -> anyhow::Result<()> {
+    match cmd {
+        WebhookCommand::Test { address } => {
+            let wallet = find_wallet(state, &address).await?;
+            state.webhook.send_test_webhook(&wallet).await?;
+
+            if json {
+                print_json(&serde_json::json!({ "success": true }))
+            } else {
+                println!("Test webhook delivered to {}", address);
+                Ok(())
+            }
+        }
+        WebhookCommand::RetryFailed { address } => {
+            let requeued =
+                WebhookEventRepository::reset_failed(&state.db.pool, address.as_deref()).await?;
+            let retried = state.webhook.retry_pending_webhooks().await?;
+
+            if json {
+                print_json(&serde_json::json!({ "requeued": requeued, "retried": retried }))
+            } else {
+                println!("Re-queued {} failed events, retried {}", requeued, retried);
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn sync(state: &AppState, cmd: SyncCommand, json: bool) -> anyhow::Result<()> {
+    let SyncCommand::Run { address } = cmd;
+
+    match address {
+        Some(address) => {
+            let wallet = find_wallet(state, &address).await?;
+            let (new_txs, webhooks) = state.sync.sync_wallet(&wallet, SYNC_LIMIT, true).await?;
+
+            if json {
+                print_json(&serde_json::json!({
+                    "new_transactions": new_txs,
+                    "webhooks_triggered": webhooks,
+                }))
+            } else {
+                println!(
+                    "Synced {}: {} new transactions, {} webhooks triggered",
+                    address, new_txs, webhooks
+                );
+                Ok(())
+            }
+        }
+        None => {
+            let report = state.sync.sync_all_wallets().await?;
+
+            if json {
+                return print_json(&report);
+            }
+
+            println!(
+                "Synced {} wallets: {} new transactions, {} webhooks triggered, {} expired",
+                report.wallets_synced,
+                report.new_transactions,
+                report.webhooks_triggered,
+                report.expired_transactions
+            );
+            for error in &report.errors {
+                println!("  error: {}", error);
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn tx(state: &AppState, cmd: TxCommand, json: bool) -> anyhow::Result<()> {
+    let TxCommand::Backfill { address, max, restart } = cmd;
+
+    let wallet = find_wallet(state, &address).await?;
+    let (new_txs, completed) = state.sync.backfill_wallet(&wallet, max, restart).await?;
+
+    if json {
+        print_json(&serde_json::json!({
+            "new_transactions": new_txs,
+            "completed": completed,
+        }))
+    } else {
+        println!(
+            "Backfilled {}: {} new transactions{}",
+            address,
+            new_txs,
+            if completed { ", history complete" } else { "; run again to continue" }
+        );
+        Ok(())
+    }
+}
+
+async fn find_wallet(state: &AppState, address: &str) -> Result<Wallet, AppError> {
+    WalletRepository::find_by_address(&state.db.pool, address)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", address)))
+}
+
+fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_wallets(wallets: &[Wallet], json: bool) -> anyhow::Result<()> {
+    if json {
+        return print_json(&wallets);
+    }
+
+    println!(
+        "{:<44}  {:<20}  {:<10}  {:<25}  WEBHOOK URL",
+        "ADDRESS", "LABEL", "STATUS", "CREATED"
+    );
+    for wallet in wallets {
+        let status = if wallet.is_archived() { "archived" } else { "active" };
+        println!(
+            "{:<44}  {:<20}  {:<10}  {:<25}  {}",
+            wallet.address,
+            wallet.label.as_deref().unwrap_or("-"),
+            status,
+            wallet.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            wallet.webhook_url.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    const ADDRESS: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("admin").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn the_command_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn wallet_add_takes_flags_and_a_trailing_json() {
+        let cli = parse(&["wallet", "add", ADDRESS, "--label", "shop", "--json"]).unwrap();
+
+        assert!(cli.json);
+        match cli.command {
+            Command::Wallet(WalletCommand::Add {
+                address,
+                chain,
+                webhook_url,
+                label,
+            }) => {
+                assert_eq!(address, ADDRESS);
+                assert_eq!(chain, Chain::Solana);
+                assert_eq!(webhook_url, None);
+                assert_eq!(label.as_deref(), Some("shop"));
+            }
+            _ => panic!("expected wallet add"),
+        }
+    }
+
+    #[test]
+    fn wallet_add_rejects_an_unknown_chain() {
+        let err = parse(&["wallet", "add", ADDRESS, "--chain", "dogecoin"])
+            .err()
+            .expect("unknown chain");
+        assert!(err.to_string().contains("Unknown chain"));
+    }
+
+    #[test]
+    fn backfill_resumes_with_the_default_budget() {
+        match parse(&["tx", "backfill", ADDRESS]).unwrap().command {
+            Command::Tx(TxCommand::Backfill { address, max, restart }) => {
+                assert_eq!(address, ADDRESS);
+                assert_eq!(max, 1000);
+                assert!(!restart);
+            }
+            _ => panic!("expected tx backfill"),
+        }
+    }
+
+    #[test]
+    fn sync_run_and_retry_failed_take_an_optional_address() {
+        assert!(matches!(
+            parse(&["sync", "run"]).unwrap().command,
+            Command::Sync(SyncCommand::Run { address: None })
+        ));
+        assert!(matches!(
+            parse(&["webhook", "retry-failed", ADDRESS]).unwrap().command,
+            Command::Webhook(WebhookCommand::RetryFailed { address: Some(_) })
+        ));
+        assert!(parse(&["webhook", "test"]).is_err());
+    }
+}
@@ -1,33 +1,288 @@
 use std::env;
+use std::str::FromStr;
 
 use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+
+/// Webhook signing secret used when WEBHOOK_SECRET is unset
+pub const DEFAULT_WEBHOOK_SECRET: &str = "default-webhook-secret-change-in-production";
+
+/// User-Agent webhook deliveries carry when WEBHOOK_USER_AGENT is unset
+pub const DEFAULT_WEBHOOK_USER_AGENT: &str = concat!("stablecoin-pay/", env!("CARGO_PKG_VERSION"));
+
+/// Log output format: human-readable text or one JSON object per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
+    pub app_env: String,
     pub database_url: String,
     pub solana_rpc_url: String,
+    pub helius_api_key: Option<String>,
+    pub helius_api_url: String,
+    pub helius_enhanced_parser: bool,
     pub usdc_mint: String,
     pub port: u16,
+    pub max_request_body_bytes: usize,
     pub webhook_secret: String,
+    pub webhook_dry_run: bool,
+    pub webhook_user_agent: String,
+    pub webhook_inspection_ttl_secs: i64,
+    pub db_max_connections: u32,
+    pub db_min_connections: u32,
+    pub db_acquire_timeout_secs: u64,
+    pub db_idle_timeout_secs: u64,
+    pub db_max_lifetime_secs: u64,
+    pub min_sol_balance_lamports: u64,
+    pub admin_api_key: Option<String>,
+    pub leader_lock_namespace: String,
+    pub fx_api_url: String,
+    pub fx_refresh_interval_secs: u64,
+    pub usdc_price_api_url: String,
+    pub balance_snapshot_interval_secs: u64,
+    pub balance_snapshot_retention_days: i64,
+    pub sync_run_retention_days: i64,
+    pub jupiter_api_url: String,
+    pub swap_input_mints: Vec<String>,
+    pub swap_max_price_impact_pct: Decimal,
+    pub swap_quote_ttl_secs: i64,
+    pub wallet_stale_after_secs: i64,
+    pub wallet_degraded_error_streak: i32,
+    pub payment_amount_tolerance: Decimal,
+    pub payment_amount_tolerance_pct: Decimal,
+    pub migration_max_attempts: u32,
+    pub webhook_redis_url: Option<String>,
+    pub log_format: LogFormat,
+    pub rpc_detail_timeout_secs: u64,
+    pub rpc_detail_concurrency: usize,
+    pub rpc_transaction_cache_entries: usize,
+    pub wallet_sync_timeout_secs: u64,
+    pub balance_cache_ttl_secs: u64,
+    pub pending_expiry_secs: u64,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         Ok(Self {
+            // "production" turns config mistakes into startup errors
+            app_env: env::var("APP_ENV").unwrap_or_else(|_| "development".to_string()),
             database_url: env::var("DATABASE_URL")
                 .context("DATABASE_URL must be set")?,
             solana_rpc_url: env::var("HELIUS_API_KEY")
                 .map(|key| format!("https://mainnet.helius-rpc.com/?api-key={}", key))
                 .or_else(|_| env::var("SOLANA_RPC_URL"))
                 .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
+            helius_api_key: env::var("HELIUS_API_KEY").ok().filter(|key| !key.is_empty()),
+            helius_api_url: env::var("HELIUS_API_URL")
+                .unwrap_or_else(|_| "https://api.helius.xyz".to_string()),
+            // Parse transactions with Helius's enhanced API (needs HELIUS_API_KEY)
+            helius_enhanced_parser: parse_env("HELIUS_ENHANCED_PARSER", false)?,
             usdc_mint: env::var("USDC_MINT")
                 .unwrap_or_else(|_| "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .context("PORT must be a valid number")?,
+            max_request_body_bytes: parse_env("MAX_REQUEST_BODY_BYTES", 64 * 1024)?,
             webhook_secret: env::var("WEBHOOK_SECRET")
-                .unwrap_or_else(|_| "default-webhook-secret-change-in-production".to_string()),
+                .unwrap_or_else(|_| DEFAULT_WEBHOOK_SECRET.to_string()),
+            // Development aid: log webhook requests instead of sending them
+            webhook_dry_run: parse_env("WEBHOOK_DRY_RUN", false)?,
+            // Sent on every delivery so merchants can allowlist our traffic
+            webhook_user_agent: env::var("WEBHOOK_USER_AGENT")
+                .ok()
+                .filter(|agent| !agent.is_empty())
+                .unwrap_or_else(|| DEFAULT_WEBHOOK_USER_AGENT.to_string()),
+            // Lifetime of the signed links support staff share to inspect an event
+            webhook_inspection_ttl_secs: parse_env("WEBHOOK_INSPECTION_TTL_SECS", 60 * 60)?,
+            db_max_connections: parse_env("DB_MAX_CONNECTIONS", 20)?,
+            db_min_connections: parse_env("DB_MIN_CONNECTIONS", 2)?,
+            db_acquire_timeout_secs: parse_env("DB_ACQUIRE_TIMEOUT_SECS", 5)?,
+            db_idle_timeout_secs: parse_env("DB_IDLE_TIMEOUT_SECS", 600)?,
+            db_max_lifetime_secs: parse_env("DB_MAX_LIFETIME_SECS", 1800)?,
+            // 0.002 SOL covers a few signatures plus rent for a new token account
+            min_sol_balance_lamports: parse_env("MIN_SOL_BALANCE_LAMPORTS", 2_000_000)?,
+            // Admin endpoints are disabled unless a key is configured
+            admin_api_key: env::var("ADMIN_API_KEY").ok().filter(|key| !key.is_empty()),
+            // Replicas sharing a namespace elect one instance to run background sync
+            leader_lock_namespace: env::var("LEADER_LOCK_NAMESPACE")
+                .unwrap_or_else(|_| "default".to_string()),
+            // Frankfurter serves the ECB reference rates as JSON
+            fx_api_url: env::var("FX_API_URL")
+                .unwrap_or_else(|_| "https://api.frankfurter.app".to_string()),
+            fx_refresh_interval_secs: parse_env("FX_REFRESH_INTERVAL_SECS", 6 * 60 * 60)?,
+            // Jupiter's price API, for the market USDC/USD price behind strict_price
+            usdc_price_api_url: env::var("USDC_PRICE_API_URL")
+                .unwrap_or_else(|_| "https://api.jup.ag/price/v2".to_string()),
+            // Snapshots are kept per day; a shorter interval refreshes the day's reading
+            balance_snapshot_interval_secs: parse_env("BALANCE_SNAPSHOT_INTERVAL_SECS", 24 * 60 * 60)?,
+            balance_snapshot_retention_days: parse_env("BALANCE_SNAPSHOT_RETENTION_DAYS", 730)?,
+            // Trimmed alongside balance snapshots
+            sync_run_retention_days: parse_env("SYNC_RUN_RETENTION_DAYS", 30)?,
+            jupiter_api_url: env::var("JUPITER_API_URL")
+                .unwrap_or_else(|_| "https://quote-api.jup.ag/v6".to_string()),
+            // Tokens payers may swap into USDC: USDT and wrapped SOL by default
+            swap_input_mints: env::var("SWAP_INPUT_MINTS")
+                .unwrap_or_else(|_| {
+                    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB,So11111111111111111111111111111111111111112"
+                        .to_string()
+                })
+                .split(',')
+                .map(|mint| mint.trim().to_string())
+                .filter(|mint| !mint.is_empty())
+                .collect(),
+            swap_max_price_impact_pct: parse_env("SWAP_MAX_PRICE_IMPACT_PCT", Decimal::ONE)?,
+            swap_quote_ttl_secs: parse_env("SWAP_QUOTE_TTL_SECS", 30)?,
+            // Wallet status thresholds: ten missed sync cycles, three failed syncs in a row
+            wallet_stale_after_secs: parse_env("WALLET_STALE_AFTER_SECS", 300)?,
+            wallet_degraded_error_streak: parse_env("WALLET_DEGRADED_ERROR_STREAK", 3)?,
+            // Default payment link tolerance: exact amounts only
+            payment_amount_tolerance: parse_env("PAYMENT_AMOUNT_TOLERANCE", Decimal::ZERO)?,
+            payment_amount_tolerance_pct: parse_env("PAYMENT_AMOUNT_TOLERANCE_PCT", Decimal::ZERO)?,
+            // Startup migration attempts, backing off 1s, 2s, 4s... up to 30s
+            migration_max_attempts: parse_env("MIGRATION_MAX_ATTEMPTS", 5)?,
+            // Wallets with a webhook_stream publish their events to this Redis
+            webhook_redis_url: env::var("WEBHOOK_REDIS_URL").ok().filter(|url| !url.is_empty()),
+            // JSON for log pipelines, text for humans
+            log_format: parse_env("LOG_FORMAT", LogFormat::Text)?,
+            // A slow getTransaction is skipped (and retried next cycle) after this
+            rpc_detail_timeout_secs: parse_env("RPC_DETAIL_TIMEOUT_SECS", 8)?,
+            // getTransaction calls in flight at once when the RPC can't batch
+            rpc_detail_concurrency: parse_env("RPC_DETAIL_CONCURRENCY", 4)?,
+            // Fetched transactions kept for other wallets in the same one; 0 disables
+            rpc_transaction_cache_entries: parse_env("RPC_TRANSACTION_CACHE_ENTRIES", 1000)?,
+            // RPC budget per wallet per sync, keeping a cycle near its 30s interval
+            wallet_sync_timeout_secs: parse_env("WALLET_SYNC_TIMEOUT_SECS", 20)?,
+            // Balances served from memory for polling clients; 0 always reads the chain
+            balance_cache_ttl_secs: parse_env("BALANCE_CACHE_TTL_SECS", 10)?,
+            // Pending transactions the cluster still doesn't know after this
+            // are expired: blockhash validity (~90s) with a wide margin
+            pending_expiry_secs: parse_env("PENDING_EXPIRY_SECS", 10 * 60)?,
         })
     }
 }
+
+impl Config {
+    pub fn is_production(&self) -> bool {
+        self.app_env == "production"
+    }
+
+    /// Reject settings that can't be right together
+    pub fn validate(&self) -> Result<()> {
+        if self.is_production() && self.webhook_secret == DEFAULT_WEBHOOK_SECRET {
+            anyhow::bail!("WEBHOOK_SECRET must be set when APP_ENV=production");
+        }
+
+        if self.webhook_inspection_ttl_secs <= 0 {
+            anyhow::bail!("WEBHOOK_INSPECTION_TTL_SECS must be positive");
+        }
+
+        if reqwest::header::HeaderValue::from_str(&self.webhook_user_agent).is_err() {
+            anyhow::bail!("WEBHOOK_USER_AGENT is not a valid header value");
+        }
+
+        Ok(())
+    }
+
+    /// Effective settings for the startup log, with secrets redacted
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let set = |value: bool| if value { "set" } else { "unset" }.to_string();
+
+        vec![
+            ("app_env", self.app_env.clone()),
+            ("port", self.port.to_string()),
+            ("database_url", redact_url(&self.database_url)),
+            ("solana_rpc_url", redact_url(&self.solana_rpc_url)),
+            ("helius_api_key", set(self.helius_api_key.is_some())),
+            ("helius_enhanced_parser", self.helius_enhanced_parser.to_string()),
+            ("usdc_mint", self.usdc_mint.clone()),
+            (
+                "webhook_secret",
+                if self.webhook_secret == DEFAULT_WEBHOOK_SECRET { "default" } else { "set" }
+                    .to_string(),
+            ),
+            ("webhook_dry_run", self.webhook_dry_run.to_string()),
+            ("webhook_user_agent", self.webhook_user_agent.clone()),
+            (
+                "webhook_inspection_ttl_secs",
+                self.webhook_inspection_ttl_secs.to_string(),
+            ),
+            ("admin_api_key", set(self.admin_api_key.is_some())),
+            (
+                "db_connections",
+                format!("{}..{}", self.db_min_connections, self.db_max_connections),
+            ),
+            ("leader_lock_namespace", self.leader_lock_namespace.clone()),
+            ("fx_api_url", redact_url(&self.fx_api_url)),
+            ("usdc_price_api_url", redact_url(&self.usdc_price_api_url)),
+            ("jupiter_api_url", redact_url(&self.jupiter_api_url)),
+            ("swap_input_mints", self.swap_input_mints.join(",")),
+            ("migration_max_attempts", self.migration_max_attempts.to_string()),
+            (
+                "webhook_redis_url",
+                self.webhook_redis_url
+                    .as_deref()
+                    .map(redact_url)
+                    .unwrap_or_else(|| "unset".to_string()),
+            ),
+            ("log_format", format!("{:?}", self.log_format).to_lowercase()),
+            (
+                "rpc_timeouts",
+                format!(
+                    "detail {}s x{}, wallet {}s",
+                    self.rpc_detail_timeout_secs,
+                    self.rpc_detail_concurrency,
+                    self.wallet_sync_timeout_secs
+                ),
+            ),
+            ("balance_cache_ttl_secs", self.balance_cache_ttl_secs.to_string()),
+            (
+                "rpc_transaction_cache_entries",
+                self.rpc_transaction_cache_entries.to_string(),
+            ),
+            ("pending_expiry_secs", self.pending_expiry_secs.to_string()),
+        ]
+    }
+}
+
+/// Hide a URL's password and query string, where API keys usually live
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            if parsed.password().is_some() {
+                let _ = parsed.set_password(Some("***"));
+            }
+            if parsed.query().is_some() {
+                parsed.set_query(Some("***"));
+            }
+            parsed.to_string()
+        }
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// Read an optional env var, falling back to `default` when unset
+fn parse_env<T: FromStr>(key: &str, default: T) -> Result<T> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| anyhow::anyhow!("{} must be a valid value, got '{}'", key, value)),
+        Err(_) => Ok(default),
+    }
+}
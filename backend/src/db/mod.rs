@@ -1,25 +1,245 @@
+use std::time::Duration;
+
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 
+use crate::config::Config;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// First wait between failed startup migration attempts; doubles each time
+const MIGRATION_RETRY_BASE: Duration = Duration::from_secs(1);
+
+/// Longest wait between startup migration attempts
+const MIGRATION_RETRY_MAX: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 pub struct Database {
     pub pool: PgPool,
+    max_connections: u32,
+}
+
+/// Snapshot of connection pool usage
+#[derive(Debug, Serialize)]
+pub struct PoolStatus {
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: u32,
+    pub max_connections: u32,
+}
+
+/// Applied schema version against the migrations this build ships
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    /// Highest successfully applied migration, if any
+    pub current_version: Option<i64>,
+    pub latest_version: Option<i64>,
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Shipped migrations not applied yet
+    pub pending: Vec<i64>,
+    /// Migrations recorded as started but not finished
+    pub failed: Vec<i64>,
+    pub up_to_date: bool,
 }
 
 impl Database {
-    pub async fn connect(database_url: &str) -> Result<Self> {
+    pub async fn connect(config: &Config) -> Result<Self> {
         let pool = PgPoolOptions::new()
-            .max_connections(10)
-            .connect(database_url)
+            .max_connections(config.db_max_connections)
+            .min_connections(config.db_min_connections)
+            .acquire_timeout(Duration::from_secs(config.db_acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(config.db_idle_timeout_secs))
+            .max_lifetime(Duration::from_secs(config.db_max_lifetime_secs))
+            .connect(&config.database_url)
             .await?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            max_connections: config.db_max_connections,
+        })
+    }
+
+    /// Wrap a pool that is already connected, e.g. a test database
+    #[cfg(test)]
+    pub fn from_pool(pool: PgPool) -> Self {
+        let max_connections = pool.options().get_max_connections();
+        Self {
+            pool,
+            max_connections,
+        }
     }
 
     pub async fn run_migrations(&self) -> Result<()> {
         tracing::info!("Running database migrations...");
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
+        MIGRATOR.run(&self.pool).await?;
         tracing::info!("Migrations complete");
         Ok(())
     }
+
+    /// Run migrations, retrying with exponential backoff so a database that
+    /// is still starting up doesn't abort the process
+    pub async fn run_migrations_with_retry(&self, max_attempts: u32) -> Result<()> {
+        let mut delay = MIGRATION_RETRY_BASE;
+        let mut attempt = 1;
+
+        loop {
+            match self.run_migrations().await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < max_attempts => {
+                    tracing::warn!(
+                        attempt,
+                        max_attempts,
+                        retry_in_secs = delay.as_secs(),
+                        error = %e,
+                        "Database migrations failed, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MIGRATION_RETRY_MAX);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Compare `_sqlx_migrations` with the migrations embedded in the binary
+    pub async fn migration_status(&self) -> Result<MigrationStatus, sqlx::Error> {
+        let (table_exists,): (bool,) =
+            sqlx::query_as("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+
+        let applied: Vec<(i64, bool, DateTime<Utc>)> = if table_exists {
+            sqlx::query_as("SELECT version, success, installed_on FROM _sqlx_migrations")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let succeeded = |version: i64| applied.iter().any(|(v, ok, _)| *v == version && *ok);
+
+        let pending: Vec<i64> = MIGRATOR
+            .iter()
+            .map(|m| m.version)
+            .filter(|version| !succeeded(*version))
+            .collect();
+        let failed: Vec<i64> = applied
+            .iter()
+            .filter(|(_, ok, _)| !ok)
+            .map(|(version, _, _)| *version)
+            .collect();
+        let current = applied
+            .iter()
+            .filter(|(_, ok, _)| *ok)
+            .max_by_key(|(version, _, _)| *version);
+
+        Ok(MigrationStatus {
+            current_version: current.map(|(version, _, _)| *version),
+            latest_version: MIGRATOR.iter().map(|m| m.version).max(),
+            applied_at: current.map(|(_, _, at)| *at),
+            up_to_date: pending.is_empty() && failed.is_empty(),
+            pending,
+            failed,
+        })
+    }
+
+    pub fn pool_status(&self) -> PoolStatus {
+        let size = self.pool.size();
+        let idle = self.pool.num_idle();
+
+        PoolStatus {
+            size,
+            idle,
+            in_use: size.saturating_sub(idle as u32),
+            max_connections: self.max_connections,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Chain;
+    use crate::repository::{TransactionRepository, WalletRepository, WebhookEventRepository};
+    use crate::services::testing::{SENDER, USDC_MINT, WALLET};
+
+    /// Run the migrations whose version passes `versions`, oldest first, so
+    /// rows can be seeded at a point in the schema's history
+    async fn migrate(pool: &PgPool, versions: impl Fn(i64) -> bool) {
+        for migration in MIGRATOR.iter().filter(|m| versions(m.version)) {
+            sqlx::raw_sql(&migration.sql).execute(pool).await.unwrap();
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn rows_from_before_chains_are_solana(pool: PgPool) {
+        migrate(&pool, |version| version < 26).await;
+        sqlx::query("INSERT INTO wallets (address) VALUES ($1)")
+            .bind(WALLET)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (signature, wallet_address, tx_type, amount, amount_raw,
+                decimals, token_mint, counterparty, status, block_time)
+            VALUES ('sig1', $1, 'receive', 1.5, 1500000, 6, $2, $3, 'confirmed', NOW())
+            "#,
+        )
+        .bind(WALLET)
+        .bind(USDC_MINT)
+        .bind(SENDER)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (event_id,): (sqlx::types::Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO webhook_events (wallet_address, transaction_signature, event_type, payload)
+            VALUES ($1, 'sig1', 'payment.received', '{}')
+            RETURNING id
+            "#,
+        )
+        .bind(WALLET)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO payment_links (slug, wallet_address) VALUES ('slug1', $1)")
+            .bind(WALLET)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO balance_snapshots (wallet_address, snapshot_date, amount, amount_raw) \
+             VALUES ($1, CURRENT_DATE, 1.5, 1500000)",
+        )
+        .bind(WALLET)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        migrate(&pool, |version| version >= 26).await;
+
+        let wallet = WalletRepository::find_by_address(&pool, WALLET).await.unwrap().unwrap();
+        assert_eq!(wallet.chain, Chain::Solana);
+        let transaction = TransactionRepository::find_by_signature(&pool, "sig1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.chain, Chain::Solana);
+        let event = WebhookEventRepository::find_by_id(&pool, event_id).await.unwrap().unwrap();
+        assert_eq!(event.chain, Chain::Solana);
+        assert_eq!(event.transaction_signature.as_deref(), Some("sig1"));
+        for table in ["payment_links", "balance_snapshots"] {
+            let chains: Vec<(String,)> =
+                sqlx::query_as(&format!("SELECT chain FROM {}", table))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(chains, [("solana".to_string(),)], "{}", table);
+        }
+    }
 }
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A recorded configuration change
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Changed fields before the call; null when the entity was created
+    pub before: Option<serde_json::Value>,
+    /// Changed fields after the call
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Keep only the top-level fields that differ between two JSON objects.
+/// Anything that isn't an object on both sides is returned whole.
+pub fn json_diff(
+    before: Option<&serde_json::Value>,
+    after: Option<&serde_json::Value>,
+) -> (Option<serde_json::Value>, Option<serde_json::Value>) {
+    let (Some(serde_json::Value::Object(before)), Some(serde_json::Value::Object(after))) =
+        (before, after)
+    else {
+        return (before.cloned(), after.cloned());
+    };
+
+    let mut changed_before = serde_json::Map::new();
+    let mut changed_after = serde_json::Map::new();
+
+    for (key, value) in before {
+        if after.get(key) != Some(value) {
+            changed_before.insert(key.clone(), value.clone());
+        }
+    }
+    for (key, value) in after {
+        if before.get(key) != Some(value) {
+            changed_after.insert(key.clone(), value.clone());
+        }
+    }
+
+    (
+        Some(serde_json::Value::Object(changed_before)),
+        Some(serde_json::Value::Object(changed_after)),
+    )
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// A wallet's USDC balance as recorded on a given (UTC) day
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BalanceSnapshot {
+    pub wallet_address: String,
+    pub snapshot_date: NaiveDate,
+    pub amount: Decimal,
+    pub amount_raw: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// One point of a balance chart
+#[derive(Debug, Clone, Serialize)]
+pub struct BalancePoint {
+    pub date: NaiveDate,
+    pub amount: String,
+    pub amount_raw: i64,
+}
+
+/// Chart points every `step_days` ending at `to`, each carrying forward the
+/// latest snapshot on or before its date so days without a reading don't
+/// leave holes. Dates before the first snapshot are left out.
+/// `snapshots` must be sorted by date.
+pub fn carry_forward(
+    snapshots: &[BalanceSnapshot],
+    from: NaiveDate,
+    to: NaiveDate,
+    step_days: i64,
+) -> Vec<BalancePoint> {
+    let mut dates = Vec::new();
+    let mut date = to;
+    while date >= from {
+        dates.push(date);
+        date -= Duration::days(step_days);
+    }
+    dates.reverse();
+
+    let mut points = Vec::with_capacity(dates.len());
+    let mut latest: Option<&BalanceSnapshot> = None;
+    let mut remaining = snapshots.iter().peekable();
+
+    for date in dates {
+        while let Some(snapshot) = remaining.next_if(|s| s.snapshot_date <= date) {
+            latest = Some(snapshot);
+        }
+
+        if let Some(snapshot) = latest {
+            points.push(BalancePoint {
+                date,
+                amount: snapshot.amount.to_string(),
+                amount_raw: snapshot.amount_raw,
+            });
+        }
+    }
+
+    points
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Blockchain a wallet, transaction or event belongs to. Addresses and
+/// signatures are only unique within a chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    #[default]
+    Solana,
+    /// Recognized so requests get a clear error; not supported yet
+    Base,
+}
+
+impl std::fmt::Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Chain::Solana => write!(f, "solana"),
+            Chain::Base => write!(f, "base"),
+        }
+    }
+}
+
+impl std::str::FromStr for Chain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solana" => Ok(Chain::Solana),
+            "base" => Ok(Chain::Base),
+            other => Err(format!("Unknown chain '{}', expected solana or base", other)),
+        }
+    }
+}
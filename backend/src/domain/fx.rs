@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+/// Fiat currencies amounts can be displayed in, besides USD itself
+pub const SUPPORTED_DISPLAY_CURRENCIES: &[&str] = &["EUR", "GBP", "CHF", "CAD", "AUD", "JPY"];
+
+/// Units of `currency` per 1 USD (USDC is treated as 1:1 with USD)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FxRate {
+    pub currency: String,
+    pub rate: Decimal,
+    pub as_of: DateTime<Utc>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl FxRate {
+    /// Convert a USD amount, rounding half to even at 2 decimal places
+    pub fn convert(&self, usd_amount: Decimal) -> Decimal {
+        (usd_amount * self.rate).round_dp_with_strategy(2, RoundingStrategy::MidpointNearestEven)
+    }
+
+    pub fn display(&self, usd_amount: Decimal) -> DisplayAmount {
+        DisplayAmount {
+            display_amount: self.convert(usd_amount).to_string(),
+            display_currency: self.currency.clone(),
+            fx_rate: self.rate.normalize().to_string(),
+            fx_rate_as_of: self.as_of,
+        }
+    }
+}
+
+/// An amount converted to the wallet's display currency, with the rate used
+/// so the conversion can be audited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayAmount {
+    pub display_amount: String,
+    pub display_currency: String,
+    pub fx_rate: String,
+    pub fx_rate_as_of: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(currency: &str, rate: Decimal) -> FxRate {
+        FxRate {
+            currency: currency.to_string(),
+            rate,
+            as_of: Utc::now(),
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn conversions_round_half_to_even_at_cents() {
+        let par = rate("EUR", Decimal::ONE);
+
+        assert_eq!(par.convert(Decimal::new(1005, 3)), Decimal::new(100, 2));
+        assert_eq!(par.convert(Decimal::new(1015, 3)), Decimal::new(102, 2));
+        assert_eq!(par.convert(Decimal::new(1006, 3)), Decimal::new(101, 2));
+        assert_eq!(par.convert(Decimal::new(1, 6)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn conversions_round_the_product_not_the_rate() {
+        let eur = rate("EUR", Decimal::new(9234, 4));
+
+        // 1.5 * 0.9234 = 1.3851
+        assert_eq!(eur.convert(Decimal::new(15, 1)), Decimal::new(139, 2));
+        // 1_000_000.000001 * 0.9234 = 923400.0000009234
+        assert_eq!(eur.convert(Decimal::new(1_000_000_000_001, 6)), Decimal::new(92_340_000, 2));
+    }
+
+    #[test]
+    fn display_shows_the_rate_without_stored_trailing_zeros() {
+        // Rates come back from DECIMAL(20, 10) with all ten places
+        let gbp = rate("GBP", Decimal::new(7_900_000_000, 10));
+        let display = gbp.display(Decimal::new(250, 2));
+
+        assert_eq!(display.display_amount, "1.98");
+        assert_eq!(display.display_currency, "GBP");
+        assert_eq!(display.fx_rate, "0.79");
+    }
+}
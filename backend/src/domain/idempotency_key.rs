@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+
+/// A remembered response for an `Idempotency-Key`. `status_code` is `None`
+/// while the first request with the key is still being handled.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct IdempotencyKey {
+    pub key: String,
+    pub endpoint: String,
+    /// SHA-256 of the request's Authorization header, hex
+    pub caller: String,
+    pub request_hash: String,
+    pub status_code: Option<i32>,
+    pub content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How long a key's response is remembered
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
@@ -1,7 +1,39 @@
+mod audit_log;
+mod balance_snapshot;
+mod chain;
+mod fx;
+mod idempotency_key;
+mod payment_link;
+mod sync_cursor;
+mod sync_run;
+mod token_metadata;
 mod transaction;
 mod wallet;
+mod wallet_group;
 mod webhook_event;
 
-pub use transaction::{Transaction, TransactionStatus, TransactionType};
-pub use wallet::Wallet;
-pub use webhook_event::{PaymentReceivedPayload, WebhookEvent, WebhookPayload, WebhookStatus};
+pub use audit_log::{json_diff, AuditLogEntry};
+pub use balance_snapshot::{carry_forward, BalancePoint, BalanceSnapshot};
+pub use chain::Chain;
+pub use fx::{DisplayAmount, FxRate, SUPPORTED_DISPLAY_CURRENCIES};
+pub use idempotency_key::{IdempotencyKey, IDEMPOTENCY_KEY_TTL_HOURS};
+pub use payment_link::{
+    AmountMatchRule, PaymentLink, PaymentLinkCancelledPayload, PaymentLinkPaidPayload,
+    PaymentLinkStatus,
+};
+pub use sync_cursor::SyncCursor;
+pub use sync_run::SyncRun;
+pub use token_metadata::{TokenMetadata, TokenMetadataSource};
+pub use transaction::{
+    from_base_units, is_plausible_amount, largest_counterparty, to_base_units, Transaction, TransactionStats,
+    TransactionStatus, TransactionType, TransferLeg,
+};
+pub use wallet::{
+    crossed_thresholds, BalanceThresholdPayload, SignatureEncoding, ThresholdDirection, Wallet,
+    WalletSettings,
+};
+pub use wallet_group::{GroupBalance, ReceiveVolume, WalletGroup, WebhookGroup};
+pub use webhook_event::{
+    redact_payload, PaymentReceivedPayload, WebhookDeliveryAttempt, WebhookEvent, WebhookPayload,
+    WebhookStatus, PAYLOAD_REDACTIONS, WEBHOOK_EVENT_TYPES,
+};
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentLinkStatus {
+    Active,
+    Paid,
+    /// A single-use link's payment fell short by more than its tolerance
+    Underpaid,
+    /// Withdrawn by the merchant; no longer matched to payments
+    Cancelled,
+}
+
+impl std::fmt::Display for PaymentLinkStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentLinkStatus::Active => write!(f, "active"),
+            PaymentLinkStatus::Paid => write!(f, "paid"),
+            PaymentLinkStatus::Underpaid => write!(f, "underpaid"),
+            PaymentLinkStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PaymentLink {
+    pub id: Uuid,
+    pub slug: String,
+    pub wallet_address: String,
+    pub amount: Option<Decimal>,
+    pub memo: Option<String>,
+    /// Reusable (donation-style) links stay active after each payment
+    pub reusable: bool,
+    pub status: PaymentLinkStatus,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Most recent payment matched to the link
+    pub paid_signature: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub payment_count: i32,
+    pub created_at: DateTime<Utc>,
+    /// Shortfall accepted as paid, in USDC
+    pub amount_tolerance: Decimal,
+    /// Shortfall accepted as paid, in percent of `amount`
+    pub amount_tolerance_pct: Decimal,
+    /// Amount of the most recent matched payment
+    pub received_amount: Option<Decimal>,
+    /// Starts at 1 and is bumped by every amendment
+    pub version: i32,
+    /// Amendments, oldest first: `{version, changed_at, before, after}` with
+    /// only the changed fields
+    pub history: serde_json::Value,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+/// How a received amount compared to a link's requested amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountMatchRule {
+    Exact,
+    Overpaid,
+    /// Short by no more than the absolute tolerance
+    AbsoluteTolerance,
+    /// Short by no more than the percentage tolerance
+    PercentTolerance,
+    Underpaid,
+}
+
+impl AmountMatchRule {
+    /// Over-payments are always accepted; a shortfall exactly at either
+    /// tolerance still counts as paid. Keep in step with the matching query
+    /// in `PaymentLinkRepository::match_payment`.
+    pub fn evaluate(
+        requested: Decimal,
+        received: Decimal,
+        tolerance: Decimal,
+        tolerance_pct: Decimal,
+    ) -> Self {
+        if received == requested {
+            return AmountMatchRule::Exact;
+        }
+        if received > requested {
+            return AmountMatchRule::Overpaid;
+        }
+
+        let shortfall = requested - received;
+        if shortfall <= tolerance {
+            AmountMatchRule::AbsoluteTolerance
+        } else if shortfall <= requested * tolerance_pct / Decimal::ONE_HUNDRED {
+            AmountMatchRule::PercentTolerance
+        } else {
+            AmountMatchRule::Underpaid
+        }
+    }
+
+    pub fn is_paid(self) -> bool {
+        self != AmountMatchRule::Underpaid
+    }
+}
+
+impl PaymentLink {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// How the most recent payment matched, for links with a fixed amount
+    pub fn match_rule(&self) -> Option<AmountMatchRule> {
+        let (requested, received) = (self.amount?, self.received_amount?);
+
+        Some(AmountMatchRule::evaluate(
+            requested,
+            received,
+            self.amount_tolerance,
+            self.amount_tolerance_pct,
+        ))
+    }
+
+    /// How much the most recent payment exceeded the requested amount
+    pub fn overpaid_by(&self) -> Option<Decimal> {
+        let (requested, received) = (self.amount?, self.received_amount?);
+
+        (received > requested).then(|| received - requested)
+    }
+
+    /// No payment has been matched yet, so the terms may still change
+    pub fn is_unmatched(&self) -> bool {
+        self.status == PaymentLinkStatus::Active && self.payment_count == 0
+    }
+
+    /// Status as shown to payers: an unpaid link past its expiry is "expired"
+    pub fn display_status(&self, now: DateTime<Utc>) -> &'static str {
+        match self.status {
+            PaymentLinkStatus::Paid => "paid",
+            PaymentLinkStatus::Underpaid => "underpaid",
+            PaymentLinkStatus::Cancelled => "cancelled",
+            PaymentLinkStatus::Active if self.is_expired(now) => "expired",
+            PaymentLinkStatus::Active => "active",
+        }
+    }
+}
+
+/// Payload structure for payment_link.paid and payment_link.underpaid
+/// webhook events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLinkPaidPayload {
+    pub slug: String,
+    pub wallet_address: String,
+    /// Amount received (same as `received_amount`)
+    pub amount: String,
+    pub requested_amount: String,
+    pub received_amount: String,
+    pub overpaid_by: Option<String>,
+    pub match_rule: AmountMatchRule,
+    pub amount_tolerance: String,
+    pub amount_tolerance_pct: String,
+    pub signature: String,
+    pub memo: Option<String>,
+    pub reusable: bool,
+    pub paid_at: DateTime<Utc>,
+}
+
+/// Payload structure for payment_link.cancelled webhook events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLinkCancelledPayload {
+    pub slug: String,
+    pub wallet_address: String,
+    pub amount: Option<String>,
+    pub memo: Option<String>,
+    pub reusable: bool,
+    /// Payments matched before cancellation (reusable links only)
+    pub payment_count: i32,
+    pub version: i32,
+    pub cancelled_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdc(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn evaluate(received: &str, tolerance: &str, tolerance_pct: &str) -> AmountMatchRule {
+        AmountMatchRule::evaluate(usdc("100"), usdc(received), usdc(tolerance), usdc(tolerance_pct))
+    }
+
+    #[test]
+    fn exact_and_over_payments_always_pay() {
+        assert_eq!(evaluate("100.000000", "0", "0"), AmountMatchRule::Exact);
+        assert_eq!(evaluate("100.000001", "0", "0"), AmountMatchRule::Overpaid);
+        assert_eq!(evaluate("99.999999", "0", "0"), AmountMatchRule::Underpaid);
+    }
+
+    #[test]
+    fn the_absolute_tolerance_includes_its_boundary() {
+        assert_eq!(evaluate("99.95", "0.05", "0"), AmountMatchRule::AbsoluteTolerance);
+        assert_eq!(evaluate("99.949999", "0.05", "0"), AmountMatchRule::Underpaid);
+    }
+
+    #[test]
+    fn the_percent_tolerance_includes_its_boundary() {
+        // 0.25% of 100 is 0.25
+        assert_eq!(evaluate("99.75", "0", "0.25"), AmountMatchRule::PercentTolerance);
+        assert_eq!(evaluate("99.749999", "0", "0.25"), AmountMatchRule::Underpaid);
+    }
+
+    #[test]
+    fn the_absolute_tolerance_is_reported_when_both_cover_the_shortfall() {
+        assert_eq!(evaluate("99.9", "0.1", "1"), AmountMatchRule::AbsoluteTolerance);
+        assert_eq!(evaluate("99.5", "0.1", "1"), AmountMatchRule::PercentTolerance);
+        assert!(!evaluate("98.9", "0.1", "1").is_paid());
+    }
+}
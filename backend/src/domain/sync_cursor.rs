@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Progress of a wallet's history backfill
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SyncCursor {
+    pub wallet_address: String,
+    /// Oldest signature whose page was fully processed; the walk resumes
+    /// before it
+    pub oldest_signature: Option<String>,
+    pub scanned: i64,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SyncCursor {
+    pub fn is_complete(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
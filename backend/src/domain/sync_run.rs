@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A persisted background sync cycle
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SyncRun {
+    pub id: i64,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub wallets_synced: i32,
+    pub new_transactions: i32,
+    pub webhooks_triggered: i32,
+    /// Pending transactions expired in the cycle
+    pub expired_transactions: i32,
+    pub error_count: i32,
+    pub errors: serde_json::Value,
+}
+
+impl SyncRun {
+    pub fn duration_ms(&self) -> i64 {
+        (self.completed_at - self.started_at).num_milliseconds()
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a token's metadata came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TokenMetadataSource {
+    /// The bundled list of well-known stablecoins
+    Static,
+    /// The mint's Metaplex metadata account
+    Metaplex,
+    /// The mint account alone: decimals are known, the symbol is made up
+    Mint,
+    /// Nothing could be read; the symbol is made up and decimals unknown
+    Unknown,
+}
+
+/// Display details of an SPL token mint
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TokenMetadata {
+    pub mint: String,
+    pub symbol: String,
+    pub name: String,
+    /// Null only when the mint couldn't be read
+    pub decimals: Option<i16>,
+    pub logo_uri: Option<String>,
+    pub source: TokenMetadataSource,
+}
+
+impl TokenMetadata {
+    /// Stand-in for a mint without metadata: its address, shortened, as the
+    /// symbol (e.g. "EPjF...Dt1v") and in full as the name
+    pub fn fallback(mint: &str, decimals: Option<i16>) -> Self {
+        let symbol = match (mint.get(..4), mint.get(mint.len().saturating_sub(4)..)) {
+            (Some(head), Some(tail)) if mint.len() > 8 => format!("{}...{}", head, tail),
+            _ => mint.to_string(),
+        };
+
+        Self {
+            mint: mint.to_string(),
+            symbol,
+            name: mint.to_string(),
+            decimals,
+            logo_uri: None,
+            source: if decimals.is_some() {
+                TokenMetadataSource::Mint
+            } else {
+                TokenMetadataSource::Unknown
+            },
+        }
+    }
+}
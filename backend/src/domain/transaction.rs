@@ -1,13 +1,19 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use super::Chain;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
-#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum TransactionType {
     Send,
     Receive,
+    /// USDC moved between the wallet's own token accounts; its balance is
+    /// unchanged and the counterparty is the wallet itself
+    SelfTransfer,
 }
 
 impl std::fmt::Display for TransactionType {
@@ -15,6 +21,7 @@ impl std::fmt::Display for TransactionType {
         match self {
             TransactionType::Send => write!(f, "send"),
             TransactionType::Receive => write!(f, "receive"),
+            TransactionType::SelfTransfer => write!(f, "self_transfer"),
         }
     }
 }
@@ -26,6 +33,9 @@ pub enum TransactionStatus {
     Confirmed,
     Pending,
     Failed,
+    /// Pending past the expiry age and unknown to the cluster: it never
+    /// landed, unlike a failed transaction
+    Expired,
 }
 
 impl std::fmt::Display for TransactionStatus {
@@ -34,6 +44,7 @@ impl std::fmt::Display for TransactionStatus {
             TransactionStatus::Confirmed => write!(f, "confirmed"),
             TransactionStatus::Pending => write!(f, "pending"),
             TransactionStatus::Failed => write!(f, "failed"),
+            TransactionStatus::Expired => write!(f, "expired"),
         }
     }
 }
@@ -41,12 +52,234 @@ impl std::fmt::Display for TransactionStatus {
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Transaction {
     pub signature: String,
+    pub chain: Chain,
     pub wallet_address: String,
     pub tx_type: TransactionType,
     pub amount: Decimal,
+    pub amount_raw: i64,
+    pub decimals: i16,
     pub token_mint: String,
     pub counterparty: String,
     pub status: TransactionStatus,
     pub block_time: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    /// SPL transfers decoded from the instructions; empty when none were
+    /// found or for rows stored before they were recorded
+    pub transfer_legs: serde_json::Value,
+    /// Text of the transaction's SPL memo instruction
+    pub memo: Option<String>,
+    /// The wallet's payment reference the memo matched, for receives
+    pub reference: Option<String>,
+}
+
+/// One SPL token transfer into or out of a wallet's token account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferLeg {
+    /// Token accounts
+    pub source: String,
+    pub destination: String,
+    /// Wallets owning those token accounts, when the transaction reports them
+    pub source_owner: Option<String>,
+    pub destination_owner: Option<String>,
+    pub amount_raw: u64,
+}
+
+/// The other side contributing most to a wallet's movement: the source owner
+/// with the largest total for receives, the destination owner for sends.
+/// Ties go to the first seen.
+pub fn largest_counterparty(
+    legs: &[TransferLeg],
+    wallet_address: &str,
+    tx_type: TransactionType,
+) -> Option<String> {
+    let mut totals: Vec<(&str, u128)> = Vec::new();
+
+    for leg in legs {
+        let (ours, other, other_account) = match tx_type {
+            TransactionType::Receive => (&leg.destination_owner, &leg.source_owner, &leg.source),
+            TransactionType::Send => (&leg.source_owner, &leg.destination_owner, &leg.destination),
+            // Both sides are the wallet
+            TransactionType::SelfTransfer => return None,
+        };
+        if ours.as_deref() != Some(wallet_address) || other.as_deref() == Some(wallet_address) {
+            continue;
+        }
+
+        let key = other.as_deref().unwrap_or(other_account);
+        match totals.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, total)) => *total += leg.amount_raw as u128,
+            None => totals.push((key, leg.amount_raw as u128)),
+        }
+    }
+
+    totals
+        .into_iter()
+        .fold(None, |best: Option<(&str, u128)>, (key, total)| match best {
+            Some((_, best_total)) if best_total >= total => best,
+            _ => Some((key, total)),
+        })
+        .map(|(key, _)| key.to_string())
+}
+
+/// Aggregate figures over a wallet's confirmed transactions
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TransactionStats {
+    pub transaction_count: i64,
+    pub received_count: i64,
+    pub sent_count: i64,
+    pub total_received: Decimal,
+    pub total_sent: Decimal,
+    pub last_transaction_at: Option<DateTime<Utc>>,
+}
+
+/// Whole tokens one transfer can plausibly move; USDC's entire supply is
+/// far below it, so a larger delta means misread balances
+const MAX_PLAUSIBLE_TOKENS: u64 = 1_000_000_000_000;
+
+/// Whether a parsed balance delta can be real: positive, at most
+/// `MAX_PLAUSIBLE_TOKENS` whole tokens, and within the i64 rows store
+pub fn is_plausible_amount(amount_raw: u64, decimals: u8) -> bool {
+    let limit = 10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| scale.checked_mul(MAX_PLAUSIBLE_TOKENS))
+        .unwrap_or(u64::MAX)
+        .min(i64::MAX as u64);
+
+    amount_raw > 0 && amount_raw <= limit
+}
+
+/// Exact decimal value of `amount_raw` base units of a token with
+/// `decimals` places. Any u64 fits Decimal's 96-bit mantissa; `None` only
+/// for more decimals than Decimal can scale (28).
+pub fn from_base_units(amount_raw: u64, decimals: u8) -> Option<Decimal> {
+    Decimal::try_from_i128_with_scale(amount_raw as i128, decimals as u32).ok()
+}
+
+/// Convert a decimal token amount into base units, rounding up so a threshold
+/// expressed as a Decimal is never loosened. Returns `None` if it doesn't fit.
+pub fn to_base_units(amount: Decimal, decimals: u32) -> Option<i64> {
+    amount
+        .checked_mul(Decimal::from(10i64.checked_pow(decimals)?))?
+        .ceil()
+        .to_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::str::FromStr;
+
+    fn transaction(amount: Decimal) -> Transaction {
+        Transaction {
+            signature: "sig".to_string(),
+            chain: crate::domain::Chain::Solana,
+            wallet_address: "wallet".to_string(),
+            tx_type: TransactionType::Receive,
+            amount,
+            amount_raw: 0,
+            decimals: 6,
+            token_mint: "mint".to_string(),
+            counterparty: "sender".to_string(),
+            status: TransactionStatus::Confirmed,
+            block_time: DateTime::UNIX_EPOCH,
+            created_at: DateTime::UNIX_EPOCH,
+            transfer_legs: serde_json::json!([]),
+            memo: None,
+            reference: None,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn six_decimal_amounts_round_trip_through_json(amount_raw in 0..=i64::MAX as u64) {
+            let amount = from_base_units(amount_raw, 6).unwrap();
+            let json = serde_json::to_value(transaction(amount)).unwrap();
+
+            // A JSON number would be read back through f64 by most clients
+            prop_assert_eq!(json["amount"].as_str(), Some(&*amount.to_string()));
+            let parsed: Transaction = serde_json::from_value(json).unwrap();
+            prop_assert_eq!(parsed.amount, amount);
+            prop_assert_eq!(parsed.amount.scale(), 6);
+        }
+
+        #[test]
+        fn plausible_amounts_are_positive_and_fit_a_row(amount_raw: u64, decimals in 0u8..=18) {
+            if is_plausible_amount(amount_raw, decimals) {
+                let amount = from_base_units(amount_raw, decimals).unwrap();
+                prop_assert!(amount > Decimal::ZERO);
+                prop_assert!(i64::try_from(amount_raw).is_ok());
+                prop_assert!(amount <= Decimal::from(MAX_PLAUSIBLE_TOKENS));
+            }
+        }
+
+        #[test]
+        fn base_units_round_trip(amount_raw in 0..=i64::MAX as u64, decimals in 0u8..=18) {
+            let amount = from_base_units(amount_raw, decimals).unwrap();
+            prop_assert_eq!(to_base_units(amount, decimals as u32), Some(amount_raw as i64));
+        }
+
+        #[test]
+        fn thresholds_round_up_to_whole_base_units(
+            units in 1i64..1_000_000_000,
+            extra in 1i64..10,
+        ) {
+            // A hundredth of a base unit or more above `units` base units
+            let threshold = Decimal::new(units * 100 + extra, 8);
+            prop_assert_eq!(to_base_units(threshold, 6), Some(units + 1));
+        }
+    }
+
+    #[test]
+    fn fifteen_digit_totals_keep_every_digit() {
+        for total in ["123456789012345.678901", "999999999999999.999999", "100000000000000.000001"]
+        {
+            let total = Decimal::from_str(total).unwrap();
+            // Beyond f64's ~15.9 significant digits
+            assert_ne!(Decimal::from_f64_retain(total.to_string().parse().unwrap()), Some(total));
+
+            let stats = TransactionStats {
+                transaction_count: 1,
+                received_count: 1,
+                sent_count: 0,
+                total_received: total,
+                total_sent: Decimal::ZERO,
+                last_transaction_at: None,
+            };
+            let json = serde_json::to_string(&stats).unwrap();
+            assert!(json.contains(&format!("\"total_received\":\"{total}\"")), "{json}");
+
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let parsed: Decimal = serde_json::from_value(value["total_received"].clone()).unwrap();
+            assert_eq!(parsed, total);
+            assert_eq!(parsed.to_string(), total.to_string());
+        }
+    }
+
+    #[test]
+    fn zero_is_never_plausible() {
+        for decimals in [0, 6, 9, 18, 30] {
+            assert!(!is_plausible_amount(0, decimals));
+        }
+    }
+
+    #[test]
+    fn u64_max_converts_exactly_at_any_scale() {
+        assert_eq!(from_base_units(u64::MAX, 0).unwrap().to_string(), "18446744073709551615");
+        assert_eq!(from_base_units(u64::MAX, 9).unwrap().to_string(), "18446744073.709551615");
+        assert!(from_base_units(u64::MAX, 28).is_some());
+        assert_eq!(from_base_units(1, 29), None);
+    }
+
+    #[test]
+    fn nine_decimal_plausibility_is_capped_by_the_stored_range() {
+        // A trillion whole tokens at 9 decimals exceeds i64, so the row's
+        // range is the limit
+        assert!(is_plausible_amount(i64::MAX as u64, 9));
+        assert!(!is_plausible_amount(i64::MAX as u64 + 1, 9));
+        assert!(!is_plausible_amount(u64::MAX, 9));
+        // At 6 decimals the token cap binds first
+        assert!(is_plausible_amount(MAX_PLAUSIBLE_TOKENS * 1_000_000, 6));
+        assert!(!is_plausible_amount(MAX_PLAUSIBLE_TOKENS * 1_000_000 + 1, 6));
+    }
 }
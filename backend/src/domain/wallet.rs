@@ -1,9 +1,144 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::Chain;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Wallet {
     pub address: String,
+    pub chain: Chain,
     pub webhook_url: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub min_webhook_amount: Decimal,
+    pub webhook_headers: serde_json::Value,
+    pub label: Option<String>,
+    pub metadata: serde_json::Value,
+    /// Transactions older than this are never synced
+    pub ignore_before: Option<DateTime<Utc>>,
+    /// ISO 4217 code amounts are additionally shown in, e.g. "EUR"
+    pub display_currency: Option<String>,
+    /// Webhook deliveries allowed per rolling minute; excess events are deferred
+    pub max_webhooks_per_minute: i32,
+    /// End of the last successful background sync
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Background syncs failed in a row; reset on success
+    pub sync_error_streak: i32,
+    pub last_sync_error: Option<String>,
+    /// Redis stream events are published to instead of `webhook_url`
+    pub webhook_stream: Option<String>,
+    /// Header carrying the webhook signature; `None` is X-Webhook-Signature
+    pub webhook_signature_header: Option<String>,
+    pub webhook_signature_encoding: SignatureEncoding,
+    /// USDC balance levels that fire balance.threshold when crossed
+    pub balance_thresholds: Vec<Decimal>,
+    /// Balance at the last threshold check
+    pub last_balance: Option<Decimal>,
+    pub last_balance_at: Option<DateTime<Utc>>,
+    pub group_id: Option<Uuid>,
+    /// Customer references payments carry in their memo, for shared addresses
+    pub payment_references: Vec<String>,
+    /// Webhook payload fields truncated or left out, see `PAYLOAD_REDACTIONS`
+    pub payload_redactions: Vec<String>,
+    /// IANA zone transaction timestamps are also shown in, e.g. "Europe/Berlin",
+    /// unless the request names one
+    pub timezone: Option<String>,
+}
+
+impl Wallet {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// The registered reference a payment's memo names, if any. Surrounding
+    /// whitespace in the memo is ignored; the comparison is exact otherwise.
+    pub fn reference_for(&self, memo: Option<&str>) -> Option<String> {
+        let memo = memo?.trim();
+        self.payment_references
+            .iter()
+            .find(|reference| reference.as_str() == memo)
+            .cloned()
+    }
+}
+
+/// Wallet settings supplied on registration or update.
+/// `None` fields keep the stored value (or the column default on insert).
+#[derive(Debug, Clone, Default)]
+pub struct WalletSettings {
+    pub webhook_url: Option<String>,
+    pub min_webhook_amount: Option<Decimal>,
+    pub webhook_headers: Option<serde_json::Value>,
+    pub label: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub ignore_before: Option<DateTime<Utc>>,
+    pub display_currency: Option<String>,
+    pub max_webhooks_per_minute: Option<i32>,
+    /// Empty string clears it
+    pub webhook_stream: Option<String>,
+    /// Replaces the stored list; empty clears it
+    pub balance_thresholds: Option<Vec<Decimal>>,
+    /// Empty string restores the default header
+    pub webhook_signature_header: Option<String>,
+    pub webhook_signature_encoding: Option<SignatureEncoding>,
+    /// Wallet group id; empty string removes the wallet from its group
+    pub group_id: Option<String>,
+    /// Replaces the stored list; empty clears it
+    pub payment_references: Option<Vec<String>>,
+    /// Replaces the stored list; empty clears it
+    pub payload_redactions: Option<Vec<String>>,
+    /// Empty string clears it
+    pub timezone: Option<String>,
+}
+
+/// How the HMAC-SHA256 digest of a webhook body is written into its
+/// signature header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+/// Which way a balance moved through a threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdDirection {
+    Above,
+    Below,
+}
+
+/// Thresholds passed between two balance readings, in configured order.
+/// Reaching a threshold exactly counts as being above it.
+pub fn crossed_thresholds(
+    previous: Decimal,
+    current: Decimal,
+    thresholds: &[Decimal],
+) -> Vec<(Decimal, ThresholdDirection)> {
+    thresholds
+        .iter()
+        .filter_map(|&threshold| {
+            if previous < threshold && current >= threshold {
+                Some((threshold, ThresholdDirection::Above))
+            } else if previous >= threshold && current < threshold {
+                Some((threshold, ThresholdDirection::Below))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Payload structure for balance.threshold webhook events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceThresholdPayload {
+    pub wallet_address: String,
+    pub threshold: String,
+    pub direction: ThresholdDirection,
+    pub previous_balance: String,
+    pub balance: String,
+    pub observed_at: DateTime<Utc>,
 }
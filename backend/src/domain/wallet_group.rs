@@ -0,0 +1,48 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+/// Wallets organized together, e.g. one seller's or store's deposit wallets
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WalletGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Group of the wallet an event is for, carried in webhook payloads so
+/// receivers can route per seller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookGroup {
+    pub id: Uuid,
+    pub name: String,
+}
+
+impl From<&WalletGroup> for WebhookGroup {
+    fn from(group: &WalletGroup) -> Self {
+        Self {
+            id: group.id,
+            name: group.name.clone(),
+        }
+    }
+}
+
+/// Sum of the latest balance snapshot of each member wallet
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct GroupBalance {
+    pub total: Decimal,
+    /// Members with at least one snapshot; the rest aren't in `total`
+    pub wallets_with_snapshot: i64,
+    /// Oldest of the snapshots summed, i.e. how stale `total` may be
+    pub oldest_snapshot_date: Option<NaiveDate>,
+}
+
+/// Confirmed USDC received by a group's wallets over trailing windows
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReceiveVolume {
+    pub received_24h: Decimal,
+    pub received_7d: Decimal,
+    pub received_30d: Decimal,
+}
@@ -2,6 +2,18 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
 
+use super::{Chain, DisplayAmount, TokenMetadata, Transaction, WebhookGroup};
+
+/// Every event type a webhook event can be recorded with
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    "payment.received",
+    "payment_link.paid",
+    "payment_link.underpaid",
+    "payment_link.cancelled",
+    "balance.threshold",
+    "test",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -21,10 +33,27 @@ impl std::fmt::Display for WebhookStatus {
     }
 }
 
+impl std::str::FromStr for WebhookStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(WebhookStatus::Pending),
+            "delivered" => Ok(WebhookStatus::Delivered),
+            "failed" => Ok(WebhookStatus::Failed),
+            other => Err(format!(
+                "Unknown webhook status '{}', expected pending, delivered or failed",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct WebhookEvent {
     pub id: Uuid,
     pub wallet_address: String,
+    pub chain: Chain,
     pub transaction_signature: Option<String>,
     pub event_type: String,
     pub payload: serde_json::Value,
@@ -34,6 +63,40 @@ pub struct WebhookEvent {
     pub delivered_at: Option<DateTime<Utc>>,
     pub last_error: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// HTTP status of the last attempt; `None` if it never got a response
+    pub last_response_status: Option<i32>,
+    pub last_response_time_ms: Option<i32>,
+    /// First 8KB of the last response body
+    pub last_response_body: Option<String>,
+    /// The exact signed body; `None` for events created before it was stored
+    #[serde(skip_serializing)]
+    pub payload_body: Option<String>,
+    /// Set while a pending event is held back by the wallet's webhook quota
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookEvent {
+    /// Bytes to deliver: the body signed at creation, so the signature stays
+    /// valid on every retry. Older events fall back to re-serializing the
+    /// payload.
+    pub fn body_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        match &self.payload_body {
+            Some(body) => Ok(body.clone().into_bytes()),
+            None => serde_json::to_vec(&self.payload),
+        }
+    }
+}
+
+/// One delivery attempt of a webhook event
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDeliveryAttempt {
+    pub attempt_number: i32,
+    pub attempted_at: DateTime<Utc>,
+    pub duration_ms: i32,
+    pub succeeded: bool,
+    /// HTTP status the endpoint answered with; `None` without a response
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
 }
 
 /// Payload structure for payment.received webhook events
@@ -42,15 +105,86 @@ pub struct PaymentReceivedPayload {
     pub signature: String,
     pub wallet_address: String,
     pub amount: String,
+    pub amount_raw: i64,
+    pub decimals: i16,
     pub token: String,
     pub counterparty: String,
     pub block_time: DateTime<Utc>,
+    /// Registered payment reference named by the memo; null when the memo
+    /// is missing or matches none
+    pub reference: Option<String>,
+    /// Amount in the wallet's display currency, when it has one and a rate is known
+    #[serde(flatten)]
+    pub display: Option<DisplayAmount>,
+}
+
+impl PaymentReceivedPayload {
+    /// `token` is the transaction mint's metadata, for its symbol
+    pub fn new(transaction: &Transaction, token: &TokenMetadata) -> Self {
+        Self {
+            signature: transaction.signature.clone(),
+            wallet_address: transaction.wallet_address.clone(),
+            amount: transaction.amount.to_string(),
+            amount_raw: transaction.amount_raw,
+            decimals: transaction.decimals,
+            token: token.symbol.clone(),
+            counterparty: transaction.counterparty.clone(),
+            block_time: transaction.block_time,
+            reference: transaction.reference.clone(),
+            display: None,
+        }
+    }
 }
 
 /// Full webhook event payload sent to webhook URLs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookPayload {
     pub event: String,
+    pub chain: Chain,
+    /// Set for wallets that belong to a group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<WebhookGroup>,
     pub timestamp: DateTime<Utc>,
     pub data: serde_json::Value,
 }
+
+/// Payload redactions a wallet can turn on: `counterparty` and `signature`
+/// truncate those fields, `metadata` leaves out the free-form `memo` and
+/// `reference` a payer attached
+pub const PAYLOAD_REDACTIONS: &[&str] = &["counterparty", "signature", "metadata"];
+
+/// Apply a wallet's redactions to the `data` of a webhook payload, before
+/// it's stored and signed. Unknown redaction names are ignored.
+pub fn redact_payload(data: &mut serde_json::Value, redactions: &[String]) {
+    let Some(fields) = data.as_object_mut() else {
+        return;
+    };
+
+    for redaction in redactions {
+        match redaction.as_str() {
+            "counterparty" | "signature" => {
+                if let Some(serde_json::Value::String(value)) = fields.get_mut(redaction.as_str()) {
+                    *value = truncate(value);
+                }
+            }
+            "metadata" => {
+                fields.remove("memo");
+                fields.remove("reference");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// First and last 4 characters, e.g. "9WzD...7L9y"; shorter values are
+/// masked entirely
+fn truncate(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "...".to_string();
+    }
+
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", head, tail)
+}
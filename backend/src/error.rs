@@ -26,6 +26,33 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unprocessable: {0}")]
+    UnprocessableEntity(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// A transaction amount that can't be real, refused before it's stored
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    /// A token amount too large to represent exactly; never replaced by a
+    /// wrapped or truncated value
+    #[error("Amount overflow: {0}")]
+    AmountOverflow(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    #[error("External API error: {0}")]
+    ExternalApi(String),
+
     #[error("Webhook delivery failed: {0}")]
     WebhookDeliveryFailed(String),
 
@@ -51,6 +78,23 @@ impl IntoResponse for AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
             }
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
+            AppError::UnprocessableEntity(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::InvalidAmount(msg) => {
+                tracing::error!("Invalid amount: {}", msg);
+                (StatusCode::UNPROCESSABLE_ENTITY, msg.clone())
+            }
+            AppError::AmountOverflow(msg) => {
+                tracing::error!("Amount overflow: {}", msg);
+                (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
+            }
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            AppError::ExternalApi(msg) => {
+                tracing::error!("External API error: {}", msg);
+                (StatusCode::BAD_GATEWAY, msg.clone())
+            }
             AppError::WebhookDeliveryFailed(msg) => {
                 tracing::error!("Webhook delivery failed: {}", msg);
                 (StatusCode::BAD_GATEWAY, msg.clone())
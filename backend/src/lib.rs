@@ -0,0 +1,167 @@
+pub mod api;
+pub mod config;
+pub mod db;
+pub mod domain;
+pub mod error;
+pub mod repository;
+pub mod services;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::services::audit::AuditService;
+use crate::services::balance_cache::BalanceCache;
+use crate::services::chain::ChainClient;
+use crate::services::events::EventBus;
+use crate::services::fx::FxService;
+use crate::services::helius::{HeliusChainClient, HeliusParser};
+use crate::services::leader::LeaderLock;
+use crate::services::solana::SolanaClient;
+use crate::services::swap::SwapService;
+use crate::services::sync::SyncService;
+use crate::services::token_accounts::TokenAccountService;
+use crate::services::token_registry::TokenRegistry;
+use crate::services::webhook::WebhookService;
+use crate::services::webhook_delivery::RedisStreamDelivery;
+
+pub struct AppState {
+    pub db: Database,
+    pub solana: Arc<dyn ChainClient>,
+    pub webhook: Arc<WebhookService>,
+    pub sync: Arc<SyncService>,
+    pub events: Arc<EventBus>,
+    pub leader: Arc<LeaderLock>,
+    pub fx: Arc<FxService>,
+    pub swap: Arc<SwapService>,
+    pub token_accounts: Arc<TokenAccountService>,
+    pub tokens: Arc<TokenRegistry>,
+    pub balances: Arc<BalanceCache>,
+    pub audit: Arc<AuditService>,
+    pub config: Config,
+}
+
+impl AppState {
+    /// Connect to the database and wire up all services from config.
+    /// Shared by the API server and the admin CLI; doesn't run migrations
+    /// or start any background task.
+    pub async fn build(config: Config) -> anyhow::Result<Self> {
+        // Initialize database
+        let db = Database::connect(&config).await?;
+
+        // Initialize Solana client, optionally parsing transactions with Helius
+        let rpc = SolanaClient::new(
+            &config.solana_rpc_url,
+            &config.usdc_mint,
+            Duration::from_secs(config.rpc_detail_timeout_secs),
+            config.rpc_detail_concurrency,
+            config.rpc_transaction_cache_entries,
+        );
+        let solana: Arc<dyn ChainClient> =
+            match (&config.helius_api_key, config.helius_enhanced_parser) {
+                (Some(api_key), true) => {
+                    let parser =
+                        HeliusParser::new(&config.helius_api_url, api_key, &config.usdc_mint);
+                    Arc::new(HeliusChainClient::new(rpc, parser))
+                }
+                (None, true) => {
+                    tracing::warn!(
+                        "HELIUS_ENHANCED_PARSER is set without HELIUS_API_KEY, using the RPC parser"
+                    );
+                    Arc::new(rpc)
+                }
+                _ => Arc::new(rpc),
+            };
+
+        // Initialize leader election for background work across replicas
+        let leader = Arc::new(LeaderLock::new(
+            db.pool.clone(),
+            &config.leader_lock_namespace,
+        ));
+
+        // Initialize FX rates for display currencies
+        let fx = Arc::new(FxService::new(
+            db.pool.clone(),
+            &config.fx_api_url,
+            Duration::from_secs(config.fx_refresh_interval_secs),
+            leader.clone(),
+            &config.usdc_price_api_url,
+            &config.usdc_mint,
+        ));
+
+        // Initialize Jupiter swaps into USDC
+        let swap = Arc::new(SwapService::new(
+            &config.jupiter_api_url,
+            &config.usdc_mint,
+            config.swap_input_mints.clone(),
+            config.swap_max_price_impact_pct,
+            chrono::Duration::seconds(config.swap_quote_ttl_secs),
+        ));
+
+        // Initialize token account preparation for receiving wallets
+        let token_accounts = Arc::new(TokenAccountService::new(solana.clone(), &config.usdc_mint));
+
+        // Initialize mint metadata lookups for symbols in responses and payloads
+        let tokens = Arc::new(TokenRegistry::new(db.pool.clone(), solana.clone()));
+
+        // Initialize the balance cache for polling clients
+        let balances = Arc::new(BalanceCache::new(
+            solana.clone(),
+            Duration::from_secs(config.balance_cache_ttl_secs),
+        ));
+
+        // Initialize the audit log of API configuration changes
+        let audit = Arc::new(AuditService::new(db.pool.clone()));
+
+        // Initialize webhook service
+        let stream = config
+            .webhook_redis_url
+            .as_deref()
+            .map(|url| RedisStreamDelivery::new(url, config.webhook_dry_run))
+            .transpose()?;
+        let webhook = Arc::new(WebhookService::new(
+            db.pool.clone(),
+            config.webhook_secret.clone(),
+            fx.clone(),
+            tokens.clone(),
+            config.webhook_dry_run,
+            config.webhook_user_agent.clone(),
+            stream,
+        ));
+
+        // Initialize event bus for live streams
+        let events = Arc::new(EventBus::new());
+
+        // Initialize sync service
+        let sync = Arc::new(SyncService::new(
+            db.pool.clone(),
+            solana.clone(),
+            webhook.clone(),
+            events.clone(),
+            leader.clone(),
+            tokens.clone(),
+            Duration::from_secs(config.balance_snapshot_interval_secs),
+            config.balance_snapshot_retention_days,
+            config.sync_run_retention_days,
+            Duration::from_secs(config.wallet_sync_timeout_secs),
+            Duration::from_secs(config.pending_expiry_secs),
+        ));
+
+        Ok(Self {
+            db,
+            solana,
+            webhook,
+            sync,
+            events,
+            leader,
+            fx,
+            swap,
+            token_accounts,
+            tokens,
+            balances,
+            audit,
+            config,
+        })
+    }
+}
@@ -1,11 +1,3 @@
-mod api;
-mod config;
-mod db;
-mod domain;
-mod error;
-mod repository;
-mod services;
-
 use std::sync::Arc;
 
 use axum::Router;
@@ -13,70 +5,60 @@ use tokio::net::TcpListener;
 use tokio::signal;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
+use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::Config;
-use crate::db::Database;
-use crate::services::solana::SolanaClient;
-use crate::services::sync::SyncService;
-use crate::services::webhook::WebhookService;
-
-pub struct AppState {
-    pub db: Database,
-    pub solana: Arc<SolanaClient>,
-    pub webhook: Arc<WebhookService>,
-    pub sync: Arc<SyncService>,
-    pub config: Config,
-}
+use stablecoin_pay::api;
+use stablecoin_pay::config::{Config, LogFormat};
+use stablecoin_pay::AppState;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
+    // Load config
+    dotenvy::dotenv().ok();
+    let config = Config::from_env()?;
+
+    // Initialize tracing. JSON output flattens event fields to the top level
+    // and reports span timings on close.
+    let json = config.log_format == LogFormat::Json;
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "stablecoin_pay=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(json.then(|| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_span_events(FmtSpan::CLOSE)
+        }))
+        .with((!json).then(tracing_subscriber::fmt::layer))
         .init();
 
-    // Load config
-    dotenvy::dotenv().ok();
-    let config = Config::from_env()?;
-
-    tracing::info!("Starting server on port {}", config.port);
-
-    // Initialize database
-    let db = Database::connect(&config.database_url).await?;
-    db.run_migrations().await?;
+    config.validate()?;
 
-    // Initialize Solana client
-    let solana = Arc::new(SolanaClient::new(&config.solana_rpc_url, &config.usdc_mint));
+    let summary = config
+        .summary()
+        .into_iter()
+        .map(|(key, value)| format!("  {:<24} {}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    tracing::info!("Effective configuration:\n{}", summary);
 
-    // Initialize webhook service
-    let webhook = Arc::new(WebhookService::new(
-        db.pool.clone(),
-        config.webhook_secret.clone(),
-    ));
+    tracing::info!("Starting server on port {}", config.port);
 
-    // Initialize sync service
-    let sync = Arc::new(SyncService::new(
-        db.pool.clone(),
-        solana.clone(),
-        webhook.clone(),
-    ));
+    // Initialize database and services
+    let state = Arc::new(AppState::build(config).await?);
+    check_rpc(&state).await?;
+    state
+        .db
+        .run_migrations_with_retry(state.config.migration_max_attempts)
+        .await?;
 
-    // Start background sync
+    // Start background sync and FX rate refresh
+    let sync = state.sync.clone();
     let sync_handle = sync.clone().start_background_sync();
-
-    // Create app state
-    let state = Arc::new(AppState {
-        db,
-        solana,
-        webhook,
-        sync: sync.clone(),
-        config,
-    });
+    let fx_handle = state.fx.clone().start_background_fetch();
 
     // Build router
     let app = Router::new()
@@ -95,17 +77,36 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Listening on {}", addr);
 
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(sync))
+        .with_graceful_shutdown(shutdown_signal(state.clone()))
         .await?;
 
-    // Wait for background sync to finish
+    // Wait for background tasks to finish
     sync_handle.abort();
+    fx_handle.abort();
     tracing::info!("Server shutdown complete");
 
     Ok(())
 }
 
-async fn shutdown_signal(sync: Arc<SyncService>) {
+/// Startup self-check that the Solana RPC answers. Fatal in production;
+/// elsewhere the readiness probe reports it until the RPC comes up.
+async fn check_rpc(state: &AppState) -> anyhow::Result<()> {
+    match state.solana.get_latest_blockhash().await {
+        Ok(_) => {
+            tracing::info!("Solana RPC reachable");
+            Ok(())
+        }
+        Err(e) if state.config.is_production() => {
+            anyhow::bail!("Solana RPC unreachable: {}", e)
+        }
+        Err(e) => {
+            tracing::warn!("Solana RPC unreachable, continuing outside production: {}", e);
+            Ok(())
+        }
+    }
+}
+
+async fn shutdown_signal(state: Arc<AppState>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -129,5 +130,6 @@ async fn shutdown_signal(sync: Arc<SyncService>) {
     }
 
     tracing::info!("Shutdown signal received, stopping background services...");
-    sync.shutdown();
+    state.sync.shutdown();
+    state.fx.shutdown();
 }
@@ -0,0 +1,67 @@
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::domain::AuditLogEntry;
+use crate::error::AppError;
+
+pub struct AuditLogRepository;
+
+impl AuditLogRepository {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &PgPool,
+        actor: &str,
+        action: &str,
+        entity_type: &str,
+        entity_id: &str,
+        before: Option<&serde_json::Value>,
+        after: Option<&serde_json::Value>,
+        request_id: Option<&str>,
+    ) -> Result<AuditLogEntry, AppError> {
+        let entry = sqlx::query_as::<_, AuditLogEntry>(
+            r#"
+            INSERT INTO audit_log (actor, action, entity_type, entity_id, before, after, request_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(actor)
+        .bind(action)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(before)
+        .bind(after)
+        .bind(request_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Newest entries first, narrowed to an entity type and/or id when given
+    pub async fn list(
+        pool: &PgPool,
+        entity_type: Option<&str>,
+        entity_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<AuditLogEntry>, AppError> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM audit_log WHERE TRUE");
+
+        if let Some(entity_type) = entity_type {
+            query.push(" AND entity_type = ").push_bind(entity_type);
+        }
+        if let Some(entity_id) = entity_id {
+            query.push(" AND entity_id = ").push_bind(entity_id);
+        }
+
+        query
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit);
+
+        let entries = query
+            .build_query_as::<AuditLogEntry>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(entries)
+    }
+}
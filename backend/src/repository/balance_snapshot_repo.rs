@@ -0,0 +1,104 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::domain::{BalanceSnapshot, GroupBalance};
+use crate::error::AppError;
+
+pub struct BalanceSnapshotRepository;
+
+impl BalanceSnapshotRepository {
+    /// Record the day's balance, replacing an earlier reading of the same day
+    pub async fn upsert(
+        pool: &PgPool,
+        wallet_address: &str,
+        snapshot_date: NaiveDate,
+        amount: Decimal,
+        amount_raw: i64,
+    ) -> Result<BalanceSnapshot, AppError> {
+        let snapshot = sqlx::query_as::<_, BalanceSnapshot>(
+            r#"
+            INSERT INTO balance_snapshots (wallet_address, snapshot_date, amount, amount_raw)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (wallet_address, snapshot_date) DO UPDATE SET
+                amount = EXCLUDED.amount,
+                amount_raw = EXCLUDED.amount_raw,
+                recorded_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(snapshot_date)
+        .bind(amount)
+        .bind(amount_raw)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Snapshots from `from` onwards, plus the latest one before it so the
+    /// first days of the range can be carried forward. Oldest first.
+    pub async fn find_since(
+        pool: &PgPool,
+        wallet_address: &str,
+        from: NaiveDate,
+    ) -> Result<Vec<BalanceSnapshot>, AppError> {
+        let snapshots = sqlx::query_as::<_, BalanceSnapshot>(
+            r#"
+            SELECT * FROM balance_snapshots
+            WHERE wallet_address = $1
+              AND snapshot_date >= COALESCE(
+                  (SELECT MAX(snapshot_date) FROM balance_snapshots
+                   WHERE wallet_address = $1 AND snapshot_date <= $2),
+                  $2
+              )
+            ORDER BY snapshot_date ASC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(from)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Total of each group member's latest snapshot
+    pub async fn latest_total_for_group(
+        pool: &PgPool,
+        group_id: Uuid,
+    ) -> Result<GroupBalance, AppError> {
+        let balance = sqlx::query_as::<_, GroupBalance>(
+            r#"
+            SELECT
+                COALESCE(SUM(latest.amount), 0) AS total,
+                COUNT(latest.amount) AS wallets_with_snapshot,
+                MIN(latest.snapshot_date) AS oldest_snapshot_date
+            FROM wallets w
+            CROSS JOIN LATERAL (
+                SELECT amount, snapshot_date FROM balance_snapshots
+                WHERE wallet_address = w.address AND chain = w.chain
+                ORDER BY snapshot_date DESC
+                LIMIT 1
+            ) latest
+            WHERE w.group_id = $1
+            "#,
+        )
+        .bind(group_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(balance)
+    }
+
+    pub async fn delete_before(pool: &PgPool, cutoff: NaiveDate) -> Result<u64, AppError> {
+        let result = sqlx::query("DELETE FROM balance_snapshots WHERE snapshot_date < $1")
+            .bind(cutoff)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
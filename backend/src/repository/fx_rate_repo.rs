@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::domain::FxRate;
+use crate::error::AppError;
+
+pub struct FxRateRepository;
+
+impl FxRateRepository {
+    pub async fn upsert(
+        pool: &PgPool,
+        currency: &str,
+        rate: Decimal,
+        as_of: DateTime<Utc>,
+    ) -> Result<FxRate, AppError> {
+        let fx_rate = sqlx::query_as::<_, FxRate>(
+            r#"
+            INSERT INTO fx_rates (currency, rate, as_of, fetched_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (currency) DO UPDATE SET
+                rate = EXCLUDED.rate,
+                as_of = EXCLUDED.as_of,
+                fetched_at = EXCLUDED.fetched_at
+            RETURNING *
+            "#,
+        )
+        .bind(currency)
+        .bind(rate)
+        .bind(as_of)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(fx_rate)
+    }
+
+    pub async fn find(pool: &PgPool, currency: &str) -> Result<Option<FxRate>, AppError> {
+        let fx_rate = sqlx::query_as::<_, FxRate>(
+            "SELECT * FROM fx_rates WHERE currency = $1",
+        )
+        .bind(currency)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(fx_rate)
+    }
+}
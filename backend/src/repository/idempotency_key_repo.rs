@@ -0,0 +1,241 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::domain::IdempotencyKey;
+use crate::error::AppError;
+
+pub struct IdempotencyKeyRepository;
+
+impl IdempotencyKeyRepository {
+    /// Claim a key for a new request. Returns `true` when this caller owns it
+    /// and should run the request: the key was unused, expired (older than
+    /// `expires_before`), or abandoned mid-request (in flight since before
+    /// `abandoned_before`). Concurrent claims of the same key serialize on the
+    /// primary key, so exactly one of them wins. Keys are scoped to the
+    /// endpoint and to the `caller`'s credential hash.
+    pub async fn claim(
+        pool: &PgPool,
+        key: &str,
+        endpoint: &str,
+        caller: &str,
+        request_hash: &str,
+        expires_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> Result<bool, AppError> {
+        let claimed: Option<(String,)> = sqlx::query_as(
+            r#"
+            INSERT INTO idempotency_keys (key, endpoint, caller, request_hash)
+            VALUES ($1, $2, $6, $3)
+            ON CONFLICT (key, endpoint, caller) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                status_code = NULL,
+                content_type = NULL,
+                response_body = NULL,
+                created_at = NOW()
+            WHERE idempotency_keys.created_at < $4
+               OR (idempotency_keys.status_code IS NULL AND idempotency_keys.created_at < $5)
+            RETURNING key
+            "#,
+        )
+        .bind(key)
+        .bind(endpoint)
+        .bind(request_hash)
+        .bind(expires_before)
+        .bind(abandoned_before)
+        .bind(caller)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    pub async fn find(
+        pool: &PgPool,
+        key: &str,
+        endpoint: &str,
+        caller: &str,
+    ) -> Result<Option<IdempotencyKey>, AppError> {
+        let record = sqlx::query_as::<_, IdempotencyKey>(
+            "SELECT * FROM idempotency_keys WHERE key = $1 AND endpoint = $2 AND caller = $3",
+        )
+        .bind(key)
+        .bind(endpoint)
+        .bind(caller)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Store the response of a claimed request for replay
+    pub async fn complete(
+        pool: &PgPool,
+        key: &str,
+        endpoint: &str,
+        caller: &str,
+        status_code: i32,
+        content_type: Option<&str>,
+        response_body: &[u8],
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = $3, content_type = $4, response_body = $5
+            WHERE key = $1 AND endpoint = $2 AND caller = $6
+            "#,
+        )
+        .bind(key)
+        .bind(endpoint)
+        .bind(status_code)
+        .bind(content_type)
+        .bind(response_body)
+        .bind(caller)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give up a claim so the key can be retried, e.g. after a server error
+    pub async fn release(
+        pool: &PgPool,
+        key: &str,
+        endpoint: &str,
+        caller: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE key = $1 AND endpoint = $2 AND caller = $3 AND status_code IS NULL
+            "#,
+        )
+        .bind(key)
+        .bind(endpoint)
+        .bind(caller)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete keys created before `cutoff`, returning how many were removed
+    pub async fn delete_before(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, AppError> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < $1")
+            .bind(cutoff)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDPOINT: &str = "POST /wallets";
+    const CALLER: &str = "";
+
+    /// Claim `key` as a request arriving now, with the middleware's windows
+    async fn claim(pool: &PgPool, key: &str, hash: &str) -> bool {
+        let now = Utc::now();
+        IdempotencyKeyRepository::claim(
+            pool,
+            key,
+            ENDPOINT,
+            CALLER,
+            hash,
+            now - chrono::Duration::hours(24),
+            now - chrono::Duration::minutes(5),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn age(pool: &PgPool, key: &str, by: chrono::Duration) {
+        sqlx::query("UPDATE idempotency_keys SET created_at = created_at - $2 WHERE key = $1")
+            .bind(key)
+            .bind(by)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn concurrent_claims_of_a_key_have_one_winner(pool: PgPool) {
+        let claims: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { claim(&pool, "key-1", "hash").await })
+            })
+            .collect();
+
+        let mut won = 0;
+        for claim in claims {
+            won += claim.await.unwrap() as usize;
+        }
+        assert_eq!(won, 1);
+    }
+
+    #[sqlx::test]
+    async fn abandoned_and_expired_claims_can_be_taken_over(pool: PgPool) {
+        assert!(claim(&pool, "in-flight", "hash").await);
+        age(&pool, "in-flight", chrono::Duration::minutes(4)).await;
+        assert!(!claim(&pool, "in-flight", "hash").await);
+        age(&pool, "in-flight", chrono::Duration::minutes(2)).await;
+        assert!(claim(&pool, "in-flight", "other").await);
+
+        // A completed response is kept for the whole TTL, then the key is free
+        assert!(claim(&pool, "done", "hash").await);
+        IdempotencyKeyRepository::complete(&pool, "done", ENDPOINT, CALLER, 201, None, b"{}")
+            .await
+            .unwrap();
+        age(&pool, "done", chrono::Duration::hours(23)).await;
+        assert!(!claim(&pool, "done", "hash").await);
+        age(&pool, "done", chrono::Duration::hours(2)).await;
+        assert!(claim(&pool, "done", "hash").await);
+        let record = IdempotencyKeyRepository::find(&pool, "done", ENDPOINT, CALLER)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status_code, None);
+    }
+
+    #[sqlx::test]
+    async fn release_keeps_completed_responses(pool: PgPool) {
+        assert!(claim(&pool, "key-1", "hash").await);
+        IdempotencyKeyRepository::complete(&pool, "key-1", ENDPOINT, CALLER, 200, None, b"{}")
+            .await
+            .unwrap();
+
+        IdempotencyKeyRepository::release(&pool, "key-1", ENDPOINT, CALLER).await.unwrap();
+        let record = IdempotencyKeyRepository::find(&pool, "key-1", ENDPOINT, CALLER).await.unwrap();
+        assert!(record.is_some());
+    }
+
+    #[sqlx::test]
+    async fn callers_have_separate_keys(pool: PgPool) {
+        assert!(claim(&pool, "key-1", "hash").await);
+        IdempotencyKeyRepository::complete(&pool, "key-1", ENDPOINT, CALLER, 200, None, b"{}")
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        let other = "a".repeat(64);
+        let claimed = IdempotencyKeyRepository::claim(
+            &pool,
+            "key-1",
+            ENDPOINT,
+            &other,
+            "hash",
+            now - chrono::Duration::hours(24),
+            now - chrono::Duration::minutes(5),
+        )
+        .await
+        .unwrap();
+        assert!(claimed);
+
+        let record = IdempotencyKeyRepository::find(&pool, "key-1", ENDPOINT, &other).await.unwrap();
+        assert_eq!(record.unwrap().status_code, None);
+    }
+}
@@ -1,7 +1,25 @@
+mod audit_log_repo;
+mod balance_snapshot_repo;
+mod fx_rate_repo;
+mod idempotency_key_repo;
+mod payment_link_repo;
+mod sync_cursor_repo;
+mod sync_run_repo;
+mod token_metadata_repo;
 mod transaction_repo;
+mod wallet_group_repo;
 mod wallet_repo;
 mod webhook_event_repo;
 
-pub use transaction_repo::TransactionRepository;
-pub use wallet_repo::WalletRepository;
-pub use webhook_event_repo::WebhookEventRepository;
+pub use audit_log_repo::AuditLogRepository;
+pub use balance_snapshot_repo::BalanceSnapshotRepository;
+pub use fx_rate_repo::FxRateRepository;
+pub use idempotency_key_repo::IdempotencyKeyRepository;
+pub use payment_link_repo::PaymentLinkRepository;
+pub use sync_cursor_repo::SyncCursorRepository;
+pub use sync_run_repo::SyncRunRepository;
+pub use token_metadata_repo::TokenMetadataRepository;
+pub use transaction_repo::{CreatedTransaction, TransactionRepository};
+pub use wallet_group_repo::WalletGroupRepository;
+pub use wallet_repo::{CreatedWallet, WalletRepository};
+pub use webhook_event_repo::{WebhookEventFilter, WebhookEventRepository};
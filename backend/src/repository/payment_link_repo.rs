@@ -0,0 +1,434 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::domain::PaymentLink;
+use crate::error::AppError;
+
+pub struct PaymentLinkRepository;
+
+impl PaymentLinkRepository {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &PgPool,
+        slug: &str,
+        wallet_address: &str,
+        amount: Option<Decimal>,
+        memo: Option<&str>,
+        reusable: bool,
+        expires_at: Option<DateTime<Utc>>,
+        amount_tolerance: Decimal,
+        amount_tolerance_pct: Decimal,
+    ) -> Result<PaymentLink, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            r#"
+            INSERT INTO payment_links (
+                slug, wallet_address, amount, memo, reusable, expires_at,
+                amount_tolerance, amount_tolerance_pct
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(slug)
+        .bind(wallet_address)
+        .bind(amount)
+        .bind(memo)
+        .bind(reusable)
+        .bind(expires_at)
+        .bind(amount_tolerance)
+        .bind(amount_tolerance_pct)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    pub async fn find_by_slug(pool: &PgPool, slug: &str) -> Result<Option<PaymentLink>, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            "SELECT * FROM payment_links WHERE slug = $1",
+        )
+        .bind(slug)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    /// Cancel an active link. `None` when it isn't active, including when a
+    /// payment settled it first: the row lock orders this against
+    /// `match_payment`, and the status is re-checked once the lock is held.
+    pub async fn cancel(pool: &PgPool, slug: &str) -> Result<Option<PaymentLink>, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            r#"
+            UPDATE payment_links SET status = 'cancelled', cancelled_at = NOW()
+            WHERE slug = $1 AND status = 'active'
+            RETURNING *
+            "#,
+        )
+        .bind(slug)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    /// Replace an unmatched link's amount and memo, bumping its version and
+    /// appending `change` to its history. `None` when the link was matched,
+    /// cancelled or amended since `version` was read.
+    pub async fn amend(
+        pool: &PgPool,
+        slug: &str,
+        version: i32,
+        amount: Option<Decimal>,
+        memo: Option<&str>,
+        change: &serde_json::Value,
+    ) -> Result<Option<PaymentLink>, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            r#"
+            UPDATE payment_links SET
+                amount = $3,
+                memo = $4,
+                version = version + 1,
+                history = history || jsonb_build_array($5::jsonb)
+            WHERE slug = $1
+              AND version = $2
+              AND status = 'active'
+              AND payment_count = 0
+            RETURNING *
+            "#,
+        )
+        .bind(slug)
+        .bind(version)
+        .bind(amount)
+        .bind(memo)
+        .bind(change)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    /// Match a received payment to the wallet's open link it pays: a link
+    /// without an amount whose memo the payment's memo names, otherwise at
+    /// least the link's amount less its tolerance (see `AmountMatchRule`),
+    /// closest amount first, preferring single-use links, then the oldest.
+    /// Records the payment on it. Only links that hadn't expired when the
+    /// payment landed are considered; cancelled ones never are. Atomic, so
+    /// concurrent syncs can't settle a single-use link twice, and the status
+    /// is re-checked under the row lock so a concurrent cancel or amendment
+    /// wins or loses as a whole.
+    pub async fn match_payment(
+        pool: &PgPool,
+        wallet_address: &str,
+        amount: Decimal,
+        signature: &str,
+        block_time: DateTime<Utc>,
+        memo: Option<&str>,
+    ) -> Result<Option<PaymentLink>, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            r#"
+            UPDATE payment_links SET
+                status = CASE WHEN reusable THEN 'active' ELSE 'paid' END,
+                paid_signature = $3,
+                paid_at = $4,
+                payment_count = payment_count + 1,
+                received_amount = $2
+            WHERE id = (
+                SELECT id FROM payment_links
+                WHERE wallet_address = $1
+                  AND (
+                      (amount IS NULL AND BTRIM(memo) = $5)
+                      OR $2 >= amount
+                          - GREATEST(amount_tolerance, amount * amount_tolerance_pct / 100)
+                  )
+                  AND status = 'active'
+                  AND (expires_at IS NULL OR expires_at > $4)
+                  AND paid_signature IS DISTINCT FROM $3
+                ORDER BY amount IS NOT NULL, ABS(amount - $2) ASC, reusable ASC, created_at ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+              AND status = 'active'
+            RETURNING *
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(amount)
+        .bind(signature)
+        .bind(block_time)
+        .bind(memo)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    /// Mark the wallet's open single-use link underpaid by a payment that
+    /// didn't match any link. Only when the wallet has exactly one such link
+    /// with a fixed amount above the payment, so the payment can't belong to
+    /// another link or be an unrelated transfer to a different one.
+    pub async fn mark_underpaid(
+        pool: &PgPool,
+        wallet_address: &str,
+        amount: Decimal,
+        signature: &str,
+        block_time: DateTime<Utc>,
+    ) -> Result<Option<PaymentLink>, AppError> {
+        let link = sqlx::query_as::<_, PaymentLink>(
+            r#"
+            WITH open_links AS (
+                SELECT id, amount FROM payment_links
+                WHERE wallet_address = $1
+                  AND status = 'active'
+                  AND NOT reusable
+                  AND amount IS NOT NULL
+                  AND (expires_at IS NULL OR expires_at > $4)
+            )
+            UPDATE payment_links SET
+                status = 'underpaid',
+                paid_signature = $3,
+                paid_at = $4,
+                payment_count = payment_count + 1,
+                received_amount = $2
+            WHERE id = (
+                SELECT id FROM open_links
+                WHERE amount > $2
+                  AND (SELECT COUNT(*) FROM open_links) = 1
+            )
+              AND status = 'active'
+            RETURNING *
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(amount)
+        .bind(signature)
+        .bind(block_time)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        AmountMatchRule, Chain, PaymentLinkStatus, TransactionStatus, TransactionType,
+        WalletSettings,
+    };
+    use crate::repository::TransactionRepository;
+    use crate::services::testing::{register_wallet, SENDER, USDC_MINT, WALLET};
+    use rust_decimal::prelude::ToPrimitive;
+
+    async fn link(
+        pool: &PgPool,
+        slug: &str,
+        amount: Option<Decimal>,
+        memo: Option<&str>,
+    ) -> PaymentLink {
+        PaymentLinkRepository::create(
+            pool,
+            slug,
+            WALLET,
+            amount,
+            memo,
+            false,
+            None,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Store a receive of `amount` and match it to the wallet's links
+    async fn pay(
+        pool: &PgPool,
+        amount: Decimal,
+        signature: &str,
+        memo: Option<&str>,
+    ) -> Option<PaymentLink> {
+        store_receive(pool, amount, signature, memo).await;
+
+        PaymentLinkRepository::match_payment(pool, WALLET, amount, signature, Utc::now(), memo)
+            .await
+            .unwrap()
+    }
+
+    async fn store_receive(pool: &PgPool, amount: Decimal, signature: &str, memo: Option<&str>) {
+        TransactionRepository::create(
+            pool,
+            Chain::Solana,
+            signature,
+            WALLET,
+            TransactionType::Receive,
+            amount,
+            (amount * Decimal::new(1_000_000, 0)).to_i64().unwrap(),
+            6,
+            USDC_MINT,
+            SENDER,
+            TransactionStatus::Confirmed,
+            Utc::now(),
+            &serde_json::json!([]),
+            memo,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn a_link_without_an_amount_is_paid_by_its_memo(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        link(&pool, "tip-jar", None, Some("order-42")).await;
+
+        assert!(pay(&pool, Decimal::new(3, 0), "sig-a", None).await.is_none());
+        assert!(pay(&pool, Decimal::new(3, 0), "sig-b", Some("order-41")).await.is_none());
+
+        let paid = pay(&pool, Decimal::new(3, 0), "sig-c", Some("order-42")).await.unwrap();
+        assert_eq!(paid.slug, "tip-jar");
+        assert_eq!(paid.paid_signature.as_deref(), Some("sig-c"));
+        assert_eq!(paid.received_amount, Some(Decimal::new(3, 0)));
+        assert_eq!(paid.payment_count, 1);
+
+        // Single-use, so the same memo doesn't pay it again
+        assert!(pay(&pool, Decimal::new(3, 0), "sig-d", Some("order-42")).await.is_none());
+    }
+
+    #[sqlx::test]
+    async fn a_memo_match_wins_over_an_amount_match(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        link(&pool, "by-amount", Some(Decimal::new(10, 0)), None).await;
+        link(&pool, "by-memo", None, Some("order-42")).await;
+
+        let paid = pay(&pool, Decimal::new(10, 0), "sig-a", Some("order-42")).await.unwrap();
+        assert_eq!(paid.slug, "by-memo");
+
+        let paid = pay(&pool, Decimal::new(10, 0), "sig-b", Some("order-42")).await.unwrap();
+        assert_eq!(paid.slug, "by-amount");
+    }
+
+    /// A single-use link for `amount` accepting the given shortfall
+    async fn tolerant_link(
+        pool: &PgPool,
+        slug: &str,
+        amount: Decimal,
+        tolerance: Decimal,
+        tolerance_pct: Decimal,
+    ) -> PaymentLink {
+        PaymentLinkRepository::create(
+            pool, slug, WALLET, Some(amount), None, false, None, tolerance, tolerance_pct,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn a_shortfall_exactly_at_the_absolute_tolerance_pays(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let (amount, tolerance) = (Decimal::new(100, 0), Decimal::new(5, 2));
+
+        tolerant_link(&pool, "just-over", amount, tolerance, Decimal::ZERO).await;
+        assert!(pay(&pool, Decimal::new(99_949_999, 6), "sig-a", None).await.is_none());
+
+        let paid = pay(&pool, Decimal::new(9995, 2), "sig-b", None).await.unwrap();
+        assert_eq!(paid.slug, "just-over");
+        assert_eq!(paid.match_rule(), Some(AmountMatchRule::AbsoluteTolerance));
+    }
+
+    #[sqlx::test]
+    async fn a_shortfall_exactly_at_the_percent_tolerance_pays(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        // 0.5% of 200 is 1.00
+        let (amount, tolerance_pct) = (Decimal::new(200, 0), Decimal::new(5, 1));
+
+        tolerant_link(&pool, "half-percent", amount, Decimal::ZERO, tolerance_pct).await;
+        assert!(pay(&pool, Decimal::new(198_999_999, 6), "sig-a", None).await.is_none());
+
+        let paid = pay(&pool, Decimal::new(199, 0), "sig-b", None).await.unwrap();
+        assert_eq!(paid.match_rule(), Some(AmountMatchRule::PercentTolerance));
+        assert_eq!(paid.status, PaymentLinkStatus::Paid);
+    }
+
+    #[sqlx::test]
+    async fn the_looser_of_the_two_tolerances_applies(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        // 2.00 absolute beats 0.5% (0.50) of 100
+        tolerant_link(&pool, "loose", Decimal::new(100, 0), Decimal::new(2, 0), Decimal::new(5, 1))
+            .await;
+
+        let paid = pay(&pool, Decimal::new(98, 0), "sig-a", None).await.unwrap();
+        assert_eq!(paid.match_rule(), Some(AmountMatchRule::AbsoluteTolerance));
+    }
+
+    #[sqlx::test]
+    async fn a_cancel_racing_a_payment_wins_or_loses_as_a_whole(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let amount = Decimal::new(10, 0);
+
+        for round in 0..20 {
+            let (slug, signature) = (format!("race-{round}"), format!("sig-{round}"));
+            link(&pool, &slug, Some(amount), None).await;
+            store_receive(&pool, amount, &signature, None).await;
+
+            let (cancelled, matched) = tokio::join!(
+                PaymentLinkRepository::cancel(&pool, &slug),
+                PaymentLinkRepository::match_payment(
+                    &pool, WALLET, amount, &signature, Utc::now(), None
+                ),
+            );
+            let (cancelled, matched) = (cancelled.unwrap(), matched.unwrap());
+            assert!(cancelled.is_some() != matched.is_some(), "round {round}: exactly one wins");
+
+            let stored = PaymentLinkRepository::find_by_slug(&pool, &slug).await.unwrap().unwrap();
+            if cancelled.is_some() {
+                assert_eq!(stored.status, PaymentLinkStatus::Cancelled);
+                assert_eq!((stored.paid_signature, stored.payment_count), (None, 0));
+            } else {
+                assert_eq!(stored.status, PaymentLinkStatus::Paid);
+                assert_eq!(stored.paid_signature.as_deref(), Some(signature.as_str()));
+                assert!(stored.cancelled_at.is_none());
+            }
+        }
+    }
+
+    #[sqlx::test]
+    async fn an_amendment_racing_a_payment_wins_or_loses_as_a_whole(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let (amount, amended) = (Decimal::new(10, 0), Decimal::new(50, 0));
+        let change = serde_json::json!({ "amount": "50" });
+
+        for round in 0..20 {
+            let (slug, signature) = (format!("race-{round}"), format!("sig-{round}"));
+            let created = link(&pool, &slug, Some(amount), None).await;
+            store_receive(&pool, amount, &signature, None).await;
+
+            let (amend, matched) = tokio::join!(
+                PaymentLinkRepository::amend(
+                    &pool, &slug, created.version, Some(amended), None, &change
+                ),
+                PaymentLinkRepository::match_payment(
+                    &pool, WALLET, amount, &signature, Utc::now(), None
+                ),
+            );
+            let (amend, matched) = (amend.unwrap(), matched.unwrap());
+            let stored = PaymentLinkRepository::find_by_slug(&pool, &slug).await.unwrap().unwrap();
+
+            // A payment for the old amount never settles the amended link
+            match (amend, matched) {
+                (Some(_), None) => {
+                    assert_eq!(stored.status, PaymentLinkStatus::Active);
+                    assert_eq!((stored.amount, stored.payment_count), (Some(amended), 0));
+                    PaymentLinkRepository::cancel(&pool, &slug).await.unwrap().unwrap();
+                }
+                (None, Some(_)) => {
+                    assert_eq!(stored.status, PaymentLinkStatus::Paid);
+                    assert_eq!((stored.amount, stored.version), (Some(amount), created.version));
+                }
+                other => panic!("round {round}: exactly one wins, got {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use sqlx::PgPool;
+
+use crate::domain::SyncCursor;
+use crate::error::AppError;
+
+pub struct SyncCursorRepository;
+
+impl SyncCursorRepository {
+    pub async fn find(pool: &PgPool, wallet_address: &str) -> Result<Option<SyncCursor>, AppError> {
+        let cursor = sqlx::query_as::<_, SyncCursor>(
+            "SELECT * FROM sync_cursors WHERE wallet_address = $1",
+        )
+        .bind(wallet_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(cursor)
+    }
+
+    /// Record that the walk processed everything down to `oldest_signature`,
+    /// `scanned` more signatures in all
+    pub async fn advance(
+        pool: &PgPool,
+        wallet_address: &str,
+        oldest_signature: &str,
+        scanned: i64,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_cursors (wallet_address, oldest_signature, scanned)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (wallet_address, chain) DO UPDATE SET
+                oldest_signature = EXCLUDED.oldest_signature,
+                scanned = sync_cursors.scanned + EXCLUDED.scanned,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(oldest_signature)
+        .bind(scanned)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark the wallet's history as fully imported
+    pub async fn complete(pool: &PgPool, wallet_address: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_cursors (wallet_address, completed_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (wallet_address, chain) DO UPDATE SET
+                completed_at = NOW(),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(wallet_address)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Forget the wallet's progress so the next backfill starts from the
+    /// newest signature
+    pub async fn delete(pool: &PgPool, wallet_address: &str) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM sync_cursors WHERE wallet_address = $1")
+            .bind(wallet_address)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::domain::SyncRun;
+use crate::error::AppError;
+
+pub struct SyncRunRepository;
+
+impl SyncRunRepository {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &PgPool,
+        started_at: DateTime<Utc>,
+        completed_at: DateTime<Utc>,
+        wallets_synced: i32,
+        new_transactions: i32,
+        webhooks_triggered: i32,
+        expired_transactions: i32,
+        error_count: i32,
+        errors: &serde_json::Value,
+    ) -> Result<SyncRun, AppError> {
+        let run = sqlx::query_as::<_, SyncRun>(
+            r#"
+            INSERT INTO sync_runs (started_at, completed_at, wallets_synced, new_transactions, webhooks_triggered, expired_transactions, error_count, errors)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(started_at)
+        .bind(completed_at)
+        .bind(wallets_synced)
+        .bind(new_transactions)
+        .bind(webhooks_triggered)
+        .bind(expired_transactions)
+        .bind(error_count)
+        .bind(errors)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Most recent runs, newest first
+    pub async fn list_recent(pool: &PgPool, limit: i64) -> Result<Vec<SyncRun>, AppError> {
+        let runs = sqlx::query_as::<_, SyncRun>(
+            "SELECT * FROM sync_runs ORDER BY started_at DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    /// Delete runs started before `cutoff`, returning how many were removed
+    pub async fn delete_before(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, AppError> {
+        let result = sqlx::query("DELETE FROM sync_runs WHERE started_at < $1")
+            .bind(cutoff)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
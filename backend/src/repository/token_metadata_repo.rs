@@ -0,0 +1,53 @@
+use sqlx::PgPool;
+
+use crate::domain::TokenMetadata;
+use crate::error::AppError;
+
+pub struct TokenMetadataRepository;
+
+impl TokenMetadataRepository {
+    pub async fn find(pool: &PgPool, mint: &str) -> Result<Option<TokenMetadata>, AppError> {
+        let token =
+            sqlx::query_as::<_, TokenMetadata>("SELECT * FROM token_metadata WHERE mint = $1")
+                .bind(mint)
+                .fetch_optional(pool)
+                .await?;
+
+        Ok(token)
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<TokenMetadata>, AppError> {
+        let tokens =
+            sqlx::query_as::<_, TokenMetadata>("SELECT * FROM token_metadata ORDER BY symbol, mint")
+                .fetch_all(pool)
+                .await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn upsert(pool: &PgPool, token: &TokenMetadata) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_metadata (mint, symbol, name, decimals, logo_uri, source)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (mint, chain) DO UPDATE SET
+                symbol = EXCLUDED.symbol,
+                name = EXCLUDED.name,
+                decimals = EXCLUDED.decimals,
+                logo_uri = EXCLUDED.logo_uri,
+                source = EXCLUDED.source,
+                resolved_at = NOW()
+            "#,
+        )
+        .bind(&token.mint)
+        .bind(&token.symbol)
+        .bind(&token.name)
+        .bind(token.decimals)
+        .bind(&token.logo_uri)
+        .bind(token.source)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,40 +1,85 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::types::Uuid;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
-use crate::domain::{Transaction, TransactionStatus, TransactionType};
+use crate::domain::{
+    Chain, ReceiveVolume, Transaction, TransactionStats, TransactionStatus, TransactionType,
+};
 use crate::error::AppError;
 
 pub struct TransactionRepository;
 
+/// A wallet's page, newest first; see `find_by_wallet`
+const FIND_BY_WALLET: &str = r#"
+    SELECT * FROM transactions
+    WHERE wallet_address = $1
+    ORDER BY block_time DESC
+    LIMIT $2 OFFSET $3
+"#;
+
+/// Result of an idempotent insert: the stored row, and whether this call created it
+#[derive(Debug, sqlx::FromRow)]
+pub struct CreatedTransaction {
+    #[sqlx(flatten)]
+    pub transaction: Transaction,
+    pub created: bool,
+}
+
 impl TransactionRepository {
+    /// Insert a transaction, or return the existing row when the signature is
+    /// already stored. `created` tells a fresh insert from a concurrent or
+    /// repeated one; only the creating caller should notify about it.
+    /// Non-positive amounts are refused with `AppError::InvalidAmount`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         pool: &PgPool,
+        chain: Chain,
         signature: &str,
         wallet_address: &str,
         tx_type: TransactionType,
         amount: Decimal,
+        amount_raw: i64,
+        decimals: i16,
         token_mint: &str,
         counterparty: &str,
         status: TransactionStatus,
         block_time: DateTime<Utc>,
-    ) -> Result<Transaction, AppError> {
-        let tx = sqlx::query_as::<_, Transaction>(
+        transfer_legs: &serde_json::Value,
+        memo: Option<&str>,
+        reference: Option<&str>,
+    ) -> Result<CreatedTransaction, AppError> {
+        if amount <= Decimal::ZERO || amount_raw <= 0 {
+            return Err(AppError::InvalidAmount(format!(
+                "transaction {} has non-positive amount {} ({} base units)",
+                signature, amount, amount_raw
+            )));
+        }
+
+        // The no-op update makes RETURNING yield the existing row on conflict;
+        // xmax is 0 only for a row this statement inserted
+        let tx = sqlx::query_as::<_, CreatedTransaction>(
             r#"
-            INSERT INTO transactions (signature, wallet_address, tx_type, amount, token_mint, counterparty, status, block_time)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (signature) DO NOTHING
-            RETURNING *
+            INSERT INTO transactions (chain, signature, wallet_address, tx_type, amount, amount_raw, decimals, token_mint, counterparty, status, block_time, transfer_legs, memo, reference)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (chain, signature) DO UPDATE SET signature = EXCLUDED.signature
+            RETURNING *, (xmax = 0) AS created
             "#,
         )
+        .bind(chain)
         .bind(signature)
         .bind(wallet_address)
         .bind(tx_type.to_string())
         .bind(amount)
+        .bind(amount_raw)
+        .bind(decimals)
         .bind(token_mint)
         .bind(counterparty)
         .bind(status.to_string())
         .bind(block_time)
+        .bind(transfer_legs)
+        .bind(memo)
+        .bind(reference)
         .fetch_one(pool)
         .await?;
 
@@ -52,27 +97,101 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    /// Newest-first page of a wallet's transactions. Served by
+    /// idx_transactions_wallet_time; keep filters on bare columns so
+    /// they stay index-usable.
     pub async fn find_by_wallet(
         pool: &PgPool,
         wallet_address: &str,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<Transaction>, AppError> {
-        let txs = sqlx::query_as::<_, Transaction>(
+        let txs = sqlx::query_as::<_, Transaction>(FIND_BY_WALLET)
+            .bind(wallet_address)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(txs)
+    }
+
+    /// Newest-first page of the transactions of a group's wallets, after
+    /// the `(block_time, signature)` of the previous page's last row. Each
+    /// member contributes at most `limit` rows read off
+    /// idx_transactions_wallet_time, so a page costs the same however much
+    /// history the members have.
+    pub async fn find_by_group(
+        pool: &PgPool,
+        group_id: Uuid,
+        after: Option<(DateTime<Utc>, &str)>,
+        limit: i64,
+    ) -> Result<Vec<Transaction>, AppError> {
+        let mut query = QueryBuilder::<Postgres>::new(
             r#"
-            SELECT * FROM transactions
-            WHERE wallet_address = $1
-            ORDER BY block_time DESC
-            LIMIT $2 OFFSET $3
+            SELECT t.* FROM wallets w
+            CROSS JOIN LATERAL (
+                SELECT * FROM transactions
+                WHERE wallet_address = w.address AND chain = w.chain
+            "#,
+        );
+        if let Some((block_time, signature)) = after {
+            query
+                .push(" AND (block_time, signature) < (")
+                .push_bind(block_time)
+                .push(", ")
+                .push_bind(signature)
+                .push(")");
+        }
+        query
+            .push(" ORDER BY block_time DESC, signature DESC LIMIT ")
+            .push_bind(limit)
+            .push(") t WHERE w.group_id = ")
+            .push_bind(group_id)
+            .push(" ORDER BY t.block_time DESC, t.signature DESC LIMIT ")
+            .push_bind(limit);
+
+        let txs = query.build_query_as::<Transaction>().fetch_all(pool).await?;
+
+        Ok(txs)
+    }
+
+    /// Confirmed receives of a group's wallets over the last 24h, 7d and 30d
+    pub async fn receive_volume_for_group(
+        pool: &PgPool,
+        group_id: Uuid,
+    ) -> Result<ReceiveVolume, AppError> {
+        let volume = sqlx::query_as::<_, ReceiveVolume>(
+            r#"
+            SELECT
+                COALESCE(SUM(t.amount) FILTER (WHERE t.block_time > NOW() - INTERVAL '24 hours'), 0) AS received_24h,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.block_time > NOW() - INTERVAL '7 days'), 0) AS received_7d,
+                COALESCE(SUM(t.amount), 0) AS received_30d
+            FROM wallets w
+            JOIN transactions t ON t.wallet_address = w.address AND t.chain = w.chain
+            WHERE w.group_id = $1
+              AND t.tx_type = 'receive'
+              AND t.status = 'confirmed'
+              AND t.block_time > NOW() - INTERVAL '30 days'
             "#,
         )
+        .bind(group_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(volume)
+    }
+
+    /// Total stored transactions for a wallet, for paging `find_by_wallet`
+    pub async fn count_by_wallet(pool: &PgPool, wallet_address: &str) -> Result<i64, AppError> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM transactions WHERE wallet_address = $1",
+        )
         .bind(wallet_address)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
+        .fetch_one(pool)
         .await?;
 
-        Ok(txs)
+        Ok(count.0)
     }
 
     pub async fn exists(pool: &PgPool, signature: &str) -> Result<bool, AppError> {
@@ -86,11 +205,79 @@ impl TransactionRepository {
         Ok(exists.0)
     }
 
-    pub async fn get_latest_signature(pool: &PgPool, wallet_address: &str) -> Result<Option<String>, AppError> {
-        let result: Option<(String,)> = sqlx::query_as(
+    pub async fn find_by_wallet_and_status(
+        pool: &PgPool,
+        wallet_address: &str,
+        status: TransactionStatus,
+    ) -> Result<Vec<Transaction>, AppError> {
+        let txs = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT * FROM transactions
+            WHERE wallet_address = $1 AND status = $2
+            ORDER BY block_time ASC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(status.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(txs)
+    }
+
+    /// Move a pending transaction to its settled or expired status.
+    /// Returns `None` if the row was already promoted (e.g. by a concurrent sync).
+    pub async fn update_status(
+        pool: &PgPool,
+        signature: &str,
+        status: TransactionStatus,
+    ) -> Result<Option<Transaction>, AppError> {
+        let tx = sqlx::query_as::<_, Transaction>(
+            r#"
+            UPDATE transactions
+            SET status = $1
+            WHERE signature = $2 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(tx)
+    }
+
+    pub async fn stats_for_wallet(pool: &PgPool, wallet_address: &str) -> Result<TransactionStats, AppError> {
+        let stats = sqlx::query_as::<_, TransactionStats>(
+            r#"
+            SELECT
+                COUNT(*) AS transaction_count,
+                COUNT(*) FILTER (WHERE tx_type = 'receive') AS received_count,
+                COUNT(*) FILTER (WHERE tx_type = 'send') AS sent_count,
+                COALESCE(SUM(amount) FILTER (WHERE tx_type = 'receive'), 0) AS total_received,
+                COALESCE(SUM(amount) FILTER (WHERE tx_type = 'send'), 0) AS total_sent,
+                MAX(block_time) AS last_transaction_at
+            FROM transactions
+            WHERE wallet_address = $1 AND status = 'confirmed'
+            "#,
+        )
+        .bind(wallet_address)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Block time of the wallet's latest confirmed transaction
+    pub async fn last_block_time(
+        pool: &PgPool,
+        wallet_address: &str,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        let last: Option<(DateTime<Utc>,)> = sqlx::query_as(
             r#"
-            SELECT signature FROM transactions
-            WHERE wallet_address = $1
+            SELECT block_time FROM transactions
+            WHERE wallet_address = $1 AND status = 'confirmed'
             ORDER BY block_time DESC
             LIMIT 1
             "#,
@@ -99,6 +286,199 @@ impl TransactionRepository {
         .fetch_optional(pool)
         .await?;
 
-        Ok(result.map(|r| r.0))
+        Ok(last.map(|(at,)| at))
+    }
+
+    /// Quarantined transactions per reason
+    pub async fn quarantine_counts(pool: &PgPool) -> Result<Vec<(String, i64)>, AppError> {
+        let counts = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT reason, COUNT(*) FROM transactions_quarantine
+            GROUP BY reason
+            ORDER BY reason
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WalletSettings;
+    use crate::services::testing::{register_wallet, SENDER, USDC_MINT, WALLET};
+
+    async fn insert(
+        pool: &PgPool,
+        signature: &str,
+        amount_raw: i64,
+    ) -> Result<CreatedTransaction, AppError> {
+        TransactionRepository::create(
+            pool,
+            Chain::Solana,
+            signature,
+            WALLET,
+            TransactionType::Receive,
+            Decimal::new(amount_raw, 6),
+            amount_raw,
+            6,
+            USDC_MINT,
+            SENDER,
+            TransactionStatus::Confirmed,
+            Utc::now(),
+            &serde_json::json!([]),
+            None,
+            None,
+        )
+        .await
+    }
+
+    #[sqlx::test]
+    async fn concurrent_inserts_create_the_row_once(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+
+        let inserts: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { insert(&pool, "sig-concurrent", 1_000_000).await })
+            })
+            .collect();
+
+        let mut created = 0;
+        for insert in inserts {
+            let outcome = insert.await.unwrap().unwrap();
+            assert_eq!(outcome.transaction.signature, "sig-concurrent");
+            created += outcome.created as usize;
+        }
+
+        assert_eq!(created, 1);
+        assert_eq!(TransactionRepository::count_by_wallet(&pool, WALLET).await.unwrap(), 1);
+    }
+
+    #[sqlx::test]
+    async fn a_repeated_insert_returns_the_stored_row(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+
+        let first = insert(&pool, "sig-repeat", 1_000_000).await.unwrap();
+        let second = insert(&pool, "sig-repeat", 2_000_000).await.unwrap();
+
+        assert!(first.created);
+        assert!(!second.created);
+        assert_eq!(second.transaction.amount_raw, 1_000_000);
+        assert_eq!(second.transaction.created_at, first.transaction.created_at);
+    }
+
+    #[sqlx::test]
+    async fn non_positive_amounts_are_refused(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+
+        for (signature, amount_raw) in [("sig-zero", 0), ("sig-negative", -1_000_000)] {
+            assert!(matches!(
+                insert(&pool, signature, amount_raw).await,
+                Err(AppError::InvalidAmount(_))
+            ));
+        }
+        assert_eq!(TransactionRepository::count_by_wallet(&pool, WALLET).await.unwrap(), 0);
+
+        // The CHECK constraint backs the guard up for writes that bypass it
+        let bypass = sqlx::query(
+            r#"
+            INSERT INTO transactions (signature, wallet_address, tx_type, amount, amount_raw,
+                                      token_mint, counterparty, status, block_time)
+            VALUES ('sig-raw', $1, 'receive', 0, 0, $2, $3, 'confirmed', NOW())
+            "#,
+        )
+        .bind(WALLET)
+        .bind(USDC_MINT)
+        .bind(SENDER)
+        .execute(&pool)
+        .await
+        .unwrap_err();
+        assert!(bypass.to_string().contains("transactions_amount_positive"), "{}", bypass);
+    }
+
+    /// The plan Postgres picks for `sql` when it may use any index, one line
+    /// per node. Sequential scans are priced out so a tiny test table doesn't
+    /// hide whether a filter can use an index at all.
+    async fn plan(pool: &PgPool, sql: &str, bind: impl FnOnce(&str) -> String) -> String {
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("SET enable_seqscan = off").execute(&mut *conn).await.unwrap();
+        let rows: Vec<(String,)> = sqlx::query_as(&bind(&format!("EXPLAIN {}", sql)))
+            .fetch_all(&mut *conn)
+            .await
+            .unwrap();
+
+        rows.into_iter().map(|(line,)| line).collect::<Vec<_>>().join("\n")
+    }
+
+    #[sqlx::test]
+    async fn wallet_queries_read_their_composite_indexes(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        // 50 wallets' histories with many counterparties and amounts, analyzed
+        // so the planner weighs each index by how selective it is. Block times
+        // are shuffled so no index gains from matching the insertion order.
+        let others = "INSERT INTO wallets (address) \
+                      SELECT 'wallet' || n FROM generate_series(1, 49) n";
+        sqlx::query(others).execute(&pool).await.unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (chain, signature, wallet_address, tx_type, amount,
+                amount_raw, decimals, token_mint, counterparty, status, block_time)
+            SELECT 'solana', 'sig' || i,
+                   CASE WHEN i % 50 = 0 THEN $1 ELSE 'wallet' || (i % 50) END,
+                   'receive', i, i * 1000000::BIGINT, 6, $2, 'counterparty' || (i % 997),
+                   'confirmed', NOW() - (i * 7919 % 50000) * INTERVAL '1 minute'
+            FROM generate_series(1, 50000) AS i
+            "#,
+        )
+        .bind(WALLET)
+        .bind(USDC_MINT)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("ANALYZE transactions").execute(&pool).await.unwrap();
+        let literal = |sql: &str| {
+            sql.replace("$1", &format!("'{}'", WALLET))
+                .replace("$2", "50")
+                .replace("$3", "0")
+                .replace("$4", &format!("'{}'", SENDER))
+        };
+
+        let page = plan(&pool, FIND_BY_WALLET, literal).await;
+        assert!(page.contains("idx_transactions_wallet_time"), "{}", page);
+        assert!(page.contains("Index Cond: ((wallet_address)::text = "), "{}", page);
+        assert!(!page.contains("Sort"), "{}", page);
+
+        // The shapes transaction search filters must keep: bare columns
+        // compared with parameters, the wallet first
+        let by_counterparty = plan(
+            &pool,
+            "SELECT * FROM transactions WHERE wallet_address = $1 AND counterparty = $4 \
+             ORDER BY block_time DESC LIMIT $2",
+            literal,
+        )
+        .await;
+        assert!(
+            by_counterparty.contains("idx_transactions_wallet_counterparty"),
+            "{}",
+            by_counterparty
+        );
+        assert!(by_counterparty.contains("(counterparty)::text = "), "{}", by_counterparty);
+
+        let by_amount = plan(
+            &pool,
+            "SELECT * FROM transactions WHERE wallet_address = $1 AND amount >= 1 AND amount < 5",
+            literal,
+        )
+        .await;
+        assert!(by_amount.contains("idx_transactions_wallet_amount"), "{}", by_amount);
+        assert!(by_amount.contains("(amount >= "), "{}", by_amount);
+
+        let by_signature =
+            plan(&pool, "SELECT * FROM transactions WHERE signature = 'sig50'", literal).await;
+        assert!(by_signature.contains("Index Cond: ((signature)::text = "), "{}", by_signature);
     }
 }
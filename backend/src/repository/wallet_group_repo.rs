@@ -0,0 +1,96 @@
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::domain::WalletGroup;
+use crate::error::AppError;
+
+pub struct WalletGroupRepository;
+
+impl WalletGroupRepository {
+    pub async fn create(
+        pool: &PgPool,
+        name: &str,
+        metadata: Option<&serde_json::Value>,
+    ) -> Result<WalletGroup, AppError> {
+        let group = sqlx::query_as::<_, WalletGroup>(
+            r#"
+            INSERT INTO wallet_groups (name, metadata)
+            VALUES ($1, COALESCE($2, '{}'))
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(metadata)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<WalletGroup>, AppError> {
+        let group = sqlx::query_as::<_, WalletGroup>("SELECT * FROM wallet_groups WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(group)
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<WalletGroup>, AppError> {
+        let groups = sqlx::query_as::<_, WalletGroup>(
+            "SELECT * FROM wallet_groups ORDER BY created_at DESC",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(groups)
+    }
+
+    /// Update the given fields of a group. Returns `None` if not found.
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        name: Option<&str>,
+        metadata: Option<&serde_json::Value>,
+    ) -> Result<Option<WalletGroup>, AppError> {
+        let group = sqlx::query_as::<_, WalletGroup>(
+            r#"
+            UPDATE wallet_groups SET
+                name = COALESCE($2, name),
+                metadata = COALESCE($3, metadata)
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(name)
+        .bind(metadata)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// Delete a group; its wallets stay registered, ungrouped. Returns
+    /// `None` if not found.
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<Option<WalletGroup>, AppError> {
+        let group = sqlx::query_as::<_, WalletGroup>(
+            "DELETE FROM wallet_groups WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// Wallets in the group, archived ones included
+    pub async fn member_count(pool: &PgPool, id: Uuid) -> Result<i64, AppError> {
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM wallets WHERE group_id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(count.0)
+    }
+}
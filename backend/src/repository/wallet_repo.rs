@@ -1,28 +1,134 @@
+use rust_decimal::Decimal;
 use sqlx::PgPool;
 
-use crate::domain::Wallet;
+use crate::domain::{Chain, Wallet, WalletSettings};
 use crate::error::AppError;
 
 pub struct WalletRepository;
 
+/// Result of a registration upsert: the stored row, and whether this call
+/// inserted it rather than updating an existing wallet
+#[derive(Debug, sqlx::FromRow)]
+pub struct CreatedWallet {
+    #[sqlx(flatten)]
+    pub wallet: Wallet,
+    pub created: bool,
+}
+
 impl WalletRepository {
-    pub async fn create(pool: &PgPool, address: &str, webhook_url: Option<&str>) -> Result<Wallet, AppError> {
+    /// Register a wallet. Re-registering an archived address un-archives it,
+    /// moving its `ignore_before` cutoff up to now (unless one is given) so
+    /// payments received while archived are never notified.
+    pub async fn create(
+        pool: &PgPool,
+        chain: Chain,
+        address: &str,
+        settings: &WalletSettings,
+    ) -> Result<CreatedWallet, AppError> {
+        // xmax is 0 only for a row this statement inserted
+        let wallet = sqlx::query_as::<_, CreatedWallet>(
+            r#"
+            INSERT INTO wallets (address, webhook_url, min_webhook_amount, webhook_headers, label, metadata, ignore_before, display_currency, max_webhooks_per_minute, webhook_stream, balance_thresholds, chain, webhook_signature_header, webhook_signature_encoding, group_id, payment_references, payload_redactions, timezone)
+            VALUES ($1, $2, COALESCE($3, 0), COALESCE($4, '{}'), $5, COALESCE($6, '{}'), $7, $8, COALESCE($9, 60), NULLIF($10, ''), COALESCE($11, '{}'), $12, NULLIF($13, ''), COALESCE($14, 'hex'), NULLIF($15, '')::uuid, COALESCE($16, '{}'), COALESCE($17, '{}'), NULLIF($18, ''))
+            ON CONFLICT (chain, address) DO UPDATE SET
+                webhook_url = COALESCE($2, wallets.webhook_url),
+                min_webhook_amount = COALESCE($3, wallets.min_webhook_amount),
+                webhook_headers = COALESCE($4, wallets.webhook_headers),
+                label = COALESCE($5, wallets.label),
+                metadata = COALESCE($6, wallets.metadata),
+                ignore_before = COALESCE(
+                    $7,
+                    CASE WHEN wallets.archived_at IS NULL THEN wallets.ignore_before
+                         ELSE GREATEST(wallets.ignore_before, NOW()) END
+                ),
+                display_currency = COALESCE($8, wallets.display_currency),
+                max_webhooks_per_minute = COALESCE($9, wallets.max_webhooks_per_minute),
+                webhook_stream = NULLIF(COALESCE($10, wallets.webhook_stream), ''),
+                balance_thresholds = COALESCE($11, wallets.balance_thresholds),
+                webhook_signature_header = NULLIF(COALESCE($13, wallets.webhook_signature_header), ''),
+                webhook_signature_encoding = COALESCE($14, wallets.webhook_signature_encoding),
+                group_id = NULLIF(COALESCE($15, wallets.group_id::text), '')::uuid,
+                payment_references = COALESCE($16, wallets.payment_references),
+                payload_redactions = COALESCE($17, wallets.payload_redactions),
+                timezone = NULLIF(COALESCE($18, wallets.timezone), ''),
+                archived_at = NULL
+            RETURNING *, (xmax = 0) AS created
+            "#,
+        )
+        .bind(address)
+        .bind(settings.webhook_url.as_deref())
+        .bind(settings.min_webhook_amount)
+        .bind(settings.webhook_headers.as_ref())
+        .bind(settings.label.as_deref())
+        .bind(settings.metadata.as_ref())
+        .bind(settings.ignore_before)
+        .bind(settings.display_currency.as_deref())
+        .bind(settings.max_webhooks_per_minute)
+        .bind(settings.webhook_stream.as_deref())
+        .bind(settings.balance_thresholds.as_deref())
+        .bind(chain)
+        .bind(settings.webhook_signature_header.as_deref())
+        .bind(settings.webhook_signature_encoding)
+        .bind(settings.group_id.as_deref())
+        .bind(settings.payment_references.as_deref())
+        .bind(settings.payload_redactions.as_deref())
+        .bind(settings.timezone.as_deref())
+        .fetch_one(pool)
+        .await?;
+
+        Ok(wallet)
+    }
+
+    /// Update the given settings of an existing wallet. Returns `None` if not found.
+    pub async fn update(pool: &PgPool, address: &str, settings: &WalletSettings) -> Result<Option<Wallet>, AppError> {
         let wallet = sqlx::query_as::<_, Wallet>(
             r#"
-            INSERT INTO wallets (address, webhook_url)
-            VALUES ($1, $2)
-            ON CONFLICT (address) DO UPDATE SET webhook_url = COALESCE($2, wallets.webhook_url)
+            UPDATE wallets SET
+                webhook_url = COALESCE($2, webhook_url),
+                min_webhook_amount = COALESCE($3, min_webhook_amount),
+                webhook_headers = COALESCE($4, webhook_headers),
+                label = COALESCE($5, label),
+                metadata = COALESCE($6, metadata),
+                ignore_before = COALESCE($7, ignore_before),
+                display_currency = COALESCE($8, display_currency),
+                max_webhooks_per_minute = COALESCE($9, max_webhooks_per_minute),
+                webhook_stream = NULLIF(COALESCE($10, webhook_stream), ''),
+                balance_thresholds = COALESCE($11, balance_thresholds),
+                webhook_signature_header = NULLIF(COALESCE($12, webhook_signature_header), ''),
+                webhook_signature_encoding = COALESCE($13, webhook_signature_encoding),
+                group_id = NULLIF(COALESCE($14, group_id::text), '')::uuid,
+                payment_references = COALESCE($15, payment_references),
+                payload_redactions = COALESCE($16, payload_redactions),
+                timezone = NULLIF(COALESCE($17, timezone), '')
+            WHERE address = $1
             RETURNING *
             "#,
         )
         .bind(address)
-        .bind(webhook_url)
-        .fetch_one(pool)
+        .bind(settings.webhook_url.as_deref())
+        .bind(settings.min_webhook_amount)
+        .bind(settings.webhook_headers.as_ref())
+        .bind(settings.label.as_deref())
+        .bind(settings.metadata.as_ref())
+        .bind(settings.ignore_before)
+        .bind(settings.display_currency.as_deref())
+        .bind(settings.max_webhooks_per_minute)
+        .bind(settings.webhook_stream.as_deref())
+        .bind(settings.balance_thresholds.as_deref())
+        .bind(settings.webhook_signature_header.as_deref())
+        .bind(settings.webhook_signature_encoding)
+        .bind(settings.group_id.as_deref())
+        .bind(settings.payment_references.as_deref())
+        .bind(settings.payload_redactions.as_deref())
+        .bind(settings.timezone.as_deref())
+        .fetch_optional(pool)
         .await?;
 
         Ok(wallet)
     }
 
+    /// Address formats differ between chains, so the address alone still
+    /// identifies one wallet
     pub async fn find_by_address(pool: &PgPool, address: &str) -> Result<Option<Wallet>, AppError> {
         let wallet = sqlx::query_as::<_, Wallet>(
             "SELECT * FROM wallets WHERE address = $1",
@@ -34,22 +140,184 @@ impl WalletRepository {
         Ok(wallet)
     }
 
+    /// List active (non-archived) wallets
     pub async fn list_all(pool: &PgPool) -> Result<Vec<Wallet>, AppError> {
+        Self::list(pool, false).await
+    }
+
+    pub async fn list(pool: &PgPool, include_archived: bool) -> Result<Vec<Wallet>, AppError> {
         let wallets = sqlx::query_as::<_, Wallet>(
-            "SELECT * FROM wallets ORDER BY created_at DESC",
+            r#"
+            SELECT * FROM wallets
+            WHERE $1 OR archived_at IS NULL
+            ORDER BY created_at DESC
+            "#,
         )
+        .bind(include_archived)
         .fetch_all(pool)
         .await?;
 
         Ok(wallets)
     }
 
-    pub async fn delete(pool: &PgPool, address: &str) -> Result<bool, AppError> {
-        let result = sqlx::query("DELETE FROM wallets WHERE address = $1")
+    /// Soft-delete a wallet. Returns `None` if the wallet doesn't exist.
+    pub async fn archive(pool: &PgPool, address: &str) -> Result<Option<Wallet>, AppError> {
+        let wallet = sqlx::query_as::<_, Wallet>(
+            r#"
+            UPDATE wallets
+            SET archived_at = COALESCE(archived_at, NOW())
+            WHERE address = $1
+            RETURNING *
+            "#,
+        )
+        .bind(address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(wallet)
+    }
+
+    /// Record a successful background sync, clearing the error streak
+    pub async fn record_sync_success(pool: &PgPool, address: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE wallets
+            SET last_synced_at = NOW(), sync_error_streak = 0, last_sync_error = NULL
+            WHERE address = $1
+            "#,
+        )
+        .bind(address)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed background sync, extending the error streak
+    pub async fn record_sync_failure(pool: &PgPool, address: &str, error: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE wallets
+            SET sync_error_streak = sync_error_streak + 1, last_sync_error = $2
+            WHERE address = $1
+            "#,
+        )
+        .bind(address)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stop delivering webhooks to a wallet by clearing its URL
+    pub async fn disable_webhook(pool: &PgPool, address: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE wallets SET webhook_url = NULL WHERE address = $1")
             .bind(address)
             .execute(pool)
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(())
+    }
+
+    /// Record the balance seen by a threshold check, returning the one
+    /// recorded before it (`None` on the first check)
+    pub async fn swap_last_balance(
+        pool: &PgPool,
+        address: &str,
+        balance: Decimal,
+    ) -> Result<Option<Decimal>, AppError> {
+        let previous: Option<(Option<Decimal>,)> = sqlx::query_as(
+            r#"
+            UPDATE wallets w SET last_balance = $2, last_balance_at = NOW()
+            FROM (SELECT address, last_balance FROM wallets WHERE address = $1 FOR UPDATE) old
+            WHERE w.address = old.address
+            RETURNING old.last_balance
+            "#,
+        )
+        .bind(address)
+        .bind(balance)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(previous.and_then(|p| p.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::WALLET;
+    use chrono::{Duration, Utc};
+
+    fn with_label(label: &str) -> WalletSettings {
+        WalletSettings {
+            label: Some(label.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn archive_then_reregister_unarchives_with_a_fresh_cutoff(pool: PgPool) {
+        let first = WalletRepository::create(&pool, Chain::Solana, WALLET, &with_label("shop"))
+            .await
+            .unwrap();
+        assert!(first.created);
+        assert!(first.wallet.ignore_before.is_none());
+
+        let archived = WalletRepository::archive(&pool, WALLET).await.unwrap().unwrap();
+        assert!(archived.is_archived());
+        assert!(WalletRepository::list_all(&pool).await.unwrap().is_empty());
+        assert_eq!(WalletRepository::list(&pool, true).await.unwrap().len(), 1);
+
+        // Archiving again keeps the original timestamp
+        let again = WalletRepository::archive(&pool, WALLET).await.unwrap().unwrap();
+        assert_eq!(again.archived_at, archived.archived_at);
+
+        let before = Utc::now();
+        let restored =
+            WalletRepository::create(&pool, Chain::Solana, WALLET, &WalletSettings::default())
+                .await
+                .unwrap();
+        assert!(!restored.created);
+        assert!(!restored.wallet.is_archived());
+        assert_eq!(restored.wallet.label.as_deref(), Some("shop"));
+        let cutoff = restored.wallet.ignore_before.expect("cutoff set on un-archive");
+        assert!(cutoff >= before - Duration::seconds(1));
+        assert!(cutoff >= archived.archived_at.unwrap());
+        assert_eq!(WalletRepository::list_all(&pool).await.unwrap().len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn reregistering_an_active_wallet_keeps_its_cutoff(pool: PgPool) {
+        WalletRepository::create(&pool, Chain::Solana, WALLET, &WalletSettings::default())
+            .await
+            .unwrap();
+
+        let again = WalletRepository::create(&pool, Chain::Solana, WALLET, &with_label("shop"))
+            .await
+            .unwrap();
+        assert!(!again.created);
+        assert!(again.wallet.ignore_before.is_none());
+        assert_eq!(again.wallet.label.as_deref(), Some("shop"));
+    }
+
+    #[sqlx::test]
+    async fn unarchiving_with_an_explicit_cutoff_uses_it(pool: PgPool) {
+        WalletRepository::create(&pool, Chain::Solana, WALLET, &WalletSettings::default())
+            .await
+            .unwrap();
+        WalletRepository::archive(&pool, WALLET).await.unwrap();
+
+        let cutoff = Utc::now() - Duration::days(3);
+        let settings = WalletSettings {
+            ignore_before: Some(cutoff),
+            ..Default::default()
+        };
+        let restored = WalletRepository::create(&pool, Chain::Solana, WALLET, &settings)
+            .await
+            .unwrap();
+        let stored = restored.wallet.ignore_before.unwrap();
+        assert!((stored - cutoff).num_milliseconds().abs() < 1);
     }
 }
@@ -1,31 +1,78 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::types::Uuid;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tokio_stream::Stream;
 
-use crate::domain::{WebhookEvent, WebhookStatus};
+use crate::domain::{Chain, WebhookDeliveryAttempt, WebhookEvent, WebhookStatus};
 use crate::error::AppError;
 
 pub struct WebhookEventRepository;
 
+/// Optional narrowing of a wallet's event list; `since` is inclusive,
+/// `until` exclusive
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WebhookEventFilter<'a> {
+    pub status: Option<WebhookStatus>,
+    pub event_type: Option<&'a str>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl WebhookEventFilter<'_> {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(status) = self.status {
+            query.push(" AND status = ").push_bind(status);
+        }
+        if let Some(event_type) = self.event_type {
+            query.push(" AND event_type = ").push_bind(event_type.to_string());
+        }
+        if let Some(since) = self.since {
+            query.push(" AND created_at >= ").push_bind(since);
+        }
+        if let Some(until) = self.until {
+            query.push(" AND created_at < ").push_bind(until);
+        }
+    }
+}
+
+/// Filters shared by the export stream and its count. `$4` is the id of the
+/// last event the client received; rows strictly after it are returned.
+macro_rules! export_filter {
+    () => {
+        r#"
+        WHERE ($1::TIMESTAMPTZ IS NULL OR created_at >= $1)
+          AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+          AND ($3::VARCHAR IS NULL OR status = $3)
+          AND ($4::UUID IS NULL OR (created_at, id) > (
+              SELECT created_at, id FROM webhook_events WHERE id = $4
+          ))
+        "#
+    };
+}
+
 impl WebhookEventRepository {
+    /// Store an event with its serialized body: kept verbatim for delivery and
+    /// parsed into the JSONB payload for querying
     pub async fn create(
         pool: &PgPool,
         wallet_address: &str,
+        chain: Chain,
         transaction_signature: Option<&str>,
         event_type: &str,
-        payload: serde_json::Value,
+        payload_body: &str,
     ) -> Result<WebhookEvent, AppError> {
         let event = sqlx::query_as::<_, WebhookEvent>(
             r#"
-            INSERT INTO webhook_events (wallet_address, transaction_signature, event_type, payload)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO webhook_events (wallet_address, chain, transaction_signature, event_type, payload, payload_body)
+            VALUES ($1, $2, $3, $4, $5::JSONB, $5)
             RETURNING *
             "#,
         )
         .bind(wallet_address)
+        .bind(chain)
         .bind(transaction_signature)
         .bind(event_type)
-        .bind(payload)
+        .bind(payload_body)
         .fetch_one(pool)
         .await?;
 
@@ -66,11 +113,58 @@ impl WebhookEventRepository {
         Ok(events)
     }
 
+    /// Like `find_by_wallet`, narrowed by the filter's set fields. Served by
+    /// idx_webhook_events_wallet_type with an event type, otherwise by
+    /// idx_webhook_events_wallet.
+    pub async fn find_by_wallet_filtered(
+        pool: &PgPool,
+        wallet_address: &str,
+        filter: &WebhookEventFilter<'_>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookEvent>, AppError> {
+        let mut query =
+            QueryBuilder::<Postgres>::new("SELECT * FROM webhook_events WHERE wallet_address = ");
+        query.push_bind(wallet_address);
+        filter.push_conditions(&mut query);
+
+        query
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let events = query
+            .build_query_as::<WebhookEvent>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(events)
+    }
+
+    /// Total events matching `find_by_wallet_filtered`'s filters, for paging
+    pub async fn count_by_wallet(
+        pool: &PgPool,
+        wallet_address: &str,
+        filter: &WebhookEventFilter<'_>,
+    ) -> Result<i64, AppError> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "SELECT COUNT(*) FROM webhook_events WHERE wallet_address = ",
+        );
+        query.push_bind(wallet_address);
+        filter.push_conditions(&mut query);
+
+        let count: (i64,) = query.build_query_as().fetch_one(pool).await?;
+
+        Ok(count.0)
+    }
+
     pub async fn find_pending(pool: &PgPool, limit: i64) -> Result<Vec<WebhookEvent>, AppError> {
         let events = sqlx::query_as::<_, WebhookEvent>(
             r#"
             SELECT * FROM webhook_events
             WHERE status = 'pending'
+              AND (next_attempt_at IS NULL OR next_attempt_at <= NOW())
             ORDER BY created_at ASC
             LIMIT $1
             "#,
@@ -98,6 +192,31 @@ impl WebhookEventRepository {
         Ok(())
     }
 
+    /// Store what the endpoint answered on the latest attempt
+    pub async fn record_response(
+        pool: &PgPool,
+        id: Uuid,
+        status: Option<i32>,
+        response_time_ms: i32,
+        body: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_events
+            SET last_response_status = $1, last_response_time_ms = $2, last_response_body = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(status)
+        .bind(response_time_ms)
+        .bind(body)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn mark_failed(pool: &PgPool, id: Uuid, error: &str) -> Result<(), AppError> {
         sqlx::query(
             r#"
@@ -133,6 +252,26 @@ impl WebhookEventRepository {
         Ok(event)
     }
 
+    /// When the wallet's most recent webhook was delivered
+    pub async fn last_delivered_for_wallet(
+        pool: &PgPool,
+        wallet_address: &str,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        let last: Option<(Option<DateTime<Utc>>,)> = sqlx::query_as(
+            r#"
+            SELECT delivered_at FROM webhook_events
+            WHERE wallet_address = $1 AND status = 'delivered'
+            ORDER BY delivered_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(wallet_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(last.and_then(|(at,)| at))
+    }
+
     pub async fn count_by_status(pool: &PgPool, status: WebhookStatus) -> Result<i64, AppError> {
         let count: (i64,) = sqlx::query_as(
             "SELECT COUNT(*) FROM webhook_events WHERE status = $1",
@@ -144,14 +283,408 @@ impl WebhookEventRepository {
         Ok(count.0)
     }
 
-    pub async fn exists_for_transaction(pool: &PgPool, transaction_signature: &str) -> Result<bool, AppError> {
+    pub async fn exists_for_transaction(
+        pool: &PgPool,
+        transaction_signature: &str,
+        event_type: &str,
+    ) -> Result<bool, AppError> {
         let exists: (bool,) = sqlx::query_as(
-            "SELECT EXISTS(SELECT 1 FROM webhook_events WHERE transaction_signature = $1)",
+            "SELECT EXISTS(SELECT 1 FROM webhook_events WHERE transaction_signature = $1 AND event_type = $2)",
         )
         .bind(transaction_signature)
+        .bind(event_type)
         .fetch_one(pool)
         .await?;
 
         Ok(exists.0)
     }
+
+    /// Record why a delivery was skipped without counting it as an attempt
+    pub async fn record_skip(pool: &PgPool, id: Uuid, reason: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE webhook_events SET last_error = $1 WHERE id = $2")
+            .bind(reason)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Hold a pending event back until `until`, recording why, without
+    /// counting an attempt
+    pub async fn defer(
+        pool: &PgPool,
+        id: Uuid,
+        until: DateTime<Utc>,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE webhook_events SET next_attempt_at = $1, last_error = $2 WHERE id = $3")
+            .bind(until)
+            .bind(reason)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Number of a wallet's events with a delivery attempt since `since`
+    pub async fn count_attempted_since(
+        pool: &PgPool,
+        wallet_address: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64, AppError> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM webhook_events WHERE wallet_address = $1 AND last_attempt_at > $2",
+        )
+        .bind(wallet_address)
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count.0)
+    }
+
+    /// Append an attempt to the event's delivery history, numbered after the
+    /// ones already recorded
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_delivery_attempt(
+        pool: &PgPool,
+        event_id: Uuid,
+        attempted_at: DateTime<Utc>,
+        duration_ms: i32,
+        succeeded: bool,
+        response_status: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_delivery_attempts (
+                event_id, attempt_number, attempted_at, duration_ms, succeeded,
+                response_status, error
+            )
+            SELECT $1, COALESCE(MAX(attempt_number), 0) + 1, $2, $3, $4, $5, $6
+            FROM webhook_delivery_attempts WHERE event_id = $1
+            "#,
+        )
+        .bind(event_id)
+        .bind(attempted_at)
+        .bind(duration_ms)
+        .bind(succeeded)
+        .bind(response_status)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// An event's delivery attempts, oldest first
+    pub async fn delivery_attempts(
+        pool: &PgPool,
+        event_id: Uuid,
+    ) -> Result<Vec<WebhookDeliveryAttempt>, AppError> {
+        let attempts = sqlx::query_as::<_, WebhookDeliveryAttempt>(
+            r#"
+            SELECT attempt_number, attempted_at, duration_ms, succeeded, response_status, error
+            FROM webhook_delivery_attempts
+            WHERE event_id = $1
+            ORDER BY attempt_number ASC
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    /// Most recent delivery attempts for a wallet since `since`, newest first,
+    /// as (succeeded, attempted_at). Reads the per-attempt history, so every
+    /// retry of one event counts.
+    pub async fn recent_attempts(
+        pool: &PgPool,
+        wallet_address: &str,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<(bool, DateTime<Utc>)>, AppError> {
+        let attempts = sqlx::query_as::<_, (bool, DateTime<Utc>)>(
+            r#"
+            SELECT a.succeeded, a.attempted_at
+            FROM webhook_delivery_attempts a
+            JOIN webhook_events e ON e.id = a.event_id
+            WHERE e.wallet_address = $1 AND a.attempted_at > $2
+            ORDER BY a.attempted_at DESC, a.id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    /// Reset failed events back to pending with a fresh attempt budget so the
+    /// retry worker picks them up. Scoped to one wallet when given.
+    pub async fn reset_failed(pool: &PgPool, wallet_address: Option<&str>) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_events
+            SET status = 'pending', attempts = 0, last_error = NULL
+            WHERE status = 'failed' AND ($1::VARCHAR IS NULL OR wallet_address = $1)
+            "#,
+        )
+        .bind(wallet_address)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Stream every matching event across wallets in `(created_at, id)`
+    /// order, without loading the result set into memory
+    pub fn export(
+        pool: &PgPool,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        status: Option<WebhookStatus>,
+        after: Option<Uuid>,
+    ) -> impl Stream<Item = Result<WebhookEvent, sqlx::Error>> + Send + '_ {
+        sqlx::query_as::<_, WebhookEvent>(concat!(
+            "SELECT * FROM webhook_events",
+            export_filter!(),
+            "ORDER BY created_at ASC, id ASC"
+        ))
+        .bind(since)
+        .bind(until)
+        .bind(status)
+        .bind(after)
+        .fetch(pool)
+    }
+
+    /// Number of rows `export` would return with the same arguments
+    pub async fn count_for_export(
+        pool: &PgPool,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        status: Option<WebhookStatus>,
+        after: Option<Uuid>,
+    ) -> Result<i64, AppError> {
+        let count: (i64,) =
+            sqlx::query_as(concat!("SELECT COUNT(*) FROM webhook_events", export_filter!()))
+                .bind(since)
+                .bind(until)
+                .bind(status)
+                .bind(after)
+                .fetch_one(pool)
+                .await?;
+
+        Ok(count.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WalletSettings;
+    use crate::services::testing::{register_wallet, WALLET};
+
+    #[sqlx::test]
+    async fn recent_attempts_counts_every_retry_of_an_event(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let event = WebhookEventRepository::create(
+            &pool,
+            WALLET,
+            Chain::Solana,
+            None,
+            "payment.received",
+            "{}",
+        )
+        .await
+        .unwrap();
+
+        let start = Utc::now() - chrono::Duration::minutes(5);
+        for (i, succeeded) in [true, false, false, false].into_iter().enumerate() {
+            let at = start + chrono::Duration::seconds(i as i64);
+            WebhookEventRepository::record_delivery_attempt(
+                &pool, event.id, at, 10, succeeded, None, None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let recent = WebhookEventRepository::recent_attempts(
+            &pool,
+            WALLET,
+            start - chrono::Duration::seconds(1),
+            10,
+        )
+        .await
+        .unwrap();
+
+        let outcomes: Vec<bool> = recent.iter().map(|(succeeded, _)| *succeeded).collect();
+        assert_eq!(outcomes, vec![false, false, false, true]);
+        assert!(recent.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let limited = WebhookEventRepository::recent_attempts(&pool, WALLET, start, 2)
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn delivery_attempts_are_numbered_per_event(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let event = WebhookEventRepository::create(
+            &pool,
+            WALLET,
+            Chain::Solana,
+            None,
+            "payment.received",
+            "{}",
+        )
+        .await
+        .unwrap();
+
+        let now = Utc::now();
+        WebhookEventRepository::record_delivery_attempt(
+            &pool, event.id, now, 15, false, Some(500), Some("HTTP 500"),
+        )
+        .await
+        .unwrap();
+        WebhookEventRepository::record_delivery_attempt(&pool, event.id, now, 9, true, Some(200), None)
+            .await
+            .unwrap();
+
+        let attempts = WebhookEventRepository::delivery_attempts(&pool, event.id).await.unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].attempt_number, 1);
+        assert!(!attempts[0].succeeded);
+        assert_eq!(attempts[0].response_status, Some(500));
+        assert_eq!(attempts[0].error.as_deref(), Some("HTTP 500"));
+        assert_eq!(attempts[1].attempt_number, 2);
+        assert!(attempts[1].succeeded);
+    }
+
+    #[sqlx::test]
+    async fn the_signed_body_survives_the_jsonb_round_trip(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        // Key order and spacing JSONB doesn't keep
+        let body = r#"{"type":"payment.received","data":{"amount":"1.500000","fee":"0"},"id":1}"#;
+        let created = WebhookEventRepository::create(
+            &pool,
+            WALLET,
+            Chain::Solana,
+            None,
+            "payment.received",
+            body,
+        )
+        .await
+        .unwrap();
+
+        let event = WebhookEventRepository::find_by_id(&pool, created.id).await.unwrap().unwrap();
+        assert_eq!(event.payload_body.as_deref(), Some(body));
+        assert_eq!(event.body_bytes().unwrap(), body.as_bytes());
+        assert_eq!(event.payload["data"]["amount"], "1.500000");
+        assert_ne!(serde_json::to_string(&event.payload).unwrap(), body);
+    }
+
+    #[sqlx::test]
+    async fn events_without_a_stored_body_reserialize_the_payload(pool: PgPool) {
+        register_wallet(&pool, WalletSettings::default()).await;
+        let created = WebhookEventRepository::create(
+            &pool,
+            WALLET,
+            Chain::Solana,
+            None,
+            "test",
+            r#"{"type":"test","id":1}"#,
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE webhook_events SET payload_body = NULL WHERE id = $1")
+            .bind(created.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let event = WebhookEventRepository::find_by_id(&pool, created.id).await.unwrap().unwrap();
+        assert_eq!(event.body_bytes().unwrap(), serde_json::to_vec(&event.payload).unwrap());
+    }
+
+    /// Stream `export` to the end from `after`, checking each row sorts after
+    /// the one before it; returns how many rows came and the last one's id.
+    /// Only the previous key is kept, never the rows.
+    async fn drain_export(
+        pool: &PgPool,
+        status: Option<WebhookStatus>,
+        after: Option<Uuid>,
+        stop_after: usize,
+    ) -> (usize, Option<Uuid>) {
+        use tokio_stream::StreamExt;
+
+        let mut rows = WebhookEventRepository::export(pool, None, None, status, after);
+        let mut previous: Option<(DateTime<Utc>, Uuid)> = None;
+        let mut count = 0;
+        while let Some(event) = rows.next().await {
+            let event = event.unwrap();
+            let key = (event.created_at, event.id);
+            assert!(previous.is_none_or(|p| p < key), "{:?} after {:?}", key, previous);
+            previous = Some(key);
+            count += 1;
+            if count == stop_after {
+                break;
+            }
+        }
+
+        (count, previous.map(|(_, id)| id))
+    }
+
+    #[sqlx::test]
+    async fn export_streams_every_row_once_and_resumes_from_its_cursor(pool: PgPool) {
+        const ROWS: i64 = 100_000;
+        register_wallet(&pool, WalletSettings::default()).await;
+        // Ten events share each timestamp, so only the id orders them
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_events (wallet_address, event_type, payload, status, created_at)
+            SELECT $1, 'payment.received', '{}',
+                   CASE WHEN i % 4 = 0 THEN 'failed' ELSE 'delivered' END,
+                   NOW() - INTERVAL '1 day' + (i / 10) * INTERVAL '1 millisecond'
+            FROM generate_series(1, $2) AS i
+            "#,
+        )
+        .bind(WALLET)
+        .bind(ROWS)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let total = WebhookEventRepository::count_for_export(&pool, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(total, ROWS);
+        let (exported, _) = drain_export(&pool, None, None, usize::MAX).await;
+        assert_eq!(exported as i64, ROWS);
+
+        // An interrupted download resumes with no gap and no repeat
+        let (first, cursor) = drain_export(&pool, None, None, 40_000).await;
+        let remaining = WebhookEventRepository::count_for_export(&pool, None, None, None, cursor)
+            .await
+            .unwrap();
+        assert_eq!(remaining, ROWS - 40_000);
+        let (rest, _) = drain_export(&pool, None, cursor, usize::MAX).await;
+        assert_eq!((first + rest) as i64, ROWS);
+
+        let failed = Some(WebhookStatus::Failed);
+        let (exported, _) = drain_export(&pool, failed, None, usize::MAX).await;
+        let counted = WebhookEventRepository::count_for_export(&pool, None, None, failed, None)
+            .await
+            .unwrap();
+        assert_eq!(counted, ROWS / 4);
+        assert_eq!(exported as i64, counted);
+    }
 }
@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::domain::json_diff;
+use crate::repository::AuditLogRepository;
+
+/// Fields whose values are masked in stored diffs; custom webhook headers
+/// often carry credentials. The diff still shows which headers changed.
+const REDACTED_FIELDS: &[&str] = &["webhook_headers"];
+
+/// Who made an API call, and the request it came in on
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    /// "admin" with the admin key, otherwise "anonymous"
+    pub actor: String,
+    /// The caller's `X-Request-Id` header, if sent
+    pub request_id: Option<String>,
+}
+
+/// Records configuration changes made through the API
+pub struct AuditService {
+    pool: PgPool,
+    /// Writes that failed since startup
+    failures: AtomicU64,
+}
+
+impl AuditService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a change, keeping only the fields that differ between `before`
+    /// and `after`. Best effort: a failed write is logged and counted, never
+    /// returned, so it can't fail the change itself.
+    pub async fn record(
+        &self,
+        context: &AuditContext,
+        action: &str,
+        entity_type: &str,
+        entity_id: &str,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) {
+        let (before, after) = json_diff(before.as_ref(), after.as_ref());
+        let before = before.map(redact);
+        let after = after.map(redact);
+
+        if let Err(e) = AuditLogRepository::create(
+            &self.pool,
+            &context.actor,
+            action,
+            entity_type,
+            entity_id,
+            before.as_ref(),
+            after.as_ref(),
+            context.request_id.as_deref(),
+        )
+        .await
+        {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                action,
+                entity_type,
+                entity_id,
+                error = %e,
+                "Failed to write audit log entry"
+            );
+        }
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures.load(Ordering::Relaxed)
+    }
+}
+
+/// Replace the values under `REDACTED_FIELDS` with a placeholder, keeping
+/// their keys
+fn redact(mut value: serde_json::Value) -> serde_json::Value {
+    if let serde_json::Value::Object(fields) = &mut value {
+        for field in REDACTED_FIELDS {
+            if let Some(serde_json::Value::Object(inner)) = fields.get_mut(*field) {
+                for inner_value in inner.values_mut() {
+                    *inner_value = serde_json::Value::String("***".into());
+                }
+            }
+        }
+    }
+
+    value
+}
@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::services::chain::ChainClient;
+use crate::services::solana::TokenBalance;
+
+/// Entries kept before stale ones are evicted
+const MAX_ENTRIES: usize = 10_000;
+
+/// An address's balances as last read from the chain
+#[derive(Debug, Clone)]
+pub struct CachedBalance {
+    pub usdc: TokenBalance,
+    pub sol_lamports: u64,
+    pub cached_at: DateTime<Utc>,
+    fetched: Instant,
+}
+
+/// Short-lived per-address balance cache for polling clients. Stale entries
+/// are still served, and refreshed in the background for the next caller.
+pub struct BalanceCache {
+    chain: Arc<dyn ChainClient>,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedBalance>>,
+    /// Addresses with a background refresh in flight, so polls don't pile up
+    refreshing: Mutex<HashSet<String>>,
+}
+
+impl BalanceCache {
+    /// A zero `ttl` disables caching
+    pub fn new(chain: Arc<dyn ChainClient>, ttl: Duration) -> Self {
+        Self {
+            chain,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            refreshing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Balances of `address`: cached when available, unless `fresh` asks to
+    /// read the chain. A stale entry is returned as is and refreshed behind
+    /// the caller.
+    pub async fn get(
+        self: &Arc<Self>,
+        address: &str,
+        fresh: bool,
+    ) -> Result<CachedBalance, AppError> {
+        if !fresh && !self.ttl.is_zero() {
+            let cached = self.entries.lock().unwrap().get(address).cloned();
+            if let Some(cached) = cached {
+                if cached.fetched.elapsed() >= self.ttl {
+                    self.spawn_refresh(address);
+                }
+                return Ok(cached);
+            }
+        }
+
+        self.fetch(address).await
+    }
+
+    /// Read both balances from the chain and cache them
+    async fn fetch(&self, address: &str) -> Result<CachedBalance, AppError> {
+        let (usdc, sol_lamports) = tokio::try_join!(
+            self.chain.get_usdc_balance(address),
+            self.chain.get_sol_balance(address),
+        )?;
+
+        let balance = CachedBalance {
+            usdc,
+            sol_lamports,
+            cached_at: Utc::now(),
+            fetched: Instant::now(),
+        };
+
+        if !self.ttl.is_zero() {
+            let mut entries = self.entries.lock().unwrap();
+            // Any address can be queried, so bound the map by dropping stale entries
+            if entries.len() >= MAX_ENTRIES {
+                entries.retain(|_, cached| cached.fetched.elapsed() < self.ttl);
+            }
+            entries.insert(address.to_string(), balance.clone());
+        }
+
+        Ok(balance)
+    }
+
+    fn spawn_refresh(self: &Arc<Self>, address: &str) {
+        if !self.refreshing.lock().unwrap().insert(address.to_string()) {
+            return;
+        }
+
+        let cache = self.clone();
+        let address = address.to_string();
+        tokio::spawn(async move {
+            // On failure the stale entry stays and the next poll tries again
+            if let Err(e) = cache.fetch(&address).await {
+                tracing::warn!("Failed to refresh cached balance of {}: {}", address, e);
+            }
+            cache.refreshing.lock().unwrap().remove(&address);
+        });
+    }
+}
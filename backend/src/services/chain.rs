@@ -0,0 +1,170 @@
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::Chain;
+use crate::error::AppError;
+use crate::services::solana::{
+    FeeReadiness, LatestBlockhash, ParsedTransaction, SignatureStatus, SolanaClient, TokenBalance,
+};
+
+/// Chain access used by sync and the handlers. `SolanaClient` is the JSON-RPC
+/// implementation; other backends (or a mock) only need the required methods,
+/// and inherit the composed ones below.
+#[async_trait]
+pub trait ChainClient: Send + Sync {
+    /// Mint of the tracked stablecoin
+    fn usdc_mint(&self) -> &str;
+
+    async fn get_usdc_balance(&self, wallet_address: &str) -> Result<TokenBalance, AppError>;
+
+    /// Native SOL balance of an account in lamports
+    async fn get_sol_balance(&self, wallet_address: &str) -> Result<u64, AppError>;
+
+    /// Whether an account exists on chain
+    async fn account_exists(&self, address: &str) -> Result<bool, AppError>;
+
+    /// Raw data of an account, or `None` when it doesn't exist
+    async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>, AppError>;
+
+    /// Blockhash for building a transaction, and the last block height at
+    /// which a transaction using it can land
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, AppError>;
+
+    /// Signatures touching the wallet, newest first, optionally starting
+    /// before `before` and stopping at `not_before`
+    async fn get_signatures(
+        &self,
+        wallet_address: &str,
+        limit: usize,
+        before: Option<&str>,
+        not_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>, AppError>;
+
+    /// One entry per input signature, `None` when the cluster doesn't know it
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[String],
+    ) -> Result<Vec<Option<SignatureStatus>>, AppError>;
+
+    /// Fetch a transaction and extract the wallet's USDC movement, `None` if
+    /// it isn't a USDC transfer
+    async fn get_transaction_details(
+        &self,
+        signature: &str,
+        wallet_address: &str,
+    ) -> Result<Option<ParsedTransaction>, AppError>;
+
+    /// Like `get_transaction_details` for several signatures, one result per
+    /// signature in order. Backends that can batch should override this.
+    async fn get_transactions_batch(
+        &self,
+        signatures: &[String],
+        wallet_address: &str,
+    ) -> Vec<Result<Option<ParsedTransaction>, AppError>> {
+        let mut results = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            results.push(self.get_transaction_details(signature, wallet_address).await);
+        }
+        results
+    }
+
+    /// Check the wallet's SOL balance against the minimum needed to pay fees
+    /// (and rent for a possible new token account)
+    async fn check_fee_readiness(
+        &self,
+        wallet_address: &str,
+        required_lamports: u64,
+    ) -> Result<FeeReadiness, AppError> {
+        let sol_balance_lamports = self.get_sol_balance(wallet_address).await?;
+        let shortfall_lamports = required_lamports.saturating_sub(sol_balance_lamports);
+
+        Ok(FeeReadiness {
+            ready: shortfall_lamports == 0,
+            sol_balance_lamports,
+            required_lamports,
+            shortfall_lamports,
+        })
+    }
+
+    /// Sync recent transactions for a wallet from the blockchain
+    async fn sync_wallet_transactions(
+        &self,
+        wallet_address: &str,
+        limit: usize,
+        not_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ParsedTransaction>, AppError> {
+        // Get recent signatures, stopping at the cutoff
+        let signatures = self
+            .get_signatures(wallet_address, limit, None, not_before)
+            .await?;
+
+        let mut transactions = Vec::new();
+
+        // Fetch details for all signatures in batched requests
+        let details = self.get_transactions_batch(&signatures, wallet_address).await;
+
+        for (signature, result) in signatures.iter().zip(details) {
+            match result {
+                // Signatures without a block time slip past the cutoff above
+                Ok(Some(tx)) if not_before.is_some_and(|cutoff| tx.block_time < cutoff) => {}
+                Ok(Some(tx)) => transactions.push(tx),
+                Ok(None) => {} // Not a USDC transfer, skip
+                Err(e) => {
+                    // Log error but continue with other transactions; the
+                    // next cycle sees the signature again
+                    tracing::warn!(
+                        wallet = %wallet_address,
+                        signature = %signature,
+                        error = %e,
+                        "Failed to fetch transaction, skipping"
+                    );
+                }
+            }
+        }
+
+        Ok(transactions)
+    }
+}
+
+/// Validate an address being registered as a wallet on `chain`, returning its
+/// canonical form. Only Solana is implemented so far.
+pub fn validate_wallet_address(chain: Chain, address: &str) -> Result<String, AppError> {
+    match chain {
+        Chain::Solana => Ok(SolanaClient::validate_wallet_address(address)?.to_string()),
+        other => Err(AppError::BadRequest(format!("Chain '{}' is not yet supported", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_chain::MockChainClient;
+    use crate::services::testing::{USDC_MINT, WALLET};
+
+    #[tokio::test]
+    async fn fee_readiness_reports_the_shortfall() {
+        let chain = MockChainClient::new(USDC_MINT);
+        chain.set_sol_balance(WALLET, 500_000);
+
+        let readiness = chain.check_fee_readiness(WALLET, 2_000_000).await.unwrap();
+        assert!(!readiness.ready);
+        assert_eq!(readiness.sol_balance_lamports, 500_000);
+        assert_eq!(readiness.required_lamports, 2_000_000);
+        assert_eq!(readiness.shortfall_lamports, 1_500_000);
+    }
+
+    #[tokio::test]
+    async fn fee_readiness_at_or_above_the_minimum_is_ready() {
+        let chain = MockChainClient::new(USDC_MINT);
+        chain.set_sol_balance(WALLET, 2_000_000);
+
+        let readiness = chain.check_fee_readiness(WALLET, 2_000_000).await.unwrap();
+        assert!(readiness.ready);
+        assert_eq!(readiness.shortfall_lamports, 0);
+
+        // An account that was never funded has nothing
+        let readiness = chain.check_fee_readiness("unfunded", 1).await.unwrap();
+        assert!(!readiness.ready);
+        assert_eq!(readiness.shortfall_lamports, 1);
+    }
+}
@@ -0,0 +1,300 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Consecutive failures within the window that open the circuit
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// Failures older than this no longer count towards the threshold
+const FAILURE_WINDOW: Duration = Duration::minutes(10);
+
+/// Cool-off after the first trip, doubled on every re-trip
+const BASE_COOLOFF: Duration = Duration::seconds(30);
+
+/// Upper bound for the cool-off period
+const MAX_COOLOFF: Duration = Duration::minutes(30);
+
+/// Outcome of asking the breaker whether a delivery may go out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitDecision {
+    /// Circuit closed, deliver normally
+    Allow,
+    /// Cool-off elapsed, this delivery is the single half-open probe
+    Probe,
+    /// Circuit open, skip delivery until the given time
+    Skip { until: DateTime<Utc> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Per-endpoint circuit state exposed in webhook stats
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitStatus {
+    pub endpoint: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub open_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    last_failure_at: Option<DateTime<Utc>>,
+    trips: u32,
+    open_until: Option<DateTime<Utc>>,
+    probing: bool,
+}
+
+impl Circuit {
+    fn state(&self, now: DateTime<Utc>) -> CircuitState {
+        match self.open_until {
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        }
+    }
+
+    fn trip(&mut self, now: DateTime<Utc>) {
+        self.trips += 1;
+        self.probing = false;
+        self.open_until = Some(now + cooloff_for(self.trips));
+    }
+}
+
+fn cooloff_for(trips: u32) -> Duration {
+    let factor = 2i32.saturating_pow(trips.saturating_sub(1));
+    BASE_COOLOFF
+        .checked_mul(factor)
+        .map(|d| d.min(MAX_COOLOFF))
+        .unwrap_or(MAX_COOLOFF)
+}
+
+/// In-memory circuit breaker keyed by webhook endpoint (we key by wallet).
+/// A hard-down merchant endpoint stops eating delivery timeouts on every
+/// retry cycle; after the cool-off a single probe decides whether to close.
+#[derive(Default)]
+pub struct CircuitBreaker {
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether we already track this endpoint (otherwise callers may seed it
+    /// from persisted attempt history, e.g. after a restart)
+    pub fn is_tracked(&self, key: &str) -> bool {
+        self.circuits.lock().unwrap().contains_key(key)
+    }
+
+    /// Seed an endpoint's state from recent history. `recent_failures` are the
+    /// consecutive failed attempts, newest first.
+    pub fn seed(&self, key: &str, recent_failures: &[DateTime<Utc>]) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(key.to_string()).or_default();
+
+        circuit.consecutive_failures = recent_failures.len() as u32;
+        circuit.last_failure_at = recent_failures.first().copied();
+
+        if circuit.consecutive_failures >= FAILURE_THRESHOLD {
+            if let Some(last) = circuit.last_failure_at {
+                circuit.trip(last);
+            }
+        }
+    }
+
+    pub fn check(&self, key: &str) -> CircuitDecision {
+        let now = Utc::now();
+        let mut circuits = self.circuits.lock().unwrap();
+
+        let circuit = match circuits.get_mut(key) {
+            Some(c) => c,
+            None => return CircuitDecision::Allow,
+        };
+
+        match (circuit.state(now), circuit.open_until) {
+            (CircuitState::Open, Some(until)) => CircuitDecision::Skip { until },
+            (CircuitState::HalfOpen, Some(until)) => {
+                // Only one probe at a time; everyone else waits for its outcome
+                if circuit.probing {
+                    CircuitDecision::Skip { until }
+                } else {
+                    circuit.probing = true;
+                    CircuitDecision::Probe
+                }
+            }
+            _ => CircuitDecision::Allow,
+        }
+    }
+
+    pub fn record_success(&self, key: &str) {
+        // Keep the entry so a healthy endpoint isn't re-seeded from history
+        self.circuits
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), Circuit::default());
+    }
+
+    pub fn record_failure(&self, key: &str) {
+        let now = Utc::now();
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(key.to_string()).or_default();
+
+        // A failed probe re-opens the circuit with a longer cool-off
+        if circuit.probing || circuit.state(now) == CircuitState::HalfOpen {
+            circuit.trip(now);
+            return;
+        }
+
+        let within_window = circuit
+            .last_failure_at
+            .is_some_and(|last| now - last <= FAILURE_WINDOW);
+
+        circuit.consecutive_failures = if within_window {
+            circuit.consecutive_failures + 1
+        } else {
+            1
+        };
+        circuit.last_failure_at = Some(now);
+
+        if circuit.consecutive_failures >= FAILURE_THRESHOLD && circuit.open_until.is_none() {
+            circuit.trip(now);
+        }
+    }
+
+    /// Endpoints whose circuit is not closed
+    pub fn snapshot(&self) -> Vec<CircuitStatus> {
+        let now = Utc::now();
+        let circuits = self.circuits.lock().unwrap();
+
+        circuits
+            .iter()
+            .filter(|(_, c)| c.state(now) != CircuitState::Closed)
+            .map(|(key, c)| CircuitStatus {
+                endpoint: key.clone(),
+                state: c.state(now),
+                consecutive_failures: c.consecutive_failures,
+                open_until: c.open_until,
+            })
+            .collect()
+    }
+
+    /// Current state of one endpoint's circuit; untracked endpoints are closed
+    pub fn status(&self, key: &str) -> CircuitStatus {
+        let now = Utc::now();
+        let circuits = self.circuits.lock().unwrap();
+
+        match circuits.get(key) {
+            Some(c) => CircuitStatus {
+                endpoint: key.to_string(),
+                state: c.state(now),
+                consecutive_failures: c.consecutive_failures,
+                open_until: c.open_until,
+            },
+            None => CircuitStatus {
+                endpoint: key.to_string(),
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                open_until: None,
+            },
+        }
+    }
+
+    /// Window over which persisted failures are considered when seeding
+    pub fn failure_window() -> Duration {
+        FAILURE_WINDOW
+    }
+
+    pub fn failure_threshold() -> u32 {
+        FAILURE_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let breaker = CircuitBreaker::new();
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record_failure("wallet");
+            assert_eq!(breaker.check("wallet"), CircuitDecision::Allow);
+        }
+
+        breaker.record_failure("wallet");
+        assert!(matches!(breaker.check("wallet"), CircuitDecision::Skip { .. }));
+        assert_eq!(breaker.status("wallet").state, CircuitState::Open);
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::new();
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record_failure("wallet");
+        }
+        breaker.record_success("wallet");
+        breaker.record_failure("wallet");
+
+        assert_eq!(breaker.check("wallet"), CircuitDecision::Allow);
+        assert_eq!(breaker.status("wallet").consecutive_failures, 1);
+    }
+
+    #[test]
+    fn seeding_recent_failures_opens_the_circuit() {
+        let breaker = CircuitBreaker::new();
+        let now = Utc::now();
+        let failures: Vec<_> = (0..FAILURE_THRESHOLD as i64)
+            .map(|i| now - Duration::seconds(i))
+            .collect();
+
+        assert!(!breaker.is_tracked("wallet"));
+        breaker.seed("wallet", &failures);
+
+        assert!(breaker.is_tracked("wallet"));
+        assert_eq!(
+            breaker.check("wallet"),
+            CircuitDecision::Skip { until: now + BASE_COOLOFF }
+        );
+    }
+
+    #[test]
+    fn seeding_fewer_failures_leaves_it_closed() {
+        let breaker = CircuitBreaker::new();
+        breaker.seed("wallet", &[Utc::now()]);
+
+        assert_eq!(breaker.check("wallet"), CircuitDecision::Allow);
+        assert_eq!(breaker.status("wallet").consecutive_failures, 1);
+    }
+
+    #[test]
+    fn only_one_probe_once_the_cooloff_elapses() {
+        let breaker = CircuitBreaker::new();
+        let long_ago = Utc::now() - MAX_COOLOFF - Duration::seconds(1);
+        let failures = vec![long_ago; FAILURE_THRESHOLD as usize];
+        breaker.seed("wallet", &failures);
+
+        assert_eq!(breaker.check("wallet"), CircuitDecision::Probe);
+        assert!(matches!(breaker.check("wallet"), CircuitDecision::Skip { .. }));
+
+        // A failed probe re-opens it with a doubled cool-off
+        breaker.record_failure("wallet");
+        let until = breaker.status("wallet").open_until.unwrap();
+        assert!(until > Utc::now() + BASE_COOLOFF);
+    }
+
+    #[test]
+    fn cooloff_doubles_up_to_the_cap() {
+        assert_eq!(cooloff_for(1), BASE_COOLOFF);
+        assert_eq!(cooloff_for(2), BASE_COOLOFF * 2);
+        assert_eq!(cooloff_for(40), MAX_COOLOFF);
+    }
+}
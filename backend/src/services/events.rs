@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+
+use crate::domain::WebhookPayload;
+use crate::error::AppError;
+
+/// Buffered events before slow subscribers start losing the oldest ones
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Maximum concurrent stream connections per wallet
+const MAX_STREAMS_PER_WALLET: usize = 5;
+
+/// An informational event for a wallet, carrying the same payload shape as
+/// the signed webhook (which remains the source of truth)
+#[derive(Debug, Clone)]
+pub struct WalletEvent {
+    pub wallet_address: String,
+    pub payload: WebhookPayload,
+}
+
+/// In-process fan-out of wallet events to live stream subscribers.
+/// Publishing never blocks: the broadcast channel is bounded and lagging
+/// receivers drop the oldest events instead of holding up the sync loop.
+pub struct EventBus {
+    sender: broadcast::Sender<WalletEvent>,
+    connections: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+/// A live subscription; releases its connection slot when dropped
+pub struct Subscription {
+    receiver: broadcast::Receiver<WalletEvent>,
+    guard: ConnectionGuard,
+}
+
+impl Subscription {
+    /// Split into the receiver and the guard, which must be kept alive for as
+    /// long as the connection is open
+    pub fn into_parts(self) -> (broadcast::Receiver<WalletEvent>, ConnectionGuard) {
+        (self.receiver, self.guard)
+    }
+}
+
+pub struct ConnectionGuard {
+    wallet_address: String,
+    connections: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.connections.lock().unwrap();
+        if let Some(count) = connections.get_mut(&self.wallet_address) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                connections.remove(&self.wallet_address);
+            }
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        Self {
+            sender,
+            connections: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Publish an event; a no-op when nobody is listening
+    pub fn publish(&self, wallet_address: &str, payload: WebhookPayload) {
+        let _ = self.sender.send(WalletEvent {
+            wallet_address: wallet_address.to_string(),
+            payload,
+        });
+    }
+
+    pub fn subscribe(&self, wallet_address: &str) -> Result<Subscription, AppError> {
+        let mut connections = self.connections.lock().unwrap();
+        let count = connections.entry(wallet_address.to_string()).or_insert(0);
+
+        if *count >= MAX_STREAMS_PER_WALLET {
+            return Err(AppError::TooManyRequests(format!(
+                "At most {} concurrent event streams per wallet",
+                MAX_STREAMS_PER_WALLET
+            )));
+        }
+        *count += 1;
+
+        Ok(Subscription {
+            receiver: self.sender.subscribe(),
+            guard: ConnectionGuard {
+                wallet_address: wallet_address.to_string(),
+                connections: self.connections.clone(),
+            },
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use chrono::{NaiveDate, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::domain::{DisplayAmount, FxRate, SUPPORTED_DISPLAY_CURRENCIES};
+use crate::error::AppError;
+use crate::repository::FxRateRepository;
+use crate::services::leader::LeaderLock;
+
+#[derive(Debug, Deserialize)]
+struct LatestRatesResponse {
+    date: NaiveDate,
+    rates: HashMap<String, serde_json::Number>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceResponse {
+    data: HashMap<String, Option<PriceEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceEntry {
+    price: String,
+}
+
+/// How long a fetched USDC/USD price is reused
+const PRICE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Keeps USD exchange rates for display currencies fresh in the database
+pub struct FxService {
+    pool: PgPool,
+    client: Client,
+    api_url: String,
+    refresh_interval: Duration,
+    leader: Arc<LeaderLock>,
+    shutdown: Arc<AtomicBool>,
+    price_api_url: String,
+    usdc_mint: String,
+    usdc_price: Mutex<Option<(Instant, Decimal)>>,
+}
+
+impl FxService {
+    pub fn new(
+        pool: PgPool,
+        api_url: &str,
+        refresh_interval: Duration,
+        leader: Arc<LeaderLock>,
+        price_api_url: &str,
+        usdc_mint: &str,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            pool,
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            refresh_interval,
+            leader,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            price_api_url: price_api_url.trim_end_matches('/').to_string(),
+            usdc_mint: usdc_mint.to_string(),
+            usdc_price: Mutex::new(None),
+        }
+    }
+
+    /// Start the background rate refresh loop
+    pub fn start_background_fetch(self: Arc<Self>) -> JoinHandle<()> {
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            info!("Background FX rate fetch started");
+
+            loop {
+                // Check for shutdown signal
+                if service.shutdown.load(Ordering::Relaxed) {
+                    info!("Background FX rate fetch shutting down");
+                    break;
+                }
+
+                // Only the leader replica fetches
+                if service.leader.ensure().await {
+                    match service.refresh_rates().await {
+                        Ok(count) => info!(count = count, "Refreshed FX rates"),
+                        Err(e) => error!("FX rate refresh failed: {}", e),
+                    }
+                }
+
+                // Wait for next cycle
+                tokio::time::sleep(service.refresh_interval).await;
+            }
+        })
+    }
+
+    /// Signal the background fetch to stop
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    /// Fetch the latest USD rates for all supported display currencies
+    pub async fn refresh_rates(&self) -> Result<usize, AppError> {
+        let url = format!(
+            "{}/latest?from=USD&to={}",
+            self.api_url,
+            SUPPORTED_DISPLAY_CURRENCIES.join(",")
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Internal(format!("FX rate request failed: {}", e)))?;
+
+        let latest: LatestRatesResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse FX rates: {}", e)))?;
+
+        let as_of = latest
+            .date
+            .and_hms_opt(0, 0, 0)
+            .map(|t| t.and_utc())
+            .unwrap_or_else(Utc::now);
+
+        let mut stored = 0;
+        for (currency, rate) in &latest.rates {
+            if !SUPPORTED_DISPLAY_CURRENCIES.contains(&currency.as_str()) {
+                continue;
+            }
+
+            // Parse from the JSON text rather than through f64
+            let rate = match Decimal::from_str(&rate.to_string())
+                .or_else(|_| Decimal::from_scientific(&rate.to_string()))
+            {
+                Ok(rate) if rate > Decimal::ZERO => rate,
+                _ => {
+                    warn!(currency = %currency, rate = %rate, "Ignoring invalid FX rate");
+                    continue;
+                }
+            };
+
+            FxRateRepository::upsert(&self.pool, currency, rate, as_of).await?;
+            stored += 1;
+        }
+
+        Ok(stored)
+    }
+
+    /// Latest stored rate for a currency. Returns `None` (never an error)
+    /// when it's unavailable, so callers can simply omit the display fields.
+    pub async fn rate(&self, currency: &str) -> Option<FxRate> {
+        match FxRateRepository::find(&self.pool, currency).await {
+            Ok(rate) => rate,
+            Err(e) => {
+                warn!(currency = %currency, error = %e, "Failed to load FX rate");
+                None
+            }
+        }
+    }
+
+    /// Convert a USD amount into the given display currency at the latest rate
+    pub async fn display(&self, currency: Option<&str>, usd_amount: Decimal) -> Option<DisplayAmount> {
+        let rate = self.rate(currency?).await?;
+        Some(rate.display(usd_amount))
+    }
+
+    /// Market price of 1 USDC in USD from the price oracle, so depegs show up
+    /// in valuations. Cached briefly; unlike display rates this is fetched on
+    /// demand and fails loudly, since the caller asked for it explicitly.
+    pub async fn usdc_usd_price(&self) -> Result<Decimal, AppError> {
+        if let Some((fetched, price)) = *self.usdc_price.lock().unwrap() {
+            if fetched.elapsed() < PRICE_CACHE_TTL {
+                return Ok(price);
+            }
+        }
+
+        let url = format!("{}?ids={}", self.price_api_url, self.usdc_mint);
+        let response: PriceResponse = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::ExternalApi(format!("USDC price request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalApi(format!("Failed to parse USDC price: {}", e)))?;
+
+        let price = response
+            .data
+            .get(&self.usdc_mint)
+            .and_then(|entry| entry.as_ref())
+            .and_then(|entry| Decimal::from_str(&entry.price).ok())
+            .filter(|price| *price > Decimal::ZERO)
+            .ok_or_else(|| AppError::ExternalApi("Price oracle returned no USDC price".into()))?;
+
+        *self.usdc_price.lock().unwrap() = Some((Instant::now(), price));
+        Ok(price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::{Json, Router};
+
+    use crate::services::testing::{serve, UNREACHABLE_URL, USDC_MINT};
+
+    fn service(pool: &PgPool, api_url: &str) -> FxService {
+        FxService::new(
+            pool.clone(),
+            api_url,
+            Duration::from_secs(60),
+            Arc::new(LeaderLock::new(pool.clone(), "test")),
+            UNREACHABLE_URL,
+            USDC_MINT,
+        )
+    }
+
+    #[sqlx::test]
+    async fn refreshed_rates_convert_with_cent_rounding(pool: PgPool) {
+        let api_url = serve(Router::new().route(
+            "/latest",
+            get(|| async {
+                Json(serde_json::json!({
+                    "date": "2026-10-13",
+                    "rates": { "EUR": 0.9234, "JPY": 1.5e2, "GBP": 0, "XYZ": 1.1 }
+                }))
+            }),
+        ))
+        .await;
+        let fx = service(&pool, &api_url);
+
+        // Zero and unsupported rates are skipped
+        assert_eq!(fx.refresh_rates().await.unwrap(), 2);
+        assert!(fx.rate("GBP").await.is_none());
+        assert!(fx.rate("XYZ").await.is_none());
+
+        let eur = fx.display(Some("EUR"), Decimal::new(15, 1)).await.unwrap();
+        assert_eq!(eur.display_amount, "1.39");
+        assert_eq!(eur.fx_rate, "0.9234");
+        assert_eq!(eur.fx_rate_as_of.to_rfc3339(), "2026-10-13T00:00:00+00:00");
+
+        let jpy = fx.display(Some("JPY"), Decimal::new(1_234_567, 6)).await.unwrap();
+        assert_eq!(jpy.display_amount, "185.19");
+        assert_eq!(jpy.fx_rate, "150");
+
+        assert!(fx.display(None, Decimal::ONE).await.is_none());
+    }
+
+    #[sqlx::test]
+    async fn a_failed_refresh_keeps_the_stored_rates(pool: PgPool) {
+        FxRateRepository::upsert(&pool, "CHF", Decimal::new(88, 2), Utc::now()).await.unwrap();
+        let fx = service(&pool, UNREACHABLE_URL);
+
+        assert!(fx.refresh_rates().await.is_err());
+        let chf = fx.display(Some("CHF"), Decimal::ONE).await.unwrap();
+        assert_eq!(chf.display_amount, "0.88");
+    }
+}
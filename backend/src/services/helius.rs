@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+
+use axum::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::domain::{
+    from_base_units, is_plausible_amount, largest_counterparty, TransactionType, TransferLeg,
+};
+use crate::error::AppError;
+use crate::services::chain::ChainClient;
+use crate::services::solana::{
+    LatestBlockhash, ParsedTransaction, SignatureStatus, SolanaClient, TokenBalance,
+};
+
+/// Most signatures Helius accepts per /v0/transactions call
+const MAX_HELIUS_BATCH: usize = 100;
+
+/// SPL Memo v2 and v1
+const MEMO_PROGRAM_IDS: &[&str] = &[
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+    "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo",
+];
+
+// Helius enhanced transaction types (only the fields we map)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnhancedTransaction {
+    signature: String,
+    timestamp: Option<i64>,
+    transaction_error: Option<serde_json::Value>,
+    #[serde(default)]
+    token_transfers: Vec<TokenTransfer>,
+    #[serde(default)]
+    account_data: Vec<AccountData>,
+    #[serde(default)]
+    instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Instruction {
+    #[serde(default)]
+    program_id: String,
+    /// Base58 encoded
+    #[serde(default)]
+    data: String,
+    #[serde(default)]
+    inner_instructions: Vec<Instruction>,
+}
+
+impl Instruction {
+    /// Text of an SPL memo instruction (either memo program version)
+    fn memo(&self) -> Option<String> {
+        if !MEMO_PROGRAM_IDS.contains(&self.program_id.as_str()) {
+            return None;
+        }
+        let data = bs58::decode(&self.data).into_vec().ok()?;
+        String::from_utf8(data).ok()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenTransfer {
+    from_user_account: Option<String>,
+    to_user_account: Option<String>,
+    #[serde(default)]
+    from_token_account: String,
+    #[serde(default)]
+    to_token_account: String,
+    /// UI amount, a JSON number
+    token_amount: Option<Decimal>,
+    mint: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountData {
+    #[serde(default)]
+    token_balance_changes: Vec<TokenBalanceChange>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBalanceChange {
+    user_account: Option<String>,
+    mint: String,
+    raw_token_amount: RawTokenAmount,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTokenAmount {
+    token_amount: String,
+    decimals: u8,
+}
+
+/// Parses transactions with Helius's enhanced transaction API, which decodes
+/// SPL transfers server-side, instead of diffing token balances ourselves
+pub struct HeliusParser {
+    client: Client,
+    api_url: String,
+    api_key: String,
+    usdc_mint: String,
+}
+
+impl HeliusParser {
+    pub fn new(api_url: &str, api_key: &str, usdc_mint: &str) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            usdc_mint: usdc_mint.to_string(),
+        }
+    }
+
+    /// Parse up to `MAX_HELIUS_BATCH` signatures. Signatures Helius doesn't
+    /// return are absent from the map, so the caller can fall back for them.
+    async fn parse_transactions(
+        &self,
+        signatures: &[String],
+        wallet_address: &str,
+    ) -> Result<HashMap<String, Option<ParsedTransaction>>, AppError> {
+        let response = self
+            .client
+            .post(format!("{}/v0/transactions", self.api_url))
+            .query(&[("api-key", &self.api_key)])
+            .json(&json!({ "transactions": signatures }))
+            .send()
+            .await
+            // The URL carries the API key, keep it out of errors and logs
+            .map_err(|e| AppError::SolanaRpc(format!("Helius request failed: {}", e.without_url())))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::SolanaRpc(format!(
+                "Helius returned {}",
+                response.status()
+            )));
+        }
+
+        let transactions: Vec<EnhancedTransaction> = response
+            .json()
+            .await
+            .map_err(|e| {
+                AppError::SolanaRpc(format!("Failed to parse Helius response: {}", e.without_url()))
+            })?;
+
+        Ok(transactions
+            .into_iter()
+            // Without a timestamp, let the RPC path resolve the block time
+            .filter(|tx| tx.timestamp.is_some())
+            .map(|tx| {
+                let parsed = self.map_transaction(&tx, wallet_address);
+                (tx.signature, parsed)
+            })
+            .collect())
+    }
+
+    /// Map an enhanced transaction to our wallet's net USDC movement
+    fn map_transaction(
+        &self,
+        tx: &EnhancedTransaction,
+        wallet_address: &str,
+    ) -> Option<ParsedTransaction> {
+        if tx.transaction_error.is_some() {
+            return None;
+        }
+
+        let mut delta: i128 = 0;
+        let mut decimals: u8 = 6;
+
+        for change in tx.account_data.iter().flat_map(|a| &a.token_balance_changes) {
+            if change.user_account.as_deref() == Some(wallet_address) && change.mint == self.usdc_mint
+            {
+                let change_raw = change.raw_token_amount.token_amount.parse::<i128>().ok()?;
+                delta = delta.checked_add(change_raw)?;
+                decimals = change.raw_token_amount.decimals;
+            }
+        }
+
+        let usdc_transfers = || tx.token_transfers.iter().filter(|t| t.mint == self.usdc_mint);
+        let ours = |owner: &Option<String>| owner.as_deref() == Some(wallet_address);
+        let scale = 10u64.checked_pow(decimals as u32).map(Decimal::from);
+        let leg = |t: &TokenTransfer| {
+            Some(TransferLeg {
+                source: t.from_token_account.clone(),
+                destination: t.to_token_account.clone(),
+                source_owner: t.from_user_account.clone(),
+                destination_owner: t.to_user_account.clone(),
+                amount_raw: t.token_amount?.checked_mul(scale?)?.round().to_u64()?,
+            })
+        };
+
+        let (tx_type, transfer_legs): (TransactionType, Vec<TransferLeg>) = match delta {
+            // USDC transfers between our wallet and someone else
+            d if d != 0 => (
+                if d > 0 { TransactionType::Receive } else { TransactionType::Send },
+                usdc_transfers()
+                    .filter(|t| ours(&t.from_user_account) != ours(&t.to_user_account))
+                    .filter_map(leg)
+                    .collect(),
+            ),
+            // No net change: a move between the wallet's own token accounts,
+            // else nothing for this wallet
+            _ => (
+                TransactionType::SelfTransfer,
+                usdc_transfers()
+                    .filter(|t| ours(&t.from_user_account) && ours(&t.to_user_account))
+                    .filter_map(leg)
+                    .collect(),
+            ),
+        };
+
+        let amount_raw = match tx_type {
+            TransactionType::SelfTransfer => transfer_legs
+                .iter()
+                .try_fold(0u64, |sum, leg| sum.checked_add(leg.amount_raw))
+                .filter(|&moved| moved > 0)?,
+            _ => u64::try_from(delta.unsigned_abs()).unwrap_or(u64::MAX),
+        };
+        let amount = from_base_units(amount_raw, decimals)
+            .filter(|_| is_plausible_amount(amount_raw, decimals));
+        let Some(amount) = amount else {
+            tracing::warn!(
+                signature = %tx.signature,
+                delta = %delta,
+                decimals,
+                metric = "parser.implausible_amount",
+                "Parsed amount is implausible, skipping"
+            );
+            return None;
+        };
+
+        // The largest contributor, else the other side of any USDC transfer
+        // touching our wallet; a self-transfer's counterparty is the wallet
+        let counterparty = match tx_type {
+            TransactionType::SelfTransfer => Some(wallet_address.to_string()),
+            _ => largest_counterparty(&transfer_legs, wallet_address, tx_type).or_else(|| {
+                usdc_transfers().find_map(|t| match tx_type {
+                    TransactionType::Receive if ours(&t.to_user_account) => {
+                        t.from_user_account.clone()
+                    }
+                    TransactionType::Send if ours(&t.from_user_account) => {
+                        t.to_user_account.clone()
+                    }
+                    _ => None,
+                })
+            }),
+        };
+
+        let block_time: DateTime<Utc> = tx
+            .timestamp
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single())?;
+
+        let memo = tx
+            .instructions
+            .iter()
+            .chain(tx.instructions.iter().flat_map(|i| &i.inner_instructions))
+            .find_map(Instruction::memo);
+
+        Some(ParsedTransaction {
+            signature: tx.signature.clone(),
+            wallet_address: wallet_address.to_string(),
+            tx_type: tx_type.to_string(),
+            amount,
+            amount_raw,
+            decimals,
+            token_mint: self.usdc_mint.clone(),
+            counterparty: counterparty.unwrap_or_else(|| "unknown".to_string()),
+            block_time,
+            transfer_legs,
+            memo,
+        })
+    }
+}
+
+/// JSON-RPC chain access with transaction parsing done by Helius. Falls back
+/// to the RPC parser for a batch Helius fails on, or signatures it omits.
+pub struct HeliusChainClient {
+    rpc: SolanaClient,
+    parser: HeliusParser,
+}
+
+impl HeliusChainClient {
+    pub fn new(rpc: SolanaClient, parser: HeliusParser) -> Self {
+        Self { rpc, parser }
+    }
+}
+
+#[async_trait]
+impl ChainClient for HeliusChainClient {
+    fn usdc_mint(&self) -> &str {
+        self.rpc.usdc_mint()
+    }
+
+    async fn get_usdc_balance(&self, wallet_address: &str) -> Result<TokenBalance, AppError> {
+        self.rpc.get_usdc_balance(wallet_address).await
+    }
+
+    async fn get_sol_balance(&self, wallet_address: &str) -> Result<u64, AppError> {
+        self.rpc.get_sol_balance(wallet_address).await
+    }
+
+    async fn account_exists(&self, address: &str) -> Result<bool, AppError> {
+        self.rpc.account_exists(address).await
+    }
+
+    async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>, AppError> {
+        self.rpc.get_account_data(address).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, AppError> {
+        self.rpc.get_latest_blockhash().await
+    }
+
+    async fn get_signatures(
+        &self,
+        wallet_address: &str,
+        limit: usize,
+        before: Option<&str>,
+        not_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>, AppError> {
+        self.rpc
+            .get_signatures(wallet_address, limit, before, not_before)
+            .await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[String],
+    ) -> Result<Vec<Option<SignatureStatus>>, AppError> {
+        self.rpc.get_signature_statuses(signatures).await
+    }
+
+    async fn get_transaction_details(
+        &self,
+        signature: &str,
+        wallet_address: &str,
+    ) -> Result<Option<ParsedTransaction>, AppError> {
+        let signatures = [signature.to_string()];
+        self.get_transactions_batch(&signatures, wallet_address)
+            .await
+            .pop()
+            .unwrap_or(Ok(None))
+    }
+
+    async fn get_transactions_batch(
+        &self,
+        signatures: &[String],
+        wallet_address: &str,
+    ) -> Vec<Result<Option<ParsedTransaction>, AppError>> {
+        let mut results = Vec::with_capacity(signatures.len());
+
+        for chunk in signatures.chunks(MAX_HELIUS_BATCH) {
+            let parsed = tokio::time::timeout(
+                self.rpc.detail_timeout(),
+                self.parser.parse_transactions(chunk, wallet_address),
+            )
+            .await;
+            let mut parsed = match parsed {
+                Ok(Ok(parsed)) => parsed,
+                Ok(Err(e)) => {
+                    tracing::warn!(error = %e, "Helius parsing failed, falling back to RPC");
+                    HashMap::new()
+                }
+                Err(_) => {
+                    tracing::warn!("Helius parsing timed out, falling back to RPC");
+                    HashMap::new()
+                }
+            };
+
+            let missing: Vec<String> = chunk
+                .iter()
+                .filter(|signature| !parsed.contains_key(*signature))
+                .cloned()
+                .collect();
+            let mut fallback = self
+                .rpc
+                .get_transactions_batch(&missing, wallet_address)
+                .await
+                .into_iter();
+
+            for signature in chunk {
+                match parsed.remove(signature) {
+                    Some(tx) => results.push(Ok(tx)),
+                    None => results.push(fallback.next().unwrap_or(Ok(None))),
+                }
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{USDC_MINT, WALLET};
+
+    const PAYER: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const AMM_AUTHORITY: &str = "GpMZbSM2GgvTKHJirzeGfMFoaZ8UR2X7F4v8vHTvxFbL";
+
+    fn token_transfer(from: &str, to: &str, ui_amount: f64) -> serde_json::Value {
+        json!({
+            "fromUserAccount": from,
+            "toUserAccount": to,
+            "fromTokenAccount": format!("{}-usdc", from),
+            "toTokenAccount": format!("{}-usdc", to),
+            "tokenAmount": ui_amount,
+            "mint": USDC_MINT,
+        })
+    }
+
+    /// Map an enhanced transaction moving the wallet's USDC by `delta_raw`
+    fn map(delta_raw: i64, token_transfers: Vec<serde_json::Value>) -> Option<ParsedTransaction> {
+        let tx: EnhancedTransaction = serde_json::from_value(json!({
+            "signature": "sig",
+            "timestamp": 1_760_000_000,
+            "transactionError": null,
+            "tokenTransfers": token_transfers,
+            "accountData": [{
+                "tokenBalanceChanges": [{
+                    "userAccount": WALLET,
+                    "mint": USDC_MINT,
+                    "rawTokenAmount": { "tokenAmount": delta_raw.to_string(), "decimals": 6 }
+                }]
+            }],
+            "instructions": []
+        }))
+        .unwrap();
+
+        // Shared, since building the HTTP client dominates a mapping
+        static PARSER: std::sync::OnceLock<HeliusParser> = std::sync::OnceLock::new();
+        PARSER
+            .get_or_init(|| HeliusParser::new("http://127.0.0.1:9", "key", USDC_MINT))
+            .map_transaction(&tx, WALLET)
+    }
+
+    #[test]
+    fn a_direct_transfer_is_attributed_to_its_sender() {
+        let parsed = map(2_500_000, vec![token_transfer(PAYER, WALLET, 2.5)]).unwrap();
+
+        assert_eq!(parsed.tx_type, "receive");
+        assert_eq!(parsed.amount_raw, 2_500_000);
+        assert_eq!(parsed.counterparty, PAYER);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].amount_raw, 2_500_000);
+        assert_eq!(parsed.transfer_legs[0].source, format!("{}-usdc", PAYER));
+    }
+
+    #[test]
+    fn a_jupiter_routed_payment_is_attributed_to_the_payer_not_the_pool() {
+        let parsed = map(
+            5_000_000,
+            vec![
+                token_transfer(AMM_AUTHORITY, PAYER, 5.0),
+                token_transfer(PAYER, WALLET, 5.0),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.counterparty, PAYER);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].source_owner.as_deref(), Some(PAYER));
+    }
+
+    #[test]
+    fn sends_are_attributed_to_the_largest_recipient() {
+        let parsed = map(
+            -3_000_000,
+            vec![
+                token_transfer(WALLET, PAYER, 0.5),
+                token_transfer(WALLET, AMM_AUTHORITY, 2.5),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tx_type, "send");
+        assert_eq!(parsed.amount_raw, 3_000_000);
+        assert_eq!(parsed.counterparty, AMM_AUTHORITY);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn mapped_amounts_are_the_plausible_balance_change(delta_raw: i64) {
+            let change = delta_raw.unsigned_abs();
+
+            match map(delta_raw, vec![]) {
+                Some(parsed) => {
+                    proptest::prop_assert_eq!(parsed.amount_raw, change);
+                    proptest::prop_assert!(parsed.amount > Decimal::ZERO);
+                    let expected = if delta_raw > 0 { "receive" } else { "send" };
+                    proptest::prop_assert_eq!(parsed.tx_type, expected);
+                }
+                None => proptest::prop_assert!(!is_plausible_amount(change, 6)),
+            }
+        }
+    }
+
+    /// A transfer between two of the wallet's own USDC accounts
+    fn internal_transfer(ui_amount: f64) -> serde_json::Value {
+        let mut transfer = token_transfer(WALLET, WALLET, ui_amount);
+        transfer["toTokenAccount"] = json!(format!("{}-usdc-2", WALLET));
+        transfer
+    }
+
+    #[test]
+    fn a_move_between_the_wallets_own_accounts_is_a_self_transfer() {
+        let parsed = map(0, vec![internal_transfer(40.0), token_transfer(PAYER, AMM_AUTHORITY, 7.0)])
+            .unwrap();
+
+        assert_eq!(parsed.tx_type, "self_transfer");
+        assert_eq!(parsed.amount_raw, 40_000_000);
+        assert_eq!(parsed.counterparty, WALLET);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].destination, format!("{}-usdc-2", WALLET));
+    }
+
+    #[test]
+    fn a_receive_alongside_a_consolidation_is_the_net_receive() {
+        let parsed =
+            map(5_000_000, vec![internal_transfer(40.0), token_transfer(PAYER, WALLET, 5.0)])
+                .unwrap();
+
+        assert_eq!(parsed.tx_type, "receive");
+        assert_eq!(parsed.amount_raw, 5_000_000);
+        assert_eq!(parsed.counterparty, PAYER);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+    }
+
+    #[test]
+    fn no_usdc_movement_for_the_wallet_is_nothing() {
+        assert!(map(0, vec![token_transfer(PAYER, AMM_AUTHORITY, 7.0)]).is_none());
+        assert!(map(0, vec![]).is_none());
+    }
+}
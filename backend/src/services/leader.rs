@@ -0,0 +1,168 @@
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection, PgPool};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Leader election across replicas via a session-level Postgres advisory
+/// lock. The lock lives on a dedicated connection detached from the pool, so
+/// it's released as soon as that connection drops (e.g. the leader dies) and
+/// another instance can take over on its next attempt.
+pub struct LeaderLock {
+    pool: PgPool,
+    namespace: String,
+    key: i64,
+    conn: Mutex<Option<PgConnection>>,
+    is_leader: AtomicBool,
+}
+
+impl LeaderLock {
+    pub fn new(pool: PgPool, namespace: &str) -> Self {
+        Self {
+            pool,
+            namespace: namespace.to_string(),
+            key: lock_key(namespace),
+            conn: Mutex::new(None),
+            is_leader: AtomicBool::new(false),
+        }
+    }
+
+    /// Make sure we hold the lock, acquiring it if it's free. Returns whether
+    /// this instance is the leader.
+    pub async fn ensure(&self) -> bool {
+        let mut conn = self.conn.lock().await;
+
+        // Still holding it? Check the session is alive
+        if let Some(held) = conn.as_mut() {
+            if held.ping().await.is_ok() {
+                return true;
+            }
+            warn!(namespace = %self.namespace, "Lost leader lock connection");
+            *conn = None;
+            self.is_leader.store(false, Ordering::Relaxed);
+        }
+
+        match self.try_acquire().await {
+            Ok(Some(acquired)) => {
+                info!(namespace = %self.namespace, "Acquired leader lock");
+                *conn = Some(acquired);
+                self.is_leader.store(true, Ordering::Relaxed);
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                warn!(namespace = %self.namespace, error = %e, "Failed to acquire leader lock");
+                false
+            }
+        }
+    }
+
+    async fn try_acquire(&self) -> Result<Option<PgConnection>, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?.detach();
+
+        let (acquired,): (bool,) = sqlx::query_as("SELECT pg_try_advisory_lock($1)")
+            .bind(self.key)
+            .fetch_one(&mut conn)
+            .await?;
+
+        Ok(acquired.then_some(conn))
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::Relaxed)
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+}
+
+/// Stable 64-bit advisory lock key for a namespace, so staging and prod
+/// sharing a cluster don't contend for the same lock
+fn lock_key(namespace: &str) -> i64 {
+    let digest = Sha256::digest(format!("stablecoin-pay:background:{}", namespace));
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    i64::from_be_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::repository::WebhookEventRepository;
+    use crate::services::testing::{receive, register_wallet, signature, Harness, WALLET};
+
+    #[sqlx::test]
+    async fn one_instance_leads_per_namespace(pool: PgPool) {
+        let first = LeaderLock::new(pool.clone(), "prod");
+        let second = LeaderLock::new(pool.clone(), "prod");
+        let staging = LeaderLock::new(pool.clone(), "staging");
+
+        assert!(first.ensure().await);
+        assert!(!second.ensure().await);
+        assert!(staging.ensure().await);
+
+        // Holding the lock, the leader keeps it
+        assert!(first.ensure().await);
+        assert!(first.is_leader());
+        assert!(!second.is_leader());
+    }
+
+    #[sqlx::test]
+    async fn another_instance_takes_over_when_the_leader_goes_away(pool: PgPool) {
+        let first = LeaderLock::new(pool.clone(), "prod");
+        let second = LeaderLock::new(pool.clone(), "prod");
+        assert!(first.ensure().await);
+        drop(first);
+
+        // The server releases the lock once it notices the session closed
+        let mut took_over = false;
+        for _ in 0..50 {
+            if second.ensure().await {
+                took_over = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(took_over);
+    }
+
+    #[sqlx::test]
+    async fn two_sync_services_sync_a_receive_once(pool: PgPool) {
+        let wallet = register_wallet(&pool, Default::default()).await;
+        let replicas = [
+            Harness::with_leader(&pool, "prod"),
+            Harness::with_leader(&pool, "prod"),
+        ];
+        for replica in &replicas {
+            replica.chain.push_transaction(receive(&signature(1), 1_000_000));
+        }
+
+        let handles: Vec<_> = replicas
+            .iter()
+            .map(|replica| replica.sync.clone().start_background_sync())
+            .collect();
+        for _ in 0..100 {
+            if replicas.iter().all(|r| r.sync.first_cycle_completed()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        for (replica, handle) in replicas.iter().zip(handles) {
+            assert!(replica.sync.first_cycle_completed());
+            replica.sync.shutdown();
+            handle.abort();
+        }
+
+        let leaders = replicas.iter().filter(|r| r.leader.is_leader()).count();
+        assert_eq!(leaders, 1);
+
+        let events = WebhookEventRepository::find_by_wallet(&pool, WALLET, 100, 0)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].wallet_address, wallet.address);
+    }
+}
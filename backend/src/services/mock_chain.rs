@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::from_base_units;
+use crate::error::AppError;
+use crate::services::chain::ChainClient;
+use crate::services::solana::{
+    LatestBlockhash, ParsedTransaction, SignatureStatus, TokenBalance,
+};
+
+/// In-memory `ChainClient` with programmable responses, so sync and handler
+/// logic can be exercised without an RPC endpoint. Unknown wallets have zero
+/// balances and no signatures; unknown signatures are not USDC transfers.
+pub struct MockChainClient {
+    usdc_mint: String,
+    state: Mutex<MockState>,
+}
+
+/// A listed signature and its block time, if known
+type ListedSignature = (String, Option<DateTime<Utc>>);
+
+#[derive(Default)]
+struct MockState {
+    usdc_balances: HashMap<String, u64>,
+    sol_balances: HashMap<String, u64>,
+    /// Newest first, as the RPC returns them
+    signatures: HashMap<String, Vec<ListedSignature>>,
+    transactions: HashMap<String, ParsedTransaction>,
+    statuses: HashMap<String, SignatureStatus>,
+    existing_accounts: HashSet<String>,
+    account_data: HashMap<String, Vec<u8>>,
+    error: Option<String>,
+    status_error: Option<String>,
+    /// getSignatureStatuses calls made so far
+    status_calls: usize,
+    latency: Duration,
+}
+
+impl MockChainClient {
+    pub fn new(usdc_mint: &str) -> Self {
+        Self {
+            usdc_mint: usdc_mint.to_string(),
+            state: Mutex::new(MockState::default()),
+        }
+    }
+
+    /// Set a wallet's USDC balance in base units (6 decimals)
+    pub fn set_usdc_balance(&self, wallet_address: &str, amount_raw: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.usdc_balances.insert(wallet_address.to_string(), amount_raw);
+    }
+
+    pub fn set_sol_balance(&self, wallet_address: &str, lamports: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.sol_balances.insert(wallet_address.to_string(), lamports);
+    }
+
+    /// Make a transaction visible: its signature is listed (newest first) for
+    /// its wallet and its details are returned by the transaction lookups.
+    /// It reports finalized unless a status was set for it.
+    pub fn push_transaction(&self, transaction: ParsedTransaction) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .statuses
+            .entry(transaction.signature.clone())
+            .or_insert_with(|| SignatureStatus {
+                err: None,
+                confirmation_status: Some("finalized".to_string()),
+            });
+        state
+            .signatures
+            .entry(transaction.wallet_address.clone())
+            .or_default()
+            .insert(0, (transaction.signature.clone(), Some(transaction.block_time)));
+        state
+            .transactions
+            .insert(transaction.signature.clone(), transaction);
+    }
+
+    /// List a signature for a wallet without USDC details (e.g. a SOL transfer)
+    pub fn push_signature(&self, wallet_address: &str, signature: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .signatures
+            .entry(wallet_address.to_string())
+            .or_default()
+            .insert(0, (signature.to_string(), None));
+    }
+
+    pub fn set_signature_status(&self, signature: &str, status: SignatureStatus) {
+        let mut state = self.state.lock().unwrap();
+        state.statuses.insert(signature.to_string(), status);
+    }
+
+    /// Mark an account as existing on chain
+    pub fn set_account_exists(&self, address: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.existing_accounts.insert(address.to_string());
+    }
+
+    /// Give an account raw data (e.g. a mint or metadata account); it also
+    /// counts as existing
+    pub fn set_account_data(&self, address: &str, data: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.existing_accounts.insert(address.to_string());
+        state.account_data.insert(address.to_string(), data);
+    }
+
+    /// Fail every call with this RPC error until cleared with `None`
+    pub fn set_error(&self, message: Option<&str>) {
+        self.state.lock().unwrap().error = message.map(str::to_string);
+    }
+
+    /// Delay every call by this long, like a slow RPC endpoint
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    async fn respond_after_latency(&self) {
+        let latency = self.state.lock().unwrap().latency;
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    /// Fail only signature status lookups with this RPC error until cleared
+    pub fn set_status_error(&self, message: Option<&str>) {
+        self.state.lock().unwrap().status_error = message.map(str::to_string);
+    }
+
+    /// Number of signature status lookups made so far
+    pub fn status_calls(&self) -> usize {
+        self.state.lock().unwrap().status_calls
+    }
+
+    fn check_error(state: &MockState) -> Result<(), AppError> {
+        match &state.error {
+            Some(message) => Err(AppError::SolanaRpc(message.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainClient for MockChainClient {
+    fn usdc_mint(&self) -> &str {
+        &self.usdc_mint
+    }
+
+    async fn get_usdc_balance(&self, wallet_address: &str) -> Result<TokenBalance, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        let balance = state.usdc_balances.get(wallet_address).copied();
+        let amount_raw = balance.unwrap_or(0);
+
+        Ok(TokenBalance {
+            mint: self.usdc_mint.clone(),
+            amount: from_base_units(amount_raw, 6).expect("USDC amounts always fit"),
+            amount_raw,
+            decimals: 6,
+            account_exists: balance.is_some(),
+        })
+    }
+
+    async fn get_sol_balance(&self, wallet_address: &str) -> Result<u64, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        Ok(state.sol_balances.get(wallet_address).copied().unwrap_or(0))
+    }
+
+    async fn account_exists(&self, address: &str) -> Result<bool, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        Ok(state.existing_accounts.contains(address))
+    }
+
+    async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        Ok(state.account_data.get(address).cloned())
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        Ok(LatestBlockhash {
+            blockhash: solana_sdk::hash::Hash::default().to_string(),
+            last_valid_block_height: 0,
+        })
+    }
+
+    async fn get_signatures(
+        &self,
+        wallet_address: &str,
+        limit: usize,
+        before: Option<&str>,
+        not_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        let listed = state
+            .signatures
+            .get(wallet_address)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let start = match before {
+            Some(before) => listed
+                .iter()
+                .position(|(signature, _)| signature == before)
+                .map_or(listed.len(), |i| i + 1),
+            None => 0,
+        };
+
+        Ok(listed[start..]
+            .iter()
+            .take_while(|(_, block_time)| match (not_before, block_time) {
+                (Some(cutoff), Some(block_time)) => *block_time >= cutoff,
+                _ => true,
+            })
+            .take(limit)
+            .map(|(signature, _)| signature.clone())
+            .collect())
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[String],
+    ) -> Result<Vec<Option<SignatureStatus>>, AppError> {
+        self.respond_after_latency().await;
+        let mut state = self.state.lock().unwrap();
+        state.status_calls += 1;
+        Self::check_error(&state)?;
+        if let Some(message) = &state.status_error {
+            return Err(AppError::SolanaRpc(message.clone()));
+        }
+
+        Ok(signatures
+            .iter()
+            .map(|signature| state.statuses.get(signature).cloned())
+            .collect())
+    }
+
+    async fn get_transaction_details(
+        &self,
+        signature: &str,
+        _wallet_address: &str,
+    ) -> Result<Option<ParsedTransaction>, AppError> {
+        self.respond_after_latency().await;
+        let state = self.state.lock().unwrap();
+        Self::check_error(&state)?;
+
+        Ok(state.transactions.get(signature).cloned())
+    }
+}
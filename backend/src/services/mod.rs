@@ -1,3 +1,20 @@
+pub mod audit;
+pub mod balance_cache;
+pub mod chain;
+pub mod circuit_breaker;
+pub mod events;
+pub mod fx;
+pub mod helius;
+pub mod leader;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock_chain;
 pub mod solana;
+pub mod solana_utils;
+pub mod swap;
 pub mod sync;
+#[cfg(test)]
+pub mod testing;
+pub mod token_accounts;
+pub mod token_registry;
 pub mod webhook;
+pub mod webhook_delivery;
@@ -1,24 +1,158 @@
+use axum::async_trait;
+use base64::Engine;
 use chrono::{DateTime, TimeZone, Utc};
-use reqwest::Client;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::domain::{
+    from_base_units, is_plausible_amount, largest_counterparty, TransactionStatus, TransactionType,
+    TransferLeg,
+};
 use crate::error::AppError;
+use crate::services::chain::ChainClient;
+
+/// Program ids that parse as valid pubkeys but can never be payment wallets
+const KNOWN_PROGRAM_IDS: &[&str] = &[
+    "11111111111111111111111111111111",             // System program
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",  // SPL Token
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",  // SPL Token-2022
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", // Associated Token Account
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",  // Memo
+    "ComputeBudget111111111111111111111111111111",  // Compute Budget
+    "BPFLoaderUpgradeab1e11111111111111111111111",  // BPF Upgradeable Loader
+    "Vote111111111111111111111111111111111111111",  // Vote program
+    "Stake11111111111111111111111111111111111111",  // Stake program
+    "SysvarRent111111111111111111111111111111111",  // Rent sysvar
+    "SysvarC1ock11111111111111111111111111111111",  // Clock sysvar
+];
+
+/// Most getTransaction calls sent in one JSON-RPC batch
+const MAX_BATCH_SIZE: usize = 50;
+
+/// Retries of a request the endpoint rate limits (HTTP 429)
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Wait before the first retry of a 429 without Retry-After; doubles after each
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Longest Retry-After honoured, so one response can't stall a wallet sync
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(5);
 
 pub struct SolanaClient {
     client: Client,
     rpc_url: String,
     pub usdc_mint: String,
+    /// Cleared once the endpoint rejects a JSON-RPC batch
+    batch_supported: AtomicBool,
+    /// Upper bound on one getTransaction call (or batch of them)
+    detail_timeout: Duration,
+    /// getTransaction calls in flight at once when not batching
+    detail_concurrency: usize,
+    /// Fetched transactions, reused when several registered wallets (or a
+    /// backfill and a sync) look at the same signature
+    transactions: TransactionCache,
+}
+
+/// Bounded least-recently-used cache of getTransaction results by signature.
+/// Only transactions the cluster returned with a block time are kept; a
+/// missing one may still confirm and must be fetched again.
+struct TransactionCache {
+    capacity: usize,
+    inner: Mutex<TransactionCacheEntries>,
+}
+
+#[derive(Default)]
+struct TransactionCacheEntries {
+    /// Signature -> result and its last use
+    results: HashMap<String, (Arc<TransactionResult>, u64)>,
+    /// Last use -> signature, least recent first
+    by_use: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl TransactionCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(TransactionCacheEntries::default()),
+        }
+    }
+
+    fn get(&self, signature: &str) -> Option<Arc<TransactionResult>> {
+        let mut inner = self.inner.lock().unwrap();
+        let entries = &mut *inner;
+
+        let (result, used) = entries.results.get_mut(signature)?;
+        entries.clock += 1;
+        entries.by_use.remove(used);
+        entries.by_use.insert(entries.clock, signature.to_string());
+        *used = entries.clock;
+
+        Some(result.clone())
+    }
+
+    fn insert(&self, signature: &str, result: Arc<TransactionResult>) {
+        if self.capacity == 0 || result.block_time.is_none() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let entries = &mut *inner;
+
+        entries.clock += 1;
+        let previous = entries
+            .results
+            .insert(signature.to_string(), (result, entries.clock));
+        if let Some((_, used)) = previous {
+            entries.by_use.remove(&used);
+        }
+        entries.by_use.insert(entries.clock, signature.to_string());
+
+        while entries.results.len() > self.capacity {
+            let Some((_, oldest)) = entries.by_use.pop_first() else {
+                break;
+            };
+            entries.results.remove(&oldest);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TokenBalance {
     pub mint: String,
     pub amount: Decimal,
+    pub amount_raw: u64, // Base units, summed across token accounts
     pub decimals: u8,
+    /// Whether the wallet has any token account for the mint; a zero balance
+    /// without one means it never held the token
+    pub account_exists: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestBlockhash {
+    pub blockhash: String,
+    pub last_valid_block_height: u64,
+}
+
+/// Whether a wallet holds enough SOL to pay transaction fees
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FeeReadiness {
+    pub ready: bool,
+    pub sol_balance_lamports: u64,
+    pub required_lamports: u64,
+    pub shortfall_lamports: u64,
 }
 
 // JSON-RPC response types
@@ -28,6 +162,14 @@ struct RpcResponse<T> {
     error: Option<RpcError>,
 }
 
+/// One entry of a JSON-RPC batch response; entries may arrive in any order
+#[derive(Debug, Deserialize)]
+struct RpcBatchResponse<T> {
+    id: usize,
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
 #[derive(Debug, Deserialize)]
 struct RpcError {
     message: String,
@@ -35,11 +177,14 @@ struct RpcError {
 
 #[derive(Debug, Deserialize)]
 struct TokenAccountsResult {
-    value: Vec<TokenAccountInfo>,
+    /// Decoded one by one so an account in an unexpected shape (e.g. base64
+    /// data for some Token-2022 accounts) can be skipped on its own
+    value: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TokenAccountInfo {
+    pubkey: Option<String>,
     account: AccountData,
 }
 
@@ -50,18 +195,18 @@ struct AccountData {
 
 #[derive(Debug, Deserialize)]
 struct ParsedData {
-    parsed: ParsedInfo,
+    parsed: Option<ParsedInfo>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ParsedInfo {
-    info: TokenInfo,
+    info: Option<TokenInfo>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TokenInfo {
-    token_amount: TokenAmount,
+    token_amount: Option<TokenAmount>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -74,8 +219,10 @@ struct TokenAmount {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TransactionResult {
+    slot: Option<u64>,
     block_time: Option<i64>,
     meta: Option<TransactionMeta>,
+    transaction: Option<TransactionEnvelope>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,11 +230,38 @@ struct TransactionResult {
 struct TransactionMeta {
     pre_token_balances: Option<Vec<TokenBalanceMeta>>,
     post_token_balances: Option<Vec<TokenBalanceMeta>>,
+    #[serde(default)]
+    inner_instructions: Vec<InnerInstructions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionEnvelope {
+    #[serde(default)]
+    message: TransactionMessage,
+}
+
+// Instructions and keys are walked loosely: programs without a jsonParsed
+// decoder come back in a different shape, and we only read SPL transfers
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionMessage {
+    #[serde(default)]
+    account_keys: Vec<serde_json::Value>,
+    #[serde(default)]
+    instructions: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InnerInstructions {
+    #[serde(default)]
+    instructions: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct TokenBalanceMeta {
+    /// Index into the message's account keys
+    account_index: Option<usize>,
     owner: Option<String>,
     mint: Option<String>,
     ui_token_amount: Option<UiTokenAmount>,
@@ -96,11 +270,39 @@ struct TokenBalanceMeta {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct UiTokenAmount {
-    ui_amount: Option<f64>,
     amount: String,
     decimals: u8,
 }
 
+// Response types for getSignatureStatuses
+#[derive(Debug, Deserialize)]
+struct SignatureStatusesResult {
+    value: Vec<Option<SignatureStatus>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub err: Option<serde_json::Value>,
+    pub confirmation_status: Option<String>,
+}
+
+impl SignatureStatus {
+    /// Map the cluster's view of a signature to our transaction status.
+    /// Returns `None` until the transaction is finalized, since a confirmed
+    /// block can still be dropped.
+    pub fn settled_status(&self) -> Option<TransactionStatus> {
+        if self.err.is_some() {
+            return Some(TransactionStatus::Failed);
+        }
+
+        match self.confirmation_status.as_deref() {
+            Some("finalized") => Some(TransactionStatus::Confirmed),
+            _ => None,
+        }
+    }
+}
+
 /// Parsed transaction ready for database storage
 #[derive(Debug, Clone)]
 pub struct ParsedTransaction {
@@ -108,163 +310,509 @@ pub struct ParsedTransaction {
     pub wallet_address: String,
     pub tx_type: String, // "send" or "receive"
     pub amount: Decimal,
+    pub amount_raw: u64, // Base units as reported by the chain
+    pub decimals: u8,
     pub token_mint: String,
     pub counterparty: String,
     pub block_time: DateTime<Utc>,
+    /// Decoded transfers into or out of the wallet's token accounts
+    pub transfer_legs: Vec<TransferLeg>,
+    /// Text of the first SPL memo instruction
+    pub memo: Option<String>,
+}
+
+/// Text of an SPL memo instruction, which jsonParsed renders as a bare string
+fn decode_memo(instruction: &serde_json::Value) -> Option<String> {
+    if instruction.get("program")?.as_str()? != "spl-memo" {
+        return None;
+    }
+    Some(instruction.get("parsed")?.as_str()?.to_string())
+}
+
+/// Source and destination token accounts and base-unit amount of an SPL
+/// token `transfer` or `transferChecked` instruction
+fn decode_transfer(instruction: &serde_json::Value) -> Option<(String, String, u64)> {
+    let program = instruction.get("program")?.as_str()?;
+    if program != "spl-token" && program != "spl-token-2022" {
+        return None;
+    }
+
+    let parsed = instruction.get("parsed")?;
+    let info = parsed.get("info")?;
+    let amount = match parsed.get("type")?.as_str()? {
+        "transfer" => info.get("amount")?.as_str()?,
+        "transferChecked" => info.get("tokenAmount")?.get("amount")?.as_str()?,
+        _ => return None,
+    };
+
+    Some((
+        info.get("source")?.as_str()?.to_string(),
+        info.get("destination")?.as_str()?.to_string(),
+        amount.parse().ok()?,
+    ))
+}
+
+/// Wait a `Retry-After` header asks for, given as delay-seconds or an HTTP
+/// date. A date already past means retrying right away.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Base-unit amount and decimals of one `getTokenAccountsByOwner` entry, or
+/// why it couldn't be read
+fn parse_token_account(account: serde_json::Value) -> Result<(u64, u8), String> {
+    let account: TokenAccountInfo =
+        serde_json::from_value(account).map_err(|e| format!("undecodable account: {}", e))?;
+    let pubkey = account.pubkey.as_deref().unwrap_or("<unknown>");
+
+    let token_amount = account
+        .account
+        .data
+        .parsed
+        .and_then(|parsed| parsed.info)
+        .and_then(|info| info.token_amount)
+        .ok_or_else(|| format!("{} has no parsed token amount", pubkey))?;
+
+    let amount = token_amount.amount.parse().map_err(|_| {
+        format!(
+            "{} has invalid token amount '{}'",
+            pubkey, token_amount.amount
+        )
+    })?;
+
+    Ok((amount, token_amount.decimals))
 }
 
 impl SolanaClient {
-    pub fn new(rpc_url: &str, usdc_mint: &str) -> Self {
+    pub fn new(
+        rpc_url: &str,
+        usdc_mint: &str,
+        detail_timeout: Duration,
+        detail_concurrency: usize,
+        transaction_cache_entries: usize,
+    ) -> Self {
         let client = Client::new();
 
         Self {
             client,
             rpc_url: rpc_url.to_string(),
             usdc_mint: usdc_mint.to_string(),
+            batch_supported: AtomicBool::new(true),
+            detail_timeout,
+            detail_concurrency: detail_concurrency.max(1),
+            transactions: TransactionCache::new(transaction_cache_entries),
         }
     }
 
-    pub fn validate_address(address: &str) -> Result<Pubkey, AppError> {
-        Pubkey::from_str(address)
-            .map_err(|_| AppError::InvalidAddress(format!("Invalid Solana address: {}", address)))
+    /// POST a JSON-RPC request. A 429 is retried after the wait its
+    /// Retry-After header asks for (capped), or an exponential backoff
+    /// without one; still rate limited after the last retry is an error.
+    async fn post_rpc<T: Serialize + ?Sized>(&self, body: &T) -> Result<Response, AppError> {
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .client
+                .post(&self.rpc_url)
+                .json(body)
+                .send()
+                .await
+                .map_err(|e| AppError::SolanaRpc(format!("Request failed: {}", e)))?;
+
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            if attempt == MAX_RATE_LIMIT_RETRIES {
+                return Err(AppError::SolanaRpc(format!(
+                    "Rate limited by the RPC endpoint after {} retries",
+                    MAX_RATE_LIMIT_RETRIES
+                )));
+            }
+
+            let delay = match retry_after(response.headers(), Utc::now()) {
+                Some(delay) => delay.min(MAX_RETRY_AFTER),
+                None => RATE_LIMIT_BACKOFF * 2u32.pow(attempt),
+            };
+            attempt += 1;
+            tracing::warn!(
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "RPC endpoint rate limited the request, retrying"
+            );
+            tokio::time::sleep(delay).await;
+        }
     }
 
-    pub async fn get_usdc_balance(&self, wallet_address: &str) -> Result<TokenBalance, AppError> {
-        // Validate address
-        Self::validate_address(wallet_address)?;
+    /// Upper bound on one transaction detail fetch
+    pub fn detail_timeout(&self) -> Duration {
+        self.detail_timeout
+    }
+
+    /// `fetch_transaction` bounded by the detail timeout; a slow response
+    /// becomes an error for that signature instead of a stall
+    async fn fetch_transaction_bounded(
+        &self,
+        signature: &str,
+    ) -> Result<Option<Arc<TransactionResult>>, AppError> {
+        tokio::time::timeout(self.detail_timeout, self.fetch_transaction(signature))
+            .await
+            .unwrap_or_else(|_| {
+                Err(AppError::SolanaRpc(format!(
+                    "getTransaction timed out after {}s",
+                    self.detail_timeout.as_secs_f64()
+                )))
+            })
+    }
+
+    /// Fetch phase of `get_transaction_details`: one transaction as the RPC
+    /// returned it (block time filled in), from the cache when seen before
+    async fn fetch_transaction(
+        &self,
+        signature: &str,
+    ) -> Result<Option<Arc<TransactionResult>>, AppError> {
+        Self::validate_signature(signature)?;
+
+        if let Some(cached) = self.transactions.get(signature) {
+            return Ok(Some(cached));
+        }
 
-        // Build JSON-RPC request for getTokenAccountsByOwner
         let body = json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "getTokenAccountsByOwner",
+            "method": "getTransaction",
             "params": [
-                wallet_address,
-                { "mint": self.usdc_mint },
-                { "encoding": "jsonParsed" }
+                signature,
+                {
+                    "encoding": "jsonParsed",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0
+                }
             ]
         });
 
-        // Make the request
-        let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::SolanaRpc(format!("Request failed: {}", e)))?;
+        let response = self.post_rpc(&body).await?;
 
-        let rpc_response: RpcResponse<TokenAccountsResult> = response
+        let rpc_response: RpcResponse<TransactionResult> = response
             .json()
             .await
             .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
 
-        // Check for RPC error
         if let Some(error) = rpc_response.error {
             return Err(AppError::SolanaRpc(error.message));
         }
 
-        // Extract balance from response
-        let result = rpc_response
-            .result
-            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+        self.cache_fetched(signature, rpc_response.result).await
+    }
 
-        let mut total_amount: u64 = 0;
-        let decimals: u8 = 6; // USDC has 6 decimals
+    /// Fill in a fetched transaction's block time and cache it
+    async fn cache_fetched(
+        &self,
+        signature: &str,
+        result: Option<TransactionResult>,
+    ) -> Result<Option<Arc<TransactionResult>>, AppError> {
+        let Some(result) = self.with_block_time(result).await? else {
+            return Ok(None);
+        };
 
-        for account in result.value {
-            let amount_str = &account.account.data.parsed.info.token_amount.amount;
-            total_amount += amount_str.parse::<u64>().unwrap_or(0);
+        let result = Arc::new(result);
+        self.transactions.insert(signature, result.clone());
+        Ok(Some(result))
+    }
+
+    /// Fetch several transactions, one result per signature in order, using
+    /// JSON-RPC batches for those not cached. Falls back to individual
+    /// requests if the endpoint rejects batches, and stops batching from then
+    /// on. Every call is bounded by the detail timeout; individual requests
+    /// run up to the detail concurrency at a time.
+    async fn fetch_transactions(
+        &self,
+        signatures: &[String],
+    ) -> Vec<Result<Option<Arc<TransactionResult>>, AppError>> {
+        let mut results: Vec<Option<Result<Option<Arc<TransactionResult>>, AppError>>> = signatures
+            .iter()
+            .map(|signature| {
+                self.transactions
+                    .get(signature)
+                    .map(|cached| Ok(Some(cached)))
+            })
+            .collect();
+        let missing: Vec<(usize, String)> = results
+            .iter()
+            .zip(signatures)
+            .enumerate()
+            .filter(|(_, (result, _))| result.is_none())
+            .map(|(index, (_, signature))| (index, signature.clone()))
+            .collect();
+
+        for chunk in missing.chunks(MAX_BATCH_SIZE) {
+            let chunk_signatures: Vec<String> = chunk
+                .iter()
+                .map(|(_, signature)| signature.clone())
+                .collect();
+
+            if self.batch_supported.load(Ordering::Relaxed) {
+                match tokio::time::timeout(
+                    self.detail_timeout,
+                    self.send_transaction_batch(&chunk_signatures),
+                )
+                .await
+                {
+                    Ok(Ok(Some(batch))) => {
+                        for ((index, signature), result) in chunk.iter().zip(batch) {
+                            let result = match result {
+                                Ok(result) => self.cache_fetched(signature, result).await,
+                                Err(e) => Err(e),
+                            };
+                            results[*index] = Some(result);
+                        }
+                        continue;
+                    }
+                    Ok(Ok(None)) => {
+                        tracing::warn!(
+                            "RPC endpoint rejected JSON-RPC batch, using individual requests"
+                        );
+                        self.batch_supported.store(false, Ordering::Relaxed);
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!(error = %e, "Batch getTransaction failed, retrying individually");
+                    }
+                    Err(_) => {
+                        tracing::warn!(
+                            count = chunk.len(),
+                            timeout_ms = self.detail_timeout.as_millis() as u64,
+                            "Batch getTransaction timed out, retrying individually"
+                        );
+                    }
+                }
+            }
+
+            let fetches: Vec<_> = chunk_signatures
+                .iter()
+                .map(|signature| self.fetch_transaction_bounded(signature))
+                .collect();
+            let fetched: Vec<_> = stream::iter(fetches)
+                .buffered(self.detail_concurrency)
+                .collect()
+                .await;
+            for ((index, _), result) in chunk.iter().zip(fetched) {
+                results[*index] = Some(result);
+            }
         }
 
-        // Convert to decimal with proper decimals
-        let amount = Decimal::new(total_amount as i64, decimals as u32);
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| Err(AppError::SolanaRpc("Transaction not fetched".into())))
+            })
+            .collect()
+    }
 
-        Ok(TokenBalance {
-            mint: self.usdc_mint.clone(),
-            amount,
-            decimals,
+    pub fn validate_address(address: &str) -> Result<Pubkey, AppError> {
+        Pubkey::from_str(address)
+            .map_err(|_| AppError::InvalidAddress(format!("Invalid Solana address: {}", address)))
+    }
+
+    /// Check a transaction signature is well-formed (base58, 64 bytes) before
+    /// it reaches the RPC
+    pub fn validate_signature(signature: &str) -> Result<Signature, AppError> {
+        Signature::from_str(signature).map_err(|_| {
+            AppError::BadRequest(format!("Invalid transaction signature: {}", signature))
         })
     }
 
-    pub async fn get_signatures(
-        &self,
-        wallet_address: &str,
-        limit: usize,
-        _before: Option<&str>,
-    ) -> Result<Vec<String>, AppError> {
-        // Validate address
-        Self::validate_address(wallet_address)?;
+    /// Strict validation for addresses registered as payment wallets.
+    /// On top of the base58 parse, rejects the default pubkey, well-known
+    /// program ids and off-curve addresses (PDAs), none of which can hold funds
+    /// as a user wallet.
+    pub fn validate_wallet_address(address: &str) -> Result<Pubkey, AppError> {
+        let pubkey = Self::validate_address(address)?;
 
-        // Build JSON-RPC request for getSignaturesForAddress
-        let body = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getSignaturesForAddress",
-            "params": [
-                wallet_address,
-                { "limit": limit }
-            ]
-        });
+        if pubkey == Pubkey::default() {
+            return Err(AppError::InvalidAddress(format!(
+                "Address {} is the default (all-zero) pubkey, not a wallet",
+                address
+            )));
+        }
 
-        // Make the request
-        let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::SolanaRpc(format!("Request failed: {}", e)))?;
+        if KNOWN_PROGRAM_IDS.contains(&pubkey.to_string().as_str()) {
+            return Err(AppError::InvalidAddress(format!(
+                "Address {} is a program id, not a wallet",
+                address
+            )));
+        }
 
-        #[derive(Debug, Deserialize)]
-        struct SignatureInfo {
-            signature: String,
+        if !pubkey.is_on_curve() {
+            return Err(AppError::InvalidAddress(format!(
+                "Address {} is off-curve (program-derived address), not a wallet",
+                address
+            )));
         }
 
-        let rpc_response: RpcResponse<Vec<SignatureInfo>> = response
-            .json()
-            .await
-            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+        Ok(pubkey)
+    }
 
-        if let Some(error) = rpc_response.error {
-            return Err(AppError::SolanaRpc(error.message));
+    /// Send one batched getTransaction request and map the responses back by id.
+    /// `Ok(None)` means the endpoint doesn't accept batches.
+    async fn send_transaction_batch(
+        &self,
+        signatures: &[String],
+    ) -> Result<Option<Vec<Result<Option<TransactionResult>, AppError>>>, AppError> {
+        let body: Vec<_> = signatures
+            .iter()
+            .enumerate()
+            .map(|(id, signature)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "getTransaction",
+                    "params": [
+                        signature,
+                        {
+                            "encoding": "jsonParsed",
+                            "commitment": "confirmed",
+                            "maxSupportedTransactionVersion": 0
+                        }
+                    ]
+                })
+            })
+            .collect();
+
+        let response = self.post_rpc(&body).await?;
+
+        // Server errors are transient, not a rejection of batching (post_rpc
+        // already retried rate limiting)
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(AppError::SolanaRpc(format!(
+                "Batch request failed: HTTP {}",
+                status
+            )));
+        }
+        if !status.is_success() {
+            return Ok(None);
         }
 
-        let result = rpc_response
-            .result
-            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+        // Endpoints without batch support answer with a single error object
+        let items: Vec<RpcBatchResponse<TransactionResult>> = match response.json().await {
+            Ok(items) => items,
+            Err(_) => return Ok(None),
+        };
+
+        let mut by_id: Vec<Option<Result<Option<TransactionResult>, AppError>>> =
+            signatures.iter().map(|_| None).collect();
 
-        Ok(result.into_iter().map(|s| s.signature).collect())
+        for item in items {
+            if let Some(slot) = by_id.get_mut(item.id) {
+                *slot = Some(match item.error {
+                    Some(error) => Err(AppError::SolanaRpc(error.message)),
+                    None => Ok(item.result),
+                });
+            }
+        }
+
+        Ok(Some(
+            by_id
+                .into_iter()
+                .map(|result| {
+                    result.unwrap_or_else(|| {
+                        Err(AppError::SolanaRpc("Missing response in batch".into()))
+                    })
+                })
+                .collect(),
+        ))
     }
 
-    /// Fetch and parse a single transaction to extract USDC transfer details
-    pub async fn get_transaction_details(
+    /// USDC transfers into or out of the wallet's token accounts, from both
+    /// top-level and inner (CPI) instructions. Moves between the wallet's own
+    /// accounts are left out, or with `internal` are the only ones returned.
+    fn transfer_legs(
         &self,
-        signature: &str,
         wallet_address: &str,
-    ) -> Result<Option<ParsedTransaction>, AppError> {
+        message: &TransactionMessage,
+        inner_instructions: &[InnerInstructions],
+        token_balances: &[&TokenBalanceMeta],
+        internal: bool,
+    ) -> Vec<TransferLeg> {
+        // Token account -> (owner, mint), from the balances the RPC reports
+        let mut accounts: HashMap<&str, (Option<&str>, Option<&str>)> = HashMap::new();
+        for balance in token_balances {
+            let pubkey = balance
+                .account_index
+                .and_then(|index| message.account_keys.get(index))
+                .and_then(|key| key.get("pubkey").or(Some(key)))
+                .and_then(|key| key.as_str());
+            if let Some(pubkey) = pubkey {
+                accounts.insert(pubkey, (balance.owner.as_deref(), balance.mint.as_deref()));
+            }
+        }
+
+        let is_ours = |account: &str| {
+            accounts.get(account).is_some_and(|(owner, mint)| {
+                *owner == Some(wallet_address) && *mint == Some(self.usdc_mint.as_str())
+            })
+        };
+        let owner_of = |account: &str| {
+            accounts
+                .get(account)
+                .and_then(|(owner, _)| owner.map(str::to_string))
+        };
+
+        message
+            .instructions
+            .iter()
+            .chain(
+                inner_instructions
+                    .iter()
+                    .flat_map(|inner| &inner.instructions),
+            )
+            .filter_map(decode_transfer)
+            .filter(|(source, destination, _)| match internal {
+                true => is_ours(source) && is_ours(destination),
+                false => is_ours(source) != is_ours(destination),
+            })
+            .map(|(source, destination, amount_raw)| TransferLeg {
+                source_owner: owner_of(&source),
+                destination_owner: owner_of(&destination),
+                source,
+                destination,
+                amount_raw,
+            })
+            .collect()
+    }
+
+    /// Fill in a missing block time from getBlockTime for the transaction's
+    /// slot. It stays missing if the node doesn't know the slot's time either.
+    async fn with_block_time(
+        &self,
+        result: Option<TransactionResult>,
+    ) -> Result<Option<TransactionResult>, AppError> {
+        let mut result = match result {
+            Some(result) if result.block_time.is_none() => result,
+            other => return Ok(other),
+        };
+        let Some(slot) = result.slot else {
+            return Ok(Some(result));
+        };
+
         let body = json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "getTransaction",
-            "params": [
-                signature,
-                {
-                    "encoding": "jsonParsed",
-                    "maxSupportedTransactionVersion": 0
-                }
-            ]
+            "method": "getBlockTime",
+            "params": [slot]
         });
 
-        let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::SolanaRpc(format!("Request failed: {}", e)))?;
+        let response = self.post_rpc(&body).await?;
 
-        let rpc_response: RpcResponse<TransactionResult> = response
+        let rpc_response: RpcResponse<i64> = response
             .json()
             .await
             .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
@@ -273,54 +821,63 @@ impl SolanaClient {
             return Err(AppError::SolanaRpc(error.message));
         }
 
-        let result = match rpc_response.result {
-            Some(r) => r,
-            None => return Ok(None), // Transaction not found
-        };
+        result.block_time = rpc_response.result;
+        Ok(Some(result))
+    }
 
-        let block_time = result
+    /// Extract our wallet's USDC movement from a getTransaction result. Only
+    /// reads it, so one fetched result serves every wallet in the transaction.
+    fn parse_transaction(
+        &self,
+        signature: &str,
+        wallet_address: &str,
+        result: &TransactionResult,
+    ) -> Option<ParsedTransaction> {
+        // Never stamp "now": that would misdate and misorder old transactions.
+        // Left unstored, it's picked up again on a later sync.
+        let Some(block_time) = result
             .block_time
-            .map(|ts| Utc.timestamp_opt(ts, 0).single())
-            .flatten()
-            .unwrap_or_else(Utc::now);
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+        else {
+            tracing::warn!(signature = %signature, "Transaction has no block time, skipping");
+            return None;
+        };
 
         // Get token balance metadata
-        let meta = match result.meta {
-            Some(m) => m,
-            None => return Ok(None),
-        };
+        let meta = result.meta.as_ref()?;
 
-        let pre_balances = meta.pre_token_balances.unwrap_or_default();
-        let post_balances = meta.post_token_balances.unwrap_or_default();
+        let pre_balances = meta.pre_token_balances.as_deref().unwrap_or_default();
+        let post_balances = meta.post_token_balances.as_deref().unwrap_or_default();
 
-        // Find USDC balances for our wallet in pre and post
+        // The wallet's USDC balances in pre and post, summed over all its
+        // token accounts so moves between them cancel out
         let mut our_pre_balance: Option<u64> = None;
         let mut our_post_balance: Option<u64> = None;
+        let mut decimals: u8 = 6; // USDC default, overridden by the RPC's token amount
         let mut counterparty: Option<String> = None;
 
-        // Check pre-balances for our wallet's USDC
-        for balance in &pre_balances {
-            if balance.owner.as_deref() == Some(wallet_address)
-                && balance.mint.as_deref() == Some(&self.usdc_mint)
-            {
-                if let Some(ref ui_amount) = balance.ui_token_amount {
-                    our_pre_balance = ui_amount.amount.parse().ok();
+        for (balances, total) in [
+            (pre_balances, &mut our_pre_balance),
+            (post_balances, &mut our_post_balance),
+        ] {
+            for balance in balances {
+                if balance.owner.as_deref() != Some(wallet_address)
+                    || balance.mint.as_deref() != Some(&self.usdc_mint)
+                {
+                    continue;
                 }
-            }
-        }
-
-        // Check post-balances for our wallet's USDC
-        for balance in &post_balances {
-            if balance.owner.as_deref() == Some(wallet_address)
-                && balance.mint.as_deref() == Some(&self.usdc_mint)
-            {
-                if let Some(ref ui_amount) = balance.ui_token_amount {
-                    our_post_balance = ui_amount.amount.parse().ok();
+                let Some(ref ui_amount) = balance.ui_token_amount else {
+                    continue;
+                };
+                if let Ok(amount) = ui_amount.amount.parse::<u64>() {
+                    *total = Some(total.unwrap_or(0).checked_add(amount)?);
+                    decimals = ui_amount.decimals;
                 }
             }
         }
 
-        // Find counterparty (other wallet involved in USDC transfer)
+        // Fallback counterparty: the first other wallet holding USDC in the
+        // transaction, which for routed payments can be an AMM vault
         for balance in post_balances.iter().chain(pre_balances.iter()) {
             if balance.mint.as_deref() == Some(&self.usdc_mint)
                 && balance.owner.as_deref() != Some(wallet_address)
@@ -332,58 +889,1052 @@ impl SolanaClient {
             }
         }
 
+        let no_message = TransactionMessage::default();
+        let message = result
+            .transaction
+            .as_ref()
+            .map_or(&no_message, |t| &t.message);
+        let balances: Vec<&TokenBalanceMeta> = pre_balances.iter().chain(post_balances).collect();
+        let legs = |internal| {
+            self.transfer_legs(
+                wallet_address,
+                message,
+                &meta.inner_instructions,
+                &balances,
+                internal,
+            )
+        };
+
         // Determine transaction type based on balance change
         let (tx_type, amount_raw) = match (our_pre_balance, our_post_balance) {
-            (Some(pre), Some(post)) if post > pre => ("receive", post - pre),
-            (Some(pre), Some(post)) if pre > post => ("send", pre - post),
-            (None, Some(post)) if post > 0 => ("receive", post), // New account with balance
-            (Some(pre), None) if pre > 0 => ("send", pre),       // Account closed
-            _ => return Ok(None), // No change or not related to this wallet
+            (Some(pre), Some(post)) if post > pre => (TransactionType::Receive, post - pre),
+            (Some(pre), Some(post)) if pre > post => (TransactionType::Send, pre - post),
+            (None, Some(post)) if post > 0 => (TransactionType::Receive, post), // New account with balance
+            (Some(pre), None) if pre > 0 => (TransactionType::Send, pre),       // Account closed
+            // No net change: a move between the wallet's own token accounts,
+            // else nothing for this wallet
+            _ => (TransactionType::SelfTransfer, 0),
+        };
+        let (amount_raw, transfer_legs) = match tx_type {
+            TransactionType::SelfTransfer => {
+                let internal = legs(true);
+                let moved = internal
+                    .iter()
+                    .try_fold(0u64, |sum, leg| sum.checked_add(leg.amount_raw))?;
+                if moved == 0 {
+                    return None;
+                }
+                (moved, internal)
+            }
+            _ => (amount_raw, legs(false)),
         };
 
-        if amount_raw == 0 {
-            return Ok(None);
-        }
+        let amount = from_base_units(amount_raw, decimals)
+            .filter(|_| is_plausible_amount(amount_raw, decimals));
+        let Some(amount) = amount else {
+            tracing::warn!(
+                signature = %signature,
+                amount_raw,
+                decimals,
+                metric = "parser.implausible_amount",
+                "Parsed amount is implausible, skipping"
+            );
+            return None;
+        };
 
-        let amount = Decimal::new(amount_raw as i64, 6); // USDC has 6 decimals
+        // Prefer the owner behind the largest direct transfer to (or from)
+        // us; a self-transfer's counterparty is the wallet itself
+        let counterparty = match tx_type {
+            TransactionType::SelfTransfer => Some(wallet_address.to_string()),
+            _ => largest_counterparty(&transfer_legs, wallet_address, tx_type).or(counterparty),
+        };
+        let memo = message
+            .instructions
+            .iter()
+            .chain(
+                meta.inner_instructions
+                    .iter()
+                    .flat_map(|inner| &inner.instructions),
+            )
+            .find_map(decode_memo);
 
-        Ok(Some(ParsedTransaction {
+        Some(ParsedTransaction {
             signature: signature.to_string(),
             wallet_address: wallet_address.to_string(),
             tx_type: tx_type.to_string(),
             amount,
+            amount_raw,
+            decimals,
             token_mint: self.usdc_mint.clone(),
             counterparty: counterparty.unwrap_or_else(|| "unknown".to_string()),
             block_time,
-        }))
+            transfer_legs,
+            memo,
+        })
     }
+}
 
-    /// Sync recent transactions for a wallet from the blockchain
-    pub async fn sync_wallet_transactions(
-        &self,
-        wallet_address: &str,
-        limit: usize,
-    ) -> Result<Vec<ParsedTransaction>, AppError> {
-        // Get recent signatures
-        let signatures = self.get_signatures(wallet_address, limit, None).await?;
+#[async_trait]
+impl ChainClient for SolanaClient {
+    fn usdc_mint(&self) -> &str {
+        &self.usdc_mint
+    }
 
-        let mut transactions = Vec::new();
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getTokenAccountsByOwner", wallet = %wallet_address))]
+    async fn get_usdc_balance(&self, wallet_address: &str) -> Result<TokenBalance, AppError> {
+        // Validate address
+        Self::validate_address(wallet_address)?;
 
-        // Fetch details for each signature
-        for signature in signatures {
-            match self
-                .get_transaction_details(&signature, wallet_address)
-                .await
-            {
-                Ok(Some(tx)) => transactions.push(tx),
-                Ok(None) => {} // Not a USDC transfer, skip
-                Err(e) => {
-                    // Log error but continue with other transactions
-                    tracing::warn!("Failed to fetch transaction {}: {}", signature, e);
-                }
-            }
-        }
+        // Build JSON-RPC request for getTokenAccountsByOwner
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTokenAccountsByOwner",
+            "params": [
+                wallet_address,
+                { "mint": self.usdc_mint },
+                { "encoding": "jsonParsed" }
+            ]
+        });
+
+        // Make the request
+        let response = self.post_rpc(&body).await?;
+
+        let rpc_response: RpcResponse<TokenAccountsResult> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        // Check for RPC error
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        // Extract balance from response
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        let mut total_amount: u64 = 0;
+        let mut decimals: u8 = 6; // USDC default, overridden by the RPC's token amount
+        let account_exists = !result.value.is_empty();
+
+        for account in result.value {
+            let (account_amount, account_decimals) = match parse_token_account(account) {
+                Ok(parsed) => parsed,
+                Err(reason) => {
+                    tracing::warn!(
+                        wallet = %wallet_address,
+                        reason = %reason,
+                        "Skipping token account in an unexpected shape"
+                    );
+                    continue;
+                }
+            };
+            total_amount = total_amount.checked_add(account_amount).ok_or_else(|| {
+                AppError::AmountOverflow(format!(
+                    "USDC balance of {} exceeds u64 base units across token accounts",
+                    wallet_address
+                ))
+            })?;
+            decimals = account_decimals;
+        }
+
+        let amount = from_base_units(total_amount, decimals).ok_or_else(|| {
+            AppError::AmountOverflow(format!(
+                "{} base units with {} decimals can't be represented",
+                total_amount, decimals
+            ))
+        })?;
+
+        Ok(TokenBalance {
+            mint: self.usdc_mint.clone(),
+            amount,
+            amount_raw: total_amount,
+            decimals,
+            account_exists,
+        })
+    }
+
+    /// Get the native SOL balance of an account in lamports
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getBalance", wallet = %wallet_address))]
+    async fn get_sol_balance(&self, wallet_address: &str) -> Result<u64, AppError> {
+        Self::validate_address(wallet_address)?;
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getBalance",
+            "params": [wallet_address]
+        });
+
+        let response = self.post_rpc(&body).await?;
+
+        #[derive(Debug, Deserialize)]
+        struct BalanceResult {
+            value: u64,
+        }
+
+        let rpc_response: RpcResponse<BalanceResult> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        Ok(result.value)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getAccountInfo", address = %address))]
+    async fn account_exists(&self, address: &str) -> Result<bool, AppError> {
+        Self::validate_address(address)?;
+
+        // An empty data slice: only existence matters
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [
+                address,
+                { "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } }
+            ]
+        });
+
+        let response = self.post_rpc(&body).await?;
+
+        #[derive(Debug, Deserialize)]
+        struct AccountInfoResult {
+            value: Option<serde_json::Value>,
+        }
+
+        let rpc_response: RpcResponse<AccountInfoResult> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        Ok(result.value.is_some())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getAccountInfo", address = %address))]
+    async fn get_account_data(&self, address: &str) -> Result<Option<Vec<u8>>, AppError> {
+        Self::validate_address(address)?;
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [address, { "encoding": "base64" }]
+        });
+
+        let response = self.post_rpc(&body).await?;
+
+        #[derive(Debug, Deserialize)]
+        struct AccountData {
+            /// `[data, encoding]`
+            data: (String, String),
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct AccountInfoResult {
+            value: Option<AccountData>,
+        }
+
+        let rpc_response: RpcResponse<AccountInfoResult> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        result
+            .value
+            .map(|account| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(&account.data.0)
+                    .map_err(|e| AppError::SolanaRpc(format!("Invalid account data: {}", e)))
+            })
+            .transpose()
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getLatestBlockhash"))]
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, AppError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getLatestBlockhash",
+            "params": [{ "commitment": "confirmed" }]
+        });
+
+        let response = self.post_rpc(&body).await?;
+
+        #[derive(Debug, Deserialize)]
+        struct BlockhashResult {
+            value: LatestBlockhash,
+        }
+
+        let rpc_response: RpcResponse<BlockhashResult> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        Ok(result.value)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getSignaturesForAddress", wallet = %wallet_address, limit = limit))]
+    async fn get_signatures(
+        &self,
+        wallet_address: &str,
+        limit: usize,
+        before: Option<&str>,
+        not_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>, AppError> {
+        // Validate address
+        Self::validate_address(wallet_address)?;
+
+        // Signatures are returned newest first; `before` pages further back.
+        // Confirmed rather than the default finalized, so new transactions are
+        // stored as pending until they finalize.
+        let mut options = json!({ "limit": limit, "commitment": "confirmed" });
+        if let Some(before) = before {
+            options["before"] = json!(before);
+        }
+
+        // Build JSON-RPC request for getSignaturesForAddress
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getSignaturesForAddress",
+            "params": [
+                wallet_address,
+                options
+            ]
+        });
+
+        // Make the request
+        let response = self.post_rpc(&body).await?;
+
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SignatureInfo {
+            signature: String,
+            block_time: Option<i64>,
+        }
+
+        let rpc_response: RpcResponse<Vec<SignatureInfo>> = response
+            .json()
+            .await
+            .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::SolanaRpc(error.message));
+        }
+
+        let result = rpc_response
+            .result
+            .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+        // Newest first, so everything after the first signature older than the
+        // cutoff is older too
+        let cutoff = not_before.map(|t| t.timestamp());
+        Ok(result
+            .into_iter()
+            .take_while(|s| match (cutoff, s.block_time) {
+                (Some(cutoff), Some(block_time)) => block_time >= cutoff,
+                _ => true,
+            })
+            .map(|s| s.signature)
+            .collect())
+    }
+
+    /// Look up the confirmation status of signatures (max 256 per RPC call).
+    /// Returns one entry per input signature, `None` when the cluster doesn't know it.
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getSignatureStatuses", count = signatures.len()))]
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[String],
+    ) -> Result<Vec<Option<SignatureStatus>>, AppError> {
+        let mut statuses = Vec::with_capacity(signatures.len());
+
+        for chunk in signatures.chunks(256) {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getSignatureStatuses",
+                "params": [
+                    chunk,
+                    { "searchTransactionHistory": true }
+                ]
+            });
+
+            let response = self.post_rpc(&body).await?;
+
+            let rpc_response: RpcResponse<SignatureStatusesResult> = response
+                .json()
+                .await
+                .map_err(|e| AppError::SolanaRpc(format!("Failed to parse response: {}", e)))?;
+
+            if let Some(error) = rpc_response.error {
+                return Err(AppError::SolanaRpc(error.message));
+            }
+
+            let result = rpc_response
+                .result
+                .ok_or_else(|| AppError::SolanaRpc("No result in response".to_string()))?;
+
+            statuses.extend(result.value);
+        }
+
+        Ok(statuses)
+    }
+
+    /// Fetch and parse a single transaction to extract USDC transfer details.
+    /// The fetch is shared through the transaction cache; attribution to the
+    /// wallet runs on every call.
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getTransaction", wallet = %wallet_address, signature = %signature))]
+    async fn get_transaction_details(
+        &self,
+        signature: &str,
+        wallet_address: &str,
+    ) -> Result<Option<ParsedTransaction>, AppError> {
+        let result = self.fetch_transaction(signature).await?;
+        Ok(result.and_then(|result| self.parse_transaction(signature, wallet_address, &result)))
+    }
+
+    /// Fetch and parse several transactions, returning one result per
+    /// signature, in order (see `fetch_transactions`)
+    #[tracing::instrument(level = "debug", skip_all, fields(rpc_method = "getTransaction", wallet = %wallet_address, count = signatures.len()))]
+    async fn get_transactions_batch(
+        &self,
+        signatures: &[String],
+        wallet_address: &str,
+    ) -> Vec<Result<Option<ParsedTransaction>, AppError>> {
+        let fetched = self.fetch_transactions(signatures).await;
+
+        signatures
+            .iter()
+            .zip(fetched)
+            .map(|(signature, result)| {
+                result.map(|result| {
+                    result.and_then(|r| self.parse_transaction(signature, wallet_address, &r))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{USDC_MINT, WALLET};
+
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    fn rejection(address: &str) -> String {
+        match SolanaClient::validate_wallet_address(address) {
+            Err(AppError::InvalidAddress(message)) => message,
+            other => panic!("expected {} to be rejected, got {:?}", address, other),
+        }
+    }
+
+    #[test]
+    fn accepts_a_normal_wallet() {
+        let pubkey = SolanaClient::validate_wallet_address(WALLET).unwrap();
+        assert_eq!(pubkey.to_string(), WALLET);
+    }
+
+    #[test]
+    fn rejects_a_program_derived_address() {
+        let program = Pubkey::from_str(TOKEN_PROGRAM).unwrap();
+        let wallet = Pubkey::from_str(WALLET).unwrap();
+        let (pda, _) = Pubkey::find_program_address(&[b"escrow", wallet.as_ref()], &program);
+
+        assert!(rejection(&pda.to_string()).contains("off-curve"));
+    }
+
+    #[test]
+    fn rejects_program_ids() {
+        assert!(rejection(TOKEN_PROGRAM).contains("program id"));
+        assert!(rejection("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").contains("program id"));
+    }
+
+    #[test]
+    fn rejects_the_all_ones_default_address() {
+        assert!(rejection("11111111111111111111111111111111").contains("default"));
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        assert!(rejection("not-a-wallet").contains("Invalid Solana address"));
+        assert!(rejection("").contains("Invalid Solana address"));
+        // Base58 but too short for a pubkey
+        assert!(rejection("9WzDXwBbmkg8ZTbNMq").contains("Invalid Solana address"));
+    }
+
+    // Parse fixtures: getTransaction results in the jsonParsed encoding,
+    // trimmed to the fields the parser reads
+
+    const PAYER: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const SECOND_PAYER: &str = "HN7cABqLq46Es1jh92dQQisAq662SmxELLLsHHe4YWrH";
+    const AMM_AUTHORITY: &str = "GpMZbSM2GgvTKHJirzeGfMFoaZ8UR2X7F4v8vHTvxFbL";
+    // Token accounts, indexed by their position in the account keys
+    const WALLET_ATA: &str = "3Kz9XQ6kDt6PA6G8G4jbZAZLLvryK3Euujj4wdtX5jBj";
+    const PAYER_ATA: &str = "5D3pVArLGsnTX9EJr4jMzuQJy9wMSnnjHRds6SnfEs9A";
+    const SECOND_PAYER_ATA: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    const AMM_VAULT: &str = "BQcdHdAQW1hczDbBi9hiegXAR7A98Q9jx3X3iBBBDiq4";
+
+    /// Shared, since building the HTTP client dominates a parse
+    fn client() -> &'static SolanaClient {
+        static CLIENT: std::sync::OnceLock<SolanaClient> = std::sync::OnceLock::new();
+        CLIENT.get_or_init(|| {
+            SolanaClient::new(
+                "http://127.0.0.1:9",
+                USDC_MINT,
+                Duration::from_secs(1),
+                1,
+                0,
+            )
+        })
+    }
+
+    /// A USDC balance of the token account at `index`
+    fn balance(index: usize, owner: &str, amount_raw: u64) -> serde_json::Value {
+        json!({
+            "accountIndex": index,
+            "mint": USDC_MINT,
+            "owner": owner,
+            "uiTokenAmount": { "amount": amount_raw.to_string(), "decimals": 6 }
+        })
+    }
+
+    fn transfer_checked(source: &str, destination: &str, amount_raw: u64) -> serde_json::Value {
+        json!({
+            "program": "spl-token",
+            "programId": TOKEN_PROGRAM,
+            "parsed": {
+                "type": "transferChecked",
+                "info": {
+                    "source": source,
+                    "destination": destination,
+                    "mint": USDC_MINT,
+                    "tokenAmount": { "amount": amount_raw.to_string(), "decimals": 6 }
+                }
+            }
+        })
+    }
+
+    fn parse(
+        token_accounts: &[&str],
+        pre: Vec<serde_json::Value>,
+        post: Vec<serde_json::Value>,
+        instructions: Vec<serde_json::Value>,
+        inner: Vec<serde_json::Value>,
+    ) -> Option<ParsedTransaction> {
+        let account_keys: Vec<_> = token_accounts
+            .iter()
+            .map(|key| json!({ "pubkey": key, "signer": false, "writable": true }))
+            .collect();
+        let result: TransactionResult = serde_json::from_value(json!({
+            "blockTime": 1_760_000_000,
+            "meta": {
+                "preTokenBalances": pre,
+                "postTokenBalances": post,
+                "innerInstructions": [{ "index": 0, "instructions": inner }]
+            },
+            "transaction": {
+                "message": { "accountKeys": account_keys, "instructions": instructions }
+            }
+        }))
+        .unwrap();
+
+        client().parse_transaction("sig", WALLET, &result)
+    }
+
+    #[test]
+    fn a_direct_transfer_is_attributed_to_its_sender() {
+        let parsed = parse(
+            &[WALLET_ATA, PAYER_ATA],
+            vec![balance(0, WALLET, 0), balance(1, PAYER, 10_000_000)],
+            vec![balance(0, WALLET, 2_500_000), balance(1, PAYER, 7_500_000)],
+            vec![json!({
+                "program": "spl-token",
+                "programId": TOKEN_PROGRAM,
+                "parsed": {
+                    "type": "transfer",
+                    "info": { "source": PAYER_ATA, "destination": WALLET_ATA, "amount": "2500000" }
+                }
+            })],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tx_type, "receive");
+        assert_eq!(parsed.amount_raw, 2_500_000);
+        assert_eq!(parsed.amount.to_string(), "2.500000");
+        assert_eq!(parsed.counterparty, PAYER);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].source, PAYER_ATA);
+        assert_eq!(
+            parsed.transfer_legs[0].destination_owner.as_deref(),
+            Some(WALLET)
+        );
+    }
+
+    #[test]
+    fn a_jupiter_routed_payment_is_attributed_to_the_payer_not_the_pool() {
+        // The payer swaps into USDC through a pool vault (an inner
+        // instruction), then sends it on. The vault is the first other USDC
+        // owner in the balances, where the old heuristic stopped.
+        let parsed = parse(
+            &[AMM_VAULT, PAYER_ATA, WALLET_ATA],
+            vec![
+                balance(0, AMM_AUTHORITY, 900_000_000),
+                balance(1, PAYER, 0),
+                balance(2, WALLET, 1_000_000),
+            ],
+            vec![
+                balance(0, AMM_AUTHORITY, 895_000_000),
+                balance(1, PAYER, 0),
+                balance(2, WALLET, 6_000_000),
+            ],
+            vec![
+                // Jupiter's route instruction has no jsonParsed decoder
+                json!({ "programId": "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "data": "3B" }),
+                transfer_checked(PAYER_ATA, WALLET_ATA, 5_000_000),
+            ],
+            vec![transfer_checked(AMM_VAULT, PAYER_ATA, 5_000_000)],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tx_type, "receive");
+        assert_eq!(parsed.amount_raw, 5_000_000);
+        assert_eq!(parsed.counterparty, PAYER);
+        // The swap leg never touched the wallet
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].source_owner.as_deref(), Some(PAYER));
+    }
+
+    #[test]
+    fn the_largest_of_several_payers_is_the_counterparty() {
+        let parsed = parse(
+            &[WALLET_ATA, PAYER_ATA, SECOND_PAYER_ATA],
+            vec![
+                balance(0, WALLET, 0),
+                balance(1, PAYER, 1_000_000),
+                balance(2, SECOND_PAYER, 9_000_000),
+            ],
+            vec![
+                balance(0, WALLET, 4_000_000),
+                balance(1, PAYER, 0),
+                balance(2, SECOND_PAYER, 6_000_000),
+            ],
+            vec![
+                transfer_checked(PAYER_ATA, WALLET_ATA, 1_000_000),
+                transfer_checked(SECOND_PAYER_ATA, WALLET_ATA, 3_000_000),
+            ],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.amount_raw, 4_000_000);
+        assert_eq!(parsed.counterparty, SECOND_PAYER);
+        assert_eq!(parsed.transfer_legs.len(), 2);
+    }
+
+    #[test]
+    fn without_transfer_instructions_the_balances_name_the_counterparty() {
+        let parsed = parse(
+            &[WALLET_ATA, PAYER_ATA],
+            vec![balance(0, WALLET, 0), balance(1, PAYER, 10_000_000)],
+            vec![balance(0, WALLET, 2_500_000), balance(1, PAYER, 7_500_000)],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.counterparty, PAYER);
+        assert!(parsed.transfer_legs.is_empty());
+    }
+
+    /// The wallet's second USDC account, e.g. one it consolidates from
+    const WALLET_OTHER_ATA: &str = "6QWeT6FpJrm8AF1btu6WH2k2Xhq6t5vbheKVfQavmeoZ";
+
+    #[test]
+    fn a_move_between_the_wallets_own_accounts_is_a_self_transfer() {
+        // An unrelated USDC holder in the same transaction is not a counterparty
+        let parsed = parse(
+            &[WALLET_ATA, WALLET_OTHER_ATA, PAYER_ATA],
+            vec![
+                balance(0, WALLET, 10_000_000),
+                balance(1, WALLET, 40_000_000),
+                balance(2, PAYER, 7_000_000),
+            ],
+            vec![
+                balance(0, WALLET, 50_000_000),
+                balance(1, WALLET, 0),
+                balance(2, PAYER, 7_000_000),
+            ],
+            vec![transfer_checked(WALLET_OTHER_ATA, WALLET_ATA, 40_000_000)],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tx_type, "self_transfer");
+        assert_eq!(parsed.amount_raw, 40_000_000);
+        assert_eq!(parsed.counterparty, WALLET);
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].source, WALLET_OTHER_ATA);
+        assert_eq!(
+            parsed.transfer_legs[0].source_owner.as_deref(),
+            Some(WALLET)
+        );
+        assert_eq!(
+            parsed.transfer_legs[0].destination_owner.as_deref(),
+            Some(WALLET)
+        );
+    }
+
+    #[test]
+    fn a_receive_alongside_a_consolidation_is_the_net_receive() {
+        let parsed = parse(
+            &[WALLET_ATA, WALLET_OTHER_ATA, PAYER_ATA],
+            vec![
+                balance(0, WALLET, 0),
+                balance(1, WALLET, 40_000_000),
+                balance(2, PAYER, 7_000_000),
+            ],
+            vec![
+                balance(0, WALLET, 45_000_000),
+                balance(1, WALLET, 0),
+                balance(2, PAYER, 2_000_000),
+            ],
+            vec![
+                transfer_checked(WALLET_OTHER_ATA, WALLET_ATA, 40_000_000),
+                transfer_checked(PAYER_ATA, WALLET_ATA, 5_000_000),
+            ],
+            vec![],
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tx_type, "receive");
+        assert_eq!(parsed.amount_raw, 5_000_000);
+        assert_eq!(parsed.counterparty, PAYER);
+        // The internal leg isn't part of the payment
+        assert_eq!(parsed.transfer_legs.len(), 1);
+        assert_eq!(parsed.transfer_legs[0].source, PAYER_ATA);
+    }
+
+    #[test]
+    fn no_usdc_movement_for_the_wallet_is_nothing() {
+        let parsed = parse(
+            &[WALLET_ATA, PAYER_ATA, SECOND_PAYER_ATA],
+            vec![
+                balance(0, WALLET, 3_000_000),
+                balance(1, PAYER, 7_000_000),
+                balance(2, SECOND_PAYER, 0),
+            ],
+            vec![
+                balance(0, WALLET, 3_000_000),
+                balance(1, PAYER, 0),
+                balance(2, SECOND_PAYER, 7_000_000),
+            ],
+            vec![transfer_checked(PAYER_ATA, SECOND_PAYER_ATA, 7_000_000)],
+            vec![],
+        );
+
+        assert!(parsed.is_none());
+    }
+
+    /// A getTransaction result of a 1 USDC receive from `PAYER`
+    fn receive_result() -> serde_json::Value {
+        json!({
+            "blockTime": 1_760_000_000,
+            "meta": {
+                "preTokenBalances": [balance(0, WALLET, 0), balance(1, PAYER, 1_000_000)],
+                "postTokenBalances": [balance(0, WALLET, 1_000_000), balance(1, PAYER, 0)]
+            },
+            "transaction": { "message": { "accountKeys": [WALLET_ATA, PAYER_ATA] } }
+        })
+    }
+
+    /// A mock RPC that hangs on batches and on `slow`, answers anything
+    /// else at once, and tracks how many requests it serves at a time
+    async fn slow_rpc(slow: String) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::routing::post;
+        use std::sync::atomic::AtomicUsize;
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (current, max) = (in_flight.clone(), peak.clone());
+        let router = axum::Router::new().route(
+            "/",
+            post(
+                move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    if body.is_array() || body["params"][0] == slow.as_str() {
+                        tokio::time::sleep(Duration::from_secs(10)).await;
+                    }
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    axum::Json(json!({ "jsonrpc": "2.0", "id": 1, "result": receive_result() }))
+                },
+            ),
+        );
+
+        (crate::services::testing::serve(router).await, peak)
+    }
+
+    #[tokio::test]
+    async fn slow_detail_calls_are_skipped_at_the_timeout() {
+        use crate::services::testing::signature;
+
+        let signatures: Vec<String> = (1..=4).map(signature).collect();
+        let (rpc_url, peak) = slow_rpc(signatures[1].clone()).await;
+        let client = SolanaClient::new(&rpc_url, USDC_MINT, Duration::from_millis(300), 2, 0);
+
+        let started = std::time::Instant::now();
+        let results = client.get_transactions_batch(&signatures, WALLET).await;
+
+        // One timeout for the batch, then one for the slow signature
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "took {:?}",
+            started.elapsed()
+        );
+        assert!(matches!(&results[1], Err(AppError::SolanaRpc(msg)) if msg.contains("timed out")));
+        for index in [0, 2, 3] {
+            let parsed = results[index].as_ref().unwrap().as_ref().unwrap();
+            assert_eq!(parsed.amount_raw, 1_000_000);
+            assert_eq!(parsed.counterparty, PAYER);
+        }
+        // Individual requests stay within the concurrency limit, besides the
+        // abandoned batch the server may still be holding
+        assert!(peak.load(Ordering::SeqCst) <= 2 + 1);
+    }
+
+    /// A mock RPC answering every getTransaction, batched or not, with
+    /// `receive_result`, and counting the signatures it is asked for
+    async fn counting_rpc() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::routing::post;
+        use std::sync::atomic::AtomicUsize;
+
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let counter = lookups.clone();
+        let router = axum::Router::new().route(
+            "/",
+            post(
+                move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    let answer = |request: &serde_json::Value| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        json!({ "jsonrpc": "2.0", "id": request["id"], "result": receive_result() })
+                    };
+                    axum::Json(match body.as_array() {
+                        Some(requests) => requests.iter().map(answer).collect(),
+                        None => answer(&body),
+                    })
+                },
+            ),
+        );
+
+        (crate::services::testing::serve(router).await, lookups)
+    }
+
+    #[tokio::test]
+    async fn two_wallets_in_one_transaction_share_one_fetch() {
+        use crate::services::testing::signature;
+
+        let (rpc_url, lookups) = counting_rpc().await;
+        let client = SolanaClient::new(&rpc_url, USDC_MINT, Duration::from_secs(5), 2, 16);
+        let sig = signature(1);
+
+        let received = client
+            .get_transaction_details(&sig, WALLET)
+            .await
+            .unwrap()
+            .unwrap();
+        let sent = client
+            .get_transaction_details(&sig, PAYER)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            (received.tx_type.as_str(), received.counterparty.as_str()),
+            ("receive", PAYER)
+        );
+        assert_eq!(
+            (sent.tx_type.as_str(), sent.counterparty.as_str()),
+            ("send", WALLET)
+        );
+        assert_eq!(
+            (received.amount_raw, sent.amount_raw),
+            (1_000_000, 1_000_000)
+        );
+
+        // Batches skip what is cached and fetch the rest once
+        let signatures = vec![sig.clone(), signature(2)];
+        for wallet in [WALLET, PAYER] {
+            let results = client.get_transactions_batch(&signatures, wallet).await;
+            assert!(results.iter().all(|result| matches!(result, Ok(Some(_)))));
+        }
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn the_transaction_cache_evicts_the_least_recently_used() {
+        use crate::services::testing::signature;
+
+        let (rpc_url, lookups) = counting_rpc().await;
+        let client = SolanaClient::new(&rpc_url, USDC_MINT, Duration::from_secs(5), 1, 2);
+        let fetch = |seed| {
+            let client = &client;
+            async move {
+                client
+                    .get_transaction_details(&signature(seed), WALLET)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        fetch(1).await;
+        fetch(2).await;
+        fetch(1).await;
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+
+        // 2 is now the least recently used, so it makes room for 3
+        fetch(3).await;
+        fetch(1).await;
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+        fetch(2).await;
+        assert_eq!(lookups.load(Ordering::SeqCst), 4);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn parsed_amounts_are_the_plausible_balance_change(pre: u64, post: u64) {
+            let parsed = parse(
+                &[WALLET_ATA],
+                vec![balance(0, WALLET, pre)],
+                vec![balance(0, WALLET, post)],
+                vec![],
+                vec![],
+            );
+            let change = pre.abs_diff(post);
+
+            match parsed {
+                Some(parsed) => {
+                    proptest::prop_assert_eq!(parsed.amount_raw, change);
+                    proptest::prop_assert!(parsed.amount > Decimal::ZERO);
+                    let expected = if post > pre { "receive" } else { "send" };
+                    proptest::prop_assert_eq!(parsed.tx_type, expected);
+                }
+                None => proptest::prop_assert!(!crate::domain::is_plausible_amount(change, 6)),
+            }
+        }
+    }
+
+    /// The USDC balance an RPC reports with these token accounts
+    async fn balance_of(accounts: &[(&str, u8)]) -> Result<TokenBalance, AppError> {
+        use axum::routing::post;
+
+        let value: Vec<_> = accounts
+            .iter()
+            .map(|(amount, decimals)| {
+                json!({
+                    "pubkey": WALLET_ATA,
+                    "account": { "data": { "parsed": { "info": {
+                        "tokenAmount": { "amount": amount, "decimals": decimals }
+                    } } } }
+                })
+            })
+            .collect();
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": value } });
+        let router =
+            axum::Router::new().route("/", post(move || async move { axum::Json(response) }));
+        let rpc_url = crate::services::testing::serve(router).await;
+
+        SolanaClient::new(&rpc_url, USDC_MINT, Duration::from_secs(1), 1, 0)
+            .get_usdc_balance(WALLET)
+            .await
+    }
+
+    #[tokio::test]
+    async fn a_balance_near_u64_max_converts_exactly() {
+        let balance = balance_of(&[("18446744073709551615", 9)]).await.unwrap();
+
+        assert_eq!(balance.amount_raw, u64::MAX);
+        assert_eq!(balance.decimals, 9);
+        assert_eq!(balance.amount.to_string(), "18446744073.709551615");
+    }
+
+    #[tokio::test]
+    async fn balances_summing_past_u64_max_overflow_instead_of_wrapping() {
+        let result = balance_of(&[("18446744073709551615", 9), ("1", 9)]).await;
+        assert!(matches!(result, Err(AppError::AmountOverflow(_))));
+
+        let result = balance_of(&[("1", 30)]).await;
+        assert!(matches!(result, Err(AppError::AmountOverflow(_))));
+    }
+
+    #[test]
+    fn a_nine_decimal_receive_keeps_every_place() {
+        let result: TransactionResult = serde_json::from_value(json!({
+            "blockTime": 1_760_000_000,
+            "meta": {
+                "preTokenBalances": [],
+                "postTokenBalances": [{
+                    "accountIndex": 0,
+                    "mint": USDC_MINT,
+                    "owner": WALLET,
+                    "uiTokenAmount": { "amount": "1500000001", "decimals": 9 }
+                }]
+            }
+        }))
+        .unwrap();
+
+        let parsed = client().parse_transaction("sig", WALLET, &result).unwrap();
+        assert_eq!(parsed.decimals, 9);
+        assert_eq!(parsed.amount.to_string(), "1.500000001");
+    }
 
-        Ok(transactions)
+    #[test]
+    fn a_change_past_the_stored_range_is_skipped() {
+        let parsed = parse(
+            &[WALLET_ATA],
+            vec![balance(0, WALLET, 0)],
+            vec![balance(0, WALLET, i64::MAX as u64 + 1)],
+            vec![],
+            vec![],
+        );
+        assert!(parsed.is_none());
     }
 }
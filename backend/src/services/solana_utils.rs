@@ -0,0 +1,81 @@
+use base64::Engine;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+use crate::error::AppError;
+
+/// System program, which creates accounts
+pub const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+/// SPL Token program
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Associated Token Account program
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Metaplex Token Metadata program
+pub const METADATA_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// `CreateIdempotent` in the ATA program: succeeds if the account exists
+const CREATE_IDEMPOTENT: u8 = 1;
+
+/// The associated token account holding `mint` for `wallet`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The Metaplex metadata account of `mint`
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Create `wallet`'s associated token account for `mint`, with `payer`
+/// funding the rent. A no-op on chain if the account already exists.
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(wallet, mint), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT],
+    }
+}
+
+/// Serialize an unsigned transaction paid by `payer`, base64 encoded for a
+/// wallet adapter to sign and send
+pub fn encode_unsigned_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    blockhash: &Hash,
+) -> Result<String, AppError> {
+    let message = Message::new_with_blockhash(instructions, Some(payer), blockhash);
+    let transaction = Transaction::new_unsigned(message);
+
+    let bytes = bincode::serialize(&transaction)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize transaction: {}", e)))?;
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
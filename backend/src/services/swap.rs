@@ -0,0 +1,396 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::AppError;
+
+/// Slippage tolerance requested from Jupiter (0.5%)
+const SLIPPAGE_BPS: u16 = 50;
+
+// Jupiter quote response (only the fields we read; the whole object is sent
+// back unchanged when building the swap)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterQuote {
+    input_mint: String,
+    in_amount: String,
+    output_mint: String,
+    out_amount: String,
+    other_amount_threshold: String,
+    price_impact_pct: String,
+    #[serde(default)]
+    route_plan: Vec<RoutePlanStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutePlanStep {
+    swap_info: SwapInfo,
+    percent: u8,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInfo {
+    label: Option<String>,
+    input_mint: String,
+    output_mint: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapResponse {
+    swap_transaction: String,
+    last_valid_block_height: u64,
+}
+
+/// One hop of a quoted route
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteStep {
+    pub label: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub percent: u8,
+}
+
+/// A Jupiter quote for swapping into USDC
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapQuote {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Base units of the input token
+    pub in_amount: String,
+    /// Expected USDC out, in base units
+    pub out_amount: String,
+    /// Least USDC out after slippage, in base units
+    pub min_out_amount: String,
+    pub slippage_bps: u16,
+    /// Price impact in percent (e.g. "0.12" for 0.12%)
+    pub price_impact_pct: String,
+    pub route: Vec<RouteStep>,
+    /// Fetch a new quote after this time
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An unsigned swap transaction for the payer to sign and send
+#[derive(Debug, Serialize)]
+pub struct SwapTransaction {
+    /// Base64-encoded serialized versioned transaction
+    pub transaction: String,
+    pub last_valid_block_height: u64,
+    pub quote: SwapQuote,
+}
+
+/// Jupiter aggregator client so payers holding another token (e.g. USDT or
+/// SOL) can settle in USDC
+pub struct SwapService {
+    client: Client,
+    api_url: String,
+    usdc_mint: String,
+    allowed_input_mints: Vec<String>,
+    max_price_impact_pct: Decimal,
+    quote_ttl: Duration,
+}
+
+impl SwapService {
+    pub fn new(
+        api_url: &str,
+        usdc_mint: &str,
+        allowed_input_mints: Vec<String>,
+        max_price_impact_pct: Decimal,
+        quote_ttl: Duration,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            usdc_mint: usdc_mint.to_string(),
+            allowed_input_mints,
+            max_price_impact_pct,
+            quote_ttl,
+        }
+    }
+
+    pub fn usdc_mint(&self) -> &str {
+        &self.usdc_mint
+    }
+
+    /// Quote swapping `amount` base units of `input_mint` into USDC
+    pub async fn quote(&self, input_mint: &str, amount: u64) -> Result<SwapQuote, AppError> {
+        let (_, quote) = self.fetch_quote(input_mint, amount).await?;
+        Ok(quote)
+    }
+
+    /// Quote and build the swap transaction for `user_public_key` to sign.
+    /// Always re-quotes, so the limits are enforced on the route being built.
+    pub async fn build(
+        &self,
+        user_public_key: &str,
+        input_mint: &str,
+        amount: u64,
+    ) -> Result<SwapTransaction, AppError> {
+        let (raw_quote, quote) = self.fetch_quote(input_mint, amount).await?;
+
+        let response = self
+            .client
+            .post(format!("{}/swap", self.api_url))
+            .json(&json!({
+                "quoteResponse": raw_quote,
+                "userPublicKey": user_public_key,
+                "wrapAndUnwrapSol": true,
+                "dynamicComputeUnitLimit": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalApi(format!("Jupiter swap request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalApi(format!(
+                "Jupiter swap returned {}",
+                response.status()
+            )));
+        }
+
+        let swap: JupiterSwapResponse = response.json().await.map_err(|e| {
+            AppError::ExternalApi(format!("Failed to parse Jupiter swap response: {}", e))
+        })?;
+
+        Ok(SwapTransaction {
+            transaction: swap.swap_transaction,
+            last_valid_block_height: swap.last_valid_block_height,
+            quote,
+        })
+    }
+
+    /// Fetch a quote, returning Jupiter's raw object alongside our summary
+    async fn fetch_quote(
+        &self,
+        input_mint: &str,
+        amount: u64,
+    ) -> Result<(serde_json::Value, SwapQuote), AppError> {
+        if !self.allowed_input_mints.iter().any(|m| m == input_mint) {
+            return Err(AppError::BadRequest(format!(
+                "Input mint {} is not accepted for swaps",
+                input_mint
+            )));
+        }
+        if amount == 0 {
+            return Err(AppError::BadRequest("amount must be positive".into()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/quote", self.api_url))
+            .query(&[
+                ("inputMint", input_mint),
+                ("outputMint", self.usdc_mint.as_str()),
+                ("amount", &amount.to_string()),
+                ("slippageBps", &SLIPPAGE_BPS.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalApi(format!("Jupiter quote request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalApi(format!(
+                "Jupiter quote returned {}",
+                response.status()
+            )));
+        }
+
+        let raw: serde_json::Value = response.json().await.map_err(|e| {
+            AppError::ExternalApi(format!("Failed to parse Jupiter quote: {}", e))
+        })?;
+        let jupiter: JupiterQuote = serde_json::from_value(raw.clone()).map_err(|e| {
+            AppError::ExternalApi(format!("Unexpected Jupiter quote shape: {}", e))
+        })?;
+
+        // Jupiter reports impact as a fraction ("0.0012" is 0.12%)
+        let price_impact_pct = jupiter
+            .price_impact_pct
+            .parse::<Decimal>()
+            .map(|fraction| (fraction * Decimal::ONE_HUNDRED).normalize())
+            .map_err(|_| AppError::ExternalApi("Invalid price impact in Jupiter quote".into()))?;
+
+        if price_impact_pct > self.max_price_impact_pct {
+            return Err(AppError::UnprocessableEntity(format!(
+                "Price impact {}% exceeds the {}% limit",
+                price_impact_pct, self.max_price_impact_pct
+            )));
+        }
+
+        let quote = SwapQuote {
+            input_mint: jupiter.input_mint,
+            output_mint: jupiter.output_mint,
+            in_amount: jupiter.in_amount,
+            out_amount: jupiter.out_amount,
+            min_out_amount: jupiter.other_amount_threshold,
+            slippage_bps: SLIPPAGE_BPS,
+            price_impact_pct: price_impact_pct.to_string(),
+            route: jupiter
+                .route_plan
+                .into_iter()
+                .map(|step| RouteStep {
+                    label: step.swap_info.label.unwrap_or_else(|| "unknown".to_string()),
+                    input_mint: step.swap_info.input_mint,
+                    output_mint: step.swap_info.output_mint,
+                    percent: step.percent,
+                })
+                .collect(),
+            expires_at: Utc::now() + self.quote_ttl,
+        };
+
+        Ok((raw, quote))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::{Query, State};
+    use axum::http::StatusCode;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::Value;
+
+    use crate::services::testing::{serve, USDC_MINT};
+
+    const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+    const PAYER: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    /// What the mock Jupiter saw: quote query strings and swap bodies
+    #[derive(Clone, Default)]
+    struct Seen {
+        quotes: Arc<Mutex<Vec<HashMap<String, String>>>>,
+        swaps: Arc<Mutex<Vec<Value>>>,
+    }
+
+    fn jupiter_quote(price_impact: &str) -> Value {
+        json!({
+            "inputMint": USDT_MINT,
+            "inAmount": "2000000",
+            "outputMint": USDC_MINT,
+            "outAmount": "1998000",
+            "otherAmountThreshold": "1988010",
+            "priceImpactPct": price_impact,
+            "contextSlot": 301234567,
+            "routePlan": [
+                {
+                    "swapInfo": {
+                        "label": "Whirlpool",
+                        "inputMint": USDT_MINT,
+                        "outputMint": USDC_MINT
+                    },
+                    "percent": 70
+                },
+                {
+                    "swapInfo": { "inputMint": USDT_MINT, "outputMint": USDC_MINT },
+                    "percent": 30
+                }
+            ]
+        })
+    }
+
+    /// A SwapService against a mock Jupiter quoting `price_impact`
+    async fn service(price_impact: &'static str, status: StatusCode) -> (SwapService, Seen) {
+        let seen = Seen::default();
+        let router = Router::new()
+            .route(
+                "/quote",
+                get(
+                    move |State(seen): State<Seen>, Query(query): Query<HashMap<String, String>>| {
+                        async move {
+                            seen.quotes.lock().unwrap().push(query);
+                            (status, Json(jupiter_quote(price_impact)))
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/swap",
+                post(|State(seen): State<Seen>, Json(body): Json<Value>| async move {
+                    seen.swaps.lock().unwrap().push(body);
+                    Json(json!({ "swapTransaction": "AQID", "lastValidBlockHeight": 280000123 }))
+                }),
+            )
+            .with_state(seen.clone());
+        let api_url = serve(router).await;
+
+        let swap = SwapService::new(
+            &format!("{}/", api_url),
+            USDC_MINT,
+            vec![USDT_MINT.to_string()],
+            Decimal::ONE,
+            Duration::seconds(30),
+        );
+        (swap, seen)
+    }
+
+    #[tokio::test]
+    async fn quotes_summarize_the_jupiter_route() {
+        let (swap, seen) = service("0.0012", StatusCode::OK).await;
+
+        let quote = swap.quote(USDT_MINT, 2_000_000).await.unwrap();
+
+        assert_eq!(quote.out_amount, "1998000");
+        assert_eq!(quote.min_out_amount, "1988010");
+        assert_eq!(quote.price_impact_pct, "0.12");
+        assert_eq!(quote.slippage_bps, SLIPPAGE_BPS);
+        let labels: Vec<_> = quote.route.iter().map(|s| (s.label.as_str(), s.percent)).collect();
+        assert_eq!(labels, [("Whirlpool", 70), ("unknown", 30)]);
+        assert!(quote.expires_at > Utc::now());
+
+        let query = &seen.quotes.lock().unwrap()[0];
+        assert_eq!(query["inputMint"], USDT_MINT);
+        assert_eq!(query["outputMint"], USDC_MINT);
+        assert_eq!(query["amount"], "2000000");
+        assert_eq!(query["slippageBps"], "50");
+    }
+
+    #[tokio::test]
+    async fn building_sends_jupiter_its_own_quote_back() {
+        let (swap, seen) = service("0.0012", StatusCode::OK).await;
+
+        let built = swap.build(PAYER, USDT_MINT, 2_000_000).await.unwrap();
+
+        assert_eq!(built.transaction, "AQID");
+        assert_eq!(built.last_valid_block_height, 280_000_123);
+        let body = &seen.swaps.lock().unwrap()[0];
+        assert_eq!(body["quoteResponse"], jupiter_quote("0.0012"));
+        assert_eq!(body["userPublicKey"], PAYER);
+    }
+
+    #[tokio::test]
+    async fn excessive_price_impact_is_refused_before_building() {
+        let (swap, seen) = service("0.0101", StatusCode::OK).await;
+
+        let err = swap.build(PAYER, USDT_MINT, 2_000_000).await.unwrap_err();
+
+        assert!(matches!(err, AppError::UnprocessableEntity(msg) if msg.contains("1.01%")));
+        assert!(seen.swaps.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unaccepted_requests_never_reach_jupiter() {
+        let (swap, seen) = service("0.0012", StatusCode::OK).await;
+
+        assert!(matches!(
+            swap.quote("So11111111111111111111111111111111111111112", 1).await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(swap.quote(USDT_MINT, 0).await, Err(AppError::BadRequest(_))));
+        assert!(seen.quotes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn jupiter_errors_are_external_api_errors() {
+        let (swap, _) = service("0.0012", StatusCode::SERVICE_UNAVAILABLE).await;
+
+        let err = swap.quote(USDT_MINT, 2_000_000).await.unwrap_err();
+        assert!(matches!(err, AppError::ExternalApi(msg) if msg.contains("503")));
+    }
+}
@@ -1,26 +1,61 @@
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::task::JoinHandle;
-use tracing::{error, info, warn};
+use tracing::{error, info, instrument, warn};
 
-use crate::domain::{TransactionStatus, TransactionType, Wallet};
-use crate::repository::{TransactionRepository, WalletRepository};
-use crate::services::solana::SolanaClient;
+use crate::domain::{
+    crossed_thresholds, AmountMatchRule, BalanceThresholdPayload, PaymentLinkPaidPayload,
+    PaymentReceivedPayload, Transaction, TransactionStatus, TransactionType, Wallet,
+    WebhookPayload, IDEMPOTENCY_KEY_TTL_HOURS,
+};
+use crate::repository::{
+    BalanceSnapshotRepository, IdempotencyKeyRepository, PaymentLinkRepository,
+    SyncCursorRepository, SyncRunRepository, TransactionRepository, WalletRepository,
+};
+use crate::services::chain::ChainClient;
+use crate::services::events::EventBus;
+use crate::services::leader::LeaderLock;
+use crate::services::solana::{ParsedTransaction, SignatureStatus, SolanaClient};
+use crate::services::token_registry::TokenRegistry;
 use crate::services::webhook::WebhookService;
 
 /// Interval between sync cycles
 const SYNC_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Number of recent transactions to fetch per wallet
-const SYNC_LIMIT: usize = 20;
+pub const SYNC_LIMIT: usize = 20;
+
+/// Signatures requested per page during backfill
+const BACKFILL_PAGE_SIZE: usize = 100;
+
+/// Errors kept per recorded sync run, and the length each is cut to
+const MAX_RECORDED_ERRORS: usize = 20;
+const MAX_RECORDED_ERROR_LEN: usize = 500;
 
 pub struct SyncService {
     pool: PgPool,
-    solana_client: Arc<SolanaClient>,
+    solana_client: Arc<dyn ChainClient>,
     webhook_service: Arc<WebhookService>,
+    events: Arc<EventBus>,
+    leader: Arc<LeaderLock>,
+    tokens: Arc<TokenRegistry>,
+    snapshot_interval: Duration,
+    snapshot_retention_days: i64,
+    sync_run_retention_days: i64,
+    /// RPC time one wallet sync may spend, across all its calls
+    wallet_sync_timeout: Duration,
+    /// Age after which a pending transaction the cluster doesn't know is expired
+    pending_expiry: Duration,
+    /// When this replica last recorded balance snapshots
+    last_snapshot_at: Mutex<Option<Instant>>,
+    /// Set once this replica finished a sync cycle, or found it isn't the
+    /// leader and has none to run
+    first_cycle_done: AtomicBool,
+    /// Parsed transactions the repository refused for their amount since startup
+    rejected_amounts: AtomicU64,
     shutdown: Arc<AtomicBool>,
 }
 
@@ -29,25 +64,57 @@ pub struct SyncReport {
     pub wallets_synced: u32,
     pub new_transactions: u32,
     pub webhooks_triggered: u32,
+    pub expired_transactions: u32,
     pub errors: Vec<String>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
 }
 
 impl SyncService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pool: PgPool,
-        solana_client: Arc<SolanaClient>,
+        solana_client: Arc<dyn ChainClient>,
         webhook_service: Arc<WebhookService>,
+        events: Arc<EventBus>,
+        leader: Arc<LeaderLock>,
+        tokens: Arc<TokenRegistry>,
+        snapshot_interval: Duration,
+        snapshot_retention_days: i64,
+        sync_run_retention_days: i64,
+        wallet_sync_timeout: Duration,
+        pending_expiry: Duration,
     ) -> Self {
         Self {
             pool,
             solana_client,
             webhook_service,
+            events,
+            leader,
+            tokens,
+            snapshot_interval,
+            snapshot_retention_days,
+            sync_run_retention_days,
+            wallet_sync_timeout,
+            pending_expiry,
+            last_snapshot_at: Mutex::new(None),
+            first_cycle_done: AtomicBool::new(false),
+            rejected_amounts: AtomicU64::new(0),
             shutdown: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Whether the background loop got through its first cycle, for the
+    /// readiness probe
+    pub fn first_cycle_completed(&self) -> bool {
+        self.first_cycle_done.load(Ordering::Relaxed)
+    }
+
+    /// Transactions refused for a non-positive amount since startup
+    pub fn rejected_amounts(&self) -> u64 {
+        self.rejected_amounts.load(Ordering::Relaxed)
+    }
+
     /// Start the background sync loop
     pub fn start_background_sync(self: Arc<Self>) -> JoinHandle<()> {
         let service = self.clone();
@@ -62,9 +129,18 @@ impl SyncService {
                     break;
                 }
 
+                // Only the leader replica syncs; the others keep retrying the
+                // lock so one takes over if the leader goes away
+                if !service.leader.ensure().await {
+                    service.first_cycle_done.store(true, Ordering::Relaxed);
+                    tokio::time::sleep(SYNC_INTERVAL).await;
+                    continue;
+                }
+
                 // Run sync cycle
                 match service.sync_all_wallets().await {
                     Ok(report) => {
+                        service.first_cycle_done.store(true, Ordering::Relaxed);
                         if report.new_transactions > 0 || !report.errors.is_empty() {
                             info!(
                                 wallets = report.wallets_synced,
@@ -76,7 +152,7 @@ impl SyncService {
                         }
                     }
                     Err(e) => {
-                        error!("Sync cycle failed: {}", e);
+                        error!(error = %e, "Sync cycle failed");
                     }
                 }
 
@@ -86,11 +162,23 @@ impl SyncService {
                         info!(count = retried, "Retried pending webhooks");
                     }
                     Err(e) => {
-                        error!("Failed to retry pending webhooks: {}", e);
+                        error!(error = %e, "Failed to retry pending webhooks");
                     }
                     _ => {}
                 }
 
+                // Record balance snapshots once per snapshot interval
+                if service.snapshot_due() {
+                    match service.snapshot_balances().await {
+                        Ok(recorded) => {
+                            info!(wallets = recorded, "Recorded balance snapshots");
+                        }
+                        Err(e) => {
+                            error!(error = %e, "Failed to record balance snapshots");
+                        }
+                    }
+                }
+
                 // Wait for next cycle
                 tokio::time::sleep(SYNC_INTERVAL).await;
             }
@@ -102,6 +190,76 @@ impl SyncService {
         self.shutdown.store(true, Ordering::Relaxed);
     }
 
+    /// Whether the snapshot interval has elapsed, claiming the slot if so
+    fn snapshot_due(&self) -> bool {
+        let mut last = self.last_snapshot_at.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < self.snapshot_interval) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
+    /// Record today's USDC balance of every active wallet (zero balances
+    /// included), then trim snapshots, sync runs and idempotency keys past
+    /// their retention horizons. A wallet whose balance can't be fetched is skipped until the
+    /// next run.
+    pub async fn snapshot_balances(&self) -> Result<u32, crate::error::AppError> {
+        let today = Utc::now().date_naive();
+        let wallets = WalletRepository::list_all(&self.pool).await?;
+        let mut recorded = 0;
+
+        for wallet in wallets {
+            let balance = match self.solana_client.get_usdc_balance(&wallet.address).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    warn!(wallet = %wallet.address, error = %e, "Balance snapshot skipped");
+                    continue;
+                }
+            };
+
+            // Snapshots store base units as BIGINT; skip rather than wrap
+            let Ok(amount_raw) = i64::try_from(balance.amount_raw) else {
+                warn!(
+                    wallet = %wallet.address,
+                    amount_raw = balance.amount_raw,
+                    "Balance snapshot skipped, amount exceeds the stored range"
+                );
+                continue;
+            };
+
+            BalanceSnapshotRepository::upsert(
+                &self.pool,
+                &wallet.address,
+                today,
+                balance.amount,
+                amount_raw,
+            )
+            .await?;
+            recorded += 1;
+        }
+
+        let cutoff = today - chrono::Duration::days(self.snapshot_retention_days);
+        let trimmed = BalanceSnapshotRepository::delete_before(&self.pool, cutoff).await?;
+        if trimmed > 0 {
+            info!(count = trimmed, "Trimmed expired balance snapshots");
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(self.sync_run_retention_days);
+        let trimmed = SyncRunRepository::delete_before(&self.pool, cutoff).await?;
+        if trimmed > 0 {
+            info!(count = trimmed, "Trimmed expired sync runs");
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+        let trimmed = IdempotencyKeyRepository::delete_before(&self.pool, cutoff).await?;
+        if trimmed > 0 {
+            info!(count = trimmed, "Trimmed expired idempotency keys");
+        }
+
+        Ok(recorded)
+    }
+
     /// Sync all registered wallets
     pub async fn sync_all_wallets(&self) -> Result<SyncReport, crate::error::AppError> {
         let mut report = SyncReport {
@@ -113,117 +271,1442 @@ impl SyncService {
         let wallets = WalletRepository::list_all(&self.pool).await?;
 
         for wallet in wallets {
-            match self.sync_wallet(&wallet).await {
-                Ok((new_txs, webhooks)) => {
+            let outcome = match self
+                .sync_wallet_counting_expired(&wallet, SYNC_LIMIT, true)
+                .await
+            {
+                Ok((new_txs, webhooks, expired)) => {
                     report.wallets_synced += 1;
                     report.new_transactions += new_txs;
-                    report.webhooks_triggered += webhooks;
+                    report.webhooks_triggered +=
+                        webhooks + self.check_balance_thresholds(&wallet).await;
+                    report.expired_transactions += expired;
+                    WalletRepository::record_sync_success(&self.pool, &wallet.address).await
                 }
                 Err(e) => {
-                    let error_msg = format!("Failed to sync wallet {}: {}", wallet.address, e);
-                    warn!("{}", error_msg);
-                    report.errors.push(error_msg);
+                    warn!(wallet = %wallet.address, error = %e, "Failed to sync wallet");
+                    report
+                        .errors
+                        .push(format!("Failed to sync wallet {}: {}", wallet.address, e));
+                    WalletRepository::record_sync_failure(
+                        &self.pool,
+                        &wallet.address,
+                        &e.to_string(),
+                    )
+                    .await
                 }
+            };
+
+            // Bookkeeping for the status endpoint must not stop the cycle
+            if let Err(e) = outcome {
+                warn!(wallet = %wallet.address, error = %e, "Failed to record sync outcome");
             }
         }
 
         report.completed_at = Some(Utc::now());
+
+        // History is best effort; failing to write it must not fail the cycle
+        if let Err(e) = self.record_run(&report).await {
+            warn!(error = %e, "Failed to record sync run");
+        }
+
         Ok(report)
     }
 
-    /// Sync a single wallet and return (new_transactions, webhooks_triggered)
-    async fn sync_wallet(&self, wallet: &Wallet) -> Result<(u32, u32), crate::error::AppError> {
-        let mut new_txs = 0u32;
-        let mut webhooks = 0u32;
+    /// Persist a completed cycle's report, keeping only its first errors
+    async fn record_run(&self, report: &SyncReport) -> Result<(), crate::error::AppError> {
+        let errors: Vec<String> = report
+            .errors
+            .iter()
+            .take(MAX_RECORDED_ERRORS)
+            .map(|e| e.chars().take(MAX_RECORDED_ERROR_LEN).collect())
+            .collect();
+        let completed_at = report.completed_at.unwrap_or_else(Utc::now);
+
+        SyncRunRepository::create(
+            &self.pool,
+            report.started_at.unwrap_or(completed_at),
+            completed_at,
+            report.wallets_synced as i32,
+            report.new_transactions as i32,
+            report.webhooks_triggered as i32,
+            report.expired_transactions as i32,
+            report.errors.len() as i32,
+            &serde_json::json!(errors),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sync a single wallet and return (new_transactions, webhooks_triggered).
+    /// With `notify` off, new transactions are stored without webhooks (as in
+    /// backfill) and pending ones are left for the next notifying sync.
+    /// RPC calls share the wallet sync timeout; storing and notifying aren't
+    /// cut short, so a stored transaction always gets its webhook.
+    #[instrument(skip_all, fields(wallet = %wallet.address, limit = limit, notify = notify))]
+    pub async fn sync_wallet(
+        &self,
+        wallet: &Wallet,
+        limit: usize,
+        notify: bool,
+    ) -> Result<(u32, u32), crate::error::AppError> {
+        let (new_txs, webhooks, _) = self
+            .sync_wallet_counting_expired(wallet, limit, notify)
+            .await?;
+        Ok((new_txs, webhooks))
+    }
+
+    /// [`Self::sync_wallet`], also returning how many pending transactions it
+    /// expired. A single getSignatureStatuses call classifies the new
+    /// transactions and re-checks the stored pending ones.
+    async fn sync_wallet_counting_expired(
+        &self,
+        wallet: &Wallet,
+        limit: usize,
+        notify: bool,
+    ) -> Result<(u32, u32, u32), crate::error::AppError> {
+        let deadline = tokio::time::Instant::now() + self.wallet_sync_timeout;
 
         // Fetch recent transactions from Solana
         let parsed_txs = self
-            .solana_client
-            .sync_wallet_transactions(&wallet.address, SYNC_LIMIT)
+            .before_deadline(
+                deadline,
+                self.solana_client.sync_wallet_transactions(
+                    &wallet.address,
+                    limit,
+                    wallet.ignore_before,
+                ),
+            )
             .await?;
 
+        let mut new = Vec::with_capacity(parsed_txs.len());
         for parsed in parsed_txs {
-            // Check if we already have this transaction
-            if TransactionRepository::exists(&self.pool, &parsed.signature).await? {
-                continue;
+            if !TransactionRepository::exists(&self.pool, &parsed.signature).await? {
+                new.push(parsed);
             }
+        }
 
-            // Determine transaction type
-            let tx_type = match parsed.tx_type.as_str() {
-                "send" => TransactionType::Send,
-                "receive" => TransactionType::Receive,
-                _ => continue,
-            };
-
-            // Store the transaction
-            let transaction = TransactionRepository::create(
+        // Best-effort: failing to re-check pending ones mustn't hold back new
+        // transactions, they're checked again next cycle
+        let pending = if notify {
+            TransactionRepository::find_by_wallet_and_status(
                 &self.pool,
-                &parsed.signature,
                 &wallet.address,
-                tx_type,
-                parsed.amount,
-                &self.solana_client.usdc_mint,
-                &parsed.counterparty,
-                TransactionStatus::Confirmed,
-                parsed.block_time,
+                TransactionStatus::Pending,
             )
-            .await;
+            .await
+            .unwrap_or_else(|e| {
+                warn!(wallet = %wallet.address, error = %e, "Failed to load pending");
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
 
-            // Handle the case where ON CONFLICT DO NOTHING returns no rows
-            let transaction = match transaction {
-                Ok(tx) => tx,
-                Err(crate::error::AppError::Database(sqlx::Error::RowNotFound)) => {
-                    // Transaction already exists (race condition), skip
-                    continue;
+        let signatures: Vec<String> = new
+            .iter()
+            .map(|parsed| parsed.signature.clone())
+            .chain(pending.iter().map(|tx| tx.signature.clone()))
+            .collect();
+        let statuses = if signatures.is_empty() {
+            None
+        } else {
+            match self
+                .before_deadline(
+                    deadline,
+                    self.solana_client.get_signature_statuses(&signatures),
+                )
+                .await
+            {
+                Ok(statuses) if statuses.len() == signatures.len() => Some(statuses),
+                Ok(_) => {
+                    warn!(wallet = %wallet.address, "Signature status check returned a short list");
+                    None
                 }
-                Err(e) => return Err(e),
-            };
+                Err(e) => {
+                    warn!(wallet = %wallet.address, error = %e, "Signature status check skipped");
+                    None
+                }
+            }
+        };
+        let (new_statuses, pending_statuses) = match &statuses {
+            Some(statuses) => {
+                let (new_statuses, pending_statuses) = statuses.split_at(new.len());
+                (Some(new_statuses), Some(pending_statuses))
+            }
+            None => (None, None),
+        };
+
+        let (mut webhooks, expired) = match pending_statuses {
+            Some(statuses) => self.check_pending(wallet, &pending, statuses).await,
+            None => (0, 0),
+        };
 
+        let mut new_txs = 0u32;
+        for (index, parsed) in new.iter().enumerate() {
+            let status = arrival_status(new_statuses.and_then(|statuses| statuses[index].as_ref()));
+            let transaction = match self.store_transaction(wallet, parsed, status).await? {
+                Some(tx) => tx,
+                None => continue,
+            };
             new_txs += 1;
+
+            if notify && self.notify_new_transaction(wallet, &transaction).await {
+                webhooks += 1;
+            }
+        }
+
+        Ok((new_txs, webhooks, expired))
+    }
+
+    /// After a sync, fetch the wallet's balance and fire balance.threshold for
+    /// each configured level crossed since the previous check. The first
+    /// check only records a baseline. Failures are logged and retried next
+    /// cycle. Returns the number of webhooks triggered.
+    async fn check_balance_thresholds(&self, wallet: &Wallet) -> u32 {
+        if wallet.balance_thresholds.is_empty() {
+            return 0;
+        }
+
+        let balance = match self.solana_client.get_usdc_balance(&wallet.address).await {
+            Ok(balance) => balance.amount,
+            Err(e) => {
+                warn!(wallet = %wallet.address, error = %e, "Balance threshold check skipped");
+                return 0;
+            }
+        };
+
+        let previous =
+            match WalletRepository::swap_last_balance(&self.pool, &wallet.address, balance).await {
+                Ok(Some(previous)) => previous,
+                Ok(None) => return 0,
+                Err(e) => {
+                    warn!(wallet = %wallet.address, error = %e, "Failed to record last balance");
+                    return 0;
+                }
+            };
+
+        let mut webhooks = 0u32;
+        for (threshold, direction) in
+            crossed_thresholds(previous, balance, &wallet.balance_thresholds)
+        {
             info!(
                 wallet = %wallet.address,
-                signature = %transaction.signature,
-                tx_type = %transaction.tx_type,
-                amount = %transaction.amount,
-                "New transaction detected"
+                threshold = %threshold,
+                direction = ?direction,
+                previous_balance = %previous,
+                balance = %balance,
+                "Balance crossed threshold"
             );
 
-            // Trigger webhook for receive transactions
-            if matches!(tx_type, TransactionType::Receive) {
-                if let Err(e) = self
-                    .webhook_service
-                    .notify_payment_received(wallet, &transaction)
-                    .await
-                {
+            let payload = BalanceThresholdPayload {
+                wallet_address: wallet.address.clone(),
+                threshold: threshold.normalize().to_string(),
+                direction,
+                previous_balance: previous.to_string(),
+                balance: balance.to_string(),
+                observed_at: Utc::now(),
+            };
+            let data = match serde_json::to_value(&payload) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!(wallet = %wallet.address, error = %e, "Failed to serialize threshold event");
+                    continue;
+                }
+            };
+
+            match self
+                .webhook_service
+                .notify_wallet_event(wallet, "balance.threshold", data)
+                .await
+            {
+                Ok(()) => webhooks += 1,
+                Err(e) => {
                     warn!(
                         wallet = %wallet.address,
-                        signature = %transaction.signature,
+                        threshold = %threshold,
                         error = %e,
-                        "Failed to send webhook notification"
+                        "Failed to send balance threshold webhook"
                     );
-                } else {
-                    webhooks += 1;
                 }
             }
         }
 
-        Ok((new_txs, webhooks))
+        webhooks
+    }
+
+    /// Fetch a single signature for a wallet and store it through the same
+    /// path as the sync loop, so a payment seen here first still gets its
+    /// webhook exactly once. A transaction from before the wallet was
+    /// registered or its `ignore_before` cutoff is stored without one, as a
+    /// backfill would. Returns `None` if it isn't a USDC transfer.
+    pub async fn sync_signature(
+        &self,
+        wallet: &Wallet,
+        signature: &str,
+    ) -> Result<Option<Transaction>, crate::error::AppError> {
+        SolanaClient::validate_signature(signature)?;
+
+        let parsed = match self
+            .solana_client
+            .get_transaction_details(signature, &wallet.address)
+            .await?
+        {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+
+        // Best-effort, as in the sync loop
+        let status = match self
+            .solana_client
+            .get_signature_statuses(&[signature.to_string()])
+            .await
+        {
+            Ok(statuses) => arrival_status(statuses.first().and_then(Option::as_ref)),
+            Err(e) => {
+                warn!(signature = %signature, error = %e, "Signature status check skipped");
+                arrival_status(None)
+            }
+        };
+
+        match self.store_transaction(wallet, &parsed, status).await? {
+            Some(transaction) => {
+                if transaction.block_time >= notify_cutoff(wallet) {
+                    self.notify_new_transaction(wallet, &transaction).await;
+                }
+                Ok(Some(transaction))
+            }
+            // Stored meanwhile by another sync
+            None => TransactionRepository::find_by_signature(&self.pool, signature).await,
+        }
+    }
+
+    /// Trigger the webhook for a newly stored transaction if it's a confirmed
+    /// receive, and settle any payment link it pays; pending ones are handled
+    /// once promoted on a later sync. Returns whether a webhook was triggered.
+    async fn notify_new_transaction(&self, wallet: &Wallet, transaction: &Transaction) -> bool {
+        if transaction.tx_type != TransactionType::Receive
+            || transaction.status != TransactionStatus::Confirmed
+        {
+            return false;
+        }
+
+        let notified = match self
+            .webhook_service
+            .notify_payment_received(wallet, transaction)
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(
+                    wallet = %wallet.address,
+                    signature = %transaction.signature,
+                    error = %e,
+                    "Failed to send webhook notification"
+                );
+                false
+            }
+        };
+
+        if let Err(e) = self.settle_payment_link(wallet, transaction).await {
+            warn!(
+                wallet = %wallet.address,
+                signature = %transaction.signature,
+                error = %e,
+                "Failed to settle payment link"
+            );
+        }
+
+        notified
+    }
+
+    /// Mark the payment link this receive pays (matched by memo for links
+    /// without an amount, otherwise by amount within the link's tolerance) or
+    /// underpays, and fire its payment_link.paid or payment_link.underpaid webhook
+    async fn settle_payment_link(
+        &self,
+        wallet: &Wallet,
+        transaction: &Transaction,
+    ) -> Result<(), crate::error::AppError> {
+        let matched = PaymentLinkRepository::match_payment(
+            &self.pool,
+            &wallet.address,
+            transaction.amount,
+            &transaction.signature,
+            transaction.block_time,
+            transaction.memo.as_deref().map(str::trim),
+        )
+        .await?;
+        let link = match matched {
+            Some(link) => link,
+            None => match PaymentLinkRepository::mark_underpaid(
+                &self.pool,
+                &wallet.address,
+                transaction.amount,
+                &transaction.signature,
+                transaction.block_time,
+            )
+            .await?
+            {
+                Some(link) => link,
+                None => return Ok(()),
+            },
+        };
+
+        // Matched links always have an amount
+        let requested = link.amount.unwrap_or(transaction.amount);
+        let match_rule = AmountMatchRule::evaluate(
+            requested,
+            transaction.amount,
+            link.amount_tolerance,
+            link.amount_tolerance_pct,
+        );
+        let event_type = if match_rule.is_paid() {
+            "payment_link.paid"
+        } else {
+            "payment_link.underpaid"
+        };
+
+        info!(
+            wallet = %wallet.address,
+            slug = %link.slug,
+            signature = %transaction.signature,
+            rule = ?match_rule,
+            event_type,
+            "Payment link settled"
+        );
+
+        let payload = PaymentLinkPaidPayload {
+            overpaid_by: link.overpaid_by().map(|a| a.to_string()),
+            slug: link.slug,
+            wallet_address: link.wallet_address,
+            amount: transaction.amount.to_string(),
+            requested_amount: requested.to_string(),
+            received_amount: transaction.amount.to_string(),
+            match_rule,
+            amount_tolerance: link.amount_tolerance.to_string(),
+            amount_tolerance_pct: link.amount_tolerance_pct.to_string(),
+            signature: transaction.signature.clone(),
+            memo: link.memo,
+            reusable: link.reusable,
+            paid_at: transaction.block_time,
+        };
+
+        self.webhook_service
+            .notify_transaction_event(
+                wallet,
+                &transaction.signature,
+                event_type,
+                serde_json::to_value(&payload)?,
+            )
+            .await
+    }
+
+    /// Store a parsed transaction if it's new. Returns `None` when it was
+    /// already stored, including when a concurrent sync won the insert race;
+    /// only the caller that created the row may notify about it. A refused
+    /// amount is counted and skipped like an unparseable transaction.
+    async fn store_transaction(
+        &self,
+        wallet: &Wallet,
+        parsed: &ParsedTransaction,
+        status: TransactionStatus,
+    ) -> Result<Option<Transaction>, crate::error::AppError> {
+        // Check if we already have this transaction
+        if TransactionRepository::exists(&self.pool, &parsed.signature).await? {
+            return Ok(None);
+        }
+
+        // Determine transaction type
+        let tx_type = match parsed.tx_type.as_str() {
+            "send" => TransactionType::Send,
+            "receive" => TransactionType::Receive,
+            "self_transfer" => TransactionType::SelfTransfer,
+            _ => return Ok(None),
+        };
+
+        // Attribute receives to the customer reference their memo names.
+        // Unmatched memos are kept and the payment still counts for the wallet.
+        let reference = match tx_type {
+            TransactionType::Receive => wallet.reference_for(parsed.memo.as_deref()),
+            TransactionType::Send | TransactionType::SelfTransfer => None,
+        };
+
+        // Store the transaction. Parsers only pass plausible amounts, which
+        // fit the BIGINT column, but never store a wrapped value.
+        let stored = match i64::try_from(parsed.amount_raw) {
+            Ok(amount_raw) => {
+                TransactionRepository::create(
+                    &self.pool,
+                    wallet.chain,
+                    &parsed.signature,
+                    &wallet.address,
+                    tx_type,
+                    parsed.amount,
+                    amount_raw,
+                    parsed.decimals as i16,
+                    self.solana_client.usdc_mint(),
+                    &parsed.counterparty,
+                    status,
+                    parsed.block_time,
+                    &serde_json::to_value(&parsed.transfer_legs)?,
+                    parsed.memo.as_deref(),
+                    reference.as_deref(),
+                )
+                .await
+            }
+            Err(_) => Err(crate::error::AppError::AmountOverflow(format!(
+                "{} base units exceed the stored range",
+                parsed.amount_raw
+            ))),
+        };
+
+        let stored = match stored {
+            Ok(stored) => stored,
+            Err(
+                crate::error::AppError::InvalidAmount(reason)
+                | crate::error::AppError::AmountOverflow(reason),
+            ) => {
+                self.rejected_amounts.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    wallet = %wallet.address,
+                    signature = %parsed.signature,
+                    error = %reason,
+                    metric = "sync.rejected_amount",
+                    "Refused to store transaction"
+                );
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        if !stored.created {
+            return Ok(None);
+        }
+
+        let transaction = stored.transaction;
+        info!(
+            wallet = %wallet.address,
+            signature = %transaction.signature,
+            tx_type = %transaction.tx_type,
+            amount = %transaction.amount,
+            "New transaction detected"
+        );
+        self.publish_transaction(&transaction).await;
+
+        Ok(Some(transaction))
+    }
+
+    /// Walk a wallet's history back from the newest signature, storing up to
+    /// `max` signatures' worth of transactions and stopping at the wallet's
+    /// `ignore_before` cutoff. Historical payments are recorded without firing
+    /// webhooks. Progress is saved after every page, so a later call (after a
+    /// crash or hitting `max`) resumes where this one stopped; `restart`
+    /// discards it. Returns the number of new transactions and whether the
+    /// whole history has been imported.
+    pub async fn backfill_wallet(
+        &self,
+        wallet: &Wallet,
+        max: usize,
+        restart: bool,
+    ) -> Result<(u32, bool), crate::error::AppError> {
+        if restart {
+            SyncCursorRepository::delete(&self.pool, &wallet.address).await?;
+        }
+
+        let cursor = SyncCursorRepository::find(&self.pool, &wallet.address).await?;
+        if cursor.as_ref().is_some_and(|c| c.is_complete()) {
+            info!(wallet = %wallet.address, "Backfill already completed");
+            return Ok((0, true));
+        }
+
+        let mut new_txs = 0u32;
+        let mut scanned = 0usize;
+        let mut before = cursor.and_then(|c| c.oldest_signature);
+        let mut completed = false;
+        if let Some(signature) = &before {
+            info!(wallet = %wallet.address, before = %signature, "Resuming backfill");
+        }
+
+        while scanned < max {
+            let batch = (max - scanned).min(BACKFILL_PAGE_SIZE);
+            let signatures = self
+                .solana_client
+                .get_signatures(
+                    &wallet.address,
+                    batch,
+                    before.as_deref(),
+                    wallet.ignore_before,
+                )
+                .await?;
+
+            if signatures.is_empty() {
+                completed = true;
+                break;
+            }
+            scanned += signatures.len();
+
+            let details = self
+                .solana_client
+                .get_transactions_batch(&signatures, &wallet.address)
+                .await;
+
+            let mut page = Vec::new();
+            for (signature, result) in signatures.iter().zip(details) {
+                match result {
+                    Ok(Some(parsed))
+                        if wallet
+                            .ignore_before
+                            .is_some_and(|cutoff| parsed.block_time < cutoff) => {}
+                    Ok(Some(parsed)) => page.push(parsed),
+                    Ok(None) => {} // Not a USDC transfer, skip
+                    Err(e) => {
+                        warn!(signature = %signature, error = %e, "Failed to fetch transaction during backfill");
+                    }
+                }
+            }
+
+            // The newest page may still be settling; best-effort, as in the sync loop
+            let page_signatures: Vec<String> =
+                page.iter().map(|parsed| parsed.signature.clone()).collect();
+            let statuses = if page.is_empty() {
+                Vec::new()
+            } else {
+                self.solana_client
+                    .get_signature_statuses(&page_signatures)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(wallet = %wallet.address, error = %e, "Signature status check skipped");
+                        Vec::new()
+                    })
+            };
+            for (index, parsed) in page.iter().enumerate() {
+                let status = arrival_status(statuses.get(index).and_then(Option::as_ref));
+                if self
+                    .store_transaction(wallet, parsed, status)
+                    .await?
+                    .is_some()
+                {
+                    new_txs += 1;
+                }
+            }
+
+            // Only once the whole page is stored, so a crash redoes at most it
+            if let Some(oldest) = signatures.last() {
+                SyncCursorRepository::advance(
+                    &self.pool,
+                    &wallet.address,
+                    oldest,
+                    signatures.len() as i64,
+                )
+                .await?;
+            }
+
+            // A short page means history ran out or we reached the cutoff
+            if signatures.len() < batch {
+                completed = true;
+                break;
+            }
+            before = signatures.last().cloned();
+        }
+
+        if completed {
+            SyncCursorRepository::complete(&self.pool, &wallet.address).await?;
+        }
+
+        info!(
+            wallet = %wallet.address,
+            scanned = scanned,
+            new_txs = new_txs,
+            completed = completed,
+            "Backfill finished"
+        );
+
+        Ok((new_txs, completed))
+    }
+
+    /// Settle the wallet's pending transactions the cluster now reports
+    /// finalized or failed, and expire those pending for longer than the
+    /// expiry age that it, searching its full history, still doesn't know.
+    /// `statuses` line up with `pending`. Failures are logged and retried
+    /// next cycle. Returns (webhooks_triggered, expired).
+    async fn check_pending(
+        &self,
+        wallet: &Wallet,
+        pending: &[Transaction],
+        statuses: &[Option<SignatureStatus>],
+    ) -> (u32, u32) {
+        let expire_before = Utc::now()
+            - chrono::Duration::from_std(self.pending_expiry).unwrap_or(chrono::Duration::MAX);
+
+        let (mut webhooks, mut expired) = (0u32, 0u32);
+        for (transaction, status) in pending.iter().zip(statuses) {
+            let signature = &transaction.signature;
+            let result = match status.as_ref().map(|s| s.settled_status()) {
+                Some(Some(settled)) => self
+                    .settle_pending(wallet, signature, settled)
+                    .await
+                    .map(|notified| webhooks += notified as u32),
+                // Landed but not finalized: check again next cycle
+                Some(None) => continue,
+                None if transaction.created_at < expire_before => self
+                    .expire_pending(signature)
+                    .await
+                    .map(|flipped| expired += flipped as u32),
+                // Not seen by the cluster yet, but not stale either
+                None => continue,
+            };
+
+            if let Err(e) = result {
+                warn!(
+                    wallet = %wallet.address,
+                    signature = %signature,
+                    error = %e,
+                    "Failed to settle pending transaction"
+                );
+            }
+        }
+
+        (webhooks, expired)
+    }
+
+    /// Mark a pending transaction expired. Expired transactions get no
+    /// webhook or live event. Returns whether this call flipped the row.
+    async fn expire_pending(&self, signature: &str) -> Result<bool, crate::error::AppError> {
+        let Some(transaction) =
+            TransactionRepository::update_status(&self.pool, signature, TransactionStatus::Expired)
+                .await?
+        else {
+            return Ok(false);
+        };
+
+        info!(
+            wallet = %transaction.wallet_address,
+            signature = %transaction.signature,
+            pending_since = %transaction.created_at,
+            "Pending transaction expired"
+        );
+
+        Ok(true)
+    }
+
+    /// Move a pending transaction to its settled status, publish it and fire
+    /// its deferred webhook, unless it predates the wallet's notify cutoff.
+    /// Returns whether a webhook was triggered.
+    async fn settle_pending(
+        &self,
+        wallet: &Wallet,
+        signature: &str,
+        status: TransactionStatus,
+    ) -> Result<bool, crate::error::AppError> {
+        // Only the sync that actually flips the row gets it back
+        let Some(transaction) =
+            TransactionRepository::update_status(&self.pool, signature, status).await?
+        else {
+            return Ok(false);
+        };
+
+        info!(
+            wallet = %wallet.address,
+            signature = %transaction.signature,
+            status = %transaction.status,
+            "Pending transaction settled"
+        );
+        self.publish_transaction(&transaction).await;
+
+        // History stored pending on registration or lookup stays silent
+        if transaction.block_time < notify_cutoff(wallet) {
+            return Ok(false);
+        }
+
+        // Fire the deferred webhook; notify_payment_received dedups on the signature
+        Ok(self.notify_new_transaction(wallet, &transaction).await)
+    }
+
+    /// Await a wallet sync's RPC step, failing it once the sync is past its
+    /// deadline
+    async fn before_deadline<T>(
+        &self,
+        deadline: tokio::time::Instant,
+        call: impl std::future::Future<Output = Result<T, crate::error::AppError>>,
+    ) -> Result<T, crate::error::AppError> {
+        tokio::time::timeout_at(deadline, call)
+            .await
+            .unwrap_or_else(|_| {
+                Err(crate::error::AppError::SolanaRpc(format!(
+                    "Wallet sync timed out after {}s of RPC calls",
+                    self.wallet_sync_timeout.as_secs()
+                )))
+            })
+    }
+
+    /// Push a stored transaction to live event stream subscribers
+    async fn publish_transaction(&self, transaction: &Transaction) {
+        let event = match transaction.tx_type {
+            TransactionType::Receive => "payment.received",
+            TransactionType::Send => "payment.sent",
+            TransactionType::SelfTransfer => "payment.self_transfer",
+        };
+
+        let token = self.tokens.resolve(&transaction.token_mint).await;
+        let data = match serde_json::to_value(PaymentReceivedPayload::new(transaction, &token)) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!(signature = %transaction.signature, error = %e, "Failed to serialize event");
+                return;
+            }
+        };
+
+        self.events.publish(
+            &transaction.wallet_address,
+            WebhookPayload {
+                event: event.to_string(),
+                chain: transaction.chain,
+                // Live streams are per wallet, so subscribers have nothing to fan out
+                group: None,
+                timestamp: Utc::now(),
+                data,
+            },
+        );
     }
 }
 
+/// Status to store a newly fetched transaction under: pending until the
+/// cluster reports it finalized. Without a status the fetch alone shows it
+/// landed, so it is stored confirmed rather than held back.
+fn arrival_status(status: Option<&SignatureStatus>) -> TransactionStatus {
+    match status {
+        Some(status) => status
+            .settled_status()
+            .unwrap_or(TransactionStatus::Pending),
+        None => TransactionStatus::Confirmed,
+    }
+}
+
+/// Transactions before this are the wallet's history and get no webhook
+fn notify_cutoff(wallet: &Wallet) -> DateTime<Utc> {
+    wallet
+        .ignore_before
+        .map_or(wallet.created_at, |ignore_before| {
+            ignore_before.max(wallet.created_at)
+        })
+}
+
 impl serde::Serialize for SyncReport {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("SyncReport", 6)?;
+        let mut state = serializer.serialize_struct("SyncReport", 7)?;
         state.serialize_field("wallets_synced", &self.wallets_synced)?;
         state.serialize_field("new_transactions", &self.new_transactions)?;
         state.serialize_field("webhooks_triggered", &self.webhooks_triggered)?;
+        state.serialize_field("expired_transactions", &self.expired_transactions)?;
         state.serialize_field("errors", &self.errors)?;
         state.serialize_field("started_at", &self.started_at)?;
         state.serialize_field("completed_at", &self.completed_at)?;
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WalletSettings;
+    use crate::repository::WebhookEventRepository;
+    use crate::services::testing::{
+        receive, register_wallet, signature, transfer, Harness, SENDER, WALLET,
+    };
+    use rust_decimal::Decimal;
+
+    async fn events(pool: &PgPool) -> Vec<crate::domain::WebhookEvent> {
+        WebhookEventRepository::find_by_wallet(pool, WALLET, 100, 0)
+            .await
+            .unwrap()
+    }
+
+    /// Store a pending receive as if first seen `age` ago
+    async fn store_pending(pool: &PgPool, signature: &str, age: chrono::Duration) {
+        TransactionRepository::create(
+            pool,
+            crate::domain::Chain::Solana,
+            signature,
+            WALLET,
+            TransactionType::Receive,
+            Decimal::ONE,
+            1_000_000,
+            6,
+            crate::services::testing::USDC_MINT,
+            SENDER,
+            TransactionStatus::Pending,
+            Utc::now() - age,
+            &serde_json::json!([]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE transactions SET created_at = $1 WHERE signature = $2")
+            .bind(Utc::now() - age)
+            .bind(signature)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    /// Register the wallet a day back, so fixtures' recent block times come
+    /// after its notify cutoff
+    async fn register_a_day_ago(pool: &PgPool) -> Wallet {
+        register_wallet(pool, WalletSettings::default()).await;
+        sqlx::query("UPDATE wallets SET created_at = $1 WHERE address = $2")
+            .bind(Utc::now() - chrono::Duration::days(1))
+            .bind(WALLET)
+            .execute(pool)
+            .await
+            .unwrap();
+        WalletRepository::find_by_address(pool, WALLET)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn status(confirmation_status: &str) -> SignatureStatus {
+        SignatureStatus {
+            err: None,
+            confirmation_status: Some(confirmation_status.to_string()),
+        }
+    }
+
+    async fn status_of(pool: &PgPool, signature: &str) -> TransactionStatus {
+        TransactionRepository::find_by_signature(pool, signature)
+            .await
+            .unwrap()
+            .unwrap()
+            .status
+    }
+
+    #[sqlx::test]
+    async fn stores_and_notifies_a_new_receive(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let sig = signature(1);
+        harness.chain.push_transaction(receive(&sig, 1_500_000));
+
+        let (new_txs, webhooks) = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!((new_txs, webhooks), (1, 1));
+
+        let stored = TransactionRepository::find_by_signature(&pool, &sig)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tx_type, TransactionType::Receive);
+        assert_eq!(stored.status, TransactionStatus::Confirmed);
+        assert_eq!(stored.amount, Decimal::new(15, 1));
+        assert_eq!(stored.amount_raw, 1_500_000);
+        assert_eq!(stored.counterparty, SENDER);
+
+        let events = events(&pool).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "payment.received");
+        assert_eq!(
+            events[0].transaction_signature.as_deref(),
+            Some(sig.as_str())
+        );
+        assert_eq!(events[0].payload["data"]["amount"], "1.500000");
+    }
+
+    #[sqlx::test]
+    async fn a_second_sync_neither_stores_nor_notifies_again(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        harness
+            .chain
+            .push_transaction(receive(&signature(1), 1_000_000));
+
+        harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        harness
+            .chain
+            .push_transaction(receive(&signature(2), 2_000_000));
+        let second = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        let third = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+
+        assert_eq!(second, (1, 1));
+        assert_eq!(third, (0, 0));
+        assert_eq!(
+            TransactionRepository::count_by_wallet(&pool, WALLET)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(events(&pool).await.len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn racing_syncs_store_and_notify_each_receive_once(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        for seed in 1..=4 {
+            harness
+                .chain
+                .push_transaction(receive(&signature(seed), 1_000_000));
+        }
+        // Both tasks list the signatures before either stores them
+        harness.chain.set_latency(Duration::from_millis(20));
+
+        let (first, second) = tokio::join!(
+            harness.sync.sync_wallet(&wallet, SYNC_LIMIT, true),
+            harness.sync.sync_wallet(&wallet, SYNC_LIMIT, true),
+        );
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        assert_eq!(first.0 + second.0, 4);
+        assert_eq!(first.1 + second.1, 4);
+        assert_eq!(
+            TransactionRepository::count_by_wallet(&pool, WALLET)
+                .await
+                .unwrap(),
+            4
+        );
+        assert_eq!(events(&pool).await.len(), 4);
+    }
+
+    #[sqlx::test]
+    async fn without_notify_transactions_are_stored_silently(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let sig = signature(1);
+        harness.chain.push_transaction(receive(&sig, 1_000_000));
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, false)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (1, 0));
+        assert!(TransactionRepository::exists(&pool, &sig).await.unwrap());
+        assert!(events(&pool).await.is_empty());
+
+        // Already stored, so a notifying sync doesn't fire for it either
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 0));
+        assert!(events(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn only_usdc_receives_are_notified(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let a_minute_ago = Utc::now() - chrono::Duration::minutes(1);
+        harness.chain.push_signature(WALLET, &signature(1));
+        harness
+            .chain
+            .push_transaction(transfer(&signature(2), "send", 1_000_000, a_minute_ago));
+        harness
+            .chain
+            .push_transaction(transfer(&signature(3), "swap", 1_000_000, a_minute_ago));
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (1, 0));
+
+        let stored = TransactionRepository::find_by_signature(&pool, &signature(2))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tx_type, TransactionType::Send);
+        assert!(!TransactionRepository::exists(&pool, &signature(1))
+            .await
+            .unwrap());
+        assert!(!TransactionRepository::exists(&pool, &signature(3))
+            .await
+            .unwrap());
+        assert!(events(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn self_transfers_are_stored_under_their_own_type_without_a_webhook(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let a_minute_ago = Utc::now() - chrono::Duration::minutes(1);
+        let consolidation = crate::services::solana::ParsedTransaction {
+            counterparty: WALLET.to_string(),
+            ..transfer(&signature(1), "self_transfer", 40_000_000, a_minute_ago)
+        };
+        harness.chain.push_transaction(consolidation);
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (1, 0));
+
+        let stored = TransactionRepository::find_by_signature(&pool, &signature(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tx_type, TransactionType::SelfTransfer);
+        assert_eq!(stored.amount_raw, 40_000_000);
+        assert_eq!(stored.counterparty, WALLET);
+        assert!(events(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn looked_up_history_is_stored_without_a_webhook(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        register_wallet(&pool, WalletSettings::default()).await;
+        let now = Utc::now();
+        sqlx::query("UPDATE wallets SET created_at = $1, ignore_before = $2 WHERE address = $3")
+            .bind(now - chrono::Duration::days(1))
+            .bind(now - chrono::Duration::hours(1))
+            .bind(WALLET)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let wallet = WalletRepository::find_by_address(&pool, WALLET)
+            .await
+            .unwrap()
+            .unwrap();
+        let (old, before_cutoff, recent) = (signature(1), signature(2), signature(3));
+        let receive_at = |sig: &str, age| transfer(sig, "receive", 1_000_000, now - age);
+        harness
+            .chain
+            .push_transaction(receive_at(&old, chrono::Duration::days(2)));
+        harness
+            .chain
+            .push_transaction(receive_at(&before_cutoff, chrono::Duration::hours(2)));
+        harness.chain.push_transaction(receive(&recent, 1_000_000));
+
+        for sig in [&old, &before_cutoff, &recent] {
+            assert!(harness
+                .sync
+                .sync_signature(&wallet, sig)
+                .await
+                .unwrap()
+                .is_some());
+        }
+
+        let events = events(&pool).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].transaction_signature.as_deref(),
+            Some(recent.as_str())
+        );
+    }
+
+    #[sqlx::test]
+    async fn an_rpc_failure_is_reported_per_wallet(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        register_wallet(&pool, WalletSettings::default()).await;
+        harness.chain.set_error(Some("node is behind"));
+
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(report.wallets_synced, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("node is behind"));
+
+        let wallet = WalletRepository::find_by_address(&pool, WALLET)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(wallet
+            .last_sync_error
+            .is_some_and(|e| e.contains("node is behind")));
+
+        harness.chain.set_error(None);
+        harness
+            .chain
+            .push_transaction(receive(&signature(1), 1_000_000));
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(report.wallets_synced, 1);
+        assert_eq!(report.new_transactions, 1);
+        assert_eq!(report.webhooks_triggered, 1);
+    }
+
+    #[sqlx::test]
+    async fn pending_receives_are_notified_once_confirmed(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_a_day_ago(&pool).await;
+        let sig = signature(1);
+        store_pending(&pool, &sig, chrono::Duration::seconds(5)).await;
+
+        // Unknown to the cluster for now: left pending, no webhook
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 0));
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Pending);
+
+        for seen in ["processed", "confirmed"] {
+            harness.chain.set_signature_status(&sig, status(seen));
+            let outcome = harness
+                .sync
+                .sync_wallet(&wallet, SYNC_LIMIT, true)
+                .await
+                .unwrap();
+            assert_eq!(outcome, (0, 0));
+        }
+
+        harness
+            .chain
+            .set_signature_status(&sig, status("finalized"));
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 1));
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Confirmed);
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 0));
+        assert_eq!(events(&pool).await.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn failed_pending_transactions_get_no_webhook(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let sig = signature(1);
+        store_pending(&pool, &sig, chrono::Duration::seconds(5)).await;
+        harness.chain.set_signature_status(
+            &sig,
+            SignatureStatus {
+                err: Some(serde_json::json!({ "InstructionError": [0, "Custom"] })),
+                confirmation_status: Some("finalized".to_string()),
+            },
+        );
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 0));
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Failed);
+        assert!(events(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn a_failed_status_check_does_not_hold_back_new_transactions(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let pending = signature(1);
+        store_pending(&pool, &pending, chrono::Duration::seconds(5)).await;
+        harness.chain.set_status_error(Some("statuses unavailable"));
+        harness
+            .chain
+            .push_transaction(receive(&signature(2), 1_000_000));
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (1, 1));
+        assert_eq!(status_of(&pool, &pending).await, TransactionStatus::Pending);
+    }
+
+    #[sqlx::test]
+    async fn a_receive_not_yet_finalized_is_stored_pending_and_notified_once_it_is(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_a_day_ago(&pool).await;
+        let sig = signature(1);
+        harness
+            .chain
+            .set_signature_status(&sig, status("confirmed"));
+        harness.chain.push_transaction(receive(&sig, 1_000_000));
+
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (1, 0));
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Pending);
+        assert!(events(&pool).await.is_empty());
+
+        harness
+            .chain
+            .set_signature_status(&sig, status("finalized"));
+        let outcome = harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(outcome, (0, 1));
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Confirmed);
+        assert_eq!(events(&pool).await.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn history_pending_at_registration_settles_without_a_webhook(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        let sig = signature(1);
+        let an_hour_ago = Utc::now() - chrono::Duration::hours(1);
+        harness
+            .chain
+            .set_signature_status(&sig, status("confirmed"));
+        harness
+            .chain
+            .push_transaction(transfer(&sig, "receive", 1_000_000, an_hour_ago));
+
+        // The registration sync stores it silently
+        assert_eq!(
+            harness
+                .sync
+                .sync_wallet(&wallet, SYNC_LIMIT, false)
+                .await
+                .unwrap(),
+            (1, 0)
+        );
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Pending);
+
+        harness
+            .chain
+            .set_signature_status(&sig, status("finalized"));
+        assert_eq!(
+            harness
+                .sync
+                .sync_wallet(&wallet, SYNC_LIMIT, true)
+                .await
+                .unwrap(),
+            (0, 0)
+        );
+        assert_eq!(status_of(&pool, &sig).await, TransactionStatus::Confirmed);
+        assert!(events(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn stale_pending_expires_unless_the_cluster_knows_it(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        register_a_day_ago(&pool).await;
+        let stale = chrono::Duration::hours(1);
+        let (unknown, settled, confirmed, fresh) =
+            (signature(1), signature(2), signature(3), signature(4));
+        store_pending(&pool, &unknown, stale).await;
+        store_pending(&pool, &settled, stale).await;
+        store_pending(&pool, &confirmed, stale).await;
+        store_pending(&pool, &fresh, chrono::Duration::seconds(5)).await;
+        harness
+            .chain
+            .set_signature_status(&settled, status("finalized"));
+        harness
+            .chain
+            .set_signature_status(&confirmed, status("confirmed"));
+
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(
+            (report.expired_transactions, report.webhooks_triggered),
+            (1, 1)
+        );
+        assert_eq!(status_of(&pool, &unknown).await, TransactionStatus::Expired);
+        assert_eq!(
+            status_of(&pool, &settled).await,
+            TransactionStatus::Confirmed
+        );
+        assert_eq!(
+            status_of(&pool, &confirmed).await,
+            TransactionStatus::Pending
+        );
+        assert_eq!(status_of(&pool, &fresh).await, TransactionStatus::Pending);
+
+        // Only the settled one was notified, and nothing changes on a re-run
+        let events = events(&pool).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].transaction_signature.as_deref(),
+            Some(settled.as_str())
+        );
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(
+            (report.expired_transactions, report.webhooks_triggered),
+            (0, 0)
+        );
+    }
+
+    #[sqlx::test]
+    async fn one_status_call_covers_new_and_pending_transactions(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        let wallet = register_wallet(&pool, WalletSettings::default()).await;
+        store_pending(&pool, &signature(1), chrono::Duration::hours(1)).await;
+        store_pending(&pool, &signature(2), chrono::Duration::seconds(5)).await;
+        harness
+            .chain
+            .push_transaction(receive(&signature(3), 1_000_000));
+        harness
+            .chain
+            .push_transaction(receive(&signature(4), 1_000_000));
+
+        harness
+            .sync
+            .sync_wallet(&wallet, SYNC_LIMIT, true)
+            .await
+            .unwrap();
+        assert_eq!(harness.chain.status_calls(), 1);
+    }
+
+    #[sqlx::test]
+    async fn sync_cycles_report_expired_transactions(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        register_wallet(&pool, WalletSettings::default()).await;
+        store_pending(&pool, &signature(1), chrono::Duration::hours(1)).await;
+
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(report.expired_transactions, 1);
+        assert_eq!(
+            status_of(&pool, &signature(1)).await,
+            TransactionStatus::Expired
+        );
+
+        let runs = SyncRunRepository::list_recent(&pool, 10).await.unwrap();
+        assert_eq!(runs[0].expired_transactions, 1);
+    }
+
+    #[sqlx::test]
+    async fn expiry_waits_when_statuses_are_unavailable(pool: PgPool) {
+        let harness = Harness::new(&pool);
+        register_wallet(&pool, WalletSettings::default()).await;
+        store_pending(&pool, &signature(1), chrono::Duration::hours(1)).await;
+        harness.chain.set_status_error(Some("statuses unavailable"));
+
+        let report = harness.sync.sync_all_wallets().await.unwrap();
+        assert_eq!(report.expired_transactions, 0);
+        assert_eq!(
+            status_of(&pool, &signature(1)).await,
+            TransactionStatus::Pending
+        );
+    }
+}
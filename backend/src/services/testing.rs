@@ -0,0 +1,202 @@
+//! Wiring shared by the crate's tests: services over `MockChainClient` and a
+//! per-test database, and fixtures for the transactions they sync.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::domain::{from_base_units, Chain, Wallet, WalletSettings};
+use crate::repository::WalletRepository;
+use crate::services::audit::AuditService;
+use crate::services::balance_cache::BalanceCache;
+use crate::services::chain::ChainClient;
+use crate::services::events::EventBus;
+use crate::services::fx::FxService;
+use crate::services::leader::LeaderLock;
+use crate::services::mock_chain::MockChainClient;
+use crate::services::solana::ParsedTransaction;
+use crate::services::swap::SwapService;
+use crate::services::sync::SyncService;
+use crate::services::token_accounts::TokenAccountService;
+use crate::services::token_registry::TokenRegistry;
+use crate::services::webhook::WebhookService;
+use crate::AppState;
+
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const WALLET: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+pub const SENDER: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+pub const WEBHOOK_SECRET: &str = "test-secret";
+
+/// Nothing listens on the discard port, so deliveries that aren't dry runs
+/// fail fast
+pub const UNREACHABLE_URL: &str = "http://127.0.0.1:9/hook";
+
+/// Services wired the way `AppState::build` does, over a mock chain
+pub struct Harness {
+    pub chain: Arc<MockChainClient>,
+    pub webhook: Arc<WebhookService>,
+    pub sync: Arc<SyncService>,
+    pub leader: Arc<LeaderLock>,
+    fx: Arc<FxService>,
+    tokens: Arc<TokenRegistry>,
+    events: Arc<EventBus>,
+}
+
+impl Harness {
+    /// Webhooks are dry runs: recorded and marked delivered, never sent
+    pub fn new(pool: &PgPool) -> Self {
+        Self::with_leader(pool, "test")
+    }
+
+    /// A harness contending for the background-sync lock under `namespace`
+    pub fn with_leader(pool: &PgPool, namespace: &str) -> Self {
+        Self::build(pool, namespace, true)
+    }
+
+    /// Webhooks are sent to the wallet's URL, with attempt history, quotas
+    /// and circuits as in production
+    pub fn delivering(pool: &PgPool) -> Self {
+        Self::build(pool, "test", false)
+    }
+
+    fn build(pool: &PgPool, namespace: &str, dry_run: bool) -> Self {
+        let chain = Arc::new(MockChainClient::new(USDC_MINT));
+        let leader = Arc::new(LeaderLock::new(pool.clone(), namespace));
+        let fx = Arc::new(FxService::new(
+            pool.clone(),
+            UNREACHABLE_URL,
+            Duration::from_secs(60),
+            leader.clone(),
+            UNREACHABLE_URL,
+            USDC_MINT,
+        ));
+        let tokens = Arc::new(TokenRegistry::new(pool.clone(), chain.clone()));
+        let webhook = Arc::new(WebhookService::new(
+            pool.clone(),
+            WEBHOOK_SECRET.to_string(),
+            fx.clone(),
+            tokens.clone(),
+            dry_run,
+            "stablecoin-pay-test".to_string(),
+            None,
+        ));
+        let events = Arc::new(EventBus::new());
+        let sync = Arc::new(SyncService::new(
+            pool.clone(),
+            chain.clone(),
+            webhook.clone(),
+            events.clone(),
+            leader.clone(),
+            tokens.clone(),
+            Duration::from_secs(3600),
+            90,
+            30,
+            Duration::from_secs(5),
+            Duration::from_secs(600),
+        ));
+
+        Self {
+            chain,
+            webhook,
+            sync,
+            leader,
+            fx,
+            tokens,
+            events,
+        }
+    }
+
+    /// An `AppState` over this harness's services, for calling handlers
+    /// directly. Config comes from the environment, as in `AppState::build`.
+    pub fn state(&self, pool: &PgPool) -> Arc<AppState> {
+        let config = Config::from_env().expect("test config");
+        let chain: Arc<dyn ChainClient> = self.chain.clone();
+
+        Arc::new(AppState {
+            db: Database::from_pool(pool.clone()),
+            solana: chain.clone(),
+            webhook: self.webhook.clone(),
+            sync: self.sync.clone(),
+            events: self.events.clone(),
+            leader: self.leader.clone(),
+            fx: self.fx.clone(),
+            swap: Arc::new(SwapService::new(
+                &config.jupiter_api_url,
+                USDC_MINT,
+                config.swap_input_mints.clone(),
+                config.swap_max_price_impact_pct,
+                chrono::Duration::seconds(config.swap_quote_ttl_secs),
+            )),
+            token_accounts: Arc::new(TokenAccountService::new(chain.clone(), USDC_MINT)),
+            tokens: self.tokens.clone(),
+            balances: Arc::new(BalanceCache::new(
+                chain,
+                Duration::from_secs(config.balance_cache_ttl_secs),
+            )),
+            audit: Arc::new(AuditService::new(pool.clone())),
+            config,
+        })
+    }
+}
+
+/// Register `WALLET` with a webhook URL on top of `settings`
+pub async fn register_wallet(pool: &PgPool, settings: WalletSettings) -> Wallet {
+    let settings = WalletSettings {
+        webhook_url: settings
+            .webhook_url
+            .or_else(|| Some(UNREACHABLE_URL.to_string())),
+        ..settings
+    };
+
+    WalletRepository::create(pool, Chain::Solana, WALLET, &settings)
+        .await
+        .expect("register wallet")
+        .wallet
+}
+
+/// A signature that passes validation, distinct per `seed`
+pub fn signature(seed: u8) -> String {
+    bs58::encode([seed; 64]).into_string()
+}
+
+/// A USDC receive into `WALLET` from `SENDER` a minute ago
+pub fn receive(signature: &str, amount_raw: u64) -> ParsedTransaction {
+    transfer(signature, "receive", amount_raw, Utc::now() - chrono::Duration::minutes(1))
+}
+
+/// A USDC movement of `WALLET` at `block_time`
+pub fn transfer(
+    signature: &str,
+    tx_type: &str,
+    amount_raw: u64,
+    block_time: DateTime<Utc>,
+) -> ParsedTransaction {
+    ParsedTransaction {
+        signature: signature.to_string(),
+        wallet_address: WALLET.to_string(),
+        tx_type: tx_type.to_string(),
+        amount: from_base_units(amount_raw, 6).expect("USDC amounts always fit"),
+        amount_raw,
+        decimals: 6,
+        token_mint: USDC_MINT.to_string(),
+        counterparty: SENDER.to_string(),
+        block_time,
+        transfer_legs: Vec::new(),
+        memo: None,
+    }
+}
+
+/// Serve `router` on an ephemeral local port for the rest of the test,
+/// returning its base URL
+pub async fn serve(router: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+    let addr = listener.local_addr().expect("mock server address");
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    format!("http://{}", addr)
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::AppError;
+use crate::services::chain::ChainClient;
+use crate::services::solana::SolanaClient;
+use crate::services::solana_utils;
+
+/// How long an account existence check is reused
+const EXISTS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// A wallet's associated token account for one mint
+#[derive(Debug, Serialize)]
+pub struct TokenAccountStatus {
+    pub mint: String,
+    pub token_account: String,
+    pub exists: bool,
+}
+
+/// An unsigned transaction creating a wallet's USDC token account, for the
+/// fee payer to sign and send
+#[derive(Debug, Serialize)]
+pub struct PrepareTransaction {
+    /// Base64-encoded serialized transaction
+    pub transaction: String,
+    pub last_valid_block_height: u64,
+    pub fee_payer: String,
+    pub mint: String,
+    pub token_account: String,
+    /// The account already existed; sending the transaction is a no-op
+    pub exists: bool,
+}
+
+/// Derives receiving wallets' associated token accounts and builds the
+/// transaction creating them, so the first payer doesn't fund the rent
+pub struct TokenAccountService {
+    chain: Arc<dyn ChainClient>,
+    usdc_mint: String,
+    /// Keyed by token account address
+    exists_cache: Mutex<HashMap<String, (bool, Instant)>>,
+}
+
+impl TokenAccountService {
+    pub fn new(chain: Arc<dyn ChainClient>, usdc_mint: &str) -> Self {
+        Self {
+            chain,
+            usdc_mint: usdc_mint.to_string(),
+            exists_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Mints wallets receive; only USDC for now
+    fn mints(&self) -> Vec<&str> {
+        vec![self.usdc_mint.as_str()]
+    }
+
+    /// Whether the wallet has a token account for each receiving mint
+    pub async fn status(&self, wallet_address: &str) -> Result<Vec<TokenAccountStatus>, AppError> {
+        let wallet = SolanaClient::validate_address(wallet_address)?;
+
+        let mut accounts = Vec::new();
+        for mint in self.mints() {
+            let mint_key = SolanaClient::validate_address(mint)?;
+            let token_account = solana_utils::associated_token_address(&wallet, &mint_key);
+            let exists = self.account_exists(&token_account).await?;
+
+            accounts.push(TokenAccountStatus {
+                mint: mint.to_string(),
+                token_account: token_account.to_string(),
+                exists,
+            });
+        }
+
+        Ok(accounts)
+    }
+
+    /// Build the transaction creating the wallet's USDC token account, paid
+    /// by `fee_payer`. Built even if the account exists: the instruction is
+    /// idempotent.
+    pub async fn build_prepare(
+        &self,
+        wallet_address: &str,
+        fee_payer: &str,
+    ) -> Result<PrepareTransaction, AppError> {
+        let wallet = SolanaClient::validate_address(wallet_address)?;
+        let payer = SolanaClient::validate_address(fee_payer)?;
+        let mint = SolanaClient::validate_address(&self.usdc_mint)?;
+
+        let token_account = solana_utils::associated_token_address(&wallet, &mint);
+        let exists = self.account_exists(&token_account).await?;
+
+        let latest = self.chain.get_latest_blockhash().await?;
+        let blockhash: Hash = latest
+            .blockhash
+            .parse()
+            .map_err(|_| AppError::SolanaRpc(format!("Invalid blockhash: {}", latest.blockhash)))?;
+
+        let instruction =
+            solana_utils::create_associated_token_account_idempotent(&payer, &wallet, &mint);
+        let transaction =
+            solana_utils::encode_unsigned_transaction(&[instruction], &payer, &blockhash)?;
+
+        Ok(PrepareTransaction {
+            transaction,
+            last_valid_block_height: latest.last_valid_block_height,
+            fee_payer: payer.to_string(),
+            mint: mint.to_string(),
+            token_account: token_account.to_string(),
+            exists,
+        })
+    }
+
+    async fn account_exists(&self, address: &Pubkey) -> Result<bool, AppError> {
+        let key = address.to_string();
+
+        if let Some((exists, checked_at)) = self.exists_cache.lock().unwrap().get(&key) {
+            if checked_at.elapsed() < EXISTS_CACHE_TTL {
+                return Ok(*exists);
+            }
+        }
+
+        let exists = self.chain.account_exists(&key).await?;
+        self.exists_cache
+            .lock()
+            .unwrap()
+            .insert(key, (exists, Instant::now()));
+
+        Ok(exists)
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+
+use crate::domain::{TokenMetadata, TokenMetadataSource};
+use crate::error::AppError;
+use crate::repository::TokenMetadataRepository;
+use crate::services::chain::ChainClient;
+use crate::services::solana_utils;
+
+/// Entries kept in memory; past this only stale fallbacks are evicted
+const MAX_ENTRIES: usize = 10_000;
+
+/// How long a mint that couldn't be resolved keeps its fallback before the
+/// chain is asked again
+const FALLBACK_TTL: Duration = Duration::from_secs(300);
+
+/// Account discriminator of Metaplex `MetadataV1`
+const METADATA_V1_KEY: u8 = 4;
+
+/// Size of an SPL Token mint account; Token-2022 mints start with the same layout
+const MINT_LEN: usize = 82;
+
+struct KnownToken {
+    mint: &'static str,
+    symbol: &'static str,
+    name: &'static str,
+    decimals: i16,
+    logo_uri: Option<&'static str>,
+}
+
+/// Well-known mints, answered without any RPC or database lookup
+const KNOWN_TOKENS: &[KnownToken] = &[
+    KnownToken {
+        mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        symbol: "USDC",
+        name: "USD Coin",
+        decimals: 6,
+        logo_uri: Some(
+            "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/\
+             EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v/logo.png",
+        ),
+    },
+    KnownToken {
+        mint: "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+        symbol: "USDT",
+        name: "USDT",
+        decimals: 6,
+        logo_uri: Some(
+            "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/\
+             Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB/logo.svg",
+        ),
+    },
+    KnownToken {
+        mint: "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo",
+        symbol: "PYUSD",
+        name: "PayPal USD",
+        decimals: 6,
+        logo_uri: None,
+    },
+    // Circle's devnet USDC, for USDC_MINT on devnet
+    KnownToken {
+        mint: "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU",
+        symbol: "USDC",
+        name: "USD Coin (Devnet)",
+        decimals: 6,
+        logo_uri: None,
+    },
+    KnownToken {
+        mint: "So11111111111111111111111111111111111111112",
+        symbol: "SOL",
+        name: "Wrapped SOL",
+        decimals: 9,
+        logo_uri: None,
+    },
+];
+
+impl KnownToken {
+    fn metadata(&self) -> TokenMetadata {
+        TokenMetadata {
+            mint: self.mint.to_string(),
+            symbol: self.symbol.to_string(),
+            name: self.name.to_string(),
+            decimals: Some(self.decimals),
+            logo_uri: self.logo_uri.map(str::to_string),
+            source: TokenMetadataSource::Static,
+        }
+    }
+}
+
+struct CachedToken {
+    token: TokenMetadata,
+    /// Set for fallbacks, which are retried once it passes
+    expires: Option<Instant>,
+}
+
+/// Resolves mint addresses to symbol, name and decimals for responses and
+/// webhook payloads: the bundled list first, then the `token_metadata`
+/// table, then the mint's Metaplex metadata account on chain. A mint that
+/// can't be resolved degrades to its shortened address instead of failing.
+pub struct TokenRegistry {
+    pool: PgPool,
+    chain: Arc<dyn ChainClient>,
+    cache: Mutex<HashMap<String, CachedToken>>,
+}
+
+impl TokenRegistry {
+    pub fn new(pool: PgPool, chain: Arc<dyn ChainClient>) -> Self {
+        Self {
+            pool,
+            chain,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Metadata of `mint`; never fails, see `TokenMetadata::fallback`
+    pub async fn resolve(&self, mint: &str) -> TokenMetadata {
+        if let Some(known) = KNOWN_TOKENS.iter().find(|t| t.mint == mint) {
+            return known.metadata();
+        }
+
+        if let Some(cached) = self.cache.lock().unwrap().get(mint) {
+            if cached
+                .expires
+                .is_none_or(|expires| expires > Instant::now())
+            {
+                return cached.token.clone();
+            }
+        }
+
+        match TokenMetadataRepository::find(&self.pool, mint).await {
+            Ok(Some(token)) => {
+                self.remember(token.clone(), None);
+                return token;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load token metadata of {}: {}", mint, e),
+        }
+
+        match self.fetch(mint).await {
+            Ok(Some(token)) => {
+                if let Err(e) = TokenMetadataRepository::upsert(&self.pool, &token).await {
+                    tracing::warn!("Failed to store token metadata of {}: {}", mint, e);
+                }
+                self.remember(token.clone(), None);
+                token
+            }
+            Ok(None) => {
+                let token = TokenMetadata::fallback(mint, None);
+                self.remember(token.clone(), Some(Instant::now() + FALLBACK_TTL));
+                token
+            }
+            Err(e) => {
+                tracing::warn!("Failed to resolve token metadata of {}: {}", mint, e);
+                let token = TokenMetadata::fallback(mint, None);
+                self.remember(token.clone(), Some(Instant::now() + FALLBACK_TTL));
+                token
+            }
+        }
+    }
+
+    /// The bundled tokens followed by every mint resolved from the chain
+    pub async fn list(&self) -> Result<Vec<TokenMetadata>, AppError> {
+        let mut tokens: Vec<TokenMetadata> =
+            KNOWN_TOKENS.iter().map(KnownToken::metadata).collect();
+        tokens.extend(TokenMetadataRepository::list(&self.pool).await?);
+
+        Ok(tokens)
+    }
+
+    /// Read the mint account for its decimals and the Metaplex metadata
+    /// account for its name and symbol. `None` when `mint` isn't a mint.
+    async fn fetch(&self, mint: &str) -> Result<Option<TokenMetadata>, AppError> {
+        let Ok(mint_key) = Pubkey::from_str(mint) else {
+            return Ok(None);
+        };
+
+        let mint_data = self.chain.get_account_data(mint).await?;
+        let Some(decimals) = mint_data.as_deref().and_then(mint_decimals) else {
+            return Ok(None);
+        };
+
+        let metadata_address = solana_utils::metadata_address(&mint_key).to_string();
+        let metadata = self
+            .chain
+            .get_account_data(&metadata_address)
+            .await?
+            .and_then(|data| decode_metadata(&data));
+
+        let mut token = TokenMetadata::fallback(mint, Some(decimals as i16));
+        if let Some((name, symbol)) = metadata {
+            if !symbol.is_empty() {
+                token.symbol = symbol;
+                token.name = if name.is_empty() {
+                    token.symbol.clone()
+                } else {
+                    name
+                };
+                token.source = TokenMetadataSource::Metaplex;
+            }
+        }
+
+        Ok(Some(token))
+    }
+
+    fn remember(&self, token: TokenMetadata, expires: Option<Instant>) {
+        let mut cache = self.cache.lock().unwrap();
+        // Any mint can be looked up, so bound the map
+        if cache.len() >= MAX_ENTRIES {
+            let now = Instant::now();
+            cache.retain(|_, cached| cached.expires.is_none_or(|expires| expires > now));
+            if cache.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+        cache.insert(token.mint.clone(), CachedToken { token, expires });
+    }
+}
+
+/// Decimals of an initialized mint account
+fn mint_decimals(data: &[u8]) -> Option<u8> {
+    // mint_authority (36) + supply (8), then decimals and is_initialized
+    if data.len() < MINT_LEN || data[45] != 1 {
+        return None;
+    }
+    Some(data[44])
+}
+
+/// Name and symbol of a Metaplex metadata account, with the padding the
+/// program stores them with stripped
+fn decode_metadata(data: &[u8]) -> Option<(String, String)> {
+    if data.first() != Some(&METADATA_V1_KEY) {
+        return None;
+    }
+
+    // key (1) + update_authority (32) + mint (32), then Borsh strings
+    let mut rest = data.get(65..)?;
+    let name = read_borsh_string(&mut rest)?;
+    let symbol = read_borsh_string(&mut rest)?;
+
+    // Fit the token_metadata columns
+    Some((
+        name.chars().take(64).collect(),
+        symbol.chars().take(32).collect(),
+    ))
+}
+
+/// A u32 little-endian length followed by that many bytes of UTF-8
+fn read_borsh_string(data: &mut &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let bytes = data.get(4..4usize.checked_add(len)?)?;
+    *data = &data[4 + len..];
+
+    Some(
+        String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_chain::MockChainClient;
+    use crate::services::testing::USDC_MINT;
+
+    const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    /// An initialized mint account with `decimals`
+    fn mint_account(decimals: u8) -> Vec<u8> {
+        let mut data = vec![0; MINT_LEN];
+        data[44] = decimals;
+        data[45] = 1;
+        data
+    }
+
+    /// A Borsh string the way the metadata program writes it: the length of
+    /// the field, NUL padding included
+    fn padded(value: &str, width: usize) -> Vec<u8> {
+        let mut field = value.as_bytes().to_vec();
+        field.resize(width, 0);
+        let mut data = (width as u32).to_le_bytes().to_vec();
+        data.extend(field);
+        data
+    }
+
+    /// A `MetadataV1` account as created on chain, with the creators and
+    /// other fields after the URI left out
+    fn metadata_account(name: &str, symbol: &str) -> Vec<u8> {
+        let mut data = vec![METADATA_V1_KEY];
+        data.extend([7; 32]);
+        data.extend(Pubkey::from_str(BONK_MINT).unwrap().to_bytes());
+        data.extend(padded(name, 32));
+        data.extend(padded(symbol, 10));
+        data.extend(padded("https://arweave.net/bonk.json", 200));
+        data.extend([0xf4, 0x01, 0]);
+        data
+    }
+
+    #[test]
+    fn metaplex_padding_is_stripped() {
+        let decoded = decode_metadata(&metadata_account("Bonk", "Bonk"));
+        assert_eq!(decoded, Some(("Bonk".to_string(), "Bonk".to_string())));
+
+        let decoded = decode_metadata(&metadata_account("  Wrapped Thing  ", "WTHG "));
+        assert_eq!(
+            decoded,
+            Some(("Wrapped Thing".to_string(), "WTHG".to_string()))
+        );
+    }
+
+    #[test]
+    fn other_accounts_and_short_data_are_not_metadata() {
+        let mut edition = metadata_account("Bonk", "Bonk");
+        edition[0] = 6;
+        assert_eq!(decode_metadata(&edition), None);
+
+        // Cut off inside the symbol
+        let full = metadata_account("Bonk", "Bonk");
+        assert_eq!(decode_metadata(&full[..65 + 36 + 6]), None);
+        assert_eq!(decode_metadata(&full[..40]), None);
+
+        // A length past the end of the data
+        let mut oversized = full[..65].to_vec();
+        oversized.extend(u32::MAX.to_le_bytes());
+        assert_eq!(decode_metadata(&oversized), None);
+    }
+
+    #[test]
+    fn long_names_are_cut_to_the_columns() {
+        let name = "N".repeat(100);
+        let mut data = metadata_account("", "");
+        data.truncate(65);
+        data.extend(padded(&name, 100));
+        data.extend(padded(&"S".repeat(40), 40));
+
+        let (name, symbol) = decode_metadata(&data).unwrap();
+        assert_eq!((name.len(), symbol.len()), (64, 32));
+    }
+
+    #[test]
+    fn only_initialized_mints_have_decimals() {
+        assert_eq!(mint_decimals(&mint_account(5)), Some(5));
+
+        let mut uninitialized = mint_account(5);
+        uninitialized[45] = 0;
+        assert_eq!(mint_decimals(&uninitialized), None);
+        assert_eq!(mint_decimals(&mint_account(5)[..MINT_LEN - 1]), None);
+    }
+
+    fn registry(pool: &PgPool) -> (TokenRegistry, Arc<MockChainClient>) {
+        let chain = Arc::new(MockChainClient::new(USDC_MINT));
+        (TokenRegistry::new(pool.clone(), chain.clone()), chain)
+    }
+
+    #[sqlx::test]
+    async fn a_mint_resolves_from_its_metaplex_account(pool: PgPool) {
+        let (tokens, chain) = registry(&pool);
+        let mint = Pubkey::from_str(BONK_MINT).unwrap();
+        chain.set_account_data(BONK_MINT, mint_account(5));
+        chain.set_account_data(
+            &solana_utils::metadata_address(&mint).to_string(),
+            metadata_account("Bonk", "Bonk"),
+        );
+
+        let token = tokens.resolve(BONK_MINT).await;
+        assert_eq!(
+            (token.symbol.as_str(), token.name.as_str()),
+            ("Bonk", "Bonk")
+        );
+        assert_eq!(token.decimals, Some(5));
+        assert_eq!(token.source, TokenMetadataSource::Metaplex);
+
+        // Stored, so a fresh registry answers without the chain
+        let (fresh, _) = registry(&pool);
+        assert_eq!(
+            fresh.resolve(BONK_MINT).await.source,
+            TokenMetadataSource::Metaplex
+        );
+    }
+
+    #[sqlx::test]
+    async fn a_mint_without_metadata_keeps_its_decimals(pool: PgPool) {
+        let (tokens, chain) = registry(&pool);
+        chain.set_account_data(BONK_MINT, mint_account(5));
+
+        let token = tokens.resolve(BONK_MINT).await;
+        assert_eq!(
+            token.symbol,
+            TokenMetadata::fallback(BONK_MINT, None).symbol
+        );
+        assert_eq!(token.decimals, Some(5));
+        assert_eq!(token.source, TokenMetadataSource::Mint);
+
+        // Not a mint at all
+        let (tokens, _) = registry(&pool);
+        let unknown = tokens.resolve(crate::services::testing::WALLET).await;
+        assert_eq!(unknown.source, TokenMetadataSource::Unknown);
+        assert_eq!(unknown.decimals, None);
+    }
+}
@@ -1,17 +1,38 @@
-use chrono::Utc;
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use reqwest::header::{HeaderName, HeaderValue};
 use sha2::Sha256;
 use sqlx::PgPool;
-use std::time::Duration;
-use tracing::{error, info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, instrument, warn};
 
-use crate::domain::{PaymentReceivedPayload, Transaction, Wallet, WebhookPayload, WebhookStatus};
+use crate::domain::{
+    redact_payload, to_base_units, PaymentReceivedPayload, SignatureEncoding, Transaction, Wallet,
+    WebhookEvent, WebhookGroup, WebhookPayload, WebhookStatus,
+};
 use crate::error::AppError;
-use crate::repository::WebhookEventRepository;
+use crate::repository::{WalletGroupRepository, WebhookEventRepository};
+use crate::services::circuit_breaker::{CircuitBreaker, CircuitDecision, CircuitStatus};
+use crate::services::fx::FxService;
+use crate::services::token_registry::TokenRegistry;
+use crate::services::webhook_delivery::{
+    signature_header, Delivery, DeliveryError, HttpDelivery, RedisStreamDelivery, WebhookDelivery,
+};
+
+pub use crate::services::webhook_delivery::{
+    WebhookHeader, WebhookRequest, DEFAULT_SIGNATURE_HEADER,
+};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// How long past its expiry an inspection token is still accepted, for
+/// clocks drifting between the replica that issued it and the one checking it
+const INSPECTION_CLOCK_SKEW_SECS: i64 = 30;
+
 /// Retry delays for webhook delivery (exponential backoff)
 const RETRY_DELAYS: [Duration; 3] = [
     Duration::from_secs(1),
@@ -22,33 +43,323 @@ const RETRY_DELAYS: [Duration; 3] = [
 /// Maximum number of delivery attempts before marking as failed
 const MAX_ATTEMPTS: i32 = 3;
 
+/// Window a wallet's `max_webhooks_per_minute` quota is counted over, and how
+/// long an event over quota is deferred
+const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+/// Maximum number of custom headers a wallet may configure
+const MAX_CUSTOM_HEADERS: usize = 20;
+
+/// Maximum length of a custom header name or value
+const MAX_CUSTOM_HEADER_LEN: usize = 1024;
+
+/// Maximum length of a wallet's signature header name (matches the column)
+const MAX_SIGNATURE_HEADER_LEN: usize = 100;
+
+/// Headers we set ourselves and that merchants can't override
+const RESERVED_HEADERS: &[&str] = &[
+    "content-type",
+    "content-length",
+    "host",
+    "transfer-encoding",
+    "connection",
+    "user-agent",
+    "x-webhook-signature",
+    "x-webhook-event",
+    "x-webhook-event-id",
+];
+
+/// Validate merchant-supplied webhook headers: valid names/values (no CR/LF
+/// injection) and none of the headers we control.
+pub fn validate_custom_headers(headers: &HashMap<String, String>) -> Result<(), AppError> {
+    if headers.len() > MAX_CUSTOM_HEADERS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} webhook headers are allowed",
+            MAX_CUSTOM_HEADERS
+        )));
+    }
+
+    for (name, value) in headers {
+        if name.len() > MAX_CUSTOM_HEADER_LEN || value.len() > MAX_CUSTOM_HEADER_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Webhook header names and values must be at most {} bytes",
+                MAX_CUSTOM_HEADER_LEN
+            )));
+        }
+
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| AppError::BadRequest(format!("Invalid webhook header name: {}", name)))?;
+
+        if RESERVED_HEADERS.contains(&header_name.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Webhook header {} is reserved",
+                name
+            )));
+        }
+
+        HeaderValue::from_str(value).map_err(|_| {
+            AppError::BadRequest(format!("Invalid value for webhook header {}", name))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Validate a wallet's signature header name: a valid header name, at most
+/// `MAX_SIGNATURE_HEADER_LEN` bytes, and not one of the other headers we set
+pub fn validate_signature_header(name: &str) -> Result<(), AppError> {
+    if name.len() > MAX_SIGNATURE_HEADER_LEN {
+        return Err(AppError::BadRequest(format!(
+            "webhook_signature_header must be at most {} bytes",
+            MAX_SIGNATURE_HEADER_LEN
+        )));
+    }
+
+    let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+        AppError::BadRequest(format!("Invalid webhook_signature_header: {}", name))
+    })?;
+
+    if !header_name.as_str().eq_ignore_ascii_case(DEFAULT_SIGNATURE_HEADER)
+        && RESERVED_HEADERS.contains(&header_name.as_str())
+    {
+        return Err(AppError::BadRequest(format!(
+            "Webhook header {} is reserved",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
 pub struct WebhookService {
-    client: Client,
     pool: PgPool,
     webhook_secret: String,
+    circuit: CircuitBreaker,
+    fx: Arc<FxService>,
+    tokens: Arc<TokenRegistry>,
+    http: HttpDelivery,
+    /// Set when WEBHOOK_REDIS_URL is configured
+    stream: Option<RedisStreamDelivery>,
+    /// Deliveries deferred by a wallet's quota since startup
+    deferred: AtomicU64,
+    /// Payments below a wallet's minimum amount not notified since startup
+    skipped_dust: AtomicU64,
+    /// Nothing is sent, so no attempt history, quota or circuit bookkeeping
+    dry_run: bool,
 }
 
 impl WebhookService {
-    pub fn new(pool: PgPool, webhook_secret: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .expect("Failed to create HTTP client");
-
+    /// `dry_run` logs deliveries and marks events delivered instead of
+    /// sending them, without recording attempts or touching quotas and
+    /// circuits
+    pub fn new(
+        pool: PgPool,
+        webhook_secret: String,
+        fx: Arc<FxService>,
+        tokens: Arc<TokenRegistry>,
+        dry_run: bool,
+        user_agent: String,
+        stream: Option<RedisStreamDelivery>,
+    ) -> Self {
         Self {
-            client,
+            http: HttpDelivery::new(pool.clone(), dry_run, user_agent),
+            stream,
             pool,
             webhook_secret,
+            circuit: CircuitBreaker::new(),
+            fx,
+            tokens,
+            deferred: AtomicU64::new(0),
+            skipped_dust: AtomicU64::new(0),
+            dry_run,
         }
     }
 
+    /// Check the wallet's per-minute webhook quota before delivering an
+    /// event. Over quota, the event stays pending with its next attempt pushed
+    /// back by a window, and `false` is returned.
+    async fn quota_allows(
+        &self,
+        wallet_address: &str,
+        max_per_minute: i32,
+        event_id: sqlx::types::Uuid,
+    ) -> Result<bool, AppError> {
+        if self.dry_run {
+            return Ok(true);
+        }
+
+        let window = chrono::Duration::from_std(QUOTA_WINDOW).expect("window fits");
+        let recent = WebhookEventRepository::count_attempted_since(
+            &self.pool,
+            wallet_address,
+            Utc::now() - window,
+        )
+        .await?;
+
+        if recent < max_per_minute as i64 {
+            return Ok(true);
+        }
+
+        let until = Utc::now() + window;
+        let reason = format!(
+            "Deferred: webhook quota of {} per minute reached, next attempt after {}",
+            max_per_minute,
+            until.to_rfc3339()
+        );
+        WebhookEventRepository::defer(&self.pool, event_id, until, &reason).await?;
+        self.deferred.fetch_add(1, Ordering::Relaxed);
+        info!(
+            event_id = %event_id,
+            wallet = %wallet_address,
+            max_per_minute,
+            "Webhook delivery deferred, wallet over quota"
+        );
+        Ok(false)
+    }
+
+    /// Consult the endpoint's circuit breaker before a delivery attempt. When
+    /// the circuit is open the skip reason is recorded on the event without
+    /// counting an attempt, and `false` is returned.
+    async fn circuit_allows(
+        &self,
+        wallet_address: &str,
+        event_id: sqlx::types::Uuid,
+    ) -> Result<bool, AppError> {
+        if self.dry_run {
+            return Ok(true);
+        }
+
+        // After a restart, rebuild the endpoint's state from persisted attempts
+        if !self.circuit.is_tracked(wallet_address) {
+            let recent = WebhookEventRepository::recent_attempts(
+                &self.pool,
+                wallet_address,
+                Utc::now() - CircuitBreaker::failure_window(),
+                CircuitBreaker::failure_threshold() as i64,
+            )
+            .await?;
+
+            let failures: Vec<_> = recent
+                .iter()
+                .take_while(|(succeeded, _)| !succeeded)
+                .map(|(_, at)| *at)
+                .collect();
+            self.circuit.seed(wallet_address, &failures);
+        }
+
+        match self.circuit.check(wallet_address) {
+            CircuitDecision::Allow | CircuitDecision::Probe => Ok(true),
+            CircuitDecision::Skip { until } => {
+                let reason = format!(
+                    "Skipped: circuit open for endpoint until {}",
+                    until.to_rfc3339()
+                );
+                WebhookEventRepository::record_skip(&self.pool, event_id, &reason).await?;
+                warn!(
+                    event_id = %event_id,
+                    wallet = %wallet_address,
+                    "Webhook delivery skipped, circuit open"
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// Feed a delivery outcome to the endpoint's circuit. Dry runs reach no
+    /// endpoint, so they leave it alone.
+    fn record_circuit_outcome(&self, wallet_address: &str, delivered: bool) {
+        if self.dry_run {
+            return;
+        }
+        if delivered {
+            self.circuit.record_success(wallet_address);
+        } else {
+            self.circuit.record_failure(wallet_address);
+        }
+    }
+
+    /// Whether wallets may publish events to a Redis stream
+    pub fn stream_enabled(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Whether events for this wallet have somewhere to go
+    fn has_destination(wallet: &Wallet) -> bool {
+        Self::uses_stream(wallet) || wallet.webhook_url.as_deref().is_some_and(|u| !u.is_empty())
+    }
+
+    /// Whether the wallet's events go to a Redis stream rather than over HTTP
+    fn uses_stream(wallet: &Wallet) -> bool {
+        wallet.webhook_stream.as_deref().is_some_and(|s| !s.is_empty())
+    }
+
+    /// One delivery attempt through the wallet's transport, appended to the
+    /// event's attempt history whatever the outcome
+    async fn attempt_delivery(
+        &self,
+        wallet: &Wallet,
+        event_id: sqlx::types::Uuid,
+        event_type: &str,
+        payload: &[u8],
+        signature: &str,
+    ) -> Result<(), DeliveryError> {
+        let delivery = Delivery {
+            event_id,
+            event_type,
+            payload,
+            signature,
+        };
+
+        let attempted_at = Utc::now();
+        let started = Instant::now();
+        let outcome = if Self::uses_stream(wallet) {
+            match self.stream.as_ref() {
+                Some(stream) => stream.deliver(wallet, &delivery).await,
+                None => Err(DeliveryError::retryable(
+                    "Webhook stream set but WEBHOOK_REDIS_URL is not configured".into(),
+                )),
+            }
+        } else {
+            self.http.deliver(wallet, &delivery).await
+        };
+        let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        let (status, error) = match &outcome {
+            Ok(status) => (*status, None),
+            Err(failure) => (failure.status, Some(failure.error.to_string())),
+        };
+        if self.dry_run {
+            return outcome.map(|_| ());
+        }
+
+        // Best-effort, like the response details: history must not fail a delivery
+        if let Err(e) = WebhookEventRepository::record_delivery_attempt(
+            &self.pool,
+            event_id,
+            attempted_at,
+            duration_ms,
+            outcome.is_ok(),
+            status.map(i32::from),
+            error.as_deref(),
+        )
+        .await
+        {
+            warn!(event_id = %event_id, error = %e, "Failed to record webhook delivery attempt");
+        }
+
+        outcome.map(|_| ())
+    }
+
     /// Sign a payload using HMAC-SHA256
-    fn sign_payload(&self, payload: &[u8]) -> String {
+    fn sign_payload(&self, payload: &[u8], encoding: SignatureEncoding) -> String {
         let mut mac = HmacSha256::new_from_slice(self.webhook_secret.as_bytes())
             .expect("HMAC can take key of any size");
         mac.update(payload);
-        let result = mac.finalize();
-        hex::encode(result.into_bytes())
+        let digest = mac.finalize().into_bytes();
+        match encoding {
+            SignatureEncoding::Hex => hex::encode(digest),
+            SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(digest),
+        }
     }
 
     /// Create a webhook event for a new transaction and attempt delivery
@@ -57,95 +368,194 @@ impl WebhookService {
         wallet: &Wallet,
         transaction: &Transaction,
     ) -> Result<(), AppError> {
-        // Check if we already have a webhook event for this transaction
-        if WebhookEventRepository::exists_for_transaction(&self.pool, &transaction.signature).await? {
+        // Skip payments below the wallet's notification threshold (dust),
+        // compared in base units to avoid Decimal rounding pitfalls
+        let min_raw = to_base_units(wallet.min_webhook_amount, transaction.decimals as u32)
+            .unwrap_or(i64::MAX);
+        if transaction.amount_raw < min_raw {
             info!(
+                wallet = %wallet.address,
                 signature = %transaction.signature,
+                amount = %transaction.amount,
+                min_webhook_amount = %wallet.min_webhook_amount,
+                "Payment below minimum webhook amount, skipping notification"
+            );
+            self.skipped_dust.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let token = self.tokens.resolve(&transaction.token_mint).await;
+        let mut payment_data = PaymentReceivedPayload::new(transaction, &token);
+        payment_data.display = self
+            .fx
+            .display(wallet.display_currency.as_deref(), transaction.amount)
+            .await;
+
+        self.notify_transaction_event(
+            wallet,
+            &transaction.signature,
+            "payment.received",
+            serde_json::to_value(&payment_data)?,
+        )
+        .await
+    }
+
+    /// Create a webhook event of the given type about a transaction and
+    /// attempt delivery. At most one event per (transaction, event type).
+    pub async fn notify_transaction_event(
+        &self,
+        wallet: &Wallet,
+        signature: &str,
+        event_type: &str,
+        data: serde_json::Value,
+    ) -> Result<(), AppError> {
+        // Check if we already have this webhook event for the transaction
+        if WebhookEventRepository::exists_for_transaction(&self.pool, signature, event_type).await? {
+            info!(
+                signature = %signature,
+                event_type = %event_type,
                 "Webhook event already exists for transaction, skipping"
             );
             return Ok(());
         }
 
-        // Skip if wallet has no webhook URL configured
-        let webhook_url = match &wallet.webhook_url {
-            Some(url) if !url.is_empty() => url.clone(),
-            _ => {
-                info!(
-                    wallet = %wallet.address,
-                    "No webhook URL configured for wallet, skipping notification"
-                );
-                return Ok(());
-            }
-        };
+        self.create_and_deliver(wallet, Some(signature), event_type, data)
+            .await
+    }
 
-        // Build the payload
-        let payment_data = PaymentReceivedPayload {
-            signature: transaction.signature.clone(),
-            wallet_address: transaction.wallet_address.clone(),
-            amount: transaction.amount.to_string(),
-            token: "USDC".to_string(),
-            counterparty: transaction.counterparty.clone(),
-            block_time: transaction.block_time,
-        };
+    /// Create a webhook event of the given type about the wallet itself
+    /// rather than a transaction, e.g. balance.threshold, and attempt delivery
+    pub async fn notify_wallet_event(
+        &self,
+        wallet: &Wallet,
+        event_type: &str,
+        data: serde_json::Value,
+    ) -> Result<(), AppError> {
+        self.create_and_deliver(wallet, None, event_type, data).await
+    }
+
+    /// Record a webhook event and attempt delivery, unless the wallet is
+    /// archived or has nowhere to send it
+    async fn create_and_deliver(
+        &self,
+        wallet: &Wallet,
+        signature: Option<&str>,
+        event_type: &str,
+        mut data: serde_json::Value,
+    ) -> Result<(), AppError> {
+        // Archived wallets don't get notifications
+        if wallet.is_archived() {
+            info!(
+                wallet = %wallet.address,
+                "Wallet is archived, skipping notification"
+            );
+            return Ok(());
+        }
 
+        // Skip if wallet has no webhook URL or stream configured
+        if !Self::has_destination(wallet) {
+            info!(
+                wallet = %wallet.address,
+                "No webhook URL configured for wallet, skipping notification"
+            );
+            return Ok(());
+        }
+
+        // Redact before storing, so the recorded event is what was sent
+        redact_payload(&mut data, &wallet.payload_redactions);
+
+        // Build the payload
         let payload = WebhookPayload {
-            event: "payment.received".to_string(),
+            event: event_type.to_string(),
+            chain: wallet.chain,
+            group: self.payload_group(wallet).await?,
             timestamp: Utc::now(),
-            data: serde_json::to_value(&payment_data)?,
+            data,
         };
 
-        let payload_json = serde_json::to_value(&payload)?;
+        // Serialize once: these exact bytes are signed and delivered on every attempt
+        let payload_body = serde_json::to_string(&payload)?;
 
         // Create the webhook event record
         let event = WebhookEventRepository::create(
             &self.pool,
             &wallet.address,
-            Some(&transaction.signature),
-            "payment.received",
-            payload_json.clone(),
+            wallet.chain,
+            signature,
+            event_type,
+            &payload_body,
         )
         .await?;
 
         info!(
             event_id = %event.id,
             wallet = %wallet.address,
-            signature = %transaction.signature,
-            "Created webhook event for payment.received"
+            signature = ?signature,
+            event_type = %event_type,
+            "Created webhook event"
         );
 
         // Attempt delivery
-        self.deliver_webhook(&webhook_url, event.id, &payload_json)
+        self.deliver_webhook(wallet, event.id, event_type, payload_body.as_bytes())
             .await
     }
 
     /// Attempt to deliver a webhook with retry logic
+    #[instrument(skip_all, fields(wallet = %wallet.address, event_id = %event_id))]
     async fn deliver_webhook(
         &self,
-        url: &str,
+        wallet: &Wallet,
         event_id: sqlx::types::Uuid,
-        payload: &serde_json::Value,
+        event_type: &str,
+        payload_bytes: &[u8],
     ) -> Result<(), AppError> {
-        let payload_bytes = serde_json::to_vec(payload)?;
-        let signature = self.sign_payload(&payload_bytes);
+        // Over quota the retry worker delivers it once the deferral passes
+        if !self
+            .quota_allows(&wallet.address, wallet.max_webhooks_per_minute, event_id)
+            .await?
+        {
+            return Ok(());
+        }
+
+        let signature = self.sign_payload(payload_bytes, wallet.webhook_signature_encoding);
 
         for (attempt, delay) in RETRY_DELAYS.iter().enumerate() {
             let attempt_num = attempt as i32 + 1;
 
-            match self.send_webhook(url, &payload_bytes, &signature).await {
+            // Leave the event pending for the retry worker while the endpoint is down
+            if !self.circuit_allows(&wallet.address, event_id).await? {
+                return Err(AppError::WebhookDeliveryFailed(
+                    "circuit open for webhook endpoint".into(),
+                ));
+            }
+
+            let started = Instant::now();
+            let outcome = self
+                .attempt_delivery(wallet, event_id, event_type, payload_bytes, &signature)
+                .await;
+            let duration_ms = started.elapsed().as_millis() as u64;
+
+            match outcome {
                 Ok(()) => {
+                    self.record_circuit_outcome(&wallet.address, true);
                     WebhookEventRepository::mark_delivered(&self.pool, event_id).await?;
                     info!(
                         event_id = %event_id,
+                        wallet = %wallet.address,
                         attempt = attempt_num,
+                        duration_ms,
                         "Webhook delivered successfully"
                     );
                     return Ok(());
                 }
-                Err(e) => {
-                    let error_msg = e.to_string();
+                Err(failure) => {
+                    self.record_circuit_outcome(&wallet.address, false);
+                    let error_msg = failure.error.to_string();
                     warn!(
                         event_id = %event_id,
+                        wallet = %wallet.address,
                         attempt = attempt_num,
+                        duration_ms,
                         error = %error_msg,
                         "Webhook delivery failed"
                     );
@@ -154,13 +564,20 @@ impl WebhookService {
                     WebhookEventRepository::increment_attempt(&self.pool, event_id, Some(&error_msg))
                         .await?;
 
+                    // The endpoint asked us to stop; don't retry
+                    if failure.gone {
+                        WebhookEventRepository::mark_failed(&self.pool, event_id, &error_msg).await?;
+                        return Err(failure.error);
+                    }
+
                     // If we've exhausted retries, mark as failed
-                    if attempt_num >= MAX_ATTEMPTS as i32 {
+                    if attempt_num >= MAX_ATTEMPTS {
                         WebhookEventRepository::mark_failed(&self.pool, event_id, &error_msg).await?;
                         error!(
                             event_id = %event_id,
-                            "Webhook delivery failed after {} attempts",
-                            MAX_ATTEMPTS
+                            wallet = %wallet.address,
+                            attempt = attempt_num,
+                            "Webhook delivery failed, attempts exhausted"
                         );
                         return Err(AppError::WebhookDeliveryFailed(error_msg));
                     }
@@ -174,34 +591,6 @@ impl WebhookService {
         Ok(())
     }
 
-    /// Send a single webhook HTTP request
-    async fn send_webhook(
-        &self,
-        url: &str,
-        payload: &[u8],
-        signature: &str,
-    ) -> Result<(), AppError> {
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .header("X-Webhook-Signature", format!("sha256={}", signature))
-            .body(payload.to_vec())
-            .send()
-            .await
-            .map_err(|e| AppError::WebhookDeliveryFailed(e.to_string()))?;
-
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(AppError::WebhookDeliveryFailed(format!(
-                "HTTP {} - {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
-            )))
-        }
-    }
-
     /// Retry all pending webhook events (for background job)
     pub async fn retry_pending_webhooks(&self) -> Result<u32, AppError> {
         let pending = WebhookEventRepository::find_pending(&self.pool, 100).await?;
@@ -227,8 +616,15 @@ impl WebhookService {
             .fetch_optional(&self.pool)
             .await?;
 
-            let webhook_url = match wallet.and_then(|w| w.webhook_url) {
-                Some(url) => url,
+            // Never deliver for archived wallets, even if queued before archival
+            if wallet.as_ref().is_some_and(|w| w.is_archived()) {
+                WebhookEventRepository::mark_failed(&self.pool, event.id, "Wallet archived")
+                    .await?;
+                continue;
+            }
+
+            let wallet = match wallet.filter(Self::has_destination) {
+                Some(wallet) => wallet,
                 None => {
                     WebhookEventRepository::mark_failed(
                         &self.pool,
@@ -240,26 +636,62 @@ impl WebhookService {
                 }
             };
 
+            // Everything that can stop the delivery runs before the circuit
+            // check, which may hand out the endpoint's only half-open probe
+            let payload_bytes = event.body_bytes()?;
+
+            if !self
+                .quota_allows(&event.wallet_address, wallet.max_webhooks_per_minute, event.id)
+                .await?
+            {
+                continue;
+            }
+
+            if !self.circuit_allows(&event.wallet_address, event.id).await? {
+                continue;
+            }
+
             // Attempt delivery (single attempt, not full retry loop)
-            let payload_bytes = serde_json::to_vec(&event.payload)?;
-            let signature = self.sign_payload(&payload_bytes);
+            let signature = self.sign_payload(&payload_bytes, wallet.webhook_signature_encoding);
 
-            match self.send_webhook(&webhook_url, &payload_bytes, &signature).await {
+            let started = Instant::now();
+            let outcome = self
+                .attempt_delivery(&wallet, event.id, &event.event_type, &payload_bytes, &signature)
+                .await;
+            let duration_ms = started.elapsed().as_millis() as u64;
+
+            match outcome {
                 Ok(()) => {
+                    self.record_circuit_outcome(&event.wallet_address, true);
                     WebhookEventRepository::mark_delivered(&self.pool, event.id).await?;
                     retried += 1;
-                    info!(event_id = %event.id, "Pending webhook delivered on retry");
+                    info!(
+                        event_id = %event.id,
+                        wallet = %event.wallet_address,
+                        attempt = event.attempts + 1,
+                        duration_ms,
+                        "Pending webhook delivered on retry"
+                    );
                 }
-                Err(e) => {
-                    let error_msg = e.to_string();
+                Err(failure) => {
+                    self.record_circuit_outcome(&event.wallet_address, false);
+                    let error_msg = failure.error.to_string();
                     let updated = WebhookEventRepository::increment_attempt(
                         &self.pool,
                         event.id,
                         Some(&error_msg),
                     )
                     .await?;
+                    warn!(
+                        event_id = %event.id,
+                        wallet = %event.wallet_address,
+                        attempt = updated.attempts,
+                        duration_ms,
+                        error = %error_msg,
+                        "Pending webhook retry failed"
+                    );
 
-                    if updated.attempts >= MAX_ATTEMPTS {
+                    if failure.gone || updated.attempts >= MAX_ATTEMPTS {
                         WebhookEventRepository::mark_failed(&self.pool, event.id, &error_msg)
                             .await?;
                     }
@@ -270,52 +702,148 @@ impl WebhookService {
         Ok(retried)
     }
 
-    /// Send a test webhook to verify URL is working
-    pub async fn send_test_webhook(&self, wallet: &Wallet) -> Result<(), AppError> {
-        let webhook_url = wallet
-            .webhook_url
-            .as_ref()
-            .ok_or_else(|| AppError::BadRequest("No webhook URL configured".into()))?;
+    /// The wallet's group as its payloads name it, looked up as each event is
+    /// created so a renamed group shows its current name
+    async fn payload_group(&self, wallet: &Wallet) -> Result<Option<WebhookGroup>, AppError> {
+        let Some(group_id) = wallet.group_id else {
+            return Ok(None);
+        };
 
-        let payload = WebhookPayload {
+        let group = WalletGroupRepository::find_by_id(&self.pool, group_id).await?;
+        Ok(group.as_ref().map(WebhookGroup::from))
+    }
+
+    async fn test_payload(&self, wallet: &Wallet) -> Result<WebhookPayload, AppError> {
+        Ok(WebhookPayload {
             event: "test".to_string(),
+            chain: wallet.chain,
+            group: self.payload_group(wallet).await?,
             timestamp: Utc::now(),
             data: serde_json::json!({
                 "message": "This is a test webhook",
                 "wallet_address": wallet.address
             }),
-        };
+        })
+    }
 
-        let payload_json = serde_json::to_value(&payload)?;
+    /// The request a test webhook to this wallet would send, signature
+    /// included, without sending it or recording an event
+    pub async fn preview_test_webhook(&self, wallet: &Wallet) -> Result<WebhookRequest, AppError> {
+        let webhook_url = wallet
+            .webhook_url
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest("No webhook URL configured".into()))?;
+
+        let payload_bytes = serde_json::to_vec(&self.test_payload(wallet).await?)?;
+        let signature = self.sign_payload(&payload_bytes, wallet.webhook_signature_encoding);
+
+        Ok(self.http.build_request(
+            wallet,
+            webhook_url,
+            "test",
+            &payload_bytes,
+            &signature,
+        ))
+    }
+
+    /// Signed token granting read access to one event's delivery details
+    /// until `expires_at`: `<event id>.<expiry unix seconds>.<hex HMAC>`
+    pub fn inspection_token(
+        &self,
+        event_id: sqlx::types::Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> String {
+        let expires = expires_at.timestamp();
+        let mac = self.inspection_mac(event_id, expires).finalize().into_bytes();
+        format!("{}.{}.{}", event_id, expires, hex::encode(mac))
+    }
+
+    /// The event an inspection token grants access to, if we signed it and
+    /// it hasn't expired at `now`. Any malformed, forged or expired token is
+    /// just `None`.
+    pub fn verify_inspection_token(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Option<sqlx::types::Uuid> {
+        let mut parts = token.splitn(3, '.');
+        let event_id = sqlx::types::Uuid::parse_str(parts.next()?).ok()?;
+        let expires: i64 = parts.next()?.parse().ok()?;
+        let mac = hex::decode(parts.next()?).ok()?;
+
+        self.inspection_mac(event_id, expires).verify_slice(&mac).ok()?;
+
+        let expired = now.timestamp() > expires.saturating_add(INSPECTION_CLOCK_SKEW_SECS);
+        (!expired).then_some(event_id)
+    }
+
+    fn inspection_mac(&self, event_id: sqlx::types::Uuid, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.webhook_secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        // Prefixed so a token MAC can never pass for a payload signature
+        mac.update(format!("webhook-inspection:{}:{}", event_id, expires).as_bytes());
+        mac
+    }
+
+    /// The signature header an event's deliveries carry, under the wallet's
+    /// current signature settings and secret
+    pub fn event_signature_header(
+        &self,
+        wallet: &Wallet,
+        event: &WebhookEvent,
+    ) -> Result<WebhookHeader, AppError> {
+        let payload_bytes = event.body_bytes()?;
+        let signature = self.sign_payload(&payload_bytes, wallet.webhook_signature_encoding);
+        Ok(signature_header(wallet, &signature))
+    }
+
+    /// Send a test webhook to verify URL is working
+    pub async fn send_test_webhook(&self, wallet: &Wallet) -> Result<(), AppError> {
+        if !Self::has_destination(wallet) {
+            return Err(AppError::BadRequest("No webhook URL configured".into()));
+        }
+
+        let payload_body = serde_json::to_string(&self.test_payload(wallet).await?)?;
 
         // Create event record for test webhook
         let event = WebhookEventRepository::create(
             &self.pool,
             &wallet.address,
+            wallet.chain,
             None, // No transaction for test webhooks
             "test",
-            payload_json.clone(),
+            &payload_body,
         )
         .await?;
 
         // Attempt single delivery (no retries for test)
-        let payload_bytes = serde_json::to_vec(&payload)?;
-        let signature = self.sign_payload(&payload_bytes);
+        let payload_bytes = event.body_bytes()?;
+        let signature = self.sign_payload(&payload_bytes, wallet.webhook_signature_encoding);
 
-        match self.send_webhook(webhook_url, &payload_bytes, &signature).await {
+        match self
+            .attempt_delivery(wallet, event.id, "test", &payload_bytes, &signature)
+            .await
+        {
             Ok(()) => {
+                // A working endpoint closes its circuit
+                self.record_circuit_outcome(&wallet.address, true);
                 WebhookEventRepository::mark_delivered(&self.pool, event.id).await?;
                 info!(wallet = %wallet.address, "Test webhook delivered successfully");
                 Ok(())
             }
-            Err(e) => {
-                let error_msg = e.to_string();
+            Err(failure) => {
+                let error_msg = failure.error.to_string();
                 WebhookEventRepository::mark_failed(&self.pool, event.id, &error_msg).await?;
-                Err(e)
+                Err(failure.error)
             }
         }
     }
 
+    /// This replica's view of a wallet's webhook circuit
+    pub fn circuit_status(&self, wallet_address: &str) -> CircuitStatus {
+        self.circuit.status(wallet_address)
+    }
+
     /// Get webhook delivery statistics
     pub async fn get_stats(&self) -> Result<WebhookStats, AppError> {
         let pending = WebhookEventRepository::count_by_status(&self.pool, WebhookStatus::Pending).await?;
@@ -326,6 +854,9 @@ impl WebhookService {
             pending,
             delivered,
             failed,
+            deferred: self.deferred.load(Ordering::Relaxed),
+            skipped_as_dust: self.skipped_dust.load(Ordering::Relaxed),
+            circuits: self.circuit.snapshot(),
         })
     }
 }
@@ -335,4 +866,361 @@ pub struct WebhookStats {
     pub pending: i64,
     pub delivered: i64,
     pub failed: i64,
+    /// Counters since startup
+    pub deferred: u64,
+    pub skipped_as_dust: u64,
+    pub circuits: Vec<CircuitStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WalletSettings;
+    use crate::services::testing::{register_wallet, Harness, WALLET};
+
+    #[sqlx::test]
+    async fn dry_run_skips_attempt_history_quota_and_circuit(pool: PgPool) {
+        let wallet = register_wallet(
+            &pool,
+            WalletSettings {
+                max_webhooks_per_minute: Some(1),
+                ..Default::default()
+            },
+        )
+        .await;
+        let webhook = Harness::new(&pool).webhook;
+
+        for level in 0..3 {
+            webhook
+                .notify_wallet_event(&wallet, "balance.threshold", serde_json::json!({ "level": level }))
+                .await
+                .unwrap();
+        }
+
+        // All three went through despite the quota of one per minute
+        let events = WebhookEventRepository::find_by_wallet(&pool, WALLET, 10, 0).await.unwrap();
+        assert_eq!(events.len(), 3);
+        for event in &events {
+            assert_eq!(event.status, WebhookStatus::Delivered);
+            let attempts = WebhookEventRepository::delivery_attempts(&pool, event.id).await.unwrap();
+            assert!(attempts.is_empty());
+        }
+
+        let recent = WebhookEventRepository::recent_attempts(
+            &pool,
+            WALLET,
+            Utc::now() - chrono::Duration::minutes(1),
+            10,
+        )
+        .await
+        .unwrap();
+        assert!(recent.is_empty());
+        assert!(!webhook.circuit.is_tracked(WALLET));
+    }
+
+    #[sqlx::test]
+    async fn inspection_tokens_tolerate_bounded_clock_skew(pool: PgPool) {
+        let webhook = Harness::new(&pool).webhook;
+        let event_id = sqlx::types::Uuid::new_v4();
+        // Whole seconds, as the token carries them
+        let expires_at = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+        let token = webhook.inspection_token(event_id, expires_at);
+        let at = |secs| expires_at + chrono::Duration::seconds(secs);
+
+        // A checking clock behind the issuer's is always fine
+        assert_eq!(webhook.verify_inspection_token(&token, at(-3600)), Some(event_id));
+        assert_eq!(webhook.verify_inspection_token(&token, at(0)), Some(event_id));
+        assert_eq!(
+            webhook.verify_inspection_token(&token, at(INSPECTION_CLOCK_SKEW_SECS)),
+            Some(event_id)
+        );
+        assert_eq!(
+            webhook.verify_inspection_token(&token, at(INSPECTION_CLOCK_SKEW_SECS + 1)),
+            None
+        );
+    }
+
+    #[sqlx::test]
+    async fn altered_inspection_tokens_are_rejected(pool: PgPool) {
+        let webhook = Harness::new(&pool).webhook;
+        let event_id = sqlx::types::Uuid::new_v4();
+        let expires_at = Utc::now() + chrono::Duration::minutes(10);
+        let token = webhook.inspection_token(event_id, expires_at);
+        let parts: Vec<&str> = token.split('.').collect();
+        let (id, expires, mac) = (parts[0], parts[1], parts[2]);
+
+        let extended = format!("{}.{}.{}", id, expires.parse::<i64>().unwrap() + 3600, mac);
+        let other_event = format!("{}.{}.{}", sqlx::types::Uuid::new_v4(), expires, mac);
+        for forged in [extended, other_event, format!("{}.{}", id, expires), "garbage".into()] {
+            assert_eq!(webhook.verify_inspection_token(&forged, Utc::now()), None);
+        }
+    }
+
+    /// A webhook endpoint answering 503 to the first request and 200 after,
+    /// recording each request's signature header and body
+    async fn flaky_endpoint() -> (String, Arc<std::sync::Mutex<Vec<(String, Vec<u8>)>>>) {
+        use axum::http::{HeaderMap, StatusCode};
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = received.clone();
+        let router = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move |headers: HeaderMap, body: axum::body::Bytes| async move {
+                let signature = headers[DEFAULT_SIGNATURE_HEADER].to_str().unwrap().to_string();
+                let mut requests = requests.lock().unwrap();
+                requests.push((signature, body.to_vec()));
+                if requests.len() == 1 {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::OK
+                }
+            }),
+        );
+
+        (crate::services::testing::serve(router).await + "/hook", received)
+    }
+
+    #[sqlx::test]
+    async fn retries_send_the_stored_redacted_body_under_a_valid_signature(pool: PgPool) {
+        use crate::services::testing::{SENDER, WEBHOOK_SECRET};
+
+        let (url, received) = flaky_endpoint().await;
+        let wallet = register_wallet(
+            &pool,
+            WalletSettings {
+                webhook_url: Some(url),
+                payload_redactions: Some(vec!["counterparty".into(), "metadata".into()]),
+                ..Default::default()
+            },
+        )
+        .await;
+        let webhook = Harness::delivering(&pool).webhook;
+
+        let data = serde_json::json!({ "counterparty": SENDER, "memo": "order-42", "amount": "5" });
+        webhook.notify_wallet_event(&wallet, "balance.threshold", data).await.unwrap();
+
+        // Left pending again, as by a restart, for the retry worker
+        let event = &WebhookEventRepository::find_by_wallet(&pool, WALLET, 1, 0).await.unwrap()[0];
+        sqlx::query("UPDATE webhook_events SET status = 'pending' WHERE id = $1")
+            .bind(event.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(webhook.retry_pending_webhooks().await.unwrap(), 1);
+
+        let stored = event.payload_body.clone().unwrap();
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        for (signature, body) in received.iter() {
+            assert_eq!(body, stored.as_bytes());
+
+            let mut mac = HmacSha256::new_from_slice(WEBHOOK_SECRET.as_bytes()).unwrap();
+            mac.update(body);
+            assert_eq!(*signature, format!("sha256={}", hex::encode(mac.finalize().into_bytes())));
+        }
+
+        // Redacted before it was stored, so the payload column agrees too
+        let sent: serde_json::Value = serde_json::from_slice(&received[0].1).unwrap();
+        assert_eq!(sent, event.payload);
+        assert_eq!(sent["data"]["counterparty"], "7xKX...gAsU");
+        assert_eq!(sent["data"]["amount"], "5");
+        assert!(sent["data"].get("memo").is_none());
+    }
+
+    #[sqlx::test]
+    async fn a_quota_deferral_does_not_use_up_the_half_open_probe(pool: PgPool) {
+        use crate::domain::Chain;
+        use crate::services::circuit_breaker::CircuitState;
+        use std::sync::atomic::AtomicUsize;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let router = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        let url = crate::services::testing::serve(router).await + "/hook";
+        register_wallet(
+            &pool,
+            WalletSettings {
+                webhook_url: Some(url),
+                max_webhooks_per_minute: Some(1),
+                ..Default::default()
+            },
+        )
+        .await;
+        let webhook = Harness::delivering(&pool).webhook;
+
+        // Tripped two minutes ago, so the 30s cool-off is over: half-open
+        let failed_at = Utc::now() - chrono::Duration::minutes(2);
+        webhook.circuit.seed(WALLET, &[failed_at; 5]);
+
+        // The quota's one delivery this minute is already used
+        let create = || WebhookEventRepository::create(&pool, WALLET, Chain::Solana, None, "t", "{}");
+        let earlier = create().await.unwrap();
+        sqlx::query(
+            "UPDATE webhook_events SET status = 'delivered', last_attempt_at = NOW() WHERE id = $1",
+        )
+        .bind(earlier.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let pending = create().await.unwrap();
+
+        assert_eq!(webhook.retry_pending_webhooks().await.unwrap(), 0);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        // Next cycle, with the quota window passed: the probe still goes out
+        sqlx::query("UPDATE webhook_events SET last_attempt_at = NOW() - INTERVAL '2 minutes'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE webhook_events SET next_attempt_at = NOW() WHERE id = $1")
+            .bind(pending.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(webhook.retry_pending_webhooks().await.unwrap(), 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(webhook.circuit.status(WALLET).state, CircuitState::Closed);
+    }
+
+    #[sqlx::test]
+    async fn a_burst_of_receives_is_held_to_the_quota_without_dust(pool: PgPool) {
+        use crate::services::testing::{receive, signature};
+        use std::sync::atomic::AtomicUsize;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let router = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        let url = crate::services::testing::serve(router).await + "/hook";
+        let wallet = register_wallet(
+            &pool,
+            WalletSettings {
+                webhook_url: Some(url),
+                min_webhook_amount: Some(rust_decimal::Decimal::ONE),
+                max_webhooks_per_minute: Some(50),
+                ..Default::default()
+            },
+        )
+        .await;
+        let harness = Harness::delivering(&pool);
+
+        // 200 receives land at once; every fourth is 0.5 USDC, under the minimum
+        for seed in 0..200u8 {
+            let amount_raw = if seed % 4 == 0 { 500_000 } else { 2_000_000 };
+            harness.chain.push_transaction(receive(&signature(seed), amount_raw));
+        }
+        let (stored, _) = harness.sync.sync_wallet(&wallet, 200, true).await.unwrap();
+        assert_eq!(stored, 200);
+
+        // Dust gets no event; the first 50 of the rest go out, the others wait
+        let stats = harness.webhook.get_stats().await.unwrap();
+        assert_eq!(stats.skipped_as_dust, 50);
+        assert_eq!(hits.load(Ordering::SeqCst), 50);
+        assert_eq!(stats.delivered, 50);
+        assert_eq!(stats.deferred, 100);
+        assert_eq!(stats.pending, 100);
+        let deferred: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM webhook_events \
+             WHERE status = 'pending' AND next_attempt_at > NOW()",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(deferred.0, 100);
+
+        // Retrying inside the window sends nothing more
+        assert_eq!(harness.webhook.retry_pending_webhooks().await.unwrap(), 0);
+        assert_eq!(hits.load(Ordering::SeqCst), 50);
+    }
+
+    /// Log output written to memory, for asserting on what gets logged
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[sqlx::test]
+    async fn delivery_failures_log_structured_fields_without_the_url(pool: PgPool) {
+        use crate::domain::Chain;
+        use tracing_subscriber::fmt::format::FmtSpan;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        // Configured as main.rs does for LOG_FORMAT=json
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_subscriber::EnvFilter::new("stablecoin_pay=debug"))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_span_events(FmtSpan::CLOSE)
+                    .with_writer(logs.clone()),
+            );
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let url = "http://127.0.0.1:9/hooks/merchant-7?token=hook-secret";
+        register_wallet(
+            &pool,
+            WalletSettings {
+                webhook_url: Some(url.to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        let event =
+            WebhookEventRepository::create(&pool, WALLET, Chain::Solana, None, "test", "{}")
+                .await
+                .unwrap();
+        let webhook = Harness::delivering(&pool).webhook;
+
+        assert_eq!(webhook.retry_pending_webhooks().await.unwrap(), 0);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> =
+            output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let failure = lines
+            .iter()
+            .find(|line| line["message"] == "Pending webhook retry failed")
+            .unwrap_or_else(|| panic!("no failure line in {}", output));
+        assert_eq!(failure["level"], "WARN");
+        assert_eq!(failure["wallet"], WALLET);
+        assert_eq!(failure["event_id"], event.id.to_string());
+        assert_eq!(failure["attempt"], 1);
+        assert!(failure["duration_ms"].is_u64());
+        let error = failure["error"].as_str().unwrap();
+        assert!(error.starts_with("Webhook delivery failed: Network error"), "{}", error);
+
+        // Neither the log nor the stored error carries the URL's path or query
+        let stored = WebhookEventRepository::find_by_id(&pool, event.id).await.unwrap().unwrap();
+        for text in [output.as_str(), stored.last_error.as_deref().unwrap()] {
+            assert!(!text.contains("hook-secret") && !text.contains("merchant-7"), "{}", text);
+        }
+    }
 }
@@ -0,0 +1,591 @@
+use axum::async_trait;
+use redis::aio::ConnectionManager;
+use reqwest::{redirect, Client, StatusCode};
+use serde::Serialize;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::{debug, error, info, warn};
+
+use crate::domain::Wallet;
+use crate::error::AppError;
+use crate::repository::{WalletRepository, WebhookEventRepository};
+
+/// Response body bytes read and kept per attempt; the rest is never read
+const MAX_RESPONSE_BODY_BYTES: usize = 8 * 1024;
+
+/// Bound on a whole delivery request, reading the response body included
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Idle connections kept per merchant host
+const MAX_IDLE_CONNECTIONS_PER_HOST: usize = 4;
+
+/// Approximate entries a wallet's Redis stream is trimmed to
+const STREAM_MAX_LEN: usize = 100_000;
+
+/// A failed delivery attempt. `gone` is set when the endpoint answered
+/// 410 Gone and the wallet's webhook URL was disabled, so retrying is pointless.
+pub struct DeliveryError {
+    pub error: AppError,
+    pub gone: bool,
+    /// HTTP status of the response, when the endpoint answered
+    pub status: Option<u16>,
+}
+
+impl DeliveryError {
+    pub fn retryable(message: String) -> Self {
+        Self {
+            error: AppError::WebhookDeliveryFailed(message),
+            gone: false,
+            status: None,
+        }
+    }
+
+    fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status.as_u16());
+        self
+    }
+}
+
+/// One attempt at delivering a recorded, signed webhook event
+pub struct Delivery<'a> {
+    pub event_id: Uuid,
+    pub event_type: &'a str,
+    pub payload: &'a [u8],
+    /// HMAC-SHA256 of `payload` in the wallet's signature encoding
+    pub signature: &'a str,
+}
+
+/// Transport for webhook events. Event records, retries, quotas and circuit
+/// breaking are handled by `WebhookService`; a backend only moves the bytes.
+/// Success carries the HTTP status for transports that have one.
+#[async_trait]
+pub trait WebhookDelivery: Send + Sync {
+    async fn deliver(
+        &self,
+        wallet: &Wallet,
+        delivery: &Delivery<'_>,
+    ) -> Result<Option<u16>, DeliveryError>;
+}
+
+/// Read at most `MAX_RESPONSE_BODY_BYTES` of a response body
+async fn read_body_prefix(mut response: reqwest::Response) -> Option<String> {
+    let mut body = Vec::new();
+    while body.len() < MAX_RESPONSE_BODY_BYTES {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+
+    if body.is_empty() {
+        return None;
+    }
+    body.truncate(MAX_RESPONSE_BODY_BYTES);
+    Some(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// The exact HTTP request a webhook delivery sends (always a POST)
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub headers: Vec<WebhookHeader>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// Id of the recorded event, the same on every retry
+pub const EVENT_ID_HEADER: &str = "X-Webhook-Event-Id";
+
+/// Event type (`payment.received`, `test`, ...) so receivers can route
+/// without parsing the body
+pub const EVENT_TYPE_HEADER: &str = "X-Webhook-Event";
+
+/// Header used when a wallet doesn't configure its own
+pub const DEFAULT_SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// The signature header a wallet's deliveries carry. The default header
+/// keeps its `sha256=` prefix; a custom header gets the bare digest, which
+/// is what platforms with fixed conventions expect.
+pub fn signature_header(wallet: &Wallet, signature: &str) -> WebhookHeader {
+    match wallet.webhook_signature_header.as_deref() {
+        Some(name) => WebhookHeader {
+            name: name.to_string(),
+            value: signature.to_string(),
+        },
+        None => WebhookHeader {
+            name: DEFAULT_SIGNATURE_HEADER.to_string(),
+            value: format!("sha256={}", signature),
+        },
+    }
+}
+
+/// POSTs events to the wallet's webhook URL
+pub struct HttpDelivery {
+    client: Client,
+    pool: PgPool,
+    /// Log requests instead of sending them
+    dry_run: bool,
+    user_agent: String,
+}
+
+impl HttpDelivery {
+    pub fn new(pool: PgPool, dry_run: bool, user_agent: String) -> Self {
+        Self::with_timeout(pool, dry_run, user_agent, REQUEST_TIMEOUT)
+    }
+
+    fn with_timeout(pool: PgPool, dry_run: bool, user_agent: String, timeout: Duration) -> Self {
+        // Never follow redirects: a 3xx could send a signed payload to a host we
+        // never validated. The timeout also bounds reading the response body.
+        let client = Client::builder()
+            .timeout(timeout)
+            .connect_timeout(Duration::from_secs(5))
+            .redirect(redirect::Policy::none())
+            .pool_max_idle_per_host(MAX_IDLE_CONNECTIONS_PER_HOST)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            pool,
+            dry_run,
+            user_agent,
+        }
+    }
+
+    /// Assemble the delivery request: merchant-configured static headers
+    /// (validated on registration) followed by the ones we control. A static
+    /// header sharing a name with the signature, User-Agent or event type
+    /// header (stored before those were reserved) is dropped.
+    pub fn build_request(
+        &self,
+        wallet: &Wallet,
+        url: &str,
+        event_type: &str,
+        payload: &[u8],
+        signature: &str,
+    ) -> WebhookRequest {
+        let signature_header = signature_header(wallet, signature);
+        let mut request_headers: Vec<WebhookHeader> = wallet
+            .webhook_headers
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| {
+                !name.eq_ignore_ascii_case(&signature_header.name)
+                    && !name.eq_ignore_ascii_case("User-Agent")
+                    && !name.eq_ignore_ascii_case(EVENT_TYPE_HEADER)
+            })
+            .filter_map(|(name, value)| {
+                value.as_str().map(|value| WebhookHeader {
+                    name: name.clone(),
+                    value: value.to_string(),
+                })
+            })
+            .collect();
+
+        request_headers.push(WebhookHeader {
+            name: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+        });
+        request_headers.push(WebhookHeader {
+            name: "User-Agent".to_string(),
+            value: self.user_agent.clone(),
+        });
+        request_headers.push(WebhookHeader {
+            name: EVENT_TYPE_HEADER.to_string(),
+            value: event_type.to_string(),
+        });
+        request_headers.push(signature_header);
+
+        WebhookRequest {
+            url: url.to_string(),
+            headers: request_headers,
+            body: String::from_utf8_lossy(payload).into_owned(),
+        }
+    }
+
+    async fn send_webhook(
+        &self,
+        request: &WebhookRequest,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut builder = self.client.post(&request.url);
+
+        for header in &request.headers {
+            builder = builder.header(header.name.as_str(), header.value.as_str());
+        }
+
+        builder.body(request.body.clone()).send().await
+    }
+
+    /// Best-effort: a failure to store debug details must not fail the delivery
+    async fn record_response(
+        &self,
+        event_id: Uuid,
+        status: Option<StatusCode>,
+        elapsed: Duration,
+        body: Option<&str>,
+    ) {
+        let elapsed_ms = elapsed.as_millis().min(i32::MAX as u128) as i32;
+
+        if let Err(e) = WebhookEventRepository::record_response(
+            &self.pool,
+            event_id,
+            status.map(|s| s.as_u16() as i32),
+            elapsed_ms,
+            body,
+        )
+        .await
+        {
+            warn!(event_id = %event_id, error = %e, "Failed to record webhook response");
+        }
+    }
+
+    /// 410 Gone is the conventional "stop sending" signal
+    async fn disable_gone_endpoint(&self, wallet_address: &str, url: &str) {
+        warn!(
+            wallet = %wallet_address,
+            url_host = %url_host(url),
+            metric = "webhook.endpoint_gone",
+            "Webhook endpoint returned 410 Gone, disabling webhook URL"
+        );
+
+        if let Err(e) = WalletRepository::disable_webhook(&self.pool, wallet_address).await {
+            error!(wallet = %wallet_address, error = %e, "Failed to disable webhook URL");
+        }
+    }
+}
+
+#[async_trait]
+impl WebhookDelivery for HttpDelivery {
+    /// Send a single webhook HTTP request, recording the endpoint's status
+    /// code, response time and the start of its body on the event either way.
+    /// 3xx counts as a failure; 410 Gone also disables the wallet's webhook URL.
+    async fn deliver(
+        &self,
+        wallet: &Wallet,
+        delivery: &Delivery<'_>,
+    ) -> Result<Option<u16>, DeliveryError> {
+        let url = wallet
+            .webhook_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| {
+                DeliveryError::retryable("Wallet webhook URL no longer configured".into())
+            })?;
+        let event_id = delivery.event_id;
+        let mut request = self.build_request(
+            wallet,
+            url,
+            delivery.event_type,
+            delivery.payload,
+            delivery.signature,
+        );
+        request.headers.push(WebhookHeader {
+            name: EVENT_ID_HEADER.to_string(),
+            value: event_id.to_string(),
+        });
+
+        if self.dry_run {
+            info!(
+                event_id = %event_id,
+                wallet = %wallet.address,
+                url_host = %url_host(&request.url),
+                "Webhook dry run, request not sent"
+            );
+            debug!(
+                event_id = %event_id,
+                headers = ?request.headers,
+                body = %request.body,
+                "Webhook dry run request"
+            );
+            return Ok(None);
+        }
+
+        let started = Instant::now();
+
+        let response = match self.send_webhook(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.record_response(event_id, None, started.elapsed(), None)
+                    .await;
+                // Without the URL: its path and query may carry a secret
+                return Err(DeliveryError::retryable(format!(
+                    "Network error: {}",
+                    e.without_url()
+                )));
+            }
+        };
+
+        // Response time is measured up to the headers, not the body
+        let elapsed = started.elapsed();
+        let status = response.status();
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown location")
+            .to_string();
+        let body = read_body_prefix(response).await;
+
+        self.record_response(event_id, Some(status), elapsed, body.as_deref())
+            .await;
+
+        if status.is_success() {
+            Ok(Some(status.as_u16()))
+        } else if status == StatusCode::GONE {
+            self.disable_gone_endpoint(&wallet.address, url).await;
+            Err(DeliveryError {
+                error: AppError::WebhookDeliveryFailed(format!(
+                    "webhook endpoint returned HTTP {}; webhook URL disabled",
+                    status
+                )),
+                gone: true,
+                status: Some(status.as_u16()),
+            })
+        } else if status.is_redirection() {
+            Err(DeliveryError::retryable(format!(
+                "webhook endpoint returned a redirect (HTTP {} to {}); provide the final URL",
+                status, location
+            ))
+            .with_status(status))
+        } else {
+            Err(
+                DeliveryError::retryable(format!("HTTP {} - {}", status, body.unwrap_or_default()))
+                    .with_status(status),
+            )
+        }
+    }
+}
+
+/// Appends events to the wallet's Redis stream (`XADD`), for merchants
+/// consuming events from a queue instead of receiving POSTs. Entries carry
+/// `event_id`, `event_type`, `wallet_address`, `signature` and `payload`
+/// fields; the signature is the same value the HTTP signature header carries.
+pub struct RedisStreamDelivery {
+    client: redis::Client,
+    /// Connected on first use, reconnecting on its own after that
+    connection: OnceCell<ConnectionManager>,
+    dry_run: bool,
+}
+
+impl RedisStreamDelivery {
+    pub fn new(redis_url: &str, dry_run: bool) -> Result<Self, AppError> {
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| AppError::Internal(format!("Invalid WEBHOOK_REDIS_URL: {}", e)))?;
+
+        Ok(Self {
+            client,
+            connection: OnceCell::new(),
+            dry_run,
+        })
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager, redis::RedisError> {
+        self.connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl WebhookDelivery for RedisStreamDelivery {
+    async fn deliver(
+        &self,
+        wallet: &Wallet,
+        delivery: &Delivery<'_>,
+    ) -> Result<Option<u16>, DeliveryError> {
+        let stream = wallet.webhook_stream.as_deref().ok_or_else(|| {
+            DeliveryError::retryable("Wallet webhook stream no longer configured".into())
+        })?;
+        let event_id = delivery.event_id.to_string();
+        let signature = signature_header(wallet, delivery.signature).value;
+
+        if self.dry_run {
+            info!(
+                event_id = %event_id,
+                wallet = %wallet.address,
+                stream = %stream,
+                "Webhook dry run, stream entry not published"
+            );
+            debug!(
+                event_id = %event_id,
+                body = %String::from_utf8_lossy(delivery.payload),
+                "Webhook dry run stream entry"
+            );
+            return Ok(None);
+        }
+
+        let mut connection = self
+            .connection()
+            .await
+            .map_err(|e| DeliveryError::retryable(format!("Redis connection failed: {}", e)))?;
+
+        let entry_id: String = redis::cmd("XADD")
+            .arg(stream)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(STREAM_MAX_LEN)
+            .arg("*")
+            .arg("event_id")
+            .arg(&event_id)
+            .arg("event_type")
+            .arg(delivery.event_type)
+            .arg("wallet_address")
+            .arg(&wallet.address)
+            .arg("signature")
+            .arg(&signature)
+            .arg("payload")
+            .arg(delivery.payload)
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| DeliveryError::retryable(format!("Redis XADD failed: {}", e)))?;
+
+        info!(event_id = %event_id, stream = %stream, entry_id = %entry_id, "Webhook event published to stream");
+
+        Ok(None)
+    }
+}
+
+/// Only the host of a webhook URL goes into logs; paths and query strings
+/// often carry tokens
+fn url_host(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(str::to_string))
+        .unwrap_or_else(|| "<invalid url>".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use axum::http::{header, StatusCode as HttpStatus};
+    use axum::routing::post;
+    use axum::Router;
+
+    use crate::domain::{Chain, WalletSettings};
+    use crate::services::testing::{register_wallet, serve, WALLET};
+
+    /// Deliver one recorded event to `path` on a server running `router`
+    async fn deliver_to(
+        pool: &PgPool,
+        router: Router,
+        path: &str,
+        timeout: Duration,
+    ) -> (Result<Option<u16>, DeliveryError>, Uuid) {
+        let base_url = serve(router).await;
+        let wallet = register_wallet(
+            pool,
+            WalletSettings {
+                webhook_url: Some(format!("{}{}", base_url, path)),
+                ..Default::default()
+            },
+        )
+        .await;
+        let event = WebhookEventRepository::create(pool, WALLET, Chain::Solana, None, "test", "{}")
+            .await
+            .unwrap();
+
+        let http = HttpDelivery::with_timeout(pool.clone(), false, "test-agent".into(), timeout);
+        let delivery = Delivery {
+            event_id: event.id,
+            event_type: "test",
+            payload: b"{}",
+            signature: "sig",
+        };
+        (http.deliver(&wallet, &delivery).await, event.id)
+    }
+
+    #[sqlx::test]
+    async fn redirects_are_recorded_not_followed(pool: PgPool) {
+        let followed = Arc::new(AtomicUsize::new(0));
+        let hits = followed.clone();
+        let router = Router::new()
+            .route(
+                "/hook",
+                post(|| async { (HttpStatus::FOUND, [(header::LOCATION, "/elsewhere")]) }),
+            )
+            .route(
+                "/elsewhere",
+                post(move || async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                }),
+            );
+
+        let (result, event_id) = deliver_to(&pool, router, "/hook", REQUEST_TIMEOUT).await;
+
+        let Err(err) = result else {
+            panic!("a redirect is a failure")
+        };
+        assert_eq!(err.status, Some(302));
+        assert!(!err.gone);
+        assert!(err.error.to_string().contains("/elsewhere"));
+        assert_eq!(followed.load(Ordering::SeqCst), 0);
+        let event = WebhookEventRepository::find_by_id(&pool, event_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.last_response_status, Some(302));
+    }
+
+    #[sqlx::test]
+    async fn only_the_start_of_a_huge_response_body_is_kept(pool: PgPool) {
+        let router = Router::new().route(
+            "/hook",
+            post(|| async {
+                (
+                    HttpStatus::INTERNAL_SERVER_ERROR,
+                    "x".repeat(10 * 1024 * 1024),
+                )
+            }),
+        );
+
+        let (result, event_id) = deliver_to(&pool, router, "/hook", REQUEST_TIMEOUT).await;
+
+        assert_eq!(result.err().unwrap().status, Some(500));
+        let event = WebhookEventRepository::find_by_id(&pool, event_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event.last_response_body.unwrap().len(),
+            MAX_RESPONSE_BODY_BYTES
+        );
+    }
+
+    #[sqlx::test]
+    async fn a_hanging_endpoint_fails_at_the_timeout(pool: PgPool) {
+        let router = Router::new().route(
+            "/hook",
+            post(|| async { tokio::time::sleep(Duration::from_secs(30)).await }),
+        );
+
+        let started = Instant::now();
+        let (result, event_id) =
+            deliver_to(&pool, router, "/hook", Duration::from_millis(300)).await;
+
+        let Err(err) = result else {
+            panic!("a hang is a failure")
+        };
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(err.status, None);
+        assert!(err.error.to_string().contains("Network error"));
+        let event = WebhookEventRepository::find_by_id(&pool, event_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.last_response_status, None);
+        assert!(event.last_response_time_ms.is_some());
+    }
+}